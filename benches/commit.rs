@@ -0,0 +1,136 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Benchmarks for the latency of `git z commit` before the first prompt.
+//!
+//! Run them with `cargo bench`. To measure the impact of a slow filesystem,
+//! set `TMPDIR` to a directory on this filesystem: the benchmark repositories
+//! are then created there.
+
+#![allow(clippy::pedantic, clippy::restriction)]
+
+// NOTE: rexpect is only compatible with Unix-like systems, so let’s just not
+// run the benchmarks on Windows.
+#[cfg(not(target_os = "windows"))]
+criterion::criterion_main!(startup::benches);
+
+#[cfg(target_os = "windows")]
+fn main() {}
+
+#[cfg(not(target_os = "windows"))]
+mod startup {
+    use std::{fs, path::Path, process::Command, time::Duration};
+
+    use assert_cmd::cargo::cargo_bin;
+    use assert_fs::{prelude::*, TempDir};
+    use criterion::{criterion_group, BenchmarkId, Criterion};
+    use eyre::{bail, Result};
+    use rexpect::session::spawn_command;
+
+    const TIMEOUT: Option<u64> = Some(10_000);
+
+    criterion_group! {
+        name = benches;
+        config = Criterion::default()
+            .sample_size(20)
+            .measurement_time(Duration::from_secs(10));
+        targets = first_prompt, config_parsing
+    }
+
+    fn first_prompt(c: &mut Criterion) {
+        let mut group = c.benchmark_group("commit_until_first_prompt");
+
+        for staged_files in [1, 100] {
+            let temp_dir = setup_repo(staged_files).unwrap();
+
+            group.bench_with_input(
+                BenchmarkId::from_parameter(staged_files),
+                &temp_dir,
+                |b, temp_dir| {
+                    b.iter(|| wait_for_first_prompt(temp_dir).unwrap())
+                },
+            );
+        }
+
+        group.finish();
+    }
+
+    fn config_parsing(c: &mut Criterion) {
+        let config = fs::read_to_string(
+            Path::new("tests")
+                .join("res")
+                .join("config")
+                .join("latest_full.toml"),
+        )
+        .unwrap();
+
+        c.bench_function("config_from_toml", |b| {
+            b.iter(|| git_z::Config::from_toml(&config).unwrap())
+        });
+    }
+
+    fn setup_repo(staged_files: usize) -> Result<TempDir> {
+        let temp_dir = TempDir::new()?;
+
+        git(&temp_dir, &["init", "--quiet"])?;
+
+        temp_dir.child("git-z.toml").write_file(
+            &Path::new("tests")
+                .join("res")
+                .join("config")
+                .join("latest_full.toml"),
+        )?;
+
+        for index in 0..staged_files {
+            temp_dir
+                .child("src")
+                .child(format!("file_{index}.rs"))
+                .write_str(&format!("pub fn function_{index}() {{}}\n"))?;
+        }
+
+        git(&temp_dir, &["add", "."])?;
+
+        Ok(temp_dir)
+    }
+
+    fn wait_for_first_prompt(temp_dir: &TempDir) -> Result<()> {
+        let mut cmd = Command::new(cargo_bin("git-z"));
+        cmd.current_dir(temp_dir)
+            .env("NO_COLOR", "true")
+            .arg("commit");
+
+        let mut process = spawn_command(cmd, TIMEOUT)?;
+        process.exp_string("Commit type")?;
+
+        // NOTE: Dropping the session kills the process, leaving the repository
+        // untouched as no question has been answered.
+        drop(process);
+
+        Ok(())
+    }
+
+    fn git(temp_dir: &TempDir, args: &[&str]) -> Result<()> {
+        let status = Command::new("git")
+            .current_dir(temp_dir)
+            .args(args)
+            .status()?;
+
+        if !status.success() {
+            bail!("Failed to run git {}", args.join(" "));
+        }
+
+        Ok(())
+    }
+}
@@ -58,11 +58,11 @@ fn version_with_features(cargo_version: &str) -> String {
 
 /// Returns the list of enabled features.
 fn features() -> Vec<&'static str> {
-    if env::var("CARGO_FEATURE_UNSTABLE_PRE_COMMIT").is_ok() {
-        vec!["unstable-pre-commit"]
-    } else {
-        vec![]
-    }
+    [("CARGO_FEATURE_CHROME_TRACE", "chrome-trace")]
+        .into_iter()
+        .filter(|(variable, _)| env::var(variable).is_ok())
+        .map(|(_, feature)| feature)
+        .collect()
 }
 
 /// Returns the version from cargo with a revision.
@@ -0,0 +1,308 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! A fake Git command used for testing.
+//!
+//! It only implements the commands called by git-z. Its behaviour is driven by
+//! files in the `.git` directory of the current directory:
+//!
+//! | File           | Behaviour                                                |
+//! |----------------|----------------------------------------------------------|
+//! | `bare`         | the repository is bare                                   |
+//! | `branch`       | the name of the current branch [default: `main`]         |
+//! | `error`        | `git commit` fails with this exit code                   |
+//! | `head_message` | the message of `HEAD` [default: no commit yet]           |
+//! | `numstat`      | the output of `git diff --cached --numstat`              |
+//! | `patch`        | the output of `git diff --cached`                        |
+//! | `subjects`     | the subjects of the recent commits                       |
+//! | `tree`         | the staged tree [default: the empty tree]                |
+//! | `upstream`     | the upstream of the current branch [default: none]       |
+//!
+//! On success, `git commit` writes its arguments to `.git/commit`. Like Git,
+//! it writes the message to `.git/COMMIT_EDITMSG` after running the
+//! `pre-commit` hook, and passes this file to the `commit-msg` hook. Hooks are
+//! looked for in `core.hooksPath` when it is set with `-c`.
+//!
+//! `git var GIT_EDITOR` prints `GIT_EDITOR` from the environment, defaulting to
+//! `vi` like Git.
+
+use std::{
+    env, fs,
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+    process::{Command, ExitCode},
+};
+
+/// The hash of the empty tree.
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// The status code of Git for fatal errors.
+const FATAL: u8 = 128;
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    let (hooks_path, args) = split_config(&args);
+
+    let result = match args {
+        ["branch", "--show-current"] => branch(),
+        ["commit", ..] => commit(args, hooks_path),
+        ["--paginate", "diff", "--cached"] => print_file("patch"),
+        ["diff", "--cached", "--numstat", "-z", "--no-renames"] => {
+            print_file("numstat")
+        }
+        ["diff", "--cached", "--no-color", "--no-ext-diff", "--no-renames", "--src-prefix=a/", "--dst-prefix=b/", "--unified=0"] => {
+            print_file("patch")
+        }
+        ["diff", "--cached", "--no-color", "--no-ext-diff"] => {
+            print_file("patch")
+        }
+        ["log", "-1", "--format=%B", "HEAD"] => head_message(),
+        ["ls-files", "--unmerged"] => print_file("unmerged"),
+        ["log", "--no-merges", "--format=%s", ..] => print_file("subjects"),
+        ["rev-parse", args @ ..] => rev_parse(args),
+        ["var", "GIT_EDITOR"] => editor(),
+        ["write-tree"] => write_tree(),
+        _ => Ok(ExitCode::SUCCESS),
+    };
+
+    result.unwrap_or_else(|error| {
+        eprintln!("fake git: {error}");
+        ExitCode::from(FATAL)
+    })
+}
+
+/// Splits the leading `-c <name>=<value>` options from the arguments.
+///
+/// Only `core.hooksPath` is supported, others are ignored.
+fn split_config<'a, 'b>(
+    mut args: &'b [&'a str],
+) -> (Option<&'a str>, &'b [&'a str]) {
+    let mut hooks_path = None;
+
+    while let ["-c", option, rest @ ..] = args {
+        if let Some(path) = option.strip_prefix("core.hooksPath=") {
+            hooks_path = Some(path);
+        }
+
+        args = rest;
+    }
+
+    (hooks_path, args)
+}
+
+/// Implements `git branch --show-current`.
+fn branch() -> io::Result<ExitCode> {
+    let branch = read_file("branch")?;
+    println!("{}", branch.as_deref().map_or("main", str::trim));
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Implements `git var GIT_EDITOR`.
+fn editor() -> io::Result<ExitCode> {
+    println!("{}", env::var("GIT_EDITOR").as_deref().unwrap_or("vi"));
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Implements `git commit`.
+fn commit(args: &[&str], hooks_path: Option<&str>) -> io::Result<ExitCode> {
+    if let Some(error) = read_file("error")? {
+        println!("fake error");
+        return Ok(ExitCode::from(error.trim().parse::<u8>().unwrap_or(1)));
+    }
+
+    let verify = !args.contains(&"--no-verify");
+
+    if verify && !run_hook(hooks_path, "pre-commit", &[])? {
+        return Ok(ExitCode::FAILURE);
+    }
+
+    let commit_editmsg = git_path("COMMIT_EDITMSG")?;
+
+    if let Some(message) = message(args) {
+        let newline = if message.ends_with('\n') { "" } else { "\n" };
+        fs::write(&commit_editmsg, format!("{message}{newline}"))?;
+    }
+
+    if verify && !run_hook(hooks_path, "commit-msg", &[&commit_editmsg])? {
+        return Ok(ExitCode::FAILURE);
+    }
+
+    println!("fake commit");
+    fs::write(git_path("commit")?, args.join(" "))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Implements `git log -1 --format=%B HEAD`.
+fn head_message() -> io::Result<ExitCode> {
+    if let Some(message) = read_file("head_message")? {
+        print!("{message}");
+        Ok(ExitCode::SUCCESS)
+    } else {
+        eprintln!("fatal: ambiguous argument 'HEAD'");
+        Ok(ExitCode::from(FATAL))
+    }
+}
+
+/// Implements `git rev-parse`.
+fn rev_parse(args: &[&str]) -> io::Result<ExitCode> {
+    match args {
+        ["--show-toplevel"] => {
+            println!("{}", env::current_dir()?.display());
+        }
+        ["--absolute-git-dir"] => {
+            println!("{}", git_dir()?.display());
+        }
+        ["--git-path", path] => {
+            println!("{}", git_path(path)?.display());
+        }
+        ["--abbrev-ref", "--symbolic-full-name", "@{u}"] => {
+            if let Some(upstream) = read_file("upstream")? {
+                print!("{upstream}");
+            } else {
+                eprintln!("fatal: no upstream configured");
+                return Ok(ExitCode::from(FATAL));
+            }
+        }
+        ["--verify", "--quiet", "HEAD"] => {
+            if read_file("head_message")?.is_none() {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        ["--is-bare-repository"] => {
+            println!("{}", git_path("bare")?.exists());
+        }
+        ["--is-inside-work-tree"] => {
+            if !git_dir()?.is_dir() {
+                return Ok(ExitCode::FAILURE);
+            }
+
+            println!("{}", !git_path("bare")?.exists());
+        }
+        _ => (),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Implements `git write-tree`.
+fn write_tree() -> io::Result<ExitCode> {
+    let tree = read_file("tree")?;
+    print!("{}", tree.unwrap_or_else(|| format!("{EMPTY_TREE}\n")));
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Returns the message passed to `git commit` with `-m`, if any.
+///
+/// The option can be grouped with other short options, as in `-em`.
+fn message<'a>(args: &[&'a str]) -> Option<&'a str> {
+    args.windows(2).find_map(|pair| match pair {
+        [option, message]
+            if !option.starts_with("--")
+                && option.starts_with('-')
+                && option.ends_with('m') =>
+        {
+            Some(*message)
+        }
+        _ => None,
+    })
+}
+
+/// Runs a hook if it exists and is executable.
+///
+/// Like Git, hooks are looked for in `core.hooksPath` if it is set, and their
+/// output is redirected to the standard error. Returns whether the hook has
+/// succeeded.
+fn run_hook(
+    hooks_path: Option<&str>,
+    name: &str,
+    args: &[&Path],
+) -> io::Result<bool> {
+    let hooks_dir = match hooks_path {
+        Some(hooks_path) => PathBuf::from(hooks_path),
+        None => git_path("hooks")?,
+    };
+    let hook = hooks_dir.join(name);
+
+    if !is_executable(&hook) {
+        return Ok(true);
+    }
+
+    io::stdout().flush()?;
+    Ok(hook_command(&hook)
+        .args(args)
+        .stdout(io::stderr())
+        .status()?
+        .success())
+}
+
+/// Builds the command to run a hook.
+#[cfg(not(windows))]
+fn hook_command(hook: &Path) -> Command {
+    Command::new(hook)
+}
+
+/// Builds the command to run a hook.
+///
+/// Hooks are shell scripts, which cannot be run directly on Windows.
+#[cfg(windows)]
+fn hook_command(hook: &Path) -> Command {
+    let mut command = Command::new("sh");
+    command.arg(hook);
+    command
+}
+
+/// Returns whether a file is executable.
+#[cfg(not(windows))]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    path.metadata()
+        .is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+/// Returns whether a file is executable.
+#[cfg(windows)]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Prints a file from the `.git` directory, if it exists.
+fn print_file(name: &str) -> io::Result<ExitCode> {
+    if let Some(content) = read_file(name)? {
+        print!("{content}");
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Reads a file from the `.git` directory, if it exists.
+fn read_file(name: &str) -> io::Result<Option<String>> {
+    match fs::read_to_string(git_path(name)?) {
+        Ok(content) => Ok(Some(content)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Returns the path to the `.git` directory.
+fn git_dir() -> io::Result<PathBuf> {
+    Ok(env::current_dir()?.join(".git"))
+}
+
+/// Returns the path to a file in the `.git` directory.
+fn git_path(name: &str) -> io::Result<PathBuf> {
+    Ok(git_dir()?.join(name))
+}
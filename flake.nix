@@ -72,10 +72,6 @@
               default = self'.packages.${packageName};
 
               ${packageName} = mkPackage { };
-
-              "${packageName}-unstable" = mkPackage {
-                extraCargoBuildOptions = [ "--features unstable-pre-commit" ];
-              };
             };
 
           ######################################################################
@@ -108,6 +104,7 @@
 
               developmentTools = with pkgs; with self'.packages; [
                 cargo-bloat
+                cargo-insta
                 cargo-outdated
                 cargo-watch
                 git
@@ -115,13 +112,6 @@
                 gitAndTools.gitflow
               ];
 
-              testEnv = [
-                {
-                  name = "TEST_PATH";
-                  eval = "$PRJ_ROOT/tests/fake_bin:${pkgs.bash}/bin";
-                }
-              ];
-
               devEnv = [
                 {
                   name = "RUSTFLAGS";
@@ -156,8 +146,7 @@
                   ++ developmentTools;
 
                 env =
-                  testEnv
-                  ++ devEnv
+                  devEnv
                   ++ ideEnv;
 
                 commands = [
@@ -185,9 +174,6 @@
                 packages =
                   buildToolchain
                   ++ checkToolchain;
-
-                env =
-                  testEnv;
               };
 
               # NOTE: Use the musl target to build a statically-linked binary.
@@ -0,0 +1,115 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Heuristics to detect likely breaking changes.
+
+use regex::Regex;
+
+use crate::{config::BreakingChangeRule, staged::StagedPatch};
+
+/// Returns the description of the rules triggered by the patch.
+///
+/// A rule is triggered when a staged file matches its `path` and, if the rule
+/// has a `pattern`, one of the lines removed from this file matches it.
+pub fn detect(
+    rules: &[BreakingChangeRule],
+    patch: &StagedPatch,
+) -> Result<Vec<String>, regex::Error> {
+    let mut triggered = vec![];
+
+    for rule in rules {
+        let path_regex = rule.path.as_deref().map(Regex::new).transpose()?;
+        let pattern_regex =
+            rule.pattern.as_deref().map(Regex::new).transpose()?;
+
+        let is_triggered = patch
+            .files
+            .iter()
+            .filter(|file| {
+                path_regex
+                    .as_ref()
+                    .map_or(true, |regex| regex.is_match(&file.path))
+            })
+            .any(|file| {
+                pattern_regex.as_ref().map_or(true, |regex| {
+                    file.removed_lines.iter().any(|line| regex.is_match(line))
+                })
+            });
+
+        if is_triggered {
+            triggered.push(rule.description.clone());
+        }
+    }
+
+    tracing::debug!(?triggered, "detected likely breaking changes");
+    Ok(triggered)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+    use crate::staged::FilePatch;
+
+    fn rule(path: Option<&str>, pattern: Option<&str>) -> BreakingChangeRule {
+        BreakingChangeRule {
+            path: path.map(String::from),
+            pattern: pattern.map(String::from),
+            description: String::from("triggered"),
+        }
+    }
+
+    fn patch() -> StagedPatch {
+        StagedPatch {
+            files: vec![
+                FilePatch {
+                    path: String::from("src/lib.rs"),
+                    added_lines: vec![String::from("pub fn new(a: u8) {}")],
+                    removed_lines: vec![String::from("pub fn new() {}")],
+                },
+                FilePatch {
+                    path: String::from("README.md"),
+                    added_lines: vec![String::from("Some text.")],
+                    removed_lines: vec![],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn triggers_on_a_matching_path() {
+        let rules = [rule(Some(r"^README\.md$"), None)];
+        assert_eq!(detect(&rules, &patch()).unwrap(), vec!["triggered"]);
+    }
+
+    #[test]
+    fn triggers_on_a_removed_line_matching_the_pattern() {
+        let rules = [rule(Some(r"\.rs$"), Some(r"^\s*pub fn "))];
+        assert_eq!(detect(&rules, &patch()).unwrap(), vec!["triggered"]);
+    }
+
+    #[test]
+    fn does_not_trigger_on_added_lines() {
+        let rules = [rule(Some(r"\.md$"), Some(r"Some text"))];
+        assert!(detect(&rules, &patch()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn does_not_trigger_without_matching_path() {
+        let rules = [rule(Some(r"openapi\.yaml$"), None)];
+        assert!(detect(&rules, &patch()).unwrap().is_empty());
+    }
+}
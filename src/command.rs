@@ -16,28 +16,84 @@
 //! The Command Line Interface for git-z.
 
 mod commit;
+mod config_command;
+mod debug;
+mod export;
+mod failure;
 mod helpers;
+mod hook;
 mod init;
+mod lint;
+mod migrate_history;
+mod revspec;
+mod serve;
+mod stats;
+mod tickets;
+mod undo;
 mod update;
+mod verify_install;
+mod verify_message;
 
-use std::error::Error as _;
+#[cfg(feature = "chrome-trace")]
+use std::fs::File;
+use std::{error::Error as _, fmt::Display, path::PathBuf};
 
-use clap::{ArgAction, Parser, Subcommand};
+use clap::{
+    error::ErrorKind, ArgAction, CommandFactory as _, Parser, Subcommand,
+};
+#[cfg(feature = "chrome-trace")]
+use eyre::Context as _;
 use eyre::{Report, Result};
 use inquire::InquireError;
-use tracing_subscriber::fmt::format::FmtSpan;
+use itertools::Itertools as _;
+#[cfg(feature = "chrome-trace")]
+use tracing_chrome::{ChromeLayerBuilder, FlushGuard};
+use tracing_subscriber::{
+    fmt::format::FmtSpan, layer::SubscriberExt as _,
+    util::SubscriberInitExt as _, EnvFilter, Layer as _,
+};
 
 use self::{
-    commit::{Commit, CommitError},
-    helpers::NotInGitWorktree,
-    init::{Init, InitError},
+    commit::{
+        editor::EditorError, subject::SubjectError, ticket::TrailerError,
+        time_tracking::TimeTrackingError, Commit, CommitError,
+    },
+    config_command::ConfigCommand,
+    debug::{Debug, DebugError},
+    export::{Export, ExportError},
+    failure::Failure,
+    helpers::{change_dirs, ChangeDirError, NotInGitWorktree, OutdatedConfig},
+    hook::{Hook, HookError},
+    init::{sync::SyncError, Init, InitError},
+    lint::{Lint, LintError},
+    migrate_history::{MigrateHistory, MigrateHistoryError},
+    revspec::RevSpecError,
+    serve::Serve,
+    stats::Stats,
+    tickets::{Tickets, TicketsError},
+    undo::{Undo, UndoError},
     update::{Update, UpdateError},
+    verify_install::{VerifyInstall, VerifyInstallError},
+    verify_message::{VerifyMessage, VerifyMessageError},
 };
 use crate::{
+    commit_cache::{self, LockError},
     config::{self, updater, FromTomlError, CONFIG_FILE_NAME},
-    error, hint,
+    diagnostics::Diagnostics,
+    error,
+    git::GitError,
+    hint,
+    list_command::ListCommandError,
+    metrics::MetricsError,
+    min_version::MinVersionError,
+    staged::StagedError,
+    workspace::WorkspaceError,
 };
 
+/// The trace filter to apply to the exported trace.
+#[cfg(feature = "chrome-trace")]
+const TRACE_OUT_FILTER: &str = "git_z=trace";
+
 /// The long version information.
 const LONG_VERSION: &str = concat!(
     env!("CARGO_PKG_VERSION"),
@@ -65,9 +121,19 @@ pub struct GitZ {
     /// The command to run.
     #[command(subcommand)]
     command: GitZCommand,
+    /// Run as if git-z was started in the given directory.
+    #[arg(short = 'C', long = "directory", value_name = "PATH")]
+    directories: Vec<PathBuf>,
     /// The verbosity level.
     #[arg(short = 'v', action = ArgAction::Count, global = true)]
     verbosity: u8,
+    /// With `--version`, print diagnostics to include in bug reports.
+    #[arg(long, requires = "version")]
+    verbose: bool,
+    /// Export a trace in the Chrome format, to open in Perfetto.
+    #[cfg(feature = "chrome-trace")]
+    #[arg(long, value_name = "FILE", global = true)]
+    trace_out: Option<PathBuf>,
 }
 
 /// The subcommands of `git-z`.
@@ -79,6 +145,31 @@ pub enum GitZCommand {
     Commit(Commit),
     /// Updates the configuration.
     Update(Update),
+    /// Checks commit messages follow the convention.
+    Lint(Lint),
+    /// Rewrites the commits not following the convention.
+    MigrateHistory(MigrateHistory),
+    /// Exports the metadata of conventional commits as JSON or CSV.
+    Export(Export),
+    /// Lists the tickets referenced in a range of commits.
+    Tickets(Tickets),
+    /// Shows usage statistics.
+    Stats(Stats),
+    /// Undoes the last commit, keeping its changes and answers.
+    Undo(Undo),
+    /// Serves the wizard over JSON-RPC for editor integrations.
+    Serve(Serve),
+    /// Verifies git-z is properly installed, for onboarding scripts.
+    VerifyInstall(VerifyInstall),
+    /// Checks a message read from the standard input, for bots and servers.
+    VerifyMessage(VerifyMessage),
+    /// Runs server-side Git hooks, like `pre-receive`.
+    Hook(Hook),
+    /// Checks the configuration.
+    Config(ConfigCommand),
+    /// Helpers to diagnose the environment.
+    #[command(hide = true)]
+    Debug(Debug),
 }
 
 /// A command.
@@ -90,13 +181,48 @@ trait Command {
 impl GitZ {
     /// Runs git-z.
     pub fn run() -> Result<()> {
-        let args = Self::parse();
-        setup_tracing(args.verbosity);
+        let args = match Self::try_parse() {
+            Ok(args) => args,
+            Err(error)
+                if error.kind() == ErrorKind::DisplayVersion
+                    && verbose_version_requested() =>
+            {
+                print_verbose_version();
+                return Ok(());
+            }
+            Err(error) => error.exit(),
+        };
 
-        let result = match args.command {
-            GitZCommand::Init(init) => init.run(),
-            GitZCommand::Commit(commit) => commit.run(),
-            GitZCommand::Update(update) => update.run(),
+        let result = {
+            // NOTE: The guard must be dropped before handling the errors, as
+            // the process may exit there without running the destructors.
+            let _trace_guard = setup_tracing(&args)?;
+
+            change_dirs(&args.directories)
+                .map_err(Report::from)
+                .and_then(|()| match args.command {
+                    GitZCommand::Init(init) => init.run(),
+                    GitZCommand::Commit(commit) => commit.run(),
+                    GitZCommand::Update(update) => update.run(),
+                    GitZCommand::Lint(lint) => lint.run(),
+                    GitZCommand::MigrateHistory(migrate_history) => {
+                        migrate_history.run()
+                    }
+                    GitZCommand::Export(export) => export.run(),
+                    GitZCommand::Tickets(tickets) => tickets.run(),
+                    GitZCommand::Stats(stats) => stats.run(),
+                    GitZCommand::Undo(undo) => undo.run(),
+                    GitZCommand::Serve(serve) => serve.run(),
+                    GitZCommand::VerifyInstall(verify_install) => {
+                        verify_install.run()
+                    }
+                    GitZCommand::VerifyMessage(verify_message) => {
+                        verify_message.run()
+                    }
+                    GitZCommand::Hook(hook) => hook.run(),
+                    GitZCommand::Config(config) => config.run(),
+                    GitZCommand::Debug(debug) => debug.run(),
+                })
         };
 
         match result {
@@ -106,12 +232,76 @@ impl GitZ {
     }
 }
 
-/// Configures the tracing subscriber given the verbosity.
-fn setup_tracing(verbosity: u8) {
-    tracing_subscriber::fmt()
-        .with_env_filter(env_filter(verbosity))
-        .with_span_events(span_events(verbosity))
-        .init();
+/// Returns whether `--verbose` has been passed along `--version`.
+///
+/// Clap prints the version as soon as it reads `--version`, so the other
+/// arguments have to be checked by hand.
+fn verbose_version_requested() -> bool {
+    std::env::args_os().any(|arg| arg == "--verbose")
+}
+
+/// Prints the long version followed by the diagnostics.
+fn print_verbose_version() {
+    let version = GitZ::command().render_long_version();
+    let diagnostics = Diagnostics::gather().to_toml();
+    print!("{}\n\n{diagnostics}", version.trim_end());
+}
+
+/// A guard writing the end of the exported trace when dropped.
+struct TraceGuard {
+    /// The guard of the Chrome layer, if a trace is exported.
+    #[cfg(feature = "chrome-trace")]
+    _chrome: Option<FlushGuard>,
+}
+
+/// Configures the tracing subscriber given the arguments.
+///
+/// The logs are printed given the verbosity. With `--trace-out`, all the spans
+/// are also exported to the given file, whatever the verbosity.
+#[cfg_attr(
+    not(feature = "chrome-trace"),
+    expect(
+        clippy::unnecessary_wraps,
+        reason = "Only exporting a trace can fail."
+    )
+)]
+fn setup_tracing(args: &GitZ) -> Result<TraceGuard> {
+    let fmt_layer = tracing_subscriber::fmt::layer()
+        .with_span_events(span_events(args.verbosity))
+        .with_filter(EnvFilter::new(env_filter(args.verbosity)));
+
+    let registry = tracing_subscriber::registry().with(fmt_layer);
+
+    #[cfg(feature = "chrome-trace")]
+    let (registry, chrome_guard) = {
+        let (chrome_layer, chrome_guard) = match &args.trace_out {
+            Some(trace_out) => {
+                let file = File::create(trace_out).wrap_err_with(|| {
+                    format!("Failed to create {}", trace_out.display())
+                })?;
+
+                let (layer, guard) = ChromeLayerBuilder::new()
+                    .writer(file)
+                    .include_args(true)
+                    .build();
+
+                (
+                    Some(layer.with_filter(EnvFilter::new(TRACE_OUT_FILTER))),
+                    Some(guard),
+                )
+            }
+            None => (None, None),
+        };
+
+        (registry.with(chrome_layer), chrome_guard)
+    };
+
+    registry.init();
+
+    Ok(TraceGuard {
+        #[cfg(feature = "chrome-trace")]
+        _chrome: chrome_guard,
+    })
 }
 
 /// Returns the trace filter to apply given the verbosity.
@@ -136,8 +326,8 @@ fn span_events(verbosity: u8) -> FmtSpan {
 enum ErrorHandling {
     /// Return the report.
     Return(Report),
-    /// Exit the program with the given status code.
-    Exit(i32),
+    /// Exit the program with the status code of the failure.
+    Exit(Failure),
 }
 
 /// Handles typical usage errors to enhance their output.
@@ -145,6 +335,8 @@ fn handle_errors(error: Report) -> Result<()> {
     let handling = if let Some(error) = error.downcast_ref::<NotInGitWorktree>()
     {
         handle_not_in_git_worktree(error)
+    } else if let Some(error) = error.downcast_ref::<ChangeDirError>() {
+        handle_change_dir_error(error)
     } else if let Some(config::LoadError::InvalidConfig(error)) =
         error.downcast_ref::<config::LoadError>()
     {
@@ -153,55 +345,105 @@ fn handle_errors(error: Report) -> Result<()> {
         error.downcast_ref::<updater::LoadError>()
     {
         handle_from_toml_error(error)
+    } else if let Some(error) = error.downcast_ref::<OutdatedConfig>() {
+        handle_outdated_config(error)
+    } else if let Some(error) = error.downcast_ref::<ListCommandError>() {
+        handle_list_command_error(error)
+    } else if let Some(error) = error.downcast_ref::<WorkspaceError>() {
+        handle_workspace_error(error)
     } else if let Some(error) = error.downcast_ref::<InitError>() {
         handle_init_error(error)
+    } else if let Some(error) = error.downcast_ref::<SyncError>() {
+        handle_sync_error(error)
     } else if let Some(error) = error.downcast_ref::<CommitError>() {
         handle_commit_error(error)
+    } else if let Some(error) = error.downcast_ref::<EditorError>() {
+        handle_editor_error(error)
+    } else if let Some(error) = error.downcast_ref::<SubjectError>() {
+        handle_subject_error(error)
+    } else if let Some(error) = error.downcast_ref::<TrailerError>() {
+        handle_trailer_error(error)
+    } else if let Some(error) = error.downcast_ref::<TimeTrackingError>() {
+        handle_time_tracking_error(error)
     } else if let Some(error) = error.downcast_ref::<UpdateError>() {
         handle_update_error(error)
-    } else if let Some(InquireError::OperationCanceled) =
-        error.downcast_ref::<InquireError>()
-    {
-        ErrorHandling::Exit(exitcode::TEMPFAIL)
-    } else if let Some(InquireError::OperationInterrupted) =
-        error.downcast_ref::<InquireError>()
+    } else if let Some(error) = error.downcast_ref::<updater::RollbackError>() {
+        handle_rollback_error(error)
+    } else if let Some(error) = error.downcast_ref::<LintError>() {
+        handle_lint_error(error)
+    } else if let Some(error) = error.downcast_ref::<MigrateHistoryError>() {
+        handle_migrate_history_error(error)
+    } else if let Some(error) = error.downcast_ref::<ExportError>() {
+        handle_export_error(error)
+    } else if let Some(error) = error.downcast_ref::<LockError>() {
+        handle_lock_error(error)
+    } else if let Some(error) = error.downcast_ref::<MetricsError>() {
+        handle_metrics_error(error)
+    } else if let Some(error) = error.downcast_ref::<TicketsError>() {
+        handle_tickets_error(error)
+    } else if let Some(error) = error.downcast_ref::<UndoError>() {
+        handle_undo_error(error)
+    } else if let Some(error) = error.downcast_ref::<VerifyInstallError>() {
+        handle_verify_install_error(error)
+    } else if let Some(error) = error.downcast_ref::<VerifyMessageError>() {
+        handle_verify_message_error(error)
+    } else if let Some(error) = error.downcast_ref::<HookError>() {
+        handle_hook_error(error)
+    } else if let Some(error) = error.downcast_ref::<RevSpecError>() {
+        handle_revspec_error(error)
+    } else if let Some(error) = error.downcast_ref::<StagedError>() {
+        handle_staged_error(error)
+    } else if let Some(error) = error.downcast_ref::<GitError>() {
+        handle_git_error(&"Git has failed", error, Failure::Git(None))
+    } else if let Some(error) = error.downcast_ref::<DebugError>() {
+        handle_debug_error(error)
+    } else if let Some(
+        InquireError::OperationCanceled | InquireError::OperationInterrupted,
+    ) = error.downcast_ref::<InquireError>()
     {
-        ErrorHandling::Exit(exitcode::TEMPFAIL)
+        ErrorHandling::Exit(Failure::Aborted)
     } else {
         ErrorHandling::Return(error)
     };
 
     match handling {
         ErrorHandling::Return(error) => Err(error),
-        ErrorHandling::Exit(code) => {
+        ErrorHandling::Exit(failure) => {
             #[expect(
                 clippy::exit,
                 reason = "This function is purposefully written to handle \
                     errors, write a useful message and exit with an error \
                     code. This is the only place in the code where it is done."
             )]
-            std::process::exit(code);
+            std::process::exit(failure.exit_code());
         }
     }
 }
 
+/// Prints proper error messages when `-C` cannot be honoured.
+fn handle_change_dir_error(error: &ChangeDirError) -> ErrorHandling {
+    error!("{error}.");
+    hint!("The OS reports: {}.", error.source);
+    ErrorHandling::Exit(Failure::Usage)
+}
+
 /// Prints proper error messages when running `git-z` outside of a Git worktree.
 fn handle_not_in_git_worktree(error: &NotInGitWorktree) -> ErrorHandling {
     match error {
         NotInGitWorktree::CannotRunGit(os_error) => {
             error!("{error}.");
             hint!("The OS reports: {os_error}.");
-            ErrorHandling::Exit(exitcode::UNAVAILABLE)
+            ErrorHandling::Exit(Failure::GitUnavailable)
         }
         NotInGitWorktree::NotInRepo => {
             error!("{error}.");
             hint!("You can initialise a Git repository by running `git init`.");
-            ErrorHandling::Exit(exitcode::USAGE)
+            ErrorHandling::Exit(Failure::Usage)
         }
         NotInGitWorktree::NotInWorktree => {
             error!("{error}.");
             hint!("You seem to be inside a Git repository, but not in a worktree.");
-            ErrorHandling::Exit(exitcode::USAGE)
+            ErrorHandling::Exit(Failure::Usage)
         }
     }
 }
@@ -227,13 +469,89 @@ fn handle_from_toml_error(error: &FromTomlError) -> ErrorHandling {
                 `git z update` again.\
             "};
         }
-        FromTomlError::ParseError(parse_error) => {
+        FromTomlError::ParseError(diagnostic) => {
             error!("Invalid configuration in {CONFIG_FILE_NAME}.");
-            hint!("\n{parse_error}");
+            hint!("\n{diagnostic}\n");
+        }
+        FromTomlError::InvalidKey { diagnostic, .. } => {
+            error!("{error} in {CONFIG_FILE_NAME}.");
+            hint!("\n{diagnostic}\n");
+        }
+        FromTomlError::MinVersion(MinVersionError::TooOld { .. }) => {
+            error!("{error}.");
+            hint!("Please update git-z to use the {CONFIG_FILE_NAME} of this repository.");
+        }
+        FromTomlError::MinVersion(MinVersionError::Invalid(_)) => {
+            error!("{error} in {CONFIG_FILE_NAME}.");
+            hint!(
+                "The version must be made of numbers, like `0.3` or `0.3.1`."
+            );
+        }
+        FromTomlError::InvalidRegex { source, .. } => {
+            error!("{error} in {CONFIG_FILE_NAME}.");
+            hint!("\n{source}\n");
+        }
+    }
+
+    ErrorHandling::Exit(Failure::Config)
+}
+
+/// Prints proper error messages for an out-of-date configuration.
+fn handle_outdated_config(error: &OutdatedConfig) -> ErrorHandling {
+    error!("{error}.");
+    hint!("Please update it by running `git z update`.");
+
+    if !error.from_flag {
+        hint!(
+            "Committing is refused because `meta.strict_version` is enabled."
+        );
+    }
+
+    ErrorHandling::Exit(Failure::Config)
+}
+
+/// Prints proper error messages for list commands failures.
+fn handle_list_command_error(error: &ListCommandError) -> ErrorHandling {
+    error!("{error}.");
+
+    match error {
+        ListCommandError::CannotRun { source, .. } => {
+            hint!("The OS reports: {source}.");
+        }
+        ListCommandError::Failed { stderr, .. } if !stderr.is_empty() => {
+            hint!("The command reports: {stderr}");
+        }
+        ListCommandError::Failed { .. } => (),
+        ListCommandError::TimedOut { .. } => {
+            hint!("The command has been killed.");
+        }
+        ListCommandError::EncodingError { .. } => {
+            hint!("The command must print UTF-8 text.");
         }
     }
 
-    ErrorHandling::Exit(exitcode::CONFIG)
+    hint!("Please check the commands in your {CONFIG_FILE_NAME}.");
+    ErrorHandling::Exit(Failure::Config)
+}
+
+/// Prints proper error messages for workspace reading failures.
+fn handle_workspace_error(error: &WorkspaceError) -> ErrorHandling {
+    error!("{error}.");
+
+    match error {
+        WorkspaceError::CannotRead { source, .. } => {
+            hint!("The OS reports: {source}.");
+        }
+        WorkspaceError::InvalidManifest { reason, .. } => {
+            hint!("{reason}.");
+        }
+        WorkspaceError::InvalidPattern { source, .. } => {
+            hint!("The parser reports: {source}.");
+        }
+    }
+
+    hint!("Please check `scopes.from` in your {CONFIG_FILE_NAME}.");
+    ErrorHandling::Exit(Failure::Config)
 }
 
 /// Prints proper error messages for `git z init` usage errors.
@@ -242,29 +560,156 @@ fn handle_init_error(error: &InitError) -> ErrorHandling {
         InitError::ExistingConfig => {
             error!("{error}.");
             hint!("You can force the command by running `git z init -f`.");
+            ErrorHandling::Exit(Failure::CannotCreate)
+        }
+        InitError::CannotReadTypesFile { source, .. } => {
+            error!("{error}.");
+            hint!("The OS reports: {source}.");
+            ErrorHandling::Exit(Failure::Io)
+        }
+        InitError::NoTypes { .. } => {
+            error!("{error}.");
+            hint!(
+                "The file must contain one type per line, optionally followed \
+                by a tab and its description."
+            );
+            ErrorHandling::Exit(Failure::Data)
+        }
+        InitError::DuplicateType { .. } => {
+            error!("{error}.");
+            hint!("Each type must be listed only once.");
+            ErrorHandling::Exit(Failure::Data)
+        }
+        InitError::InvalidConfig(source) => {
+            error!("{error}.");
+            hint!("This is a bug in git-z, please report it. The parser reports: {source}.");
+            ErrorHandling::Exit(Failure::Config)
         }
     }
+}
 
-    ErrorHandling::Exit(exitcode::CANTCREAT)
+/// Prints proper error messages for errors when copying a configuration.
+fn handle_sync_error(error: &SyncError) -> ErrorHandling {
+    match error {
+        SyncError::NoConfig { .. } => {
+            error!("{error}.");
+            hint!("Please check the path or URL passed to `--sync-from`.");
+            ErrorHandling::Exit(Failure::Usage)
+        }
+        SyncError::CannotRead { source, .. } => {
+            error!("{error}.");
+            hint!("The OS reports: {source}.");
+            ErrorHandling::Exit(Failure::Io)
+        }
+        SyncError::Git(git_error) => {
+            handle_git_error(error, git_error, Failure::Git(None))
+        }
+        SyncError::CannotClone { stderr, .. } => {
+            error!("{error}.");
+            if !stderr.is_empty() {
+                hint!("Git reports: {stderr}");
+            }
+            ErrorHandling::Exit(Failure::Git(None))
+        }
+        SyncError::InvalidConfig { source, .. } => {
+            error!("{error}.");
+            handle_from_toml_error(source)
+        }
+        SyncError::CannotEdit { source, .. } => {
+            error!("{error}.");
+            hint!("\n{source}");
+            ErrorHandling::Exit(Failure::Config)
+        }
+    }
 }
 
 /// Prints proper error messages for `git z commit` usage errors.
+#[expect(
+    clippy::too_many_lines,
+    reason = "This is a flat dispatch over the error variants."
+)]
 fn handle_commit_error(error: &CommitError) -> ErrorHandling {
     match error {
-        #[cfg(feature = "unstable-pre-commit")]
         CommitError::CannotRunPreCommit(os_error) => {
             error!("{error}.");
             hint!("The OS reports: {os_error}.");
-            ErrorHandling::Exit(exitcode::UNAVAILABLE)
+            ErrorHandling::Exit(Failure::GitUnavailable)
         }
-        #[cfg(feature = "unstable-pre-commit")]
         CommitError::PreCommitFailed => {
             error!("{error}.");
             // NOTE: Use 1 as exit code to maintain the same behaviour as Git.
-            ErrorHandling::Exit(1)
+            ErrorHandling::Exit(Failure::CheckFailed)
+        }
+        CommitError::PreCommitTimeout { .. } => {
+            error!("{error}.");
+            hint!(
+                "You can raise `hooks.timeout` in the configuration, or skip \
+                the hook with `--no-verify`."
+            );
+            ErrorHandling::Exit(Failure::CheckFailed)
+        }
+        CommitError::UnstagedLeftover { patch }
+        | CommitError::UnstagedConflict { patch } => {
+            error!("{error}.");
+            hint! {"
+                They are saved in {patch}. Restore them by running
+                `git apply --3way {patch}`, then delete the patch.\
+            ", patch = patch.display()};
+            ErrorHandling::Exit(Failure::Git(None))
+        }
+        CommitError::TooLarge => {
+            error!("{error}.");
+            ErrorHandling::Exit(Failure::CheckFailed)
+        }
+        CommitError::NothingToAmend => {
+            error!("{error}.");
+            ErrorHandling::Exit(Failure::Usage)
+        }
+        CommitError::UnresolvedConflicts { .. } => {
+            error!("{error}.");
+            hint!(
+                "Please resolve them and stage the result with `git add` \
+                before committing."
+            );
+            ErrorHandling::Exit(Failure::Busy)
+        }
+        CommitError::NotATerminal => {
+            error!("{error}.");
+            hint! {"
+                To commit without a terminal, pass the answers on the command line:
+
+                    git z commit --non-interactive --type <type> --description <description>
+
+                You can also pass `--plain` to read the answers line by line.
+            "};
+            ErrorHandling::Exit(Failure::Usage)
+        }
+        CommitError::InvalidAnswers { violations } => {
+            error!("{error}.");
+            let violations = violations
+                .iter()
+                .map(|violation| format!("  - {violation}"))
+                .join("\n");
+            hint!("{violations}\n");
+            ErrorHandling::Exit(Failure::Data)
+        }
+        CommitError::SecretsFound => {
+            error!("{error}.");
+            hint!("If these are false positives, you can commit anyway by running `git z commit --allow-secrets`.");
+            ErrorHandling::Exit(Failure::CheckFailed)
         }
         CommitError::Git { status_code } => {
-            ErrorHandling::Exit(status_code.unwrap_or(1_i32))
+            ErrorHandling::Exit(Failure::Git(*status_code))
+        }
+        CommitError::UnknownTemplate { available, .. } => {
+            error!("{error}.");
+            if available.is_empty() {
+                hint!("No named template is defined in `templates.named` in your {CONFIG_FILE_NAME}.");
+            } else {
+                let available = available.join(", ");
+                hint!("The available templates are: {available}.");
+            }
+            ErrorHandling::Exit(Failure::Usage)
         }
         CommitError::Template(tera_error) => {
             error!("{tera_error} from the configuration.");
@@ -273,7 +718,25 @@ fn handle_commit_error(error: &CommitError) -> ErrorHandling {
                 hint!("\n{parse_error}\n");
             }
 
-            ErrorHandling::Exit(exitcode::CONFIG)
+            ErrorHandling::Exit(Failure::Config)
+        }
+        CommitError::InvalidRenderedMessage {
+            first_line,
+            error: parse_error,
+        } => {
+            error!("{error}.");
+            hint!("{parse_error}:\n\n    {first_line}\n");
+            hint!(
+                "Please check `templates.commit` in your {CONFIG_FILE_NAME}."
+            );
+            ErrorHandling::Exit(Failure::Config)
+        }
+        CommitError::LeadingBlankLine => {
+            error!("{error}.");
+            hint!(
+                "Please check `templates.commit` in your {CONFIG_FILE_NAME}."
+            );
+            ErrorHandling::Exit(Failure::Config)
         }
     }
 }
@@ -299,5 +762,472 @@ fn handle_update_error(error: &UpdateError) -> ErrorHandling {
         }
     }
 
-    ErrorHandling::Exit(exitcode::CONFIG)
+    ErrorHandling::Exit(Failure::Config)
+}
+
+/// Prints proper error messages for `git z update --rollback` errors.
+fn handle_rollback_error(error: &updater::RollbackError) -> ErrorHandling {
+    error!("{error}.");
+
+    match error {
+        updater::RollbackError::ConfigFileError(_)
+        | updater::RollbackError::GitZDir(_) => {
+            ErrorHandling::Exit(Failure::Git(None))
+        }
+        updater::RollbackError::NoBackup => {
+            hint!("A backup is written each time `git z update` saves {CONFIG_FILE_NAME}.");
+            ErrorHandling::Exit(Failure::Usage)
+        }
+        updater::RollbackError::ReadDir(source)
+        | updater::RollbackError::Restore(source)
+        | updater::RollbackError::Delete(source) => {
+            hint!("The OS reports: {source}.");
+            ErrorHandling::Exit(Failure::Io)
+        }
+    }
+}
+
+/// Prints proper error messages for `git z lint` usage errors.
+fn handle_lint_error(error: &LintError) -> ErrorHandling {
+    match error {
+        LintError::InvalidCommits { count } => {
+            error!("{error}.");
+            ErrorHandling::Exit(Failure::Violations(*count))
+        }
+        LintError::CannotReadMessageFile { source, .. } => {
+            error!("{error}.");
+            hint!("The OS reports: {source}.");
+            ErrorHandling::Exit(Failure::Io)
+        }
+        LintError::InvalidMessage => {
+            error!("{error}.");
+            ErrorHandling::Exit(Failure::Violations(1))
+        }
+        LintError::CannotReadRepos { source, .. }
+        | LintError::CannotReadCommonConfig { source, .. } => {
+            error!("{error}.");
+            hint!("The OS reports: {source}.");
+            ErrorHandling::Exit(Failure::Io)
+        }
+        LintError::InvalidReposPattern { .. } => {
+            error!("{error}.");
+            hint!("Pass a file listing the repositories or a glob pattern.");
+            ErrorHandling::Exit(Failure::Usage)
+        }
+        LintError::NoRepos { .. } => {
+            error!("{error}.");
+            hint!("Check the path of the file or the glob pattern.");
+            ErrorHandling::Exit(Failure::Usage)
+        }
+        LintError::CannotStartThreads(source) => {
+            error!("{error}.");
+            hint!("The OS reports: {source}.");
+            ErrorHandling::Exit(Failure::Io)
+        }
+        LintError::FailedRepos { .. } => {
+            error!("{error}.");
+            hint!("See the summary above for the reason of each failure.");
+            ErrorHandling::Exit(Failure::Data)
+        }
+    }
+}
+
+/// Prints proper error messages for `git z migrate-history` usage errors.
+fn handle_migrate_history_error(error: &MigrateHistoryError) -> ErrorHandling {
+    match error {
+        MigrateHistoryError::CommitInProgress => {
+            error!("{error}.");
+            hint!("Please finish it with `git z commit` before rewriting the history.");
+            ErrorHandling::Exit(Failure::Busy)
+        }
+        MigrateHistoryError::DirtyWorktree => {
+            error!("{error}.");
+            hint!("Please commit or stash them before rewriting the history.");
+            ErrorHandling::Exit(Failure::Busy)
+        }
+        MigrateHistoryError::NotInCurrentBranch { .. } => {
+            error!("{error}.");
+            hint!("Only the history of the current branch can be rewritten.");
+            ErrorHandling::Exit(Failure::Usage)
+        }
+        MigrateHistoryError::MergeInHistory => {
+            error!("{error}.");
+            hint!("Rewriting a history containing merge commits is not supported.");
+            ErrorHandling::Exit(Failure::Usage)
+        }
+        MigrateHistoryError::Git(git_error) => {
+            handle_git_error(error, git_error, Failure::Git(None))
+        }
+        MigrateHistoryError::WriteError(io_error) => {
+            error!("{error}.");
+            hint!("The OS reports: {io_error}.");
+            ErrorHandling::Exit(Failure::Io)
+        }
+        MigrateHistoryError::RebaseFailed => {
+            error!("{error}.");
+            hint! {"
+                You can inspect the situation with `git status`, then either fix
+                it and run `git rebase --continue`, or run `git rebase --abort` to
+                get back to the original history.\
+            "};
+            ErrorHandling::Exit(Failure::CheckFailed)
+        }
+    }
+}
+
+/// Prints proper error messages for `git z export` failures.
+fn handle_export_error(error: &ExportError) -> ErrorHandling {
+    match error {
+        ExportError::CannotWrite(source) => {
+            error!("{error}.");
+            hint!("The OS reports: {source}.");
+            ErrorHandling::Exit(Failure::Io)
+        }
+    }
+}
+
+/// Prints proper error messages for `git z tickets` errors.
+fn handle_tickets_error(error: &TicketsError) -> ErrorHandling {
+    match error {
+        TicketsError::NoTicketConfig => {
+            error!("{error}.");
+            hint!("Please add a [ticket] table to your {CONFIG_FILE_NAME}.");
+            ErrorHandling::Exit(Failure::Config)
+        }
+        TicketsError::NoStatusCommand => {
+            error!("{error}.");
+            hint! {"
+                Please set `status_command` in the [ticket] table of your
+                {CONFIG_FILE_NAME}, to print the state of the ticket in
+                `GITZ_TICKET`.
+            "};
+            ErrorHandling::Exit(Failure::Config)
+        }
+        TicketsError::OpenTickets { .. }
+        | TicketsError::UnknownStates { .. } => {
+            error!("{error}.");
+            ErrorHandling::Exit(Failure::CheckFailed)
+        }
+    }
+}
+
+/// Prints proper error messages for usage metrics errors.
+fn handle_metrics_error(error: &MetricsError) -> ErrorHandling {
+    error!("{error}.");
+
+    match error {
+        MetricsError::GitZDir(_) => ErrorHandling::Exit(Failure::Git(None)),
+        MetricsError::Io(source) => {
+            hint!("The OS reports: {source}.");
+            ErrorHandling::Exit(Failure::Io)
+        }
+        MetricsError::Parse(_) | MetricsError::Serialize(_) => {
+            hint!("You can delete it to start counting again.");
+            ErrorHandling::Exit(Failure::Data)
+        }
+    }
+}
+
+/// Prints proper error messages for commit cache lock errors.
+fn handle_lock_error(error: &LockError) -> ErrorHandling {
+    error!("{error}.");
+
+    match error {
+        LockError::GitZDir(_) => ErrorHandling::Exit(Failure::Git(None)),
+        LockError::CreateDir(source)
+        | LockError::Open(source)
+        | LockError::Lock(source) => {
+            hint!("The OS reports: {source}.");
+            ErrorHandling::Exit(Failure::Io)
+        }
+        LockError::AlreadyLocked => {
+            hint!("Please wait for the other git z command to finish.");
+
+            if let Ok(lock_file) = commit_cache::lock_file() {
+                hint!(
+                    "If none is running, the lock is stale: you can remove \
+                    {}.",
+                    lock_file.display()
+                );
+            }
+
+            ErrorHandling::Exit(Failure::Busy)
+        }
+    }
+}
+
+/// Prints proper error messages for `git z verify-install` errors.
+fn handle_verify_install_error(error: &VerifyInstallError) -> ErrorHandling {
+    match error {
+        VerifyInstallError::ChecksFailed { .. } => {
+            error!("{error}.");
+            ErrorHandling::Exit(Failure::CheckFailed)
+        }
+    }
+}
+
+/// Prints proper error messages for `git z verify-message` errors.
+fn handle_verify_message_error(error: &VerifyMessageError) -> ErrorHandling {
+    match error {
+        VerifyMessageError::CannotReadMessage(source)
+        | VerifyMessageError::CannotReadConfig { source, .. } => {
+            error!("{error}.");
+            hint!("The OS reports: {source}.");
+            ErrorHandling::Exit(Failure::Io)
+        }
+        VerifyMessageError::InvalidMessage => {
+            error!("{error}.");
+            ErrorHandling::Exit(Failure::Violations(1))
+        }
+        VerifyMessageError::UnverifiableMessage => {
+            error!("{error}.");
+            hint!(
+                "The list commands and workspaces are not read by `git z \
+                    verify-message`."
+            );
+            ErrorHandling::Exit(Failure::Violations(1))
+        }
+    }
+}
+
+/// Prints proper error messages for `git z hook` errors.
+fn handle_hook_error(error: &HookError) -> ErrorHandling {
+    match error {
+        HookError::CannotReadUpdates(source)
+        | HookError::CannotReadConfig { source, .. } => {
+            error!("{error}.");
+            hint!("The OS reports: {source}.");
+            ErrorHandling::Exit(Failure::Io)
+        }
+        HookError::InvalidRefUpdate { .. } => {
+            error!("{error}.");
+            hint!(
+                "The hook expects `<old> <new> <ref>` lines, as sent by Git."
+            );
+            ErrorHandling::Exit(Failure::Usage)
+        }
+        HookError::InvalidCommits { count } => {
+            error!("{error}.");
+            hint! {"
+                Please reword them, for instance with `git z migrate-history`,
+                then push again.
+            "};
+            ErrorHandling::Exit(Failure::Violations(*count))
+        }
+    }
+}
+
+/// Prints proper error messages for `git z undo` usage errors.
+fn handle_undo_error(error: &UndoError) -> ErrorHandling {
+    match error {
+        UndoError::CommitInProgress => {
+            error!("{error}.");
+            hint!("Please finish it with `git z commit` before undoing the last one.");
+            ErrorHandling::Exit(Failure::Busy)
+        }
+        UndoError::NoCommit => {
+            error!("{error}.");
+            ErrorHandling::Exit(Failure::Usage)
+        }
+        UndoError::RootCommit => {
+            error!("{error}.");
+            hint!("It has no parent to go back to.");
+            ErrorHandling::Exit(Failure::Usage)
+        }
+        UndoError::MergeCommit => {
+            error!("{error}.");
+            hint!("Undoing a merge commit is not supported.");
+            ErrorHandling::Exit(Failure::Usage)
+        }
+        UndoError::Git(git_error) => {
+            handle_git_error(error, git_error, Failure::Git(None))
+        }
+    }
+}
+
+/// Prints proper error messages for errors when selecting a range of commits.
+fn handle_revspec_error(error: &RevSpecError) -> ErrorHandling {
+    match error {
+        RevSpecError::Git(git_error) => {
+            handle_git_error(error, git_error, Failure::Usage)
+        }
+        RevSpecError::NoTag => {
+            error!("{error}.");
+            hint!("You can select the range of commits explicitly instead of using `--since-tag`.");
+            ErrorHandling::Exit(Failure::Usage)
+        }
+    }
+}
+
+/// Prints proper error messages for errors when reading the staged changes.
+fn handle_staged_error(error: &StagedError) -> ErrorHandling {
+    match error {
+        StagedError::Git(git_error) => {
+            handle_git_error(error, git_error, Failure::Git(None))
+        }
+    }
+}
+
+/// Prints proper error messages for errors when running Git.
+///
+/// The context describes what git-z was doing. It is printed when Git fails,
+/// which then exits with the given failure.
+fn handle_git_error(
+    context: &dyn Display,
+    error: &GitError,
+    failure: Failure,
+) -> ErrorHandling {
+    match error {
+        GitError::CannotRun(os_error) => {
+            error!("{error}.");
+            hint!("The OS reports: {os_error}.");
+            ErrorHandling::Exit(Failure::GitUnavailable)
+        }
+        GitError::Failed(git_error) => {
+            error!("{context}.");
+            hint!("Git reports: {git_error}");
+            ErrorHandling::Exit(failure)
+        }
+        GitError::Encoding(_) => ErrorHandling::Exit(Failure::Data),
+    }
+}
+
+/// Prints proper error messages for subject decoration failures.
+fn handle_subject_error(error: &SubjectError) -> ErrorHandling {
+    match error {
+        SubjectError::Empty => {
+            error!("{error}.");
+            hint!(
+                "Please check `templates.subject` in your {CONFIG_FILE_NAME}."
+            );
+            ErrorHandling::Exit(Failure::Config)
+        }
+        SubjectError::MultipleLines { subject } => {
+            error!("{error}:\n\n    {subject}\n");
+            hint!(
+                "Please check `templates.subject` in your {CONFIG_FILE_NAME}."
+            );
+            ErrorHandling::Exit(Failure::Config)
+        }
+        SubjectError::TooLong { subject, .. } => {
+            error!("{error}:\n\n    {subject}\n");
+            hint!("Please use a shorter description.");
+            ErrorHandling::Exit(Failure::Data)
+        }
+    }
+}
+
+/// Prints proper error messages for ticket trailer errors.
+fn handle_trailer_error(error: &TrailerError) -> ErrorHandling {
+    match error {
+        TrailerError::InvalidToken { .. } => {
+            error!("{error}.");
+            hint!(
+                "Please set `ticket.trailer` in your {CONFIG_FILE_NAME} to a \
+                token made of letters, digits and hyphens, like `Refs`."
+            );
+            ErrorHandling::Exit(Failure::Config)
+        }
+    }
+}
+
+/// Prints proper error messages for time tracking errors.
+fn handle_time_tracking_error(error: &TimeTrackingError) -> ErrorHandling {
+    match error {
+        TimeTrackingError::InvalidToken { .. } => {
+            error!("{error}.");
+            hint!(
+                "Please set `time_tracking.trailer` in your {CONFIG_FILE_NAME} \
+                to a token made of letters, digits and hyphens, like \
+                `Time-spent`."
+            );
+            ErrorHandling::Exit(Failure::Config)
+        }
+    }
+}
+
+/// Prints proper error messages for editor errors.
+fn handle_editor_error(error: &EditorError) -> ErrorHandling {
+    match error {
+        EditorError::NoEditor => {
+            error!("{error}.");
+            hint!(
+                "You can set one with `git config --global core.editor \
+                <editor>`, or in the `GIT_EDITOR` environment variable."
+            );
+            ErrorHandling::Exit(Failure::Config)
+        }
+        EditorError::CannotRunGit(os_error)
+        | EditorError::CannotRunEditor {
+            source: os_error, ..
+        } => {
+            error!("{error}.");
+            hint!("The OS reports: {os_error}.");
+            ErrorHandling::Exit(Failure::GitUnavailable)
+        }
+        EditorError::EditorFailed { .. } => {
+            error!("{error}.");
+            ErrorHandling::Exit(Failure::CheckFailed)
+        }
+    }
+}
+
+/// Prints proper error messages for `git z debug` usage errors.
+fn handle_debug_error(error: &DebugError) -> ErrorHandling {
+    match error {
+        DebugError::ReadMessage(io_error) => {
+            error!("{error}.");
+            hint!("The OS reports: {io_error}.");
+            ErrorHandling::Exit(Failure::Io)
+        }
+        DebugError::InvalidMessage(parse_error) => {
+            error!("{error}.");
+            hint!("{parse_error}.");
+            ErrorHandling::Exit(Failure::Data)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    // NOTE: Features can add global arguments, so the help is snapshotted with
+    // the default features only.
+    #[cfg(not(feature = "chrome-trace"))]
+    #[test]
+    fn renders_the_help() {
+        // Use a fixed width so that the snapshots do not depend on the terminal.
+        let mut command = GitZ::command().term_width(80);
+        command.build();
+
+        insta::assert_snapshot!(command.render_long_help().to_string());
+
+        for subcommand in command.get_subcommands_mut() {
+            if subcommand.is_hide_set() || subcommand.get_name() == "help" {
+                continue;
+            }
+
+            insta::assert_snapshot!(
+                format!("help_{}", subcommand.get_name()),
+                subcommand.render_long_help().to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn renders_the_long_version() {
+        insta::with_settings!({
+            filters => vec![
+                (r"^git-z .*", "git-z [VERSION]"),
+                (r"(?m)^(revision|features|target|profile|built by): .*$", "$1: [REDACTED]"),
+            ],
+        }, {
+            insta::assert_snapshot!(
+                GitZ::command().render_long_version().to_string()
+            );
+        });
+    }
 }
@@ -15,68 +15,284 @@
 
 //! The `commit` subcommand.
 
-use std::{fs, path::PathBuf, process::Command};
+pub mod context;
+mod diff;
+pub mod editor;
+mod hooks;
+mod operation;
+mod plain;
+mod select;
+mod signing;
+mod stash;
+pub mod subject;
+mod template;
+mod terminal;
+mod text;
+pub mod ticket;
+pub mod time_tracking;
+mod tui;
+
+use std::{
+    cell::Cell,
+    env, fs,
+    io::{self, IsTerminal as _},
+    mem, panic,
+    path::PathBuf,
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
 
 use clap::Parser;
+use colored::Colorize as _;
 use eyre::{eyre, Context as _, Result};
 use indexmap::IndexMap;
-use inquire::{validator::Validation, Confirm, CustomUserError, Select, Text};
+use inquire::{validator::Validation, Confirm, CustomUserError, InquireError};
+use is_executable::IsExecutable as _;
 use itertools::Itertools as _;
 use regex::Regex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tera::{Context, Tera};
 use thiserror::Error;
 
 use crate::{
-    command::helpers::load_config,
-    commit_cache::{CommitCache, WizardState},
-    config::{Config, Scopes, Ticket},
+    breaking_change,
+    command::helpers::{complete_lists, load_config_file, OnOutdated},
+    commit_cache::{CommitCache, WizardAnswers, WizardState},
+    config::{
+        repo_root, BreakingChangeRule, Config, Question, ScopeList, ScopeOrder,
+        Scopes, Severity, Step, Suggestions, Ticket, Type, TypeOrder,
+    },
+    conventional_commit::{footer_token, ConventionalCommit, ParseError},
+    description_suggestion, git,
+    helpers::{display_width, pad},
+    hint, history,
+    issue_title::{self, IssueTitle},
+    lint::{
+        self, ticket_regex, Violation, MAX_DESCRIPTION_LEN, MIN_DESCRIPTION_LEN,
+    },
+    list_command::{self, ListCommandError},
+    metrics,
+    prefetch::Prefetch,
+    reword, scope_frequency, scope_paths, secrets, sections,
+    staged::{self, StagedChanges, StagedPatch},
     tracing::LogResult as _,
+    type_list, type_suggestion, warning,
+};
+
+use self::{
+    context::{CommitContext, StagedDiff},
+    operation::Operation,
+    stash::Stash,
+    tui::{Form, Outcome},
 };
 
 use super::helpers::ensure_in_git_worktree;
 
-#[cfg(feature = "unstable-pre-commit")]
-use std::{env, io};
+/// The size of a page in the terminal.
+const PAGE_SIZE: usize = 15;
+
+/// The number of commits to look at for the recently used types.
+const RECENT_COMMITS: usize = 100;
 
-#[cfg(feature = "unstable-pre-commit")]
-use is_executable::IsExecutable as _;
+/// The token of the footer closing the ticket fixed with `--fixes`.
+const CLOSING_TOKEN: &str = "Fixes";
 
-#[cfg(feature = "unstable-pre-commit")]
-use crate::warning;
+/// The type prefilled by `--fixes`, if it is configured.
+const FIX_TYPE: &str = "fix";
 
-/// The size of a page in the terminal.
-const PAGE_SIZE: usize = 15;
+/// The token of the trailer added by `commit.tool_footer`.
+const TOOL_FOOTER_TOKEN: &str = "Generated-by";
+
+/// The interval at which the end of the pre-commit hook is checked.
+const HOOK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The time given to the suggestion commands before they are killed.
+const SUGGESTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The number of lines shown in the preview of a message to reuse.
+const REUSE_PREVIEW_LINES: usize = 3;
+
+/// The maximum length of a line in the preview of a message to reuse.
+const REUSE_PREVIEW_LINE_LENGTH: usize = 50;
+
+/// The steps asked for a merge commit, which only build the subject line.
+const MERGE_STEPS: [Step; 3] = [Step::Type, Step::Scope, Step::Ticket];
 
 /// The commit command.
 #[derive(Debug, Parser)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "These are independent command-line flags."
+)]
 pub struct Commit {
     /// Print the commit message instead of calling `git commit`.
     #[arg(long)]
     print_only: bool,
-    /// Do not run the pre-commit hook.
-    #[cfg(feature = "unstable-pre-commit")]
-    #[arg(long, short = 'n')]
+    /// Do not open the commit message in an editor [default: `commit.edit`].
+    #[arg(long, overrides_with = "edit")]
+    no_edit: bool,
+    /// Open the commit message in an editor, even if `commit.edit` is false
+    /// or with `--print-only`.
+    #[arg(long, overrides_with = "no_edit")]
+    edit: bool,
+    /// Amend the last commit, prefilling the wizard from its message.
+    #[arg(long)]
+    amend: bool,
+    /// Commit even if secrets are detected in the staged changes.
+    #[arg(long)]
+    allow_secrets: bool,
+    /// Answer all the questions at once in a full-screen form, where Ctrl-D
+    /// commits right away with the current answers.
+    #[arg(long, conflicts_with = "plain")]
+    tui: bool,
+    /// Use plain prompts, for screen readers and dumb terminals.
+    #[arg(long)]
+    plain: bool,
+    /// Use a named template from `templates.named` [default: selected by
+    /// `templates.branch_overrides`].
+    #[arg(long, value_name = "NAME")]
+    template: Option<String>,
+    /// Do not ask anything, taking the answers from the command line.
+    #[arg(
+        long,
+        conflicts_with_all = ["tui", "plain"],
+        requires = "type",
+        requires = "description"
+    )]
+    non_interactive: bool,
+    /// The type of commit, with `--non-interactive`.
+    #[arg(long = "type", requires = "non_interactive")]
+    r#type: Option<String>,
+    /// The scope of the commit, with `--non-interactive`.
+    #[arg(long, requires = "non_interactive")]
+    scope: Option<String>,
+    /// The short description, with `--non-interactive`.
+    #[arg(long, requires = "non_interactive")]
+    description: Option<String>,
+    /// The description of the breaking change, with `--non-interactive`.
+    #[arg(long, requires = "non_interactive")]
+    breaking_change: Option<String>,
+    /// The linked ticket, with `--non-interactive`.
+    #[arg(long, requires = "non_interactive")]
+    ticket: Option<String>,
+    /// Fix a ticket: prefill the wizard with its reference and title, and close
+    /// it with a `Fixes` footer.
+    #[arg(
+        long,
+        value_name = "TICKET",
+        conflicts_with_all = ["non_interactive", "amend"],
+    )]
+    fixes: Option<String>,
+    /// After committing, offer to make more commits, keeping the scope and
+    /// ticket.
+    #[arg(
+        long,
+        conflicts_with_all = ["print_only", "amend", "non_interactive"]
+    )]
+    keep_wizard_open: bool,
+    /// Refuse to commit with an out-of-date configuration [default:
+    /// `meta.strict_version`].
+    #[arg(long)]
+    strict: bool,
+    /// Bypass the pre-commit and commit-msg hooks, like `git commit`.
+    #[arg(long, short = 'n', overrides_with = "verify")]
     no_verify: bool,
+    /// Run the hooks, overriding a previous `--no-verify`.
+    #[arg(long, overrides_with = "no_verify")]
+    verify: bool,
+    /// Run the pre-commit hook with the unstaged changes stashed.
+    ///
+    /// git-z then runs the hook itself, even without `hooks.run_pre_commit`.
+    #[arg(long, conflicts_with = "no_verify")]
+    stash_unstaged: bool,
     /// Extra arguments to be passed to `git commit`.
     #[arg(last = true)]
     extra_args: Vec<String>,
+    /// Whether git-z has run the pre-commit hook itself.
+    #[arg(skip)]
+    pre_commit_has_run: Cell<bool>,
 }
 
 /// Usage errors of `git z commit`.
 #[derive(Debug, Error)]
 pub enum CommitError {
     /// The pre-commit hook could not be run.
-    #[cfg(feature = "unstable-pre-commit")]
     #[error("Failed to run the pre-commit hook")]
     CannotRunPreCommit(#[source] io::Error),
     /// The pre-commit hook has failed.
-    #[cfg(feature = "unstable-pre-commit")]
     #[error("The pre-commit hook has failed")]
     PreCommitFailed,
+    /// The pre-commit hook has not finished before the timeout.
+    #[error("The pre-commit hook has timed out after {}s", timeout.as_secs())]
+    PreCommitTimeout {
+        /// The configured timeout.
+        timeout: Duration,
+    },
+    /// Unstaged changes from a previous run have not been restored.
+    #[error("Unstaged changes from a previous run have not been restored")]
+    UnstagedLeftover {
+        /// The patch containing the unstaged changes.
+        patch: PathBuf,
+    },
+    /// The unstaged changes conflict with the changes made by the pre-commit
+    /// hook.
+    #[error(
+        "The unstaged changes conflict with the changes made by the \
+            pre-commit hook"
+    )]
+    UnstagedConflict {
+        /// The patch containing the unstaged changes.
+        patch: PathBuf,
+    },
+    /// The staged changes are larger than the configured limits.
+    #[error("The staged changes are too large")]
+    TooLarge,
+    /// Secrets have been detected in the staged changes.
+    #[error("The staged changes seem to contain secrets")]
+    SecretsFound,
+    /// There is no commit to amend.
+    #[error("There is no commit to amend")]
+    NothingToAmend,
+    /// An operation in progress has unresolved conflicts.
+    #[error("The {operation} in progress has unresolved conflicts")]
+    UnresolvedConflicts {
+        /// The operation in progress.
+        operation: Operation,
+    },
+    /// The standard input is not a terminal, so the wizard cannot be run.
+    #[error("The standard input is not a terminal")]
+    NotATerminal,
+    /// The answers given on the command line produce an invalid message.
+    #[error("The answers do not produce a valid commit message")]
+    InvalidAnswers {
+        /// The violations of the convention.
+        violations: Vec<String>,
+    },
+    /// The selected named template does not exist.
+    #[error("There is no template named `{name}`")]
+    UnknownTemplate {
+        /// The name of the template.
+        name: String,
+        /// The names of the available templates.
+        available: Vec<String>,
+    },
     /// The commit template is invalid.
     #[error("Failed to parse the commit template")]
     Template(#[source] tera::Error),
+    /// The commit template does not produce a valid conventional commit.
+    #[error("The commit template produces an invalid conventional commit")]
+    InvalidRenderedMessage {
+        /// The first line of the rendered message.
+        first_line: String,
+        /// The parse error.
+        #[source]
+        error: ParseError,
+    },
+    /// The commit template renders a blank line before the subject.
+    #[error("The commit template renders a blank line before the subject")]
+    LeadingBlankLine,
     /// Git has returned an error.
     #[error("Git has returned an error")]
     Git {
@@ -85,9 +301,27 @@ pub enum CommitError {
     },
 }
 
-/// A conventional commit message.
+/// The user interface of the wizard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WizardUi {
+    /// Ask the questions one after the other.
+    Prompts,
+    /// Ask all the questions at once in a full-screen form.
+    Tui,
+    /// Ask the questions one after the other, without redrawing anything.
+    Plain,
+}
+
+/// The part of the configuration exposed to the commit template.
 #[derive(Debug, Serialize)]
-struct CommitMessage {
+struct TemplateConfig<'a> {
+    /// The ticket reference configuration.
+    ticket: Option<&'a Ticket>,
+}
+
+/// A conventional commit message.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommitMessage {
     /// The type of commit.
     r#type: String,
     /// The optional scope of the commit.
@@ -106,38 +340,226 @@ impl super::Command for Commit {
         tracing::info!(params = ?self, "running commit");
 
         ensure_in_git_worktree()?;
+        self.ensure_can_ask()?;
+        let _lock = CommitCache::lock()?;
+
+        let operation = Operation::detect()?;
+        let prepared_message = self.prepared_message(operation)?;
 
-        let config = load_config()?;
+        let (mut config, staged_patch) = self.load_config_and_staged_patch()?;
+        let merge_mode = self.template.is_none()
+            && operation == Some(Operation::Merge)
+            && prepared_message.is_some()
+            && config.templates.merge.is_some();
 
-        #[cfg(feature = "unstable-pre-commit")]
-        if !self.no_verify {
-            run_pre_commit_hook()?;
+        if merge_mode {
+            template::select_merge(&mut config);
+        } else {
+            template::select(&mut config, self.template.as_deref())?;
         }
 
-        let commit_message = make_commit_message(&config)?;
+        let ui = (!self.non_interactive).then(|| self.ui());
+        self.check_staged_changes(&config, &staged_patch, ui)?;
 
-        if self.print_only {
-            tracing::debug!("printing the commit message");
-            println!("{commit_message}");
+        if self.non_interactive {
+            let commit_message = self
+                .make_message_from_args(&config, prepared_message.as_deref())?;
+            self.print_or_commit(&config, &commit_message, &self.extra_args)?;
+
+            tracing::info!("commit success!");
+            return Ok(());
+        }
+
+        if !self.print_only
+            && !self.amend
+            && operation.is_none()
+            && self.split_by_scope(&config)?
+        {
+            return Ok(());
+        }
+
+        let mut cache = CommitCache::load()?;
+        let mut context = CommitContext::with_staged_patch(staged_patch);
+        self.commit_from_wizard(
+            &config,
+            &mut cache,
+            &mut context,
+            |cache, context| {
+                if self.amend {
+                    make_amend_message(
+                        &config,
+                        cache,
+                        context,
+                        &self.extra_args,
+                        self.ui(),
+                    )
+                } else if let Some(prepared_message) =
+                    prepared_message.as_ref().filter(|_| merge_mode)
+                {
+                    make_merge_message(
+                        &config,
+                        cache,
+                        context,
+                        &self.extra_args,
+                        self.ui(),
+                        prepared_message,
+                    )
+                } else if let (Some(operation), Some(prepared_message)) =
+                    (operation, &prepared_message)
+                {
+                    make_prepared_message(
+                        &config,
+                        cache,
+                        context,
+                        &self.extra_args,
+                        self.ui(),
+                        operation,
+                        prepared_message,
+                    )
+                } else if let Some(ticket) = &self.fixes {
+                    make_fixes_message(
+                        &config,
+                        cache,
+                        context,
+                        &self.extra_args,
+                        self.ui(),
+                        ticket,
+                    )
+                } else {
+                    make_commit_message(
+                        &config,
+                        cache,
+                        context,
+                        &self.extra_args,
+                        self.ui(),
+                        config.wizard_steps(),
+                    )
+                }
+            },
+        )?;
+
+        if self.keep_wizard_open {
+            self.commit_more(&config, &cache.wizard_answers)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Commit {
+    /// Loads the configuration and the staged patch.
+    ///
+    /// When git-z does not run the pre-commit hook, the staged patch is loaded
+    /// while completing the lists to reduce the latency before the first
+    /// prompt. Otherwise, it is loaded after running the hook.
+    fn load_config_and_staged_patch(&self) -> Result<(Config, StagedPatch)> {
+        let mut config = load_config_file(self.on_outdated())?;
+
+        if self.runs_pre_commit(&config, &self.expected_extra_args()?) {
+            complete_lists(&mut config)?;
+            let staged_patch = self.run_pre_commit_and_load_patch(&config)?;
+            return Ok((config, staged_patch));
+        }
+
+        let (lists, staged_patch) = thread::scope(|scope| {
+            let staged_patch = scope.spawn(StagedPatch::load);
+            (complete_lists(&mut config), staged_patch.join())
+        });
+
+        lists?;
+        let staged_patch =
+            staged_patch.unwrap_or_else(|panic| panic::resume_unwind(panic))?;
+
+        Ok((config, staged_patch))
+    }
+
+    /// Runs the pre-commit hook, then loads the staged patch.
+    fn run_pre_commit_and_load_patch(
+        &self,
+        config: &Config,
+    ) -> Result<StagedPatch> {
+        // NOTE: The pre-commit hook can update the index, so let’s load the
+        // staged patch after running it.
+        self.pre_commit_has_run.set(true);
+        self.run_pre_commit_hook(config)?;
+        Ok(StagedPatch::load()?)
+    }
+
+    /// Checks the staged changes before running the wizard.
+    ///
+    /// The pre-commit hook, if any, must have been run before.
+    fn check_staged_changes(
+        &self,
+        config: &Config,
+        staged_patch: &StagedPatch,
+        ui: Option<WizardUi>,
+    ) -> Result<()> {
+        check_commit_size(config, ui)?;
+        check_secrets(config, staged_patch, self.allow_secrets)?;
+
+        if !self.print_only {
+            signing::warn_if_no_agent(config, &self.extra_args);
+        }
+
+        Ok(())
+    }
+
+    /// Makes a commit with the message built by the wizard.
+    ///
+    /// This is shared by every commit made with the wizard: the time spent is
+    /// tracked, the commit is made with the extra arguments from the cache, then
+    /// the cache is discarded. If `git commit` fails, the completed cache is
+    /// kept so that the message can be reused.
+    ///
+    /// When amending, the time spent is added to the one of the amended commit,
+    /// and it is not logged again on the ticket. The same goes for a reused
+    /// message, whose time spent is already up to date when amending.
+    fn commit_from_wizard(
+        &self,
+        config: &Config,
+        cache: &mut CommitCache,
+        context: &mut CommitContext,
+        make_message: impl FnOnce(
+            &mut CommitCache,
+            &mut CommitContext,
+        ) -> Result<String>,
+    ) -> Result<()> {
+        let wizard_start = Instant::now();
+        time_tracking::start_timer(config, cache);
+
+        let commit_message = make_message(cache, context)?;
+        let wizard_duration = wizard_start.elapsed();
+        let reused = context.is_message_reused();
+
+        let time_spent = if self.amend && reused {
+            None
         } else {
-            let mut git_commit = Command::new("git");
+            time_tracking::time_spent(config, cache, self.ui())?
+        };
 
-            git_commit.arg("commit");
-            #[cfg(feature = "unstable-pre-commit")]
-            git_commit.arg("--no-verify");
-            git_commit
-                .args(&self.extra_args)
-                .args(["-em", &commit_message]);
+        let commit_message = if self.amend {
+            time_tracking::add_to_trailer(
+                config,
+                time_spent.as_deref(),
+                commit_message,
+            )
+        } else {
+            time_tracking::add_trailer(
+                config,
+                time_spent.as_deref(),
+                commit_message,
+            )
+        };
 
-            tracing::debug!(?git_commit, "calling git commit");
-            let status = git_commit.status().log_err()?;
-            tracing::debug!(?status);
+        self.print_or_commit(config, &commit_message, cache.extra_args())?;
 
-            if !status.success() {
-                Err(CommitError::Git {
-                    status_code: status.code(),
-                })
-                .log_err()?;
+        if !self.print_only {
+            record_wizard_commit(wizard_duration);
+
+            if let Some(time_spent) =
+                time_spent.filter(|_| !self.amend && !reused)
+            {
+                time_tracking::log_work(config, &commit_message, &time_spent);
             }
         }
 
@@ -145,421 +567,2695 @@ impl super::Command for Commit {
         CommitCache::discard()?;
         Ok(())
     }
-}
-
-impl CommitMessage {
-    /// Runs the wizard to build a commit message from user input.
-    #[tracing::instrument(level = "trace", skip_all)]
-    fn run_wizard(config: &Config, cache: &mut CommitCache) -> Result<Self> {
-        let commit_message = Self {
-            r#type: ask_type(config, cache)?,
-            scope: ask_scope(config, cache)?,
-            description: ask_description(cache)?,
-            breaking_change: ask_breaking_change(cache)?,
-            ticket: ask_ticket(config, cache)?,
-        };
 
-        // NOTE: Marking the wizard as completed allows to skip the wizard on
-        // next run if `git commit` has failed and there is a valid
-        // `COMMIT_EDITMSG` file. In order to ensure `git z commit` does not
-        // reuse an outdated message, let’s delete any existing `COMMIT_EDITMSG`
-        // before marking the wizard as completed.
-        delete_last_commit_message()?;
-        cache.mark_wizard_as_completed()?;
+    /// Returns whether git-z runs the pre-commit hook itself before calling
+    /// `git commit` with `extra_args`.
+    ///
+    /// This is always the case with `--stash-unstaged`, since Git would run the
+    /// hook against the unstaged changes otherwise.
+    fn runs_pre_commit(&self, config: &Config, extra_args: &[String]) -> bool {
+        !self.no_verify(extra_args)
+            && (self.stash_unstaged
+                || config
+                    .hooks
+                    .as_ref()
+                    .is_some_and(|hooks| hooks.run_pre_commit))
+    }
 
-        tracing::debug!(?commit_message);
-        Ok(commit_message)
+    /// Returns whether the hooks are bypassed when calling `git commit` with
+    /// `extra_args`.
+    ///
+    /// Like with Git, `--no-verify` or `-n` in the extra arguments bypass them,
+    /// and `--verify` runs them again: the last one wins.
+    fn no_verify(&self, extra_args: &[String]) -> bool {
+        extra_args.iter().take_while(|arg| *arg != "--").fold(
+            self.no_verify,
+            |no_verify, arg| match arg.as_str() {
+                "--no-verify" | "-n" => true,
+                "--verify" => false,
+                _ => no_verify,
+            },
+        )
     }
 
-    /// Builds a dummy commit message.
-    fn dummy() -> Self {
-        Self {
-            r#type: String::from("dummy"),
-            scope: Some(String::from("dummy")),
-            description: String::from("dummy commit"),
-            breaking_change: Some(String::from("Dummy breaking change.")),
-            ticket: Some(String::from("#0")),
+    /// Returns the extra arguments `git commit` is expected to be called with.
+    ///
+    /// Without extra arguments on the command line, the ones of the previous
+    /// run are reused with its answers or message.
+    fn expected_extra_args(&self) -> Result<Vec<String>> {
+        if self.non_interactive || !self.extra_args.is_empty() {
+            Ok(self.extra_args.clone())
+        } else {
+            Ok(CommitCache::load()?.extra_args().to_vec())
         }
     }
-}
 
-/// Runs the pre-commit hook if it exists.
-#[cfg(feature = "unstable-pre-commit")]
-#[tracing::instrument(level = "trace")]
-fn run_pre_commit_hook() -> Result<()> {
-    let pre_commit = pre_commit()?;
+    /// Runs the pre-commit hook, stashing the unstaged changes if requested.
+    fn run_pre_commit_hook(&self, config: &Config) -> Result<()> {
+        let timeout = config
+            .hooks
+            .as_ref()
+            .and_then(|hooks| hooks.timeout)
+            .map(Duration::from_secs);
 
-    if pre_commit.exists() {
-        if pre_commit.is_executable() {
-            tracing::info!(path = ?pre_commit, "running the pre-commit hook");
+        if !self.stash_unstaged {
+            return run_pre_commit_hook(timeout);
+        }
 
-            let status = Command::new(pre_commit)
-                .status()
-                .map_err(CommitError::CannotRunPreCommit)
-                .log_err()?;
+        let stash = Stash::save()?;
+        let result = run_pre_commit_hook(timeout);
 
-            if !status.success() {
-                Err(CommitError::PreCommitFailed).log_err()?;
-            }
+        if let Some(stash) = stash {
+            stash.restore()?;
+        }
+
+        result
+    }
 
-            tracing::info!("the pre-commit hook has returned a success");
+    /// Returns what to do if the configuration is out of date.
+    ///
+    /// Updating the configuration requires to ask questions, so it is only
+    /// offered with the interactive prompts.
+    fn on_outdated(&self) -> OnOutdated {
+        if self.strict {
+            OnOutdated::Refuse
+        } else if self.non_interactive || self.ui() == WizardUi::Plain {
+            OnOutdated::Warn
         } else {
-            let path = pre_commit
-                .strip_prefix(env::current_dir()?)
-                .unwrap_or(&pre_commit)
-                .display();
+            OnOutdated::OfferUpdate
+        }
+    }
 
-            warning!(
-                "The `{path}` hook was ignored because it is not set as \
-                executable."
-            );
+    /// Ensures the answers can be asked to the user.
+    ///
+    /// Inquire needs a terminal, so let’s fail early with a clear error instead
+    /// of in the middle of the wizard. Plain prompts can read the answers from
+    /// any input.
+    fn ensure_can_ask(&self) -> Result<()> {
+        if !self.non_interactive
+            && self.ui() != WizardUi::Plain
+            && !io::stdin().is_terminal()
+        {
+            Err(CommitError::NotATerminal).log_err()?;
         }
-    } else {
-        tracing::debug!("no pre-commit hook to run");
+
+        Ok(())
     }
 
-    Ok(())
-}
+    /// Returns the message prepared by Git for the operation in progress.
+    ///
+    /// Committing is refused while there are unresolved conflicts, as Git would
+    /// only refuse it after the wizard.
+    fn prepared_message(
+        &self,
+        operation: Option<Operation>,
+    ) -> Result<Option<String>> {
+        let Some(operation) = operation else {
+            return Ok(None);
+        };
 
-/// Makes a commit message.
-#[tracing::instrument(level = "trace", skip_all)]
-fn make_commit_message(config: &Config) -> Result<String> {
-    let mut cache = CommitCache::load()?;
+        if operation::has_conflicts()? {
+            Err(CommitError::UnresolvedConflicts { operation }).log_err()?;
+        }
 
-    match cache.wizard_state {
-        WizardState::NotStarted | WizardState::Ongoing => {
-            make_message_from_wizard(config, &mut cache)
+        if self.amend {
+            Ok(None)
+        } else {
+            operation::prepared_message()
         }
-        WizardState::Completed => {
-            tracing::debug!(
-                "completed wizard state present, checking whether a valid \
-                commit message is present"
-            );
-            if let Some(message) = last_commit_message()? {
-                tracing::debug!(
-                    "valid commit message present, asking the user whether to \
-                    use it"
-                );
-                let do_reuse_message = ask_reuse_message()?;
+    }
 
-                if do_reuse_message {
-                    tracing::debug!("reusing the commit message");
-                    Ok(message)
-                } else {
-                    tracing::debug!("not reusing the commit message");
-                    cache.reset()?;
-                    make_message_from_wizard(config, &mut cache)
-                }
+    /// Makes a commit message from the answers given on the command line.
+    ///
+    /// The body of the message prepared by Git for an operation in progress, if
+    /// any, is kept.
+    fn make_message_from_args(
+        &self,
+        config: &Config,
+        prepared_message: Option<&str>,
+    ) -> Result<String> {
+        let answers = CommitMessage {
+            r#type: self.r#type.clone().unwrap_or_default(),
+            scope: self.scope.clone(),
+            description: self.description.clone().unwrap_or_default(),
+            breaking_change: self.breaking_change.clone(),
+            ticket: self.ticket.clone(),
+        };
+
+        tracing::debug!(?answers);
+
+        // The subject decorations are not conventional, so the message is
+        // checked before decorating it.
+        let tera = build_and_check_template(config)?;
+        let context = template_context(config, &answers).log_err()?;
+        let message = render_conventional(&tera, config, &answers, &context)?;
+        let violations = lint::check_message(config, &message);
+
+        if !violations.is_empty() {
+            Err(CommitError::InvalidAnswers {
+                violations: violations
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+            })
+            .log_err()?;
+        }
+
+        let message = subject::decorate(&tera, config, &context, message)?;
+
+        if self.amend {
+            Ok(reword::merge_messages(
+                config,
+                &message,
+                &amended_message()?,
+            ))
+        } else if let Some(prepared_message) = prepared_message {
+            Ok(reword::merge_messages(config, &message, prepared_message))
+        } else {
+            Ok(message)
+        }
+    }
+
+    /// Prints the commit message or calls `git commit` with it.
+    fn print_or_commit(
+        &self,
+        config: &Config,
+        commit_message: &str,
+        extra_args: &[String],
+    ) -> Result<()> {
+        if self.print_only {
+            tracing::debug!("printing the commit message");
+
+            // NOTE: Git does not open the editor here, so git-z opens it
+            // itself when explicitly asked to.
+            let message = if self.edit && !self.non_interactive {
+                let edited = editor::edit(&sections::scaffold(commit_message))?;
+                reword::clean_up(&edited)
             } else {
-                tracing::debug!("no valid commit message, rerun the wizard");
-                cache.mark_wizard_as_ongoing()?;
-                make_message_from_wizard(config, &mut cache)
+                unedited_message(config, commit_message)
+            };
+
+            println!("{message}");
+            Ok(())
+        } else {
+            self.git_commit(config, commit_message, extra_args)
+        }
+    }
+
+    /// Returns the user interface of the wizard.
+    ///
+    /// Plain prompts are used automatically in dumb terminals, like Emacs shell
+    /// buffers.
+    fn ui(&self) -> WizardUi {
+        if self.tui {
+            WizardUi::Tui
+        } else if self.plain || is_dumb_terminal() {
+            WizardUi::Plain
+        } else {
+            WizardUi::Prompts
+        }
+    }
+
+    /// Returns whether to open the commit message in an editor.
+    fn edit(&self, config: &Config) -> bool {
+        if self.no_edit || self.non_interactive {
+            false
+        } else {
+            self.edit
+                || config
+                    .commit
+                    .as_ref()
+                    .and_then(|commit| commit.edit)
+                    .unwrap_or(true)
+        }
+    }
+
+    /// Calls `git commit` with the given message and extra arguments.
+    fn git_commit(
+        &self,
+        config: &Config,
+        commit_message: &str,
+        extra_args: &[String],
+    ) -> Result<()> {
+        let mut git_commit = Command::new("git");
+
+        // NOTE: The pre-commit hook has already been run by git-z, but the
+        // other hooks still have to be run by Git.
+        if self.pre_commit_has_run.get() {
+            let hooks_path = hooks::without_pre_commit()?;
+            git_commit
+                .arg("-c")
+                .arg(format!("core.hooksPath={}", hooks_path.display()));
+        }
+
+        git_commit.arg("commit");
+        if self.amend {
+            git_commit.arg("--amend");
+        }
+        if self.no_verify {
+            git_commit.arg("--no-verify");
+        }
+        git_commit.args(extra_args);
+
+        if self.edit(config) {
+            git_commit.args(["-em", &sections::scaffold(commit_message)]);
+        } else {
+            git_commit.args(["-m", &unedited_message(config, commit_message)]);
+        }
+
+        tracing::debug!(?git_commit, "calling git commit");
+        let status = git_commit.status().log_err()?;
+        tracing::debug!(?status);
+
+        if !status.success() {
+            Err(CommitError::Git {
+                status_code: status.code(),
+            })
+            .log_err()?;
+        }
+
+        Ok(())
+    }
+
+    /// Offers to make more commits until the user declines.
+    ///
+    /// The scope and ticket of the previous commit are kept as defaults. When
+    /// nothing is staged, the changes to commit are selected with
+    /// `git add --patch`.
+    #[tracing::instrument(level = "trace", skip_all)]
+    fn commit_more(
+        &self,
+        config: &Config,
+        answers: &WizardAnswers,
+    ) -> Result<()> {
+        let mut scope = answers.scope.clone();
+        let mut ticket = answers.ticket.clone();
+
+        while confirm(
+            self.ui(),
+            "Do you want to make another commit?",
+            Some("The scope and ticket will be kept as defaults."),
+            true,
+        )? {
+            if StagedChanges::load()?.files.is_empty() {
+                staged::stage_interactively()?;
+            }
+
+            if StagedChanges::load()?.files.is_empty() {
+                warning!("There are no staged changes.");
+                continue;
             }
+
+            let staged_patch = if self.runs_pre_commit(config, &self.extra_args)
+            {
+                self.run_pre_commit_and_load_patch(config)?
+            } else {
+                StagedPatch::load()?
+            };
+
+            self.check_staged_changes(config, &staged_patch, Some(self.ui()))?;
+            let mut context = CommitContext::with_staged_patch(staged_patch);
+
+            let mut cache = CommitCache {
+                wizard_state: WizardState::Ongoing,
+                extra_args: self.extra_args.clone(),
+                wizard_answers: WizardAnswers {
+                    scope,
+                    ticket,
+                    ..WizardAnswers::default()
+                },
+                ..CommitCache::default()
+            };
+
+            self.commit_from_wizard(
+                config,
+                &mut cache,
+                &mut context,
+                |cache, context| run_wizard(config, cache, context, self.ui()),
+            )?;
+
+            scope = cache.wizard_answers.scope;
+            ticket = cache.wizard_answers.ticket;
+        }
+
+        Ok(())
+    }
+
+    /// Offers to split the staged changes into one commit per scope.
+    ///
+    /// Returns whether the changes have been committed.
+    #[tracing::instrument(level = "trace", skip_all)]
+    fn split_by_scope(&self, config: &Config) -> Result<bool> {
+        let Some(scope_paths) = &config.scope_paths else {
+            return Ok(false);
+        };
+
+        if CommitCache::load()?.wizard_state != WizardState::NotStarted {
+            tracing::debug!("a commit is in progress, not splitting");
+            return Ok(false);
+        }
+
+        let paths = StagedChanges::load()?
+            .files
+            .into_iter()
+            .map(|file| file.path)
+            .collect::<Vec<_>>();
+
+        let groups = scope_paths::group_by_scope(scope_paths, &paths)
+            .wrap_err("Invalid regex in the scope paths")
+            .log_err()?;
+
+        if groups.len() < 2 {
+            return Ok(false);
+        }
+
+        let scopes = groups
+            .iter()
+            .map(|(scope, paths)| {
+                let scope = scope.as_deref().unwrap_or("no scope");
+                format!("  - {scope}: {} file(s)", paths.len())
+            })
+            .join("\n");
+
+        hint!("The staged changes span several scopes:\n{scopes}\n");
+
+        let do_split = confirm(
+            self.ui(),
+            "Do you want to split them into one commit per scope?",
+            Some(
+                "The wizard will be run once per scope, with only the matching \
+                files staged.",
+            ),
+            true,
+        )?;
+
+        if !do_split {
+            tracing::debug!("not splitting the commit");
+            return Ok(false);
+        }
+
+        let tree = staged::write_index_tree()?;
+        let result = self.commit_groups(config, &tree, &groups);
+
+        if result.is_err() {
+            // NOTE: Restore the index as it was before splitting, so the
+            // changes that have not been committed yet are staged again.
+            staged::read_index_tree(&tree)?;
+        }
+
+        result.map(|()| true)
+    }
+
+    /// Commits each group of paths in a separate commit.
+    fn commit_groups(
+        &self,
+        config: &Config,
+        tree: &str,
+        groups: &IndexMap<Option<String>, Vec<String>>,
+    ) -> Result<()> {
+        staged::unstage_all()?;
+
+        for (scope, paths) in groups {
+            tracing::info!(?scope, ?paths, "committing a group of paths");
+            staged::stage_from_tree(tree, paths)?;
+
+            let mut cache = CommitCache {
+                wizard_state: WizardState::Ongoing,
+                extra_args: self.extra_args.clone(),
+                wizard_answers: WizardAnswers {
+                    scope: scope.clone(),
+                    ..WizardAnswers::default()
+                },
+                ..CommitCache::default()
+            };
+            let mut context = CommitContext::default();
+
+            self.commit_from_wizard(
+                config,
+                &mut cache,
+                &mut context,
+                |cache, context| run_wizard(config, cache, context, self.ui()),
+            )?;
         }
+
+        Ok(())
     }
 }
 
-/// Makes a commit message by running the wizard.
-#[tracing::instrument(level = "trace", skip_all)]
-fn make_message_from_wizard(
+impl<'a> From<&'a Config> for TemplateConfig<'a> {
+    fn from(config: &'a Config) -> Self {
+        Self {
+            ticket: config.ticket.as_ref(),
+        }
+    }
+}
+
+impl CommitMessage {
+    /// Runs the wizard to build a commit message from user input.
+    ///
+    /// Only the given steps are asked. The answers to the other ones are taken
+    /// from the cache.
+    #[tracing::instrument(level = "trace", skip_all)]
+    fn run_wizard(
+        preview_tera: &Tera,
+        config: &Config,
+        cache: &mut CommitCache,
+        context: &mut CommitContext,
+        ui: WizardUi,
+        steps: Vec<Step>,
+    ) -> Result<Self> {
+        let mut prefetched = Prefetched::start(config, context);
+        let mut commit_message = Self::placeholders(config);
+        commit_message.take_unasked_answers(cache, &steps);
+        let skip_rest = Cell::new(false);
+
+        for (index, step) in steps.into_iter().enumerate() {
+            // NOTE: Before the type is chosen, its placeholder skips nothing.
+            // The answers to questions asked earlier are cleared afterwards.
+            let skipped = step.question().is_some_and(|question| {
+                skips(config, &commit_message.r#type, question)
+            });
+
+            if skipped {
+                continue;
+            }
+
+            // NOTE: After Ctrl-D, the remaining questions are only asked when
+            // their default answer is invalid.
+            if skip_rest.get()
+                && commit_message.take_default_answer(
+                    step,
+                    config,
+                    cache,
+                    context,
+                    &mut prefetched,
+                )?
+            {
+                continue;
+            }
+
+            if index > 0 {
+                print_preview(preview_tera, config, &commit_message);
+            }
+
+            match step {
+                Step::Type => {
+                    commit_message.r#type =
+                        ask_type(config, cache, context, ui, &skip_rest)?;
+                }
+                Step::Scope => {
+                    commit_message.scope =
+                        ask_scope(config, cache, context, ui, &skip_rest)?;
+                }
+                Step::Description => {
+                    let subject_width = |description: &str| {
+                        subject_width(
+                            preview_tera,
+                            config,
+                            &commit_message,
+                            description,
+                        )
+                    };
+                    let description = ask_description(
+                        config,
+                        cache,
+                        context,
+                        prefetched.take_description_suggestions(),
+                        &subject_width,
+                        ui,
+                        &skip_rest,
+                    )?;
+                    commit_message.description = description;
+                }
+                Step::BreakingChange => {
+                    commit_message.breaking_change = ask_breaking_change(
+                        config,
+                        cache,
+                        context,
+                        prefetched.take_likely_breaking_changes(),
+                        ui,
+                        &skip_rest,
+                    )?;
+                }
+                Step::Ticket => {
+                    commit_message.ticket = ask_ticket(
+                        config,
+                        cache,
+                        context,
+                        prefetched.take_detected_ticket(),
+                        ui,
+                        &skip_rest,
+                    )?;
+                }
+            }
+        }
+
+        commit_message.clear_skipped(config, cache, context)?;
+        complete_wizard(cache, context)?;
+
+        tracing::debug!(?commit_message);
+        Ok(commit_message)
+    }
+
+    /// Runs the full-screen form to build a commit message from user input.
+    #[tracing::instrument(level = "trace", skip_all)]
+    fn run_form(
+        config: &Config,
+        cache: &mut CommitCache,
+        context: &mut CommitContext,
+        preview: &dyn Fn(&Self) -> String,
+    ) -> Result<Self> {
+        let prefetched = Prefetched::start(config, context);
+        let form = Form::new(
+            config,
+            cache,
+            wait_for_likely_breaking_changes(
+                prefetched.likely_breaking_changes,
+            ),
+            prefetched.detected_ticket.wait()?.into_ticket(),
+        );
+
+        match tui::run(form, preview)? {
+            Outcome::Submitted(commit_message) => {
+                commit_message.save_answers(cache, context)?;
+                complete_wizard(cache, context)?;
+
+                tracing::debug!(?commit_message);
+                Ok(commit_message)
+            }
+            Outcome::Canceled(answers) => {
+                // NOTE: Save the answers so that they can be reused next time,
+                // like the sequential prompts do after each question.
+                answers.save_answers(cache, context)?;
+                Err(InquireError::OperationCanceled).log_err()?
+            }
+        }
+    }
+
+    /// Takes the answers to the steps not asked from the cache.
+    fn take_unasked_answers(&mut self, cache: &CommitCache, steps: &[Step]) {
+        let cached = |step| !steps.contains(&step);
+
+        if cached(Step::Type) {
+            cache
+                .r#type()
+                .unwrap_or_default()
+                .clone_into(&mut self.r#type);
+        }
+
+        if cached(Step::Scope) {
+            self.scope = cache.scope().map(ToOwned::to_owned);
+        }
+
+        if cached(Step::Description) {
+            cache
+                .description()
+                .unwrap_or_default()
+                .clone_into(&mut self.description);
+        }
+
+        if cached(Step::BreakingChange) {
+            self.breaking_change =
+                cache.breaking_change().map(ToOwned::to_owned);
+        }
+
+        if cached(Step::Ticket) {
+            self.ticket = cache.ticket().map(ToOwned::to_owned);
+        }
+    }
+
+    /// Takes the default answer to `step`, if it is valid.
+    ///
+    /// Like in the form, the default answers come from the cache or the
+    /// detection. The answer is saved in the cache, as if it had been given.
+    /// Returns whether the answer has been taken.
+    fn take_default_answer(
+        &mut self,
+        step: Step,
+        config: &Config,
+        cache: &mut CommitCache,
+        context: &CommitContext,
+        prefetched: &mut Prefetched,
+    ) -> Result<bool> {
+        let taken = match step {
+            Step::Type => {
+                let r#type = cache
+                    .r#type()
+                    .filter(|r#type| config.types.contains_key(*r#type))
+                    .or_else(|| config.types.keys().next().map(String::as_str))
+                    .map(ToOwned::to_owned);
+
+                r#type.is_some_and(|r#type| {
+                    cache.set_type(&r#type);
+                    self.r#type = r#type;
+                    true
+                })
+            }
+            Step::Scope => {
+                let scope = config.scopes.as_ref().and_then(|scopes| {
+                    cache.scope().filter(|scope| match scopes {
+                        Scopes::Any { .. } => !scope.is_empty(),
+                        Scopes::List { list, .. } => {
+                            list.position(scope).is_some()
+                        }
+                    })
+                });
+                let required =
+                    config.scopes.as_ref().is_some_and(Scopes::is_required);
+
+                if scope.is_none() && required {
+                    false
+                } else {
+                    self.scope = scope.map(ToOwned::to_owned);
+                    cache.set_scope(self.scope.as_deref());
+                    true
+                }
+            }
+            Step::Description => {
+                let description = cache.description().unwrap_or_default();
+
+                if lint::check_description(description).is_none() {
+                    description.clone_into(&mut self.description);
+                    true
+                } else {
+                    false
+                }
+            }
+            Step::BreakingChange => {
+                self.breaking_change =
+                    cache.breaking_change().map(ToOwned::to_owned);
+                true
+            }
+            Step::Ticket => match &config.ticket {
+                None => {
+                    self.ticket = None;
+                    true
+                }
+                Some(Ticket { required, .. }) => {
+                    let detection = prefetched.take_detected_ticket().wait()?;
+                    let ticket = cache
+                        .ticket()
+                        .map(ToOwned::to_owned)
+                        .or_else(|| detection.ticket.clone())
+                        .filter(|ticket| !ticket.is_empty());
+
+                    let valid =
+                        ticket.as_deref().map_or(!*required, |ticket| {
+                            matches!(
+                                validate_ticket(ticket),
+                                Ok(Validation::Valid)
+                            )
+                        });
+
+                    if valid {
+                        cache.set_ticket(ticket.as_deref());
+                        self.ticket = ticket;
+                    } else {
+                        // NOTE: Give the detection back for the question.
+                        prefetched.detected_ticket =
+                            Prefetch::ready(Ok(detection));
+                    }
+
+                    valid
+                }
+            },
+        };
+
+        tracing::debug!(?step, taken);
+        context.save(cache)?;
+        Ok(taken)
+    }
+
+    /// Clears the answers to the questions skipped by the type.
+    fn clear_skipped(
+        &mut self,
+        config: &Config,
+        cache: &mut CommitCache,
+        context: &CommitContext,
+    ) -> Result<()> {
+        if skips(config, &self.r#type, Question::Scope) {
+            self.scope = None;
+            cache.set_scope(None);
+        }
+
+        if skips(config, &self.r#type, Question::BreakingChange) {
+            self.breaking_change = None;
+            cache.set_breaking_change(None);
+        }
+
+        if skips(config, &self.r#type, Question::Ticket) {
+            self.ticket = None;
+            cache.set_ticket(None);
+        }
+
+        context.save(cache)?;
+        Ok(())
+    }
+
+    /// Saves the answers in the cache.
+    fn save_answers(
+        &self,
+        cache: &mut CommitCache,
+        context: &CommitContext,
+    ) -> Result<()> {
+        cache.set_type(&self.r#type);
+        cache.set_scope(self.scope.as_deref());
+        cache.set_description(&self.description);
+        cache.set_breaking_change(self.breaking_change.as_deref());
+        cache.set_ticket(self.ticket.as_deref());
+        context.save(cache)?;
+        Ok(())
+    }
+
+    /// Builds a commit message with placeholders for the unanswered fields.
+    ///
+    /// Optional fields that are not asked with the current configuration are
+    /// left empty.
+    fn placeholders(config: &Config) -> Self {
+        Self {
+            r#type: String::from("<type>"),
+            scope: config.scopes.as_ref().map(|_| String::from("<scope>")),
+            description: String::from("<description>"),
+            breaking_change: Some(String::from("<breaking change>")),
+            ticket: config.ticket.as_ref().map(|_| String::from("<ticket>")),
+        }
+    }
+
+    /// Builds a commit message from answers given by other means than the
+    /// wizard, using dummy values for the missing type and description.
+    pub fn from_answers(answers: WizardAnswers) -> Self {
+        let dummy = Self::dummy();
+
+        Self {
+            r#type: answers.r#type.unwrap_or(dummy.r#type),
+            scope: answers.scope,
+            description: answers.description.unwrap_or(dummy.description),
+            breaking_change: answers.breaking_change,
+            ticket: answers.ticket,
+        }
+    }
+
+    /// Builds a dummy commit message.
+    fn dummy() -> Self {
+        Self {
+            r#type: String::from("dummy"),
+            scope: Some(String::from("dummy")),
+            description: String::from("dummy commit"),
+            breaking_change: Some(String::from("Dummy breaking change.")),
+            ticket: Some(String::from("#0")),
+        }
+    }
+}
+
+/// Data needed by the last questions of the wizard.
+///
+/// It is fetched in the background while the user answers the first questions,
+/// so that the last prompts appear without delay.
+struct Prefetched {
+    /// The likely breaking changes detected in the staged changes.
+    likely_breaking_changes: Prefetch<Result<Vec<String>>>,
+    /// The ticket detected from the branch or the last commit.
+    detected_ticket: Prefetch<Result<ticket::Detection>>,
+    /// The descriptions suggested from the staged changes.
+    description_suggestions: Prefetch<Result<Vec<String>>>,
+}
+
+impl Prefetched {
+    /// Starts fetching the data needed with the current configuration.
+    ///
+    /// The staged patch already loaded in the context, if any, is reused.
+    fn start(config: &Config, context: &CommitContext) -> Self {
+        let likely_breaking_changes = match &config.breaking_change {
+            Some(breaking_change) if !breaking_change.rules.is_empty() => {
+                let rules = breaking_change.rules.clone();
+                let staged_patch = context.loaded_staged_patch();
+                Prefetch::spawn(move || {
+                    detect_breaking_changes(&rules, staged_patch)
+                })
+            }
+            _ => Prefetch::ready(Ok(vec![])),
+        };
+
+        let detected_ticket = match &config.ticket {
+            Some(Ticket {
+                prefixes,
+                branch_patterns,
+                ..
+            }) => {
+                let prefixes = prefixes.clone();
+                let branch_patterns =
+                    branch_patterns.clone().unwrap_or_default();
+                Prefetch::spawn(move || {
+                    ticket::detect(&prefixes, &branch_patterns)
+                })
+            }
+            None => Prefetch::ready(Ok(ticket::Detection::default())),
+        };
+
+        let description_suggestions = match &config.suggestions {
+            Some(Suggestions {
+                descriptions,
+                description_command,
+            }) if *descriptions || description_command.is_some() => {
+                let descriptions = *descriptions;
+                let description_command = description_command.clone();
+                let staged_patch = context.loaded_staged_patch();
+                let staged_diff = context.staged_diff();
+                Prefetch::spawn(move || {
+                    suggest_descriptions(
+                        descriptions,
+                        description_command.as_deref(),
+                        staged_patch,
+                        &staged_diff,
+                    )
+                })
+            }
+            _ => Prefetch::ready(Ok(vec![])),
+        };
+
+        Self {
+            likely_breaking_changes,
+            detected_ticket,
+            description_suggestions,
+        }
+    }
+
+    /// Takes the likely breaking changes, leaving none behind.
+    fn take_likely_breaking_changes(
+        &mut self,
+    ) -> Prefetch<Result<Vec<String>>> {
+        mem::replace(
+            &mut self.likely_breaking_changes,
+            Prefetch::ready(Ok(vec![])),
+        )
+    }
+
+    /// Takes the detected ticket, leaving none behind.
+    fn take_detected_ticket(&mut self) -> Prefetch<Result<ticket::Detection>> {
+        mem::replace(
+            &mut self.detected_ticket,
+            Prefetch::ready(Ok(ticket::Detection::default())),
+        )
+    }
+
+    /// Takes the description suggestions, leaving none behind.
+    fn take_description_suggestions(
+        &mut self,
+    ) -> Prefetch<Result<Vec<String>>> {
+        mem::replace(
+            &mut self.description_suggestions,
+            Prefetch::ready(Ok(vec![])),
+        )
+    }
+}
+
+/// Marks the wizard as completed.
+///
+/// This allows to skip the wizard on next run if `git commit` has failed and
+/// there is a valid `COMMIT_EDITMSG` file. In order to ensure `git z commit`
+/// does not reuse an outdated message, any existing `COMMIT_EDITMSG` is deleted
+/// before marking the wizard as completed.
+///
+/// In an ephemeral context, nothing is done since no commit follows.
+fn complete_wizard(
+    cache: &mut CommitCache,
+    context: &CommitContext,
+) -> Result<()> {
+    if context.is_ephemeral() {
+        tracing::trace!("not completing the ephemeral wizard");
+        return Ok(());
+    }
+
+    delete_last_commit_message()?;
+    cache.mark_wizard_as_completed(&staged::write_index_tree()?);
+    context.save(cache)?;
+    Ok(())
+}
+
+/// Runs the pre-commit hook if it exists.
+///
+/// Like Git, the hook is run from the root of the worktree, with `GIT_DIR` and
+/// `GIT_INDEX_FILE` set. Its standard input is empty and its output is
+/// redirected to the standard error.
+#[tracing::instrument(level = "trace")]
+fn run_pre_commit_hook(timeout: Option<Duration>) -> Result<()> {
+    // NOTE: The paths returned by `git rev-parse --git-path` can be relative to
+    // the current directory, which is not the one of the hook.
+    let current_dir = env::current_dir()?;
+    let pre_commit = current_dir.join(pre_commit()?);
+
+    if pre_commit.exists() {
+        if pre_commit.is_executable() {
+            tracing::info!(path = ?pre_commit, ?timeout, "running the pre-commit hook");
+            eprintln!("{}", "Running the pre-commit hook...".dimmed());
+
+            let start = Instant::now();
+            let mut hook = Command::new(&pre_commit);
+            hook.current_dir(repo_root()?)
+                .env("GIT_DIR", git::dir()?)
+                .env("GIT_INDEX_FILE", current_dir.join(git::path("index")?))
+                .stdin(Stdio::null())
+                .stdout(io::stderr());
+
+            if timeout.is_some() {
+                isolate(&mut hook);
+            }
+
+            let hook = hook
+                .spawn()
+                .map_err(CommitError::CannotRunPreCommit)
+                .log_err()?;
+
+            let status = wait_with_timeout(hook, timeout);
+
+            let duration = start.elapsed();
+            tracing::info!(?duration, "the pre-commit hook has ended");
+
+            let status = status?;
+
+            if !status.success() {
+                Err(CommitError::PreCommitFailed).log_err()?;
+            }
+
+            eprintln!(
+                "{}",
+                format!(
+                    "The pre-commit hook has passed in {:.1}s.\n",
+                    duration.as_secs_f64()
+                )
+                .dimmed()
+            );
+        } else {
+            let path = pre_commit
+                .strip_prefix(&current_dir)
+                .unwrap_or(&pre_commit)
+                .display();
+
+            warning!(
+                "The `{path}` hook was ignored because it is not set as \
+                executable."
+            );
+        }
+    } else {
+        tracing::debug!("no pre-commit hook to run");
+    }
+
+    Ok(())
+}
+
+/// Waits for the pre-commit hook to end, killing it after the timeout.
+fn wait_with_timeout(
+    mut hook: Child,
+    timeout: Option<Duration>,
+) -> Result<ExitStatus> {
+    let Some(timeout) = timeout else {
+        return Ok(hook
+            .wait()
+            .map_err(CommitError::CannotRunPreCommit)
+            .log_err()?);
+    };
+
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if let Some(status) = hook
+            .try_wait()
+            .map_err(CommitError::CannotRunPreCommit)
+            .log_err()?
+        {
+            return Ok(status);
+        }
+
+        if Instant::now() >= deadline {
+            kill_group(&mut hook)?;
+            hook.wait()?;
+            return Err(CommitError::PreCommitTimeout { timeout }).log_err()?;
+        }
+
+        thread::sleep(HOOK_POLL_INTERVAL);
+    }
+}
+
+/// Runs a command in its own process group.
+///
+/// This way, the processes it spawns can be killed along with it.
+// NOTE: The group is not the foreground one of the terminal anymore, so Ctrl-C
+// does not reach it. This is why only the hooks with a timeout are isolated.
+#[cfg(unix)]
+fn isolate(command: &mut Command) {
+    use std::os::unix::process::CommandExt as _;
+
+    command.process_group(0);
+}
+
+/// Runs a command in its own process group.
+///
+/// There are no process groups on Windows, so there is nothing to do.
+#[cfg(not(unix))]
+fn isolate(_command: &mut Command) {}
+
+/// Kills a process isolated by [`isolate`] along with the processes it spawned.
+#[cfg(unix)]
+fn kill_group(child: &mut Child) -> io::Result<()> {
+    let group = format!("-{}", child.id());
+    let killed = Command::new("kill")
+        .args(["-KILL", "--", &group])
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+
+    if killed {
+        Ok(())
+    } else {
+        tracing::warn!("cannot kill the process group, killing the process");
+        child.kill()
+    }
+}
+
+/// Kills a process isolated by [`isolate`].
+#[cfg(not(unix))]
+fn kill_group(child: &mut Child) -> io::Result<()> {
+    child.kill()
+}
+
+/// Checks the size of the staged changes against the configured limits.
+///
+/// Without user interface, the commit is refused when a limit is exceeded with
+/// the `error` severity.
+#[tracing::instrument(level = "trace", skip_all)]
+fn check_commit_size(config: &Config, ui: Option<WizardUi>) -> Result<()> {
+    let Some(size) = config
+        .commit
+        .as_ref()
+        .and_then(|commit| commit.size.as_ref())
+    else {
+        return Ok(());
+    };
+
+    let staged_changes = StagedChanges::load()?;
+    let files = staged_changes.files.len();
+    let lines = staged_changes.changed_lines();
+
+    let exceeded_limits = [
+        size.max_files.filter(|max_files| files > *max_files).map(
+            |max_files| format!("{files} files changed (max {max_files})"),
+        ),
+        size.max_lines.filter(|max_lines| lines > *max_lines).map(
+            |max_lines| format!("{lines} lines changed (max {max_lines})"),
+        ),
+    ]
+    .into_iter()
+    .flatten()
+    .join(", ");
+
+    if exceeded_limits.is_empty() {
+        return Ok(());
+    }
+
+    warning!("The staged changes are large: {exceeded_limits}.");
+    hint!("Consider splitting them into smaller commits.");
+
+    match size.severity {
+        Severity::Warning => Ok(()),
+        Severity::Error => {
+            let commit_anyway = match ui {
+                Some(ui) => {
+                    confirm(ui, "Do you want to commit anyway?", None, false)?
+                }
+                None => false,
+            };
+
+            if commit_anyway {
+                tracing::info!("committing large changes anyway");
+                Ok(())
+            } else {
+                Err(CommitError::TooLarge).log_err()?
+            }
+        }
+    }
+}
+
+/// Scans the staged changes for secrets.
+#[tracing::instrument(level = "trace", skip_all)]
+fn check_secrets(
+    config: &Config,
+    staged_patch: &StagedPatch,
+    allow_secrets: bool,
+) -> Result<()> {
+    let mut rules = vec![];
+
+    if !config
+        .secrets
+        .as_ref()
+        .is_some_and(|secrets| secrets.disable_default_rules)
+    {
+        rules.extend(secrets::default_rules());
+    }
+
+    if let Some(secrets) = &config.secrets {
+        rules.extend(secrets.rules.iter().cloned());
+    }
+
+    if rules.is_empty() {
+        return Ok(());
+    }
+
+    let findings = secrets::scan(&rules, staged_patch)
+        .wrap_err("Invalid regex in the secret rules")
+        .log_err()?;
+
+    if findings.is_empty() {
+        return Ok(());
+    }
+
+    let findings = findings
+        .iter()
+        .map(|finding| format!("  - {}: {}", finding.path, finding.description))
+        .join("\n");
+
+    if allow_secrets {
+        warning!("The staged changes seem to contain secrets:\n{findings}\n");
+        tracing::info!("committing secrets anyway");
+        Ok(())
+    } else {
+        hint!("Possible secrets found in the staged changes:\n{findings}\n");
+        Err(CommitError::SecretsFound).log_err()?
+    }
+}
+
+/// Records the metrics of a commit made with the wizard.
+fn record_wizard_commit(wizard_duration: Duration) {
+    metrics::record(|metrics| {
+        metrics.wizard_commits += 1;
+        metrics.wizard_seconds += wizard_duration.as_secs();
+    });
+}
+
+/// Returns the message to use when it is not opened in an editor.
+///
+/// Git only strips the comments when the message is edited, so let’s do it
+/// here unless disabled in the configuration.
+pub fn unedited_message(config: &Config, commit_message: &str) -> String {
+    if config.templates.strip_comments.unwrap_or(true) {
+        reword::clean_up(commit_message)
+    } else {
+        commit_message.to_owned()
+    }
+}
+
+/// Makes a commit message.
+///
+/// When the wizard is run, only the given steps are asked.
+#[tracing::instrument(level = "trace", skip_all)]
+fn make_commit_message(
+    config: &Config,
+    cache: &mut CommitCache,
+    context: &mut CommitContext,
+    extra_args: &[String],
+    ui: WizardUi,
+    steps: Vec<Step>,
+) -> Result<String> {
+    match cache.wizard_state {
+        WizardState::NotStarted | WizardState::Ongoing => {
+            make_message_from_wizard(
+                config, cache, context, extra_args, ui, steps,
+            )
+        }
+        WizardState::Completed => {
+            tracing::debug!(
+                "completed wizard state present, checking whether a valid \
+                commit message is present"
+            );
+            let last_commit_message = last_commit_message()?;
+
+            if last_commit_message.is_some() && staged_tree_has_changed(cache)?
+            {
+                tracing::debug!(
+                    "the staged changes have changed, rerun the wizard"
+                );
+                hint!(
+                    "The staged changes have been modified since your last \
+                    commit message was written."
+                );
+                cache.mark_wizard_as_ongoing();
+                context.save(cache)?;
+                make_message_from_wizard(
+                    config, cache, context, extra_args, ui, steps,
+                )
+            } else if let Some(message) = last_commit_message {
+                tracing::debug!(
+                    "valid commit message present, asking the user whether to \
+                    use it"
+                );
+                let do_reuse_message = ask_reuse_message(
+                    &message,
+                    reused_extra_args(cache, extra_args),
+                    ui,
+                )?;
+
+                if do_reuse_message {
+                    tracing::debug!("reusing the commit message");
+                    context.mark_message_as_reused();
+                    update_extra_args(cache, context, extra_args, true)?;
+                    Ok(reword::dedup_trailers(&message))
+                } else {
+                    tracing::debug!("not reusing the commit message");
+                    cache.reset()?;
+                    make_message_from_wizard(
+                        config, cache, context, extra_args, ui, steps,
+                    )
+                }
+            } else {
+                tracing::debug!("no valid commit message, rerun the wizard");
+                cache.mark_wizard_as_ongoing();
+                context.save(cache)?;
+                make_message_from_wizard(
+                    config, cache, context, extra_args, ui, steps,
+                )
+            }
+        }
+    }
+}
+
+/// Makes a commit message to amend the last commit.
+///
+/// On a fresh start, the wizard is prefilled from the message of `HEAD` and its
+/// body is kept. If a previous run has been aborted, the usual logic applies,
+/// still keeping the body of the message of `HEAD`.
+#[tracing::instrument(level = "trace", skip_all)]
+fn make_amend_message(
+    config: &Config,
+    cache: &mut CommitCache,
+    context: &mut CommitContext,
+    extra_args: &[String],
+    ui: WizardUi,
+) -> Result<String> {
+    if cache.wizard_state != WizardState::NotStarted {
+        if cache.old_message.is_none() {
+            cache.old_message = Some(amended_message()?);
+        }
+
+        return make_commit_message(
+            config,
+            cache,
+            context,
+            extra_args,
+            ui,
+            config.wizard_steps(),
+        );
+    }
+
+    reword_message(config, cache, context, extra_args, ui, &amended_message()?)
+}
+
+/// Makes a commit message for an operation in progress, like a merge.
+///
+/// On a fresh start, the wizard is prefilled from the message prepared by Git
+/// and its body is kept. If a previous run has been aborted, the usual logic
+/// applies, still keeping the body of the prepared message.
+#[tracing::instrument(level = "trace", skip_all)]
+fn make_prepared_message(
+    config: &Config,
+    cache: &mut CommitCache,
+    context: &mut CommitContext,
+    extra_args: &[String],
+    ui: WizardUi,
+    operation: Operation,
+    prepared_message: &str,
+) -> Result<String> {
+    if cache.wizard_state != WizardState::NotStarted {
+        if cache.old_message.is_none() {
+            cache.old_message = Some(prepared_message.to_owned());
+        }
+
+        return make_commit_message(
+            config,
+            cache,
+            context,
+            extra_args,
+            ui,
+            config.wizard_steps(),
+        );
+    }
+
+    hint!(
+        "A {operation} is in progress: the wizard is prefilled from the \
+        message prepared by Git."
+    );
+
+    reword_message(config, cache, context, extra_args, ui, prepared_message)
+}
+
+/// Makes a commit message for a merge in progress, with the merge template.
+///
+/// Only the questions of the subject line are asked with the sequential
+/// prompts. On a fresh start, the description is the subject prepared by Git,
+/// and its merge summary is kept as the body. If a previous run has been
+/// aborted, the usual logic applies, still in this mode.
+#[tracing::instrument(level = "trace", skip_all)]
+fn make_merge_message(
+    config: &Config,
+    cache: &mut CommitCache,
+    context: &mut CommitContext,
+    extra_args: &[String],
+    ui: WizardUi,
+    prepared_message: &str,
+) -> Result<String> {
+    let ui = match ui {
+        WizardUi::Tui => WizardUi::Prompts,
+        WizardUi::Prompts | WizardUi::Plain => ui,
+    };
+
+    let steps = config
+        .wizard_steps()
+        .into_iter()
+        .filter(|step| MERGE_STEPS.contains(step))
+        .collect();
+
+    if cache.wizard_state != WizardState::NotStarted {
+        if cache.old_message.is_none() {
+            cache.old_message = Some(prepared_message.to_owned());
+        }
+
+        return make_commit_message(
+            config, cache, context, extra_args, ui, steps,
+        );
+    }
+
+    hint!(
+        "A merge is in progress: the summary prepared by Git is kept as the \
+        body of the message."
+    );
+
+    cache.wizard_answers = reword::prefilled_answers(config, prepared_message);
+    cache.old_message = Some(prepared_message.to_owned());
+    cache.set_extra_args(extra_args);
+    cache.mark_wizard_as_ongoing();
+    context.save(cache)?;
+
+    let tera = build_and_check_template(config)?;
+    let rendered = ask_and_render(&tera, config, cache, context, ui, steps)?;
+    Ok(keep_old_message(config, cache, &rendered))
+}
+
+/// Runs the wizard prefilled from an old message, keeping its body.
+///
+/// The old message is saved in the cache, so that its body is still kept if
+/// the wizard is aborted then resumed.
+fn reword_message(
+    config: &Config,
+    cache: &mut CommitCache,
+    context: &mut CommitContext,
+    extra_args: &[String],
+    ui: WizardUi,
+    old_message: &str,
+) -> Result<String> {
+    cache.wizard_answers = reword::prefilled_answers(config, old_message);
+    cache.old_message = Some(old_message.to_owned());
+    cache.set_extra_args(extra_args);
+    cache.mark_wizard_as_ongoing();
+    context.save(cache)?;
+
+    let rendered = run_wizard(config, cache, context, ui)?;
+    Ok(keep_old_message(config, cache, &rendered))
+}
+
+/// Merges the rendered message with the reworded one, if any.
+fn keep_old_message(
+    config: &Config,
+    cache: &CommitCache,
+    rendered: &str,
+) -> String {
+    match &cache.old_message {
+        Some(old_message) => {
+            reword::merge_messages(config, rendered, old_message)
+        }
+        None => rendered.to_owned(),
+    }
+}
+
+/// Makes a commit message fixing a ticket.
+///
+/// On a fresh start, the wizard is prefilled with the `fix` type, the ticket and
+/// its title as the description. If a previous run has been aborted, the usual
+/// logic applies. In any case, the footer referencing the ticket then uses the
+/// closing keyword.
+#[tracing::instrument(level = "trace", skip_all)]
+fn make_fixes_message(
+    config: &Config,
+    cache: &mut CommitCache,
+    context: &mut CommitContext,
+    extra_args: &[String],
+    ui: WizardUi,
+    ticket: &str,
+) -> Result<String> {
+    let message = if cache.wizard_state == WizardState::NotStarted {
+        cache.wizard_answers = WizardAnswers {
+            r#type: config
+                .types
+                .contains_key(FIX_TYPE)
+                .then(|| String::from(FIX_TYPE)),
+            description: ticket::fetch_title(config, ticket),
+            ticket: Some(ticket.to_owned()),
+            ..WizardAnswers::default()
+        };
+        cache.set_extra_args(extra_args);
+        cache.mark_wizard_as_ongoing();
+        context.save(cache)?;
+
+        run_wizard(config, cache, context, ui)?
+    } else {
+        make_commit_message(
+            config,
+            cache,
+            context,
+            extra_args,
+            ui,
+            config.wizard_steps(),
+        )?
+    };
+
+    Ok(match cache.ticket() {
+        Some(ticket) => add_closing_footer(&message, ticket),
+        None => message,
+    })
+}
+
+/// Makes a commit message by running the wizard on the given steps.
+#[tracing::instrument(level = "trace", skip_all)]
+fn make_message_from_wizard(
+    config: &Config,
+    cache: &mut CommitCache,
+    context: &mut CommitContext,
+    extra_args: &[String],
+    ui: WizardUi,
+    steps: Vec<Step>,
+) -> Result<String> {
+    let tera = build_and_check_template(config)?;
+
+    let do_reuse_answers = if cache.wizard_state == WizardState::Ongoing {
+        tracing::debug!(
+            "ongoing wizard state present, asking the user whether to use it"
+        );
+        let do_reuse_answers =
+            ask_reuse_answers(reused_extra_args(cache, extra_args), ui)?;
+
+        if do_reuse_answers {
+            tracing::debug!("reusing answers");
+        } else {
+            tracing::debug!("not reusing answers");
+            cache.reset()?;
+        }
+
+        do_reuse_answers
+    } else {
+        false
+    };
+
+    // NOTE: When starting over while rewording a message, the wizard is
+    // prefilled again from this message.
+    if !do_reuse_answers {
+        if let Some(old_message) = &cache.old_message {
+            cache.wizard_answers =
+                reword::prefilled_answers(config, old_message);
+        }
+    }
+
+    update_extra_args(cache, context, extra_args, do_reuse_answers)?;
+    let rendered = ask_and_render(&tera, config, cache, context, ui, steps)?;
+    Ok(keep_old_message(config, cache, &rendered))
+}
+
+/// Returns whether the staged changes have changed since the wizard has
+/// completed.
+///
+/// Caches without a staged tree are considered unchanged, so that the last
+/// commit message can still be reused.
+fn staged_tree_has_changed(cache: &CommitCache) -> Result<bool> {
+    match cache.staged_tree() {
+        Some(staged_tree) => Ok(staged::write_index_tree()? != staged_tree),
+        None => Ok(false),
+    }
+}
+
+/// Returns the extra arguments of the previous run that would be reused.
+///
+/// They are reused only when no extra argument is given on the command line.
+fn reused_extra_args<'a>(
+    cache: &'a CommitCache,
+    extra_args: &[String],
+) -> &'a [String] {
+    if extra_args.is_empty() {
+        cache.extra_args()
+    } else {
+        &[]
+    }
+}
+
+/// Updates the extra arguments for `git commit` in the cache.
+///
+/// When reusing a previous run, its extra arguments are kept unless new ones
+/// are given on the command line.
+fn update_extra_args(
+    cache: &mut CommitCache,
+    context: &CommitContext,
+    extra_args: &[String],
+    do_reuse: bool,
+) -> Result<()> {
+    if !do_reuse || !extra_args.is_empty() {
+        cache.set_extra_args(extra_args);
+        context.save(cache)?;
+    }
+
+    tracing::debug!(extra_args = ?cache.extra_args());
+    Ok(())
+}
+
+/// Makes a commit message by running the wizard, starting from the answers
+/// present in the cache.
+#[tracing::instrument(level = "trace", skip_all)]
+pub fn run_wizard(
+    config: &Config,
+    cache: &mut CommitCache,
+    context: &mut CommitContext,
+    ui: WizardUi,
+) -> Result<String> {
+    let tera = build_and_check_template(config)?;
+    ask_and_render(&tera, config, cache, context, ui, config.wizard_steps())
+}
+
+/// Renders a commit message from answers given by other means than the wizard.
+pub fn render_message(
+    config: &Config,
+    commit_message: &CommitMessage,
+) -> Result<String> {
+    let tera = build_and_check_template(config)?;
+    render(&tera, config, commit_message)
+}
+
+/// Runs the wizard and renders the commit message.
+///
+/// Only the given steps are asked with the prompts, while the full-screen form
+/// always asks every step.
+fn ask_and_render(
+    tera: &Tera,
+    config: &Config,
+    cache: &mut CommitCache,
+    context: &mut CommitContext,
+    ui: WizardUi,
+    steps: Vec<Step>,
+) -> Result<String> {
+    let preview_tera = preview_tera(tera, config);
+
+    let commit_message = match ui {
+        WizardUi::Prompts | WizardUi::Plain => CommitMessage::run_wizard(
+            &preview_tera,
+            config,
+            cache,
+            context,
+            ui,
+            steps,
+        )?,
+        WizardUi::Tui => {
+            CommitMessage::run_form(config, cache, context, &|answers| {
+                preview(&preview_tera, config, answers)
+            })?
+        }
+    };
+
+    render(tera, config, &commit_message)
+}
+
+/// Returns the templates used to render the previews.
+///
+/// The previews are rendered from partial answers, on each keystroke with the
+/// full-screen form, so `issue_title` only returns the titles already cached.
+fn preview_tera(tera: &Tera, config: &Config) -> Tera {
+    let mut preview_tera = tera.clone();
+    preview_tera.register_function(
+        issue_title::FUNCTION_NAME,
+        IssueTitle::cached(title_command(config)),
+    );
+    preview_tera
+}
+
+/// Prints a preview of the commit message, if enabled in the configuration.
+fn print_preview(tera: &Tera, config: &Config, answers: &CommitMessage) {
+    let enabled = config
+        .commit
+        .as_ref()
+        .and_then(|commit| commit.preview)
+        .unwrap_or(true);
+
+    if enabled {
+        let preview = unedited_message(config, &preview(tera, config, answers))
+            .lines()
+            .map(|line| format!("  │ {line}"))
+            .join("\n");
+
+        eprintln!("{}", format!("Preview:\n{preview}\n").dimmed());
+    }
+}
+
+/// Renders a preview of the commit message from partial answers.
+///
+/// Contrary to [`render`], the message is not checked, and errors are returned
+/// as the preview itself.
+fn preview(tera: &Tera, config: &Config, answers: &CommitMessage) -> String {
+    let rendered = template_context(config, answers).and_then(|context| {
+        let message =
+            tera.render(&template_name(config, &answers.r#type), &context)?;
+        Ok((context, message))
+    });
+
+    match rendered {
+        Ok((context, message)) => {
+            let message =
+                ticket::add_trailer(config, answers.ticket.as_deref(), message);
+            let message = add_tool_footer(config, message);
+
+            match config.templates.subject {
+                Some(_) => subject::render(tera, &context)
+                    .map_or(message.clone(), |subject| {
+                        subject::replace_subject(&message, &subject)
+                    }),
+                None => message,
+            }
+        }
+        Err(error) => format!("Failed to render the template: {error}"),
+    }
+}
+
+/// Renders the commit message.
+fn render(
+    tera: &Tera,
+    config: &Config,
+    commit_message: &CommitMessage,
+) -> Result<String> {
+    let context = template_context(config, commit_message).log_err()?;
+    let message = render_conventional(tera, config, commit_message, &context)?;
+    subject::decorate(tera, config, &context, message)
+}
+
+/// Renders the commit message before decorating its subject line.
+fn render_conventional(
+    tera: &Tera,
+    config: &Config,
+    commit_message: &CommitMessage,
+    context: &Context,
+) -> Result<String> {
+    let message = tera
+        .render(&template_name(config, &commit_message.r#type), context)
+        .log_err()?;
+    let message =
+        ticket::add_trailer(config, commit_message.ticket.as_deref(), message);
+    let message = add_tool_footer(config, message);
+    tracing::debug!(rendered_message = ?message,);
+    check_rendered_message(&message)?;
+    Ok(message)
+}
+
+/// Builds the context to render the commit template.
+///
+/// Besides the answers, the template can access the `[ticket]` table as
+/// `config.ticket`, the custom variables from `[template_vars]` as `vars` and
+/// the value of the tool footer as `generated_by`.
+fn template_context(
+    config: &Config,
+    answers: &CommitMessage,
+) -> tera::Result<Context> {
+    let mut context = Context::from_serialize(answers)?;
+
+    context.insert("config", &TemplateConfig::from(config));
+    context.insert(
+        "vars",
+        &config.template_vars.as_ref().unwrap_or(&IndexMap::new()),
+    );
+    context.insert("generated_by", &generated_by(config));
+
+    Ok(context)
+}
+
+/// Returns the value of the tool footer, if enabled.
+fn generated_by(config: &Config) -> Option<String> {
+    config
+        .commit
+        .as_ref()
+        .and_then(|commit| commit.tool_footer)
+        .unwrap_or(false)
+        .then(|| format!("git-z {}", env!("CARGO_PKG_VERSION")))
+}
+
+/// Adds the tool footer to a rendered message, if enabled.
+///
+/// The template can place the footer itself. Otherwise, it is appended to the
+/// existing footers, or as a new paragraph if there are none.
+fn add_tool_footer(config: &Config, message: String) -> String {
+    let Some(generated_by) = generated_by(config) else {
+        return message;
+    };
+
+    let already_placed = message.lines().any(|line| {
+        line.strip_prefix(TOOL_FOOTER_TOKEN)
+            .is_some_and(|rest| rest.starts_with(':'))
+    });
+
+    if already_placed {
+        return message;
+    }
+
+    let has_footers = ConventionalCommit::parse(&message)
+        .is_ok_and(|commit| !commit.footers.is_empty());
+    let separator = if has_footers { "\n" } else { "\n\n" };
+
+    format!(
+        "{}{separator}{TOOL_FOOTER_TOKEN}: {generated_by}\n",
+        message.trim_end()
+    )
+}
+
+/// Makes the footer referencing a ticket close it.
+///
+/// The token of the first footer referencing the ticket is replaced with the
+/// closing keyword. If there is none, a closing footer is appended.
+fn add_closing_footer(message: &str, ticket: &str) -> String {
+    let closing_footer = format!("{CLOSING_TOKEN}: {ticket}");
+    let mut placed = false;
+
+    let lines = message
+        .lines()
+        .map(|line| {
+            let references_ticket = footer_token(line).is_some_and(|token| {
+                !token.starts_with("BREAKING")
+                    && line
+                        .get(token.len()..)
+                        .map(|rest| rest.trim_start_matches(':').trim())
+                        == Some(ticket)
+            });
+
+            if references_ticket && !placed {
+                placed = true;
+                closing_footer.as_str()
+            } else {
+                line
+            }
+        })
+        .join("\n");
+
+    if placed {
+        return format!("{lines}\n");
+    }
+
+    let has_footers = ConventionalCommit::parse(message)
+        .is_ok_and(|commit| !commit.footers.is_empty());
+    let separator = if has_footers { "\n" } else { "\n\n" };
+
+    format!("{}{separator}{closing_footer}\n", message.trim_end())
+}
+
+/// Returns the name of the template to render for a commit type.
+///
+/// A type with its own template overrides the selected commit template.
+fn template_name(config: &Config, r#type: &str) -> String {
+    match config.types.get(r#type).and_then(Type::template) {
+        Some(_) => format!("types.{type}"),
+        None => String::from("templates.commit"),
+    }
+}
+
+/// Returns whether the wizard skips a question for a commit type.
+fn skips(config: &Config, r#type: &str, question: Question) -> bool {
+    config
+        .types
+        .get(r#type)
+        .is_some_and(|r#type| r#type.skips(question))
+}
+
+/// Loads the commit templates and checks for errors.
+///
+/// Besides the commit template, the templates of the types are loaded as
+/// `types.<type>`. The templates can call `issue_title(id)` to embed the title
+/// of an issue.
+#[tracing::instrument(level = "trace", skip_all)]
+pub fn build_and_check_template(config: &Config) -> Result<Tera> {
+    let mut tera = Tera::default();
+    let mut names = vec![String::from("templates.commit")];
+
+    ticket::check_trailer_token(config)?;
+
+    // NOTE: The dummy commit below must not fetch anything, so the function is
+    // only able to fetch the titles once the templates have been checked.
+    tera.register_function(issue_title::FUNCTION_NAME, IssueTitle::new(None));
+
+    tera.add_raw_template("templates.commit", &config.templates.commit)
+        .map_err(CommitError::Template)
+        .log_err()?;
+
+    for (r#type, template) in type_templates(config)? {
+        let name = format!("types.{type}");
+        tera.add_raw_template(&name, template)
+            .map_err(CommitError::Template)
+            .log_err()?;
+        names.push(name);
+    }
+
+    if let Some(template) = &config.templates.subject {
+        tera.add_raw_template(subject::TEMPLATE_NAME, template)
+            .map_err(CommitError::Template)
+            .log_err()?;
+    }
+
+    // Render a dummy commit to catch early any variable error.
+    let context =
+        template_context(config, &CommitMessage::dummy()).log_err()?;
+
+    if config.templates.subject.is_some() {
+        subject::render(&tera, &context)?;
+    }
+
+    for name in names {
+        let dummy_message = tera
+            .render(&name, &context)
+            .map_err(CommitError::Template)
+            .log_err()?;
+
+        check_rendered_message(&dummy_message)?;
+    }
+
+    tera.register_function(
+        issue_title::FUNCTION_NAME,
+        IssueTitle::new(title_command(config)),
+    );
+
+    Ok(tera)
+}
+
+/// Returns the command printing the title of an issue, if any.
+fn title_command(config: &Config) -> Option<&str> {
+    config
+        .ticket
+        .as_ref()
+        .and_then(|ticket| ticket.title_command.as_deref())
+}
+
+/// Returns the named templates used by the types.
+fn type_templates(config: &Config) -> Result<Vec<(&str, &str)>, CommitError> {
+    config
+        .types
+        .iter()
+        .filter_map(|(r#type, config_type)| {
+            config_type.template().map(|name| (r#type, name))
+        })
+        .map(|(r#type, name)| {
+            config
+                .templates
+                .named
+                .as_ref()
+                .and_then(|named| named.get(name))
+                .map(|template| (r#type.as_str(), template.as_str()))
+                .ok_or_else(|| CommitError::UnknownTemplate {
+                    name: name.to_owned(),
+                    available: config
+                        .templates
+                        .named
+                        .iter()
+                        .flat_map(|named| named.keys().cloned())
+                        .collect(),
+                })
+        })
+        .collect::<Result<_, _>>()
+        .log_err()
+}
+
+/// Checks the rendered message is a valid conventional commit.
+///
+/// This catches mistakes in the template, like a missing `:` or a line break
+/// in the first line, before they make it to the history. As the parser skips
+/// the leading blank lines, the raw first line is checked first.
+fn check_rendered_message(message: &str) -> Result<(), CommitError> {
+    let first_line = message.lines().next().unwrap_or_default();
+
+    if first_line.trim().is_empty() {
+        return Err(CommitError::LeadingBlankLine).log_err();
+    }
+
+    ConventionalCommit::parse(message)
+        .map(|_| ())
+        .map_err(|error| CommitError::InvalidRenderedMessage {
+            first_line: first_line.to_owned(),
+            error,
+        })
+        .log_err()
+}
+
+/// Asks the user whether to reuse the commit message from an aborted run.
+///
+/// A preview of the message is shown in the help message, so that a stale
+/// message is not reused blindly.
+fn ask_reuse_message(
+    message: &str,
+    extra_args: &[String],
+    ui: WizardUi,
+) -> Result<bool> {
+    let help_message = with_extra_args_help(
+        &format!(
+            "Last message: `{}`. This will use your last commit message \
+                without running the wizard.",
+            reuse_preview(message)
+        ),
+        extra_args,
+    );
+
+    confirm(
+        ui,
+        "A previous run has been aborted. Do you want to reuse your commit \
+            message?",
+        Some(&help_message),
+        true,
+    )
+}
+
+/// Asks the user whether to reuse answers from an aborted run.
+fn ask_reuse_answers(extra_args: &[String], ui: WizardUi) -> Result<bool> {
+    let help_message = with_extra_args_help(
+        "The wizard will be run as usual with your answers pre-selected.",
+        extra_args,
+    );
+
+    confirm(
+        ui,
+        "A previous run has been aborted. Do you want to reuse your answers?",
+        Some(&help_message),
+        true,
+    )
+}
+
+/// Asks the user a yes / no question.
+fn confirm(
+    ui: WizardUi,
+    message: &str,
+    help_message: Option<&str>,
+    default: bool,
+) -> Result<bool> {
+    if ui == WizardUi::Plain {
+        return plain::confirm(message, help_message, default);
+    }
+
+    let mut prompt = Confirm::new(message).with_default(default);
+
+    if let Some(help_message) = help_message {
+        prompt = prompt.with_help_message(help_message);
+    }
+
+    Ok(prompt.prompt().log_err()?)
+}
+
+/// Builds a one-line preview of a message, with its subject and first body
+/// lines.
+fn reuse_preview(message: &str) -> String {
+    let lines: Vec<&str> = message
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    let mut preview = lines
+        .iter()
+        .take(REUSE_PREVIEW_LINES)
+        .map(|line| truncate(line, REUSE_PREVIEW_LINE_LENGTH))
+        .join(" | ");
+
+    if lines.len() > REUSE_PREVIEW_LINES {
+        preview.push_str(" | ...");
+    }
+
+    preview
+}
+
+/// Truncates a line to a maximum number of characters, ending it with `...`.
+fn truncate(line: &str, max_length: usize) -> String {
+    if line.chars().count() <= max_length {
+        line.to_owned()
+    } else {
+        let truncated: String =
+            line.chars().take(max_length.saturating_sub(3)).collect();
+        format!("{truncated}...")
+    }
+}
+
+/// Completes a help message with the extra arguments that would be reused.
+fn with_extra_args_help(help_message: &str, extra_args: &[String]) -> String {
+    if extra_args.is_empty() {
+        help_message.to_owned()
+    } else {
+        format!(
+            "{help_message} The extra arguments `{}` will be passed to `git \
+                commit` again.",
+            extra_args.join(" ")
+        )
+    }
+}
+
+/// Asks the user which type of commit they wants.
+fn ask_type(
     config: &Config,
     cache: &mut CommitCache,
+    context: &CommitContext,
+    ui: WizardUi,
+    skip_rest: &Cell<bool>,
 ) -> Result<String> {
-    let tera = build_and_check_template(config)?;
+    let recent = if config
+        .type_list
+        .as_ref()
+        .is_some_and(|type_list| type_list.order == TypeOrder::Recent)
+    {
+        recent_types(config)?
+    } else {
+        vec![]
+    };
 
-    if cache.wizard_state == WizardState::Ongoing {
-        tracing::debug!(
-            "ongoing wizard state present, asking the user whether to use it"
-        );
-        let do_reuse_answers = ask_reuse_answers()?;
+    let arranged =
+        type_list::arrange(&config.types, config.type_list.as_ref(), &recent);
 
-        if do_reuse_answers {
-            tracing::debug!("reusing answers");
-        } else {
-            tracing::debug!("not reusing answers");
-            cache.reset()?;
+    let suggestion = match cache.r#type() {
+        None if suggests_type(config) => {
+            suggest_type(context).filter(|suggestion| {
+                arranged.types.contains_key(suggestion.r#type)
+            })
         }
-    }
+        _ => None,
+    };
 
-    let commit_message = CommitMessage::run_wizard(config, cache)?;
-    let context = Context::from_serialize(commit_message).log_err()?;
-    let message = tera.render("templates.commit", &context).log_err()?;
-    tracing::debug!(rendered_message = ?message,);
+    let help_message = suggestion.as_ref().map(|suggestion| {
+        format!(
+            "`{}` is suggested as {}.",
+            suggestion.r#type, suggestion.reason
+        )
+    });
 
-    Ok(message)
-}
+    let selected = suggestion
+        .map(|suggestion| suggestion.r#type)
+        .or_else(|| cache.r#type())
+        .unwrap_or_default();
+    let cursor = arranged.types.get_index_of(selected).unwrap_or_default();
 
-/// Loads the commit template and checks for errors.
-#[tracing::instrument(level = "trace", skip_all)]
-fn build_and_check_template(config: &Config) -> Result<Tera> {
-    let mut tera = Tera::default();
+    let r#type = if ui == WizardUi::Plain {
+        if let Some(help_message) = &help_message {
+            eprintln!("{help_message}");
+        }
 
-    tera.add_raw_template("templates.commit", &config.templates.commit)
-        .map_err(CommitError::Template)
-        .log_err()?;
+        let types = format_types(&arranged.types);
+        plain::select("Commit type", &types, cursor, false, Some(skip_rest))?
+            .and_then(|index| arranged.types.get_index(index))
+            .map(|(r#type, _)| r#type.clone())
+            .unwrap_or_default()
+    } else {
+        let mut prompt = select::Select::new(
+            "Commit type",
+            &arranged.types,
+            cursor,
+            PAGE_SIZE,
+        )
+        .with_groups(&arranged.groups)
+        .with_skip_rest(skip_rest);
 
-    // Render a dummy commit to catch early any variable error.
-    tera.render(
-        "templates.commit",
-        &Context::from_serialize(CommitMessage::dummy()).log_err()?,
-    )
-    .map_err(CommitError::Template)
-    .log_err()?;
+        if let Some(help_message) = &help_message {
+            prompt = prompt.with_help_message(help_message);
+        }
 
-    Ok(tera)
+        prompt.prompt()?
+    };
+
+    tracing::debug!(?r#type);
+    cache.set_type(&r#type);
+    context.save(cache)?;
+
+    Ok(r#type)
 }
 
-/// Asks the user whether to reuse the commit message from an aborted run.
-fn ask_reuse_message() -> Result<bool> {
-    Ok(Confirm::new(
-        "A previous run has been aborted. Do you want to reuse your commit \
-            message?",
-    )
-    .with_help_message(
-        "This will use your last commit message without running the wizard.",
-    )
-    .with_default(true)
-    .prompt()
-    .log_err()?)
+/// Suggests descriptions from the staged changes.
+///
+/// The suggestions of the command come first, followed by the generated ones.
+/// Only the ones passing the description checks are kept. The staged patch is
+/// only loaded if it has not been already.
+#[tracing::instrument(level = "trace", skip(staged_patch, staged_diff))]
+fn suggest_descriptions(
+    generated: bool,
+    command: Option<&str>,
+    staged_patch: Option<Arc<StagedPatch>>,
+    staged_diff: &StagedDiff,
+) -> Result<Vec<String>> {
+    let mut suggestions = vec![];
+
+    if let Some(command) = command {
+        suggestions.extend(
+            list_command::run_with_input(
+                command,
+                &staged_diff.get()?,
+                SUGGESTION_TIMEOUT,
+            )?
+            .into_iter()
+            .filter(|suggestion| lint::check_description(suggestion).is_none()),
+        );
+    }
+
+    if generated {
+        let patch = match staged_patch {
+            Some(patch) => patch,
+            None => Arc::new(StagedPatch::load()?),
+        };
+
+        suggestions.extend(description_suggestion::suggest(&patch));
+    }
+
+    let suggestions = suggestions.into_iter().unique().collect();
+    tracing::debug!(?suggestions);
+    Ok(suggestions)
 }
 
-/// Asks the user whether to reuse answers from an aborted run.
-fn ask_reuse_answers() -> Result<bool> {
-    Ok(Confirm::new(
-        "A previous run has been aborted. Do you want to reuse your answers?",
-    )
-    .with_help_message(
-        "The wizard will be run as usual with your answers pre-selected.",
-    )
-    .with_default(true)
-    .prompt()
-    .log_err()?)
+/// Waits for the suggested descriptions.
+///
+/// As they are only a convenience, a failure is reported as a warning and the
+/// wizard goes on without suggestions.
+fn wait_for_description_suggestions(
+    suggestions: Prefetch<Result<Vec<String>>>,
+) -> Vec<String> {
+    suggestions.wait().unwrap_or_else(|error| {
+        warning!("No description can be suggested: {error}.");
+
+        if let Some(ListCommandError::Failed { stderr, .. }) =
+            error.downcast_ref::<ListCommandError>()
+        {
+            if !stderr.is_empty() {
+                hint!("The command reports: {stderr}");
+            }
+        }
+
+        vec![]
+    })
 }
 
-/// Asks the user which type of commit they wants.
-fn ask_type(config: &Config, cache: &mut CommitCache) -> Result<String> {
-    let cached = cache.r#type().unwrap_or_default();
-    let cursor = config.types.get_index_of(cached).unwrap_or_default();
-
-    let choice = Select::new("Commit type", format_types(&config.types))
-        .with_starting_cursor(cursor)
-        .with_page_size(PAGE_SIZE)
-        .with_formatter(&|choice| remove_type_description(choice.value))
-        .prompt()
-        .log_err()?;
-    let r#type = remove_type_description(&choice);
+/// Returns whether to suggest a type from the staged changes.
+fn suggests_type(config: &Config) -> bool {
+    config
+        .wizard
+        .as_ref()
+        .is_some_and(|wizard| wizard.suggest_type)
+}
 
-    tracing::debug!(?r#type);
-    cache.set_type(&r#type)?;
+/// Suggests a type from the staged changes.
+///
+/// The staged patch is only loaded if it has not been already. As the
+/// suggestion is only a convenience, a failure is reported as a warning and the
+/// wizard goes on without suggestion.
+fn suggest_type(
+    context: &CommitContext,
+) -> Option<type_suggestion::Suggestion> {
+    let patch = match context.loaded_staged_patch() {
+        Some(patch) => patch,
+        None => match StagedPatch::load().log_err() {
+            Ok(patch) => Arc::new(patch),
+            Err(error) => {
+                warning!("No type can be suggested: {error}.");
+                return None;
+            }
+        },
+    };
 
-    Ok(r#type)
+    type_suggestion::suggest(&patch)
 }
 
 /// Asks the user to which scope the changes are applicable.
 fn ask_scope(
     config: &Config,
     cache: &mut CommitCache,
+    context: &CommitContext,
+    ui: WizardUi,
+    skip_rest: &Cell<bool>,
 ) -> Result<Option<String>> {
     let scope = match &config.scopes {
         None => None,
 
-        Some(Scopes::Any) => Text::new("Scope")
+        Some(Scopes::Any { required }) if ui == WizardUi::Plain => plain::text(
+            "Scope",
+            cache.scope().unwrap_or_default(),
+            None,
+            required.then_some::<plain::Validator>(validate_scope),
+            !*required,
+            Some(skip_rest),
+        )?,
+
+        Some(Scopes::List {
+            list,
+            order,
+            required,
+            ..
+        }) if ui == WizardUi::Plain => {
+            let names = order_scopes(config, list, *order);
+            let cached = cache.scope().unwrap_or_default();
+            let cursor =
+                names.iter().position(|s| s == cached).unwrap_or_default();
+
+            plain::select(
+                "Scope",
+                &format_scopes(list, &names),
+                cursor,
+                !*required,
+                Some(skip_rest),
+            )?
+            .and_then(|index| names.get(index).cloned())
+        }
+
+        Some(Scopes::Any { required: true }) => Some(
+            text::Text::new("Scope")
+                .with_initial_value(cache.scope().unwrap_or_default())
+                .with_validator(validate_scope)
+                .with_skip_rest(skip_rest)
+                .prompt()?,
+        ),
+
+        Some(Scopes::Any { required: false }) => text::Text::new("Scope")
             .with_initial_value(cache.scope().unwrap_or_default())
             .with_help_message("Press ESC or leave empty to omit the scope.")
-            .prompt_skippable()
-            .log_err()?
+            .with_skip_rest(skip_rest)
+            .prompt_skippable()?
             .filter(|s| !s.is_empty()),
 
-        Some(Scopes::List { list }) => {
+        Some(Scopes::List {
+            list,
+            order,
+            required,
+            ..
+        }) => {
+            let names = order_scopes(config, list, *order);
             let cached = cache.scope().unwrap_or_default();
             let cursor =
-                list.iter().position(|s| s == cached).unwrap_or_default();
+                names.iter().position(|s| s == cached).unwrap_or_default();
 
-            let help_message = "↑↓ to move, enter to select, type to \
-                filter, ESC to leave empty, update `git-z.toml` to add new \
-                scopes";
+            let help_message = if *required {
+                "Update `git-z.toml` to add new scopes."
+            } else {
+                "ESC to leave empty, update `git-z.toml` to add new scopes."
+            };
+
+            // NOTE: The options contain the descriptions, so that they are
+            // displayed in a column. The scope is retrieved from the index of
+            // the selected option.
+            let options = format_scopes(list, &names)
+                .into_iter()
+                .map(|option| (option, String::new()))
+                .collect::<IndexMap<_, _>>();
+
+            let prompt =
+                select::Select::new("Scope", &options, cursor, PAGE_SIZE)
+                    .with_help_message(help_message)
+                    .with_skip_rest(skip_rest);
+
+            let selected = if *required {
+                Some(prompt.prompt()?)
+            } else {
+                prompt.prompt_skippable()?
+            };
 
-            Select::new("Scope", list.clone())
-                .with_starting_cursor(cursor)
-                .with_help_message(help_message)
-                .with_page_size(PAGE_SIZE)
-                .prompt_skippable()
-                .log_err()?
+            selected
+                .and_then(|option| options.get_index_of(&option))
+                .and_then(|index| names.get(index).cloned())
         }
     };
 
     tracing::debug!(?scope);
-    cache.set_scope(scope.as_deref())?;
+    cache.set_scope(scope.as_deref());
+    context.save(cache)?;
 
     Ok(scope)
 }
 
+/// Orders the list of scopes for the wizard.
+fn order_scopes(
+    config: &Config,
+    list: &ScopeList,
+    order: ScopeOrder,
+) -> Vec<String> {
+    match order {
+        ScopeOrder::Config => list.names(),
+        ScopeOrder::Frequency => {
+            scope_frequency::order_by_frequency(config, &list.names())
+        }
+    }
+}
+
+/// Formats the scopes for the prompts, with their description in a column.
+fn format_scopes(list: &ScopeList, names: &[String]) -> Vec<String> {
+    let width = names
+        .iter()
+        .map(|name| display_width(name))
+        .max()
+        .unwrap_or_default();
+
+    names
+        .iter()
+        .map(|name| match list.description(name) {
+            Some(description) => format!("{}  {description}", pad(name, width)),
+            None => name.clone(),
+        })
+        .collect()
+}
+
+/// Returns the width of the subject with the given description.
+///
+/// The subject is the first line of the preview, so that the decorations of
+/// the template are counted.
+fn subject_width(
+    tera: &Tera,
+    config: &Config,
+    answers: &CommitMessage,
+    description: &str,
+) -> usize {
+    let answers = CommitMessage {
+        r#type: answers.r#type.clone(),
+        scope: answers.scope.clone(),
+        description: description.to_owned(),
+        breaking_change: answers.breaking_change.clone(),
+        ticket: answers.ticket.clone(),
+    };
+    let preview = preview(tera, config, &answers);
+
+    display_width(preview.lines().next().unwrap_or_default())
+}
+
 /// Asks the user for a commit description.
-fn ask_description(cache: &mut CommitCache) -> Result<String> {
-    let placeholder =
-        "describe your change with a short description (5-50 characters)";
-    let message = "You will be able to add a long description to your \
-        commit in an editor later.";
-
-    let description = Text::new("Short description")
-        .with_placeholder(placeholder)
-        .with_initial_value(cache.description().unwrap_or_default())
-        .with_help_message(message)
-        .with_validator(validate_description)
-        .prompt()
-        .log_err()?;
+///
+/// The help message tells how many columns are left for it in the subject,
+/// and a gauge of the subject length is updated while typing.
+fn ask_description(
+    config: &Config,
+    cache: &mut CommitCache,
+    context: &CommitContext,
+    suggestions: Prefetch<Result<Vec<String>>>,
+    subject_width: &dyn Fn(&str) -> usize,
+    ui: WizardUi,
+    skip_rest: &Cell<bool>,
+) -> Result<String> {
+    let max_length = config.max_subject_length();
+    let room = max_length
+        .saturating_sub(subject_width(""))
+        .min(MAX_DESCRIPTION_LEN);
+    let placeholder = format!(
+        "describe your change with a short description \
+            ({MIN_DESCRIPTION_LEN}-{} characters)",
+        room.max(MIN_DESCRIPTION_LEN)
+    );
+    let message = format!(
+        "The subject has room for {room} more characters. You will be able \
+            to add a long description to your commit in an editor later."
+    );
+    let suggestions = wait_for_description_suggestions(suggestions);
+
+    let description = if ui == WizardUi::Plain {
+        let mut help_message = format!("Please {placeholder}. {message}");
+
+        if !suggestions.is_empty() {
+            help_message.push_str("\nSuggestions: ");
+            help_message.push_str(&suggestions.join("; "));
+        }
+
+        plain::text(
+            "Short description",
+            cache.description().unwrap_or_default(),
+            Some(&help_message),
+            Some(validate_description),
+            false,
+            Some(skip_rest),
+        )?
+        .unwrap_or_default()
+    } else {
+        let gauge = |description: &str| {
+            tui::gauge(subject_width(description), max_length)
+        };
+        let help_message = if suggestions.is_empty() {
+            message
+        } else {
+            format!("Use ↑↓ and tab to pick a suggestion. {message}")
+        };
+
+        text::Text::new("Short description")
+            .with_placeholder(&placeholder)
+            .with_initial_value(cache.description().unwrap_or_default())
+            .with_help_message(&help_message)
+            .with_validator(validate_description)
+            .with_suggestions(&suggestions)
+            .with_status(&gauge)
+            .with_skip_rest(skip_rest)
+            .prompt()?
+    };
 
     tracing::debug!(?description);
-    cache.set_description(&description)?;
+    cache.set_description(&description);
+    context.save(cache)?;
 
     Ok(description)
 }
 
 /// Asks the user for an optional breaking change description.
-fn ask_breaking_change(cache: &mut CommitCache) -> Result<Option<String>> {
-    let breaking_change = Text::new("BREAKING CHANGE")
-        .with_placeholder("Summary of the breaking change.")
-        .with_initial_value(cache.breaking_change().unwrap_or_default())
-        .with_help_message(
-            "Press ESC or leave empty if there are no breaking changes.",
-        )
-        .prompt_skippable()
-        .log_err()?
-        .filter(|s| !s.is_empty());
+///
+/// When `breaking_change.suggestion_command` is set, the user is first asked
+/// whether the commit is a breaking change. If so, the output of the command
+/// pre-fills the description.
+fn ask_breaking_change(
+    config: &Config,
+    cache: &mut CommitCache,
+    context: &CommitContext,
+    likely_breaking_changes: Prefetch<Result<Vec<String>>>,
+    ui: WizardUi,
+    skip_rest: &Cell<bool>,
+) -> Result<Option<String>> {
+    let suggestion_command =
+        config.breaking_change.as_ref().and_then(|breaking_change| {
+            breaking_change.suggestion_command.as_deref()
+        });
+
+    let mut suggestion = None;
+
+    if cache.breaking_change().is_none() {
+        let likely_breaking_changes =
+            wait_for_likely_breaking_changes(likely_breaking_changes);
+        let is_likely = !likely_breaking_changes.is_empty();
+
+        if is_likely {
+            let reasons = likely_breaking_changes
+                .iter()
+                .map(|reason| format!("  - {reason}"))
+                .join("\n");
+
+            hint!(
+                "The staged changes look like a breaking change:\n{reasons}\n"
+            );
+        }
+
+        if is_likely || suggestion_command.is_some() {
+            let is_breaking_change =
+                confirm(ui, "Is this a breaking change?", None, is_likely)?;
+
+            if !is_breaking_change {
+                tracing::debug!("no breaking change");
+                cache.set_breaking_change(None);
+                context.save(cache)?;
+                return Ok(None);
+            }
+
+            suggestion = suggestion_command.and_then(|command| {
+                suggest_breaking_change(command, &context.staged_diff())
+            });
+        }
+    }
+
+    let initial_value = cache
+        .breaking_change()
+        .or(suggestion.as_deref())
+        .unwrap_or_default();
+
+    let breaking_change = if ui == WizardUi::Plain {
+        plain::text(
+            "BREAKING CHANGE",
+            initial_value,
+            Some("Summarise the breaking change, if any."),
+            None,
+            true,
+            Some(skip_rest),
+        )?
+    } else {
+        text::Text::new("BREAKING CHANGE")
+            .with_placeholder("Summary of the breaking change.")
+            .with_initial_value(initial_value)
+            .with_help_message(
+                "Press ESC or leave empty if there are no breaking changes.",
+            )
+            .with_skip_rest(skip_rest)
+            .prompt_skippable()?
+    }
+    .filter(|s| !s.is_empty());
 
     tracing::debug!(?breaking_change);
-    cache.set_breaking_change(breaking_change.as_deref())?;
+    cache.set_breaking_change(breaking_change.as_deref());
+    context.save(cache)?;
 
     Ok(breaking_change)
 }
 
+/// Suggests a description of the breaking change with `command`.
+///
+/// The command receives the staged diff on its standard input, and the lines
+/// it prints are joined into one. It is killed if it runs for too long. As the
+/// suggestion is only a convenience, a failure is reported as a warning and no
+/// description is suggested.
+fn suggest_breaking_change(
+    command: &str,
+    staged_diff: &StagedDiff,
+) -> Option<String> {
+    hint!("Running `{command}` to suggest the breaking change...");
+
+    let suggestion =
+        staged_diff
+            .get()
+            .map_err(eyre::Report::from)
+            .and_then(|diff| {
+                Ok(list_command::run_with_input(
+                    command,
+                    &diff,
+                    SUGGESTION_TIMEOUT,
+                )?)
+            });
+
+    match suggestion {
+        Ok(lines) => {
+            let suggestion = lines.join(" ");
+            tracing::debug!(?suggestion);
+            (!suggestion.is_empty()).then_some(suggestion)
+        }
+        Err(error) => {
+            warning!("No breaking change can be suggested: {error}.");
+
+            if let Some(ListCommandError::Failed { stderr, .. }) =
+                error.downcast_ref::<ListCommandError>()
+            {
+                if !stderr.is_empty() {
+                    hint!("The command reports: {stderr}");
+                }
+            }
+
+            None
+        }
+    }
+}
+
+/// Detects likely breaking changes from the staged changes.
+///
+/// The staged patch is only loaded if it has not been already.
+#[tracing::instrument(level = "trace", skip_all)]
+fn detect_breaking_changes(
+    rules: &[BreakingChangeRule],
+    staged_patch: Option<Arc<StagedPatch>>,
+) -> Result<Vec<String>> {
+    let patch = match staged_patch {
+        Some(patch) => patch,
+        None => Arc::new(StagedPatch::load()?),
+    };
+
+    breaking_change::detect(rules, &patch)
+        .wrap_err("Invalid regex in the breaking change rules")
+        .log_err()
+}
+
+/// Waits for the likely breaking changes.
+///
+/// As the detection is only advisory, a failure is reported as a warning and
+/// the wizard goes on as if nothing had been detected.
+fn wait_for_likely_breaking_changes(
+    likely_breaking_changes: Prefetch<Result<Vec<String>>>,
+) -> Vec<String> {
+    likely_breaking_changes.wait().unwrap_or_else(|error| {
+        warning!("Likely breaking changes cannot be detected: {error}.");
+        vec![]
+    })
+}
+
 /// Optionally asks the user for a ticket reference.
 fn ask_ticket(
     config: &Config,
     cache: &mut CommitCache,
+    context: &CommitContext,
+    detected_ticket: Prefetch<Result<ticket::Detection>>,
+    ui: WizardUi,
+    skip_rest: &Cell<bool>,
 ) -> Result<Option<String>> {
     let ticket = match &config.ticket {
         None => None,
-        Some(Ticket { required, prefixes }) => {
+        Some(Ticket {
+            required, prefixes, ..
+        }) => {
             let placeholder = ticket_placeholder(prefixes)?;
             let cached_answer = cache.ticket();
-            let ticket_from_branch = get_ticket_from_branch(prefixes)?;
+            let detected_ticket = detected_ticket.wait()?.into_ticket();
 
             let initial_value = cached_answer.unwrap_or_else(|| {
-                ticket_from_branch.as_deref().unwrap_or_default()
+                detected_ticket.as_deref().unwrap_or_default()
             });
 
-            let prompt = Text::new("Issue / ticket number")
-                .with_placeholder(&placeholder)
-                .with_initial_value(initial_value)
-                .with_validator(validate_ticket);
-
-            if *required {
-                Some(prompt.prompt().log_err()?)
+            if ui == WizardUi::Plain {
+                let help_message = format!("In the form {placeholder}.");
+
+                plain::text(
+                    "Issue / ticket number",
+                    initial_value,
+                    Some(&help_message),
+                    Some(validate_ticket),
+                    !*required,
+                    Some(skip_rest),
+                )?
             } else {
-                prompt
-                    .with_help_message(
-                        "Press ESC to omit the ticket reference.",
-                    )
-                    .prompt_skippable()
-                    .log_err()?
+                let prompt = text::Text::new("Issue / ticket number")
+                    .with_placeholder(&placeholder)
+                    .with_initial_value(initial_value)
+                    .with_validator(validate_ticket)
+                    .with_skip_rest(skip_rest);
+
+                if *required {
+                    Some(prompt.prompt()?)
+                } else {
+                    prompt
+                        .with_help_message(
+                            "Press ESC to omit the ticket reference.",
+                        )
+                        .prompt_skippable()?
+                }
             }
         }
     };
 
     tracing::debug!(?ticket);
-    cache.set_ticket(ticket.as_deref())?;
-
-    Ok(ticket)
-}
-
-/// Tries to extract a ticket number from the name of the current Git branch.
-#[tracing::instrument(level = "trace")]
-fn get_ticket_from_branch(prefixes: &[String]) -> Result<Option<String>> {
-    // Replace `#` with an empty string in the regex, as we want to match
-    // branches like `feature/23-name` when `#` is a valid prefix like for
-    // GitHub or GitLab issues.
-    let regex = ticket_regex(prefixes).replace('#', "");
-
-    let ticket = Regex::new(&regex)
-        .wrap_err("Impossible to build a regex from the list of prefixes")
-        .log_err()?
-        .captures(&get_current_branch()?)
-        .map(|captures| captures[0].to_owned())
-        .map(|ticket| {
-            #[expect(
-                clippy::unwrap_used,
-                reason = "This regex is known to be valid."
-            )]
-            let regex = &Regex::new(r"^\d+$").unwrap();
-
-            // If one of the valid prefixes is `#` and the matched ticket ID is
-            // only made of numbers, we are in the GitHub / GitLab style, so
-            // let’s add a `#` as a prefix to the ticket ID.
-            if prefixes.contains(&String::from("#")) && regex.is_match(&ticket)
-            {
-                format!("#{ticket}")
-            } else {
-                ticket
-            }
-        });
+    cache.set_ticket(ticket.as_deref());
+    context.save(cache)?;
 
-    tracing::trace!(?ticket);
     Ok(ticket)
 }
 
-/// Gets the name of the current Git branch.
-#[tracing::instrument(level = "trace")]
-fn get_current_branch() -> Result<String> {
-    let git_branch = Command::new("git")
-        .args(["branch", "--show-current"])
-        .output()
-        .log_err()?;
-
-    if !git_branch.status.success() {
-        return Err(eyre!("Failed to run `git branch --show-current`"))
-            .log_err();
-    }
-
-    let current_branch = String::from_utf8(git_branch.stdout).log_err()?;
-    tracing::trace!(?current_branch);
-    Ok(current_branch)
+/// Returns whether the terminal cannot handle cursor movements.
+fn is_dumb_terminal() -> bool {
+    env::var("TERM").is_ok_and(|term| term == "dumb")
+        || env::var_os("INSIDE_EMACS").is_some()
 }
 
-/// Formats the list of types and their description.
+/// Formats the list of types and their description for the plain prompts.
 fn format_types(types: &IndexMap<String, String>) -> Vec<String> {
-    let Some(max_type_len) = types.keys().map(String::len).max() else {
-        return vec![];
-    };
+    let width = types.keys().map(|ty| display_width(ty)).max().unwrap_or(0);
 
     types
         .iter()
-        .map(|(ty, doc)| {
-            let padding = " ".repeat(max_type_len - ty.len());
-            format!("{ty}{padding}  {doc}")
-        })
+        .map(|(ty, doc)| format!("{} - {doc}", pad(ty, width)))
         .collect()
 }
 
-/// Removes the type description from the choice.
-#[expect(
-    clippy::missing_panics_doc,
-    reason = "The unwrap in the function cannot actually panic."
-)]
-fn remove_type_description(choice: &str) -> String {
-    #[expect(
-        clippy::unwrap_used,
-        reason = "Even an empty string will contain at least one split, so the \
-            only call to next will always return Some(value)."
-    )]
-    choice.split(' ').next().unwrap().to_owned()
-}
-
 /// Validates the commit description.
-#[expect(
-    clippy::missing_panics_doc,
-    reason = "The unwrap in the function cannot actually panic."
-)]
 #[expect(
     clippy::unnecessary_wraps,
     reason = "The signature of the function is imposed by Inquire."
@@ -567,24 +3263,23 @@ fn remove_type_description(choice: &str) -> String {
 fn validate_description(
     description: &str,
 ) -> Result<Validation, CustomUserError> {
-    #[expect(
-        clippy::unwrap_used,
-        reason = "We know from the first condition that description.len() > 0, \
-            so there is at least one character in the string. Hence, \
-            description.chars().next() in the third condition will always \
-            return Some(value)."
-    )]
-    if description.len() < 5 {
-        Ok(Validation::Invalid(
-            "The description must be longer than 5 characters".into(),
-        ))
-    } else if description.len() > 50 {
-        Ok(Validation::Invalid(
-            "The description must not be longer than 50 characters".into(),
-        ))
-    } else if description.chars().next().unwrap().is_uppercase() {
+    match lint::check_description(description) {
+        Some(violation) => {
+            Ok(Validation::Invalid(violation.to_string().into()))
+        }
+        None => Ok(Validation::Valid),
+    }
+}
+
+/// Validates the scope, when it is required.
+#[expect(
+    clippy::unnecessary_wraps,
+    reason = "The signature of the function is imposed by Inquire."
+)]
+fn validate_scope(scope: &str) -> Result<Validation, CustomUserError> {
+    if scope.is_empty() {
         Ok(Validation::Invalid(
-            "The description must start in lowercase".into(),
+            Violation::MissingScope.to_string().into(),
         ))
     } else {
         Ok(Validation::Valid)
@@ -615,12 +3310,6 @@ fn validate_ticket(ticket: &str) -> Result<Validation, CustomUserError> {
     }
 }
 
-/// Builds a regex to match valid tickets from the list of valid prefixes.
-fn ticket_regex(prefixes: &[String]) -> String {
-    let prefixes = prefixes.join("|");
-    format!("(?:{prefixes})\\d+")
-}
-
 /// Builds the ticket placeholder from the list of valid prefixes.
 fn ticket_placeholder(prefixes: &[String]) -> Result<String> {
     prefixes
@@ -657,6 +3346,49 @@ fn last_commit_message() -> Result<Option<String>> {
     Ok(maybe_message)
 }
 
+/// Returns the types used in the most recent commits, the most recent first.
+#[tracing::instrument(level = "trace", skip(config))]
+fn recent_types(config: &Config) -> Result<Vec<String>> {
+    let recent_types =
+        history::conventional_commits(config, Some(RECENT_COMMITS))?
+            .into_iter()
+            .map(|commit| commit.r#type)
+            .unique()
+            .collect();
+
+    tracing::trace!(?recent_types);
+    Ok(recent_types)
+}
+
+/// Gets the name of the current Git branch, or `None` if `HEAD` is detached.
+#[tracing::instrument(level = "trace")]
+fn current_branch() -> Result<Option<String>> {
+    // NOTE: On a detached HEAD, Git succeeds but prints no branch name.
+    let current_branch = git::run_trimmed(&["branch", "--show-current"])?;
+    tracing::trace!(?current_branch);
+    Ok((!current_branch.is_empty()).then_some(current_branch))
+}
+
+/// Returns the message of the `HEAD` commit, or `None` if there is no commit
+/// yet.
+#[tracing::instrument(level = "trace")]
+fn head_message() -> Result<Option<String>> {
+    if !git::succeeds(&["rev-parse", "--verify", "--quiet", "HEAD"])? {
+        return Ok(None);
+    }
+
+    let head_message = git::run(&["log", "-1", "--format=%B", "HEAD"])?;
+    tracing::trace!(?head_message);
+    Ok(Some(head_message))
+}
+
+/// Returns the message of the commit to amend.
+fn amended_message() -> Result<String> {
+    Ok(head_message()?
+        .ok_or(CommitError::NothingToAmend)
+        .log_err()?)
+}
+
 /// Deletes the last commit message if it exists.
 #[tracing::instrument(level = "trace")]
 fn delete_last_commit_message() -> Result<()> {
@@ -678,27 +3410,192 @@ fn delete_last_commit_message() -> Result<()> {
 
 /// Returns the path to the `COMMIT_EDITMSG` file.
 fn commit_editmsg() -> Result<PathBuf> {
-    Ok(git_dir()?.join("COMMIT_EDITMSG"))
+    Ok(git::path("COMMIT_EDITMSG")?)
 }
 
 /// Returns the path to the pre-commit hook.
-#[cfg(feature = "unstable-pre-commit")]
 fn pre_commit() -> Result<PathBuf> {
-    Ok(git_dir()?.join("hooks").join("pre-commit"))
+    // NOTE: Git resolves `hooks/` to `core.hooksPath` if it is set.
+    Ok(git::path("hooks/pre-commit")?)
 }
 
-/// Returns the path of the Git directory.
-#[tracing::instrument(level = "trace")]
-fn git_dir() -> Result<PathBuf> {
-    let git_rev_parse = Command::new("git")
-        .args(["rev-parse", "--git-dir"])
-        .output()
-        .log_err()?;
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    #[test]
+    fn aligns_the_descriptions_of_the_types() {
+        let types = IndexMap::from([
+            (String::from("feat"), String::from("A new feature")),
+            (String::from("修正"), String::from("A bug fix")),
+            (String::from("ci"), String::from("A CI change")),
+        ]);
+
+        assert_eq!(
+            format_types(&types),
+            [
+                "feat - A new feature",
+                "修正 - A bug fix",
+                "ci   - A CI change",
+            ]
+        );
+    }
+
+    #[test]
+    fn aligns_the_descriptions_of_the_scopes() {
+        let list = ScopeList::Described(IndexMap::from([
+            (String::from("api"), String::from("the HTTP API")),
+            (String::from("配置"), String::from("the configuration")),
+            (String::from("équipe"), String::from("the team")),
+        ]));
+        let names = list.names();
+
+        assert_eq!(
+            format_scopes(&list, &names),
+            [
+                "api     the HTTP API",
+                "配置    the configuration",
+                "équipe  the team",
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_the_default_template_with_a_minimal_message() {
+        let message = CommitMessage {
+            r#type: String::from("feat"),
+            scope: None,
+            description: String::from("add a feature"),
+            breaking_change: None,
+            ticket: None,
+        };
+
+        insta::assert_snapshot!(
+            render_message(&Config::default(), &message).unwrap()
+        );
+    }
+
+    #[test]
+    fn renders_the_default_template_with_a_full_message() {
+        let message = CommitMessage {
+            r#type: String::from("fix"),
+            scope: Some(String::from("api")),
+            description: String::from("stop returning the secrets"),
+            breaking_change: Some(String::from("The secrets are gone.")),
+            ticket: Some(String::from("#42")),
+        };
+
+        insta::assert_snapshot!(
+            render_message(&Config::default(), &message).unwrap()
+        );
+    }
+
+    #[test]
+    fn replaces_the_footer_referencing_the_ticket() {
+        let message = "fix: patch a bug\n\nRefs: #1\nRefs: #12\n";
+
+        assert_eq!(
+            add_closing_footer(message, "#12"),
+            "fix: patch a bug\n\nRefs: #1\nFixes: #12\n"
+        );
+    }
+
+    #[test]
+    fn appends_the_closing_footer_if_the_ticket_is_not_referenced() {
+        assert_eq!(
+            add_closing_footer("fix: patch a bug\n", "#12"),
+            "fix: patch a bug\n\nFixes: #12\n"
+        );
+    }
+
+    fn config_with_tool_footer() -> Config {
+        Config {
+            commit: Some(toml::from_str("tool_footer = true").unwrap()),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn does_not_add_the_tool_footer_by_default() {
+        let message = String::from("feat: add a feature\n");
+
+        assert_eq!(
+            add_tool_footer(&Config::default(), message.clone()),
+            message
+        );
+    }
+
+    #[test]
+    fn appends_the_tool_footer_as_a_new_paragraph() {
+        let message = String::from("feat: add a feature\n\n# A comment.\n");
+
+        assert_eq!(
+            add_tool_footer(&config_with_tool_footer(), message),
+            format!(
+                "feat: add a feature\n\n# A comment.\n\nGenerated-by: git-z {}\n",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+
+    #[test]
+    fn appends_the_tool_footer_to_the_existing_footers() {
+        let message = String::from("feat: add a feature\n\nRefs: #42\n");
+
+        assert_eq!(
+            add_tool_footer(&config_with_tool_footer(), message),
+            format!(
+                "feat: add a feature\n\nRefs: #42\nGenerated-by: git-z {}\n",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+
+    #[test]
+    fn lets_the_template_place_the_tool_footer() {
+        let mut config = config_with_tool_footer();
+        config.templates.commit = String::from(
+            "{{ type }}: {{ description }}\n\n\
+            Generated-by: {{ generated_by }}\n\n\
+            # A comment.\n",
+        );
+
+        let message = CommitMessage {
+            r#type: String::from("feat"),
+            scope: None,
+            description: String::from("add a feature"),
+            breaking_change: None,
+            ticket: None,
+        };
+
+        assert_eq!(
+            render_message(&config, &message).unwrap(),
+            format!(
+                "feat: add a feature\n\nGenerated-by: git-z {}\n\n# A comment.\n",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+
+    #[test]
+    fn previews_the_subject_and_first_body_lines_of_a_message() {
+        let message = "feat: add a feature\n\nFirst line.\nSecond line.\n";
 
-    if !git_rev_parse.status.success() {
-        return Err(eyre!("Failed to run `git rev-parse --git-dir`")).log_err();
+        assert_eq!(
+            reuse_preview(message),
+            "feat: add a feature | First line. | Second line."
+        );
     }
 
-    let git_dir = String::from_utf8(git_rev_parse.stdout).log_err()?;
-    Ok(PathBuf::from(git_dir.trim()))
+    #[test]
+    fn truncates_the_preview_of_a_long_message() {
+        let message = format!("feat: {}\n\n1\n2\n3\n", "a".repeat(60));
+
+        assert_eq!(
+            reuse_preview(&message),
+            format!("feat: {}... | 1 | 2 | ...", "a".repeat(41))
+        );
+    }
 }
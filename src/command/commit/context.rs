@@ -0,0 +1,121 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Runtime context of a commit.
+//!
+//! Contrary to the commit cache, it is never saved: it only lives as long as
+//! the commit being made, and is passed alongside the cache to the wizard.
+
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::{
+    commit_cache::{CommitCache, SaveError},
+    staged::{self, StagedError, StagedPatch},
+};
+
+/// What is known about the commit being made, besides the answers.
+#[derive(Debug, Default)]
+pub struct CommitContext {
+    /// The patch of the staged changes, once loaded.
+    staged_patch: Option<Arc<StagedPatch>>,
+    /// The diff of the staged changes, loaded on first use.
+    staged_diff: StagedDiff,
+    /// Whether the commit cache is kept in memory only, never being saved.
+    ephemeral: bool,
+    /// Whether the message of a previous run is reused as is.
+    reused_message: bool,
+}
+
+impl CommitContext {
+    /// Builds a context from the already loaded patch of the staged changes.
+    pub fn with_staged_patch(staged_patch: StagedPatch) -> Self {
+        Self {
+            staged_patch: Some(Arc::new(staged_patch)),
+            ..Self::default()
+        }
+    }
+
+    /// Builds a context in which the commit cache is never saved to the repo.
+    ///
+    /// This allows to run the wizard without touching the actual cache.
+    pub fn ephemeral() -> Self {
+        Self {
+            ephemeral: true,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the patch of the staged changes, if already loaded.
+    ///
+    /// This is meant to be passed to background tasks, which can then load it
+    /// themselves if needed.
+    pub fn loaded_staged_patch(&self) -> Option<Arc<StagedPatch>> {
+        self.staged_patch.clone()
+    }
+
+    /// Returns the diff of the staged changes, loaded on first use.
+    ///
+    /// The handle can be passed to background tasks, so that the diff is only
+    /// computed once per commit.
+    pub fn staged_diff(&self) -> StagedDiff {
+        self.staged_diff.clone()
+    }
+
+    /// Returns whether the commit cache is kept in memory only.
+    pub fn is_ephemeral(&self) -> bool {
+        self.ephemeral
+    }
+
+    /// Marks the message of a previous run as reused.
+    pub fn mark_message_as_reused(&mut self) {
+        self.reused_message = true;
+    }
+
+    /// Returns whether the message of a previous run is reused.
+    pub fn is_message_reused(&self) -> bool {
+        self.reused_message
+    }
+
+    /// Saves the commit cache, unless the context is ephemeral.
+    pub fn save(&self, cache: &CommitCache) -> Result<(), SaveError> {
+        if self.ephemeral {
+            tracing::trace!("not saving the ephemeral commit cache");
+            return Ok(());
+        }
+
+        cache.save()
+    }
+}
+
+/// The diff of the staged changes, shared between the users of a context.
+#[derive(Debug, Clone, Default)]
+pub struct StagedDiff(Arc<Mutex<Option<Arc<str>>>>);
+
+impl StagedDiff {
+    /// Returns the diff, running `git diff` the first time only.
+    pub fn get(&self) -> Result<Arc<str>, StagedError> {
+        // NOTE: The lock is kept while loading the diff, so that concurrent
+        // users wait for it instead of running `git diff` again.
+        let mut diff = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(diff) = &*diff {
+            return Ok(Arc::clone(diff));
+        }
+
+        let loaded: Arc<str> = staged::diff()?.into();
+        *diff = Some(Arc::clone(&loaded));
+        Ok(loaded)
+    }
+}
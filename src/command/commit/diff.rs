@@ -0,0 +1,51 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! A view of the staged diff, available from the prompts of the wizard.
+//!
+//! The diff is paged by Git, with the pager configured by the user. The prompts
+//! run in raw mode, so it is left while the pager runs, and the prompt is then
+//! redrawn with its state intact.
+
+use std::{io, process::Command};
+
+use crossterm::terminal;
+use eyre::Result;
+
+use crate::tracing::LogResult as _;
+
+/// Shows the staged diff in the pager.
+///
+/// The diff is written to the standard error, as the standard output is kept
+/// free for `--print-only`.
+#[tracing::instrument(level = "trace")]
+pub fn show_staged() -> Result<()> {
+    terminal::disable_raw_mode().log_err()?;
+
+    let status = Command::new("git")
+        .args(["--paginate", "diff", "--cached"])
+        .stdout(io::stderr())
+        .status();
+
+    terminal::enable_raw_mode().log_err()?;
+
+    // NOTE: Git prints its own errors, and failing to show the diff must not
+    // abort the wizard.
+    if !status.log_err()?.success() {
+        tracing::warn!("failed to show the staged diff");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,159 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The editor opened by git-z itself, when Git does not open one.
+//!
+//! The editor is resolved by Git with `git var GIT_EDITOR`, so that
+//! `GIT_EDITOR`, `core.editor`, `VISUAL` and `EDITOR` are respected in the same
+//! order. Like Git, the editor is run through the shell, so that it can contain
+//! arguments.
+
+use std::{
+    fs::{self, File},
+    io,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use eyre::Result;
+use thiserror::Error;
+
+use crate::{git, tracing::LogResult as _};
+
+/// The file in the Git directory in which the message is edited.
+const EDIT_FILE: &str = "GITZ_EDITMSG";
+
+/// Errors that can occur when opening the editor.
+#[derive(Debug, Error)]
+pub enum EditorError {
+    /// No editor is configured.
+    #[error("No editor is configured")]
+    NoEditor,
+    /// The `git` command cannot be run.
+    #[error("Failed to run the git command")]
+    CannotRunGit(#[source] io::Error),
+    /// The editor cannot be run.
+    #[error("Failed to run the editor `{editor}`")]
+    CannotRunEditor {
+        /// The editor command.
+        editor: String,
+        /// The error returned by the OS.
+        #[source]
+        source: io::Error,
+    },
+    /// The editor has returned an error.
+    #[error("The editor `{editor}` has returned an error")]
+    EditorFailed {
+        /// The editor command.
+        editor: String,
+    },
+}
+
+/// Opens a message in the editor and returns the edited message.
+#[tracing::instrument(level = "trace", skip_all)]
+pub fn edit(message: &str) -> Result<String> {
+    let editor = resolve()?;
+
+    // NOTE: Like Git, `:` means the message is to be left as is.
+    if editor == ":" {
+        return Ok(message.to_owned());
+    }
+
+    let file = git::path(EDIT_FILE)?;
+    fs::write(&file, message).log_err()?;
+
+    let status = editor_command(&editor, &file.to_string_lossy())
+        .stdout(terminal())
+        .status()
+        .map_err(|source| EditorError::CannotRunEditor {
+            editor: editor.clone(),
+            source,
+        })
+        .log_err()?;
+
+    if !status.success() {
+        Err(EditorError::EditorFailed { editor }).log_err()?;
+    }
+
+    let edited = fs::read_to_string(&file).log_err()?;
+    fs::remove_file(&file).log_err()?;
+
+    Ok(edited)
+}
+
+/// Returns the editor configured for Git.
+fn resolve() -> Result<String> {
+    let git_var = Command::new("git")
+        .args(["var", "GIT_EDITOR"])
+        .output()
+        .map_err(EditorError::CannotRunGit)
+        .log_err()?;
+
+    let editor = String::from_utf8_lossy(&git_var.stdout).trim().to_owned();
+    tracing::debug!(?editor);
+
+    if git_var.status.success() && !editor.is_empty() {
+        Ok(editor)
+    } else {
+        Err(EditorError::NoEditor).log_err()?
+    }
+}
+
+/// Builds the command running the editor on a file.
+///
+/// The file is passed as a positional parameter of the shell, so that it does
+/// not need to be quoted.
+fn editor_command(editor: &str, file: &str) -> Command {
+    let mut command = Command::new(shell());
+    command
+        .arg("-c")
+        .arg(format!("{editor} \"$@\""))
+        .arg(editor)
+        .arg(file);
+    command
+}
+
+/// Returns where the editor can write to the terminal.
+///
+/// The standard output is reserved for the output of git-z, like the message
+/// printed with `--print-only`, so the editor writes to the terminal or to the
+/// standard error.
+fn terminal() -> Stdio {
+    File::options()
+        .write(true)
+        .open("/dev/tty")
+        .map_or_else(|_| Stdio::from(io::stderr()), Stdio::from)
+}
+
+/// Returns the shell running the editor.
+#[cfg(not(target_os = "windows"))]
+fn shell() -> PathBuf {
+    PathBuf::from("sh")
+}
+
+/// Returns the shell running the editor.
+///
+/// Git for Windows runs the editor through the `sh` it bundles, which is
+/// usually not in the `PATH`, so it is looked up from the Git installation.
+#[cfg(target_os = "windows")]
+fn shell() -> PathBuf {
+    let exec_path = git::run_trimmed(&["--exec-path"]).unwrap_or_default();
+
+    PathBuf::from(exec_path)
+        .ancestors()
+        .flat_map(|dir| [dir.join("bin/sh.exe"), dir.join("usr/bin/sh.exe")])
+        .find(|sh| sh.is_file())
+        .unwrap_or_else(|| PathBuf::from("sh"))
+}
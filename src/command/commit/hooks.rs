@@ -0,0 +1,87 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Hooks directory without the pre-commit hook.
+//!
+//! Git cannot skip only the pre-commit hook: `--no-verify` skips the commit-msg
+//! hook as well. When git-z has already run the pre-commit hook, `git commit`
+//! is then pointed to a directory of wrappers calling all the other hooks.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use eyre::Result;
+use is_executable::IsExecutable as _;
+
+use crate::{
+    commit_cache::gitz_dir, helpers::set_executable, tracing::LogResult as _,
+};
+
+use crate::git;
+
+/// The name of the directory containing the wrappers.
+const HOOKS_DIR_NAME: &str = "hooks";
+
+/// Writes the wrappers of all the hooks but pre-commit.
+///
+/// Returns the directory to use as `core.hooksPath`.
+#[tracing::instrument(level = "trace")]
+pub fn without_pre_commit() -> Result<PathBuf> {
+    // NOTE: The paths returned by Git can be relative to the current directory,
+    // while hooks are run from the root of the worktree.
+    let current_dir = env::current_dir()?;
+    let hooks_dir = current_dir.join(git::path("hooks")?);
+    let wrappers_dir = current_dir.join(gitz_dir()?).join(HOOKS_DIR_NAME);
+
+    if wrappers_dir.exists() {
+        fs::remove_dir_all(&wrappers_dir).log_err()?;
+    }
+
+    fs::create_dir_all(&wrappers_dir).log_err()?;
+
+    if hooks_dir.is_dir() {
+        for entry in fs::read_dir(&hooks_dir)? {
+            let hook = entry?.path();
+
+            if hook.file_name().is_some_and(|name| name != "pre-commit")
+                && hook.is_file()
+                && hook.is_executable()
+            {
+                write_wrapper(&wrappers_dir, &hook)?;
+            }
+        }
+    }
+
+    tracing::debug!(?wrappers_dir);
+    Ok(wrappers_dir)
+}
+
+/// Writes a wrapper calling a hook.
+///
+/// The hook is called with `exec` so that it sees its own path in `$0`.
+fn write_wrapper(wrappers_dir: &Path, hook: &Path) -> Result<()> {
+    let Some(name) = hook.file_name() else {
+        return Ok(());
+    };
+
+    let wrapper = wrappers_dir.join(name);
+    let hook = hook.display().to_string().replace('\'', r"'\''");
+    fs::write(&wrapper, format!("#!/bin/sh\nexec '{hook}' \"$@\"\n"))?;
+    set_executable(&wrapper)?;
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Operations of Git in progress, like a merge.
+//!
+//! When committing during such an operation, Git has usually prepared a message
+//! in `MERGE_MSG`. The wizard is then prefilled from it instead of clobbering
+//! it with a fresh message.
+
+use std::{fmt, fs, io, process::Command};
+
+use eyre::Result;
+
+use crate::{git, tracing::LogResult as _};
+
+/// An operation of Git in progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// A merge.
+    Merge,
+    /// A cherry-pick.
+    CherryPick,
+    /// A revert.
+    Revert,
+    /// A rebase.
+    Rebase,
+}
+
+impl Operation {
+    /// Detects the operation in progress, if any.
+    #[tracing::instrument(name = "detect_operation", level = "trace")]
+    pub fn detect() -> Result<Option<Self>> {
+        let operation = if git::path("MERGE_HEAD")?.exists() {
+            Some(Self::Merge)
+        } else if git::path("CHERRY_PICK_HEAD")?.exists() {
+            Some(Self::CherryPick)
+        } else if git::path("REVERT_HEAD")?.exists() {
+            Some(Self::Revert)
+        } else if git::path("rebase-merge")?.exists()
+            || git::path("rebase-apply")?.exists()
+        {
+            Some(Self::Rebase)
+        } else {
+            None
+        };
+
+        tracing::debug!(?operation);
+        Ok(operation)
+    }
+}
+
+impl fmt::Display for Operation {
+    #[expect(
+        clippy::min_ident_chars,
+        reason = "This is the conventional name in `fmt` implementations."
+    )]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Merge => "merge",
+            Self::CherryPick => "cherry-pick",
+            Self::Revert => "revert",
+            Self::Rebase => "rebase",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// Returns the message prepared by Git for the operation, if any.
+pub fn prepared_message() -> Result<Option<String>> {
+    match fs::read_to_string(git::path("MERGE_MSG")?) {
+        Ok(message) => Ok(Some(message)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error).log_err()?,
+    }
+}
+
+/// Returns whether the index contains unresolved conflicts.
+#[tracing::instrument(level = "trace")]
+pub fn has_conflicts() -> Result<bool> {
+    let git_ls_files = Command::new("git")
+        .args(["ls-files", "--unmerged"])
+        .output()
+        .log_err()?;
+
+    let has_conflicts = !git_ls_files.stdout.is_empty();
+    tracing::debug!(?has_conflicts);
+    Ok(has_conflicts)
+}
@@ -0,0 +1,295 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Plain prompts, for screen readers and dumb terminals.
+//!
+//! Contrary to Inquire, they never move the cursor nor redraw anything: each
+//! question is printed on its own lines and the answer is read as a line of
+//! input. Choices are numbered and selected by typing their number.
+
+use std::{
+    cell::Cell,
+    io::{self, BufRead as _, Write as _},
+};
+
+use eyre::Result;
+use inquire::{
+    validator::{ErrorMessage, Validation},
+    CustomUserError, InquireError,
+};
+use itertools::Itertools as _;
+
+use crate::tracing::LogResult as _;
+
+/// The answer to omit an optional question.
+const OMIT: &str = "-";
+
+/// The answer to accept the default and skip the remaining questions.
+const SKIP_REST: &str = "!";
+
+/// A validator for text answers, with the same signature as for Inquire.
+pub type Validator = fn(&str) -> Result<Validation, CustomUserError>;
+
+/// Asks the user to choose from a numbered list.
+///
+/// Returns the index of the choice, or `None` if the question is skippable and
+/// the user has chosen to omit it. When `skip_rest` is provided, the user can
+/// accept the default and skip the remaining questions, which sets it.
+pub fn select(
+    message: &str,
+    choices: &[String],
+    default: usize,
+    skippable: bool,
+    skip_rest: Option<&Cell<bool>>,
+) -> Result<Option<usize>> {
+    let choices_list = choices
+        .iter()
+        .enumerate()
+        .map(|(index, choice)| format!("  {}) {choice}", index + 1))
+        .join("\n");
+
+    eprintln!("{message}:");
+    if skippable {
+        eprintln!("  0) none");
+    }
+    eprintln!("{choices_list}");
+    print_skip_rest_hint(skip_rest);
+
+    loop {
+        let answer = read_answer(&format!("Choice [{}]: ", default + 1))?;
+
+        if let Some(skip_rest) = skip_rest.filter(|_| is_skip_rest(&answer)) {
+            skip_rest.set(true);
+            return Ok(Some(default));
+        }
+
+        match parse_choice(&answer, choices.len(), default, skippable) {
+            Ok(choice) => return Ok(choice),
+            Err(error) => eprintln!("{error}"),
+        }
+    }
+}
+
+/// Asks the user for a text.
+///
+/// An empty answer keeps the initial value. Returns `None` if the question is
+/// skippable and the user has chosen to omit it. When `skip_rest` is provided,
+/// the user can accept the initial value and skip the remaining questions,
+/// which sets it.
+pub fn text(
+    message: &str,
+    initial_value: &str,
+    help_message: Option<&str>,
+    validator: Option<Validator>,
+    skippable: bool,
+    skip_rest: Option<&Cell<bool>>,
+) -> Result<Option<String>> {
+    if let Some(help_message) = help_message {
+        eprintln!("{help_message}");
+    }
+
+    if skippable {
+        eprintln!("Enter `{OMIT}` to omit.");
+    }
+
+    print_skip_rest_hint(skip_rest);
+
+    let prompt = if initial_value.is_empty() {
+        format!("{message}: ")
+    } else {
+        format!("{message} [{initial_value}]: ")
+    };
+
+    loop {
+        let answer = read_answer(&prompt)?;
+        let accepts_default = skip_rest.is_some() && is_skip_rest(&answer);
+        let answer = if accepts_default { "" } else { &answer };
+        let set_skip_rest = || {
+            if let Some(skip_rest) = skip_rest.filter(|_| accepts_default) {
+                skip_rest.set(true);
+            }
+        };
+
+        let Some(text) = parse_text(answer, initial_value, skippable) else {
+            set_skip_rest();
+            return Ok(None);
+        };
+
+        let validation = validator
+            .map(|validator| validator(&text))
+            .transpose()
+            .map_err(InquireError::Custom)
+            .log_err()?;
+
+        match validation {
+            None | Some(Validation::Valid) => {
+                set_skip_rest();
+                return Ok(Some(text));
+            }
+            Some(Validation::Invalid(ErrorMessage::Custom(error))) => {
+                eprintln!("{error}");
+            }
+            Some(Validation::Invalid(ErrorMessage::Default)) => {
+                eprintln!("Invalid answer.");
+            }
+        }
+    }
+}
+
+/// Asks the user a yes / no question.
+pub fn confirm(
+    message: &str,
+    help_message: Option<&str>,
+    default: bool,
+) -> Result<bool> {
+    if let Some(help_message) = help_message {
+        eprintln!("{help_message}");
+    }
+
+    let choices = if default { "Y/n" } else { "y/N" };
+
+    loop {
+        let answer = read_answer(&format!("{message} ({choices}) "))?;
+
+        match parse_confirmation(&answer, default) {
+            Some(confirmation) => return Ok(confirmation),
+            None => eprintln!("Please answer yes or no."),
+        }
+    }
+}
+
+/// Tells how to skip the remaining questions, if possible.
+fn print_skip_rest_hint(skip_rest: Option<&Cell<bool>>) {
+    if skip_rest.is_some() {
+        eprintln!(
+            "Enter `{SKIP_REST}` to accept the default and use the defaults \
+                for the remaining questions."
+        );
+    }
+}
+
+/// Returns whether the answer asks to skip the remaining questions.
+fn is_skip_rest(answer: &str) -> bool {
+    answer.trim() == SKIP_REST
+}
+
+/// Prints the prompt and reads a line of answer.
+///
+/// The end of the input cancels the operation, like ESC does with Inquire.
+fn read_answer(prompt: &str) -> Result<String> {
+    eprint!("{prompt}");
+    io::stderr().flush().log_err()?;
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).log_err()? == 0 {
+        eprintln!();
+        return Err(InquireError::OperationCanceled).log_err()?;
+    }
+
+    Ok(answer.trim_end_matches(['\r', '\n']).to_owned())
+}
+
+/// Parses the answer to a choice among `len` items.
+fn parse_choice(
+    answer: &str,
+    len: usize,
+    default: usize,
+    skippable: bool,
+) -> Result<Option<usize>, String> {
+    let answer = answer.trim();
+
+    if answer.is_empty() {
+        return Ok(Some(default));
+    }
+
+    match answer.parse::<usize>() {
+        Ok(0) if skippable => Ok(None),
+        Ok(number) if (1..=len).contains(&number) => Ok(Some(number - 1)),
+        _ => {
+            let min = usize::from(!skippable);
+            Err(format!("Please enter a number between {min} and {len}."))
+        }
+    }
+}
+
+/// Parses the answer to a text question.
+fn parse_text(
+    answer: &str,
+    initial_value: &str,
+    skippable: bool,
+) -> Option<String> {
+    if skippable && answer.trim() == OMIT {
+        None
+    } else if !answer.is_empty() {
+        Some(answer.to_owned())
+    } else if !initial_value.is_empty() {
+        Some(initial_value.to_owned())
+    } else if skippable {
+        None
+    } else {
+        Some(String::new())
+    }
+}
+
+/// Parses the answer to a yes / no question.
+fn parse_confirmation(answer: &str, default: bool) -> Option<bool> {
+    match answer.trim().to_lowercase().as_str() {
+        "" => Some(default),
+        "y" | "yes" => Some(true),
+        "n" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    #[test]
+    fn parses_choices() {
+        assert_eq!(parse_choice("", 3, 1, false), Ok(Some(1)));
+        assert_eq!(parse_choice(" 3 ", 3, 0, false), Ok(Some(2)));
+        assert_eq!(parse_choice("0", 3, 0, true), Ok(None));
+        assert_eq!(
+            parse_choice("0", 3, 0, false),
+            Err(String::from("Please enter a number between 1 and 3."))
+        );
+        assert_eq!(
+            parse_choice("feat", 3, 0, true),
+            Err(String::from("Please enter a number between 0 and 3."))
+        );
+    }
+
+    #[test]
+    fn parses_texts() {
+        assert_eq!(parse_text("text", "", false), Some(String::from("text")));
+        assert_eq!(parse_text("", "init", false), Some(String::from("init")));
+        assert_eq!(parse_text("", "", false), Some(String::new()));
+        assert_eq!(parse_text("", "", true), None);
+        assert_eq!(parse_text("-", "init", true), None);
+        assert_eq!(parse_text("-", "", false), Some(String::from("-")));
+    }
+
+    #[test]
+    fn parses_confirmations() {
+        assert_eq!(parse_confirmation("", true), Some(true));
+        assert_eq!(parse_confirmation("", false), Some(false));
+        assert_eq!(parse_confirmation("Yes", false), Some(true));
+        assert_eq!(parse_confirmation("n", true), Some(false));
+        assert_eq!(parse_confirmation("maybe", true), None);
+    }
+}
@@ -0,0 +1,547 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! A select prompt with contextual help.
+//!
+//! It looks and behaves like the one from Inquire, but the help line below the
+//! options shows the description of the highlighted option instead of a static
+//! message. The filter only matches the names of the options, never their
+//! descriptions.
+
+use std::cell::Cell;
+
+use colored::Colorize as _;
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+};
+use eyre::Result;
+use indexmap::IndexMap;
+use inquire::InquireError;
+
+use crate::{helpers::display_width, tracing::LogResult as _};
+
+use super::{diff, terminal::Terminal};
+
+/// The keys help displayed below the description.
+const KEYS_HELP: &str = "↑↓ to move, enter to select, type to filter";
+
+/// The keys help for skipping the remaining questions.
+const SKIP_REST_HELP: &str = "ctrl-d to use the defaults for the rest";
+
+/// The keys help for viewing the staged diff.
+const SHOW_DIFF_HELP: &str = "ctrl-v to view the diff";
+
+/// A select prompt.
+#[derive(Debug)]
+pub struct Select<'a> {
+    /// The message of the prompt.
+    message: &'a str,
+    /// The options and their description.
+    options: &'a IndexMap<String, String>,
+    /// The group of each option, displayed as separators.
+    groups: Option<&'a IndexMap<String, String>>,
+    /// A message displayed above the keys help.
+    help_message: Option<&'a str>,
+    /// The number of options displayed at once.
+    page_size: usize,
+    /// The filter typed by the user.
+    filter: String,
+    /// The position of the highlighted option among the filtered ones.
+    cursor: usize,
+    /// The position of the first displayed option among the filtered ones.
+    scroll: usize,
+    /// The flag to set when the user wants to skip the remaining questions.
+    skip_rest: Option<&'a Cell<bool>>,
+}
+
+/// The result of a key press.
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    /// The prompt continues.
+    Continue,
+    /// The user has selected the option with the given index.
+    Select(usize),
+    /// The user has pressed ESC.
+    Cancel,
+    /// The user has pressed Ctrl-C.
+    Interrupt,
+    /// The user wants to view the staged diff.
+    ShowDiff,
+}
+
+impl<'a> Select<'a> {
+    /// Builds a select prompt, with the option at `cursor` highlighted.
+    pub fn new(
+        message: &'a str,
+        options: &'a IndexMap<String, String>,
+        cursor: usize,
+        page_size: usize,
+    ) -> Self {
+        let mut select = Self {
+            message,
+            options,
+            groups: None,
+            help_message: None,
+            page_size: page_size.max(1),
+            filter: String::new(),
+            cursor: cursor.min(options.len().saturating_sub(1)),
+            scroll: 0,
+            skip_rest: None,
+        };
+
+        select.adjust_scroll();
+        select
+    }
+
+    /// Displays the options under a separator for their group.
+    ///
+    /// The options of a group must be contiguous.
+    pub const fn with_groups(
+        mut self,
+        groups: &'a IndexMap<String, String>,
+    ) -> Self {
+        self.groups = Some(groups);
+        self
+    }
+
+    /// Displays a message above the keys help.
+    pub const fn with_help_message(mut self, help_message: &'a str) -> Self {
+        self.help_message = Some(help_message);
+        self
+    }
+
+    /// Lets the user select the highlighted option with Ctrl-D and skip the
+    /// remaining questions, which sets `skip_rest`.
+    pub const fn with_skip_rest(mut self, skip_rest: &'a Cell<bool>) -> Self {
+        self.skip_rest = Some(skip_rest);
+        self
+    }
+
+    /// Runs the prompt and returns the name of the selected option.
+    ///
+    /// ESC cancels the operation.
+    pub fn prompt(self) -> Result<String> {
+        match self.run()? {
+            Some(answer) => Ok(answer),
+            None => Err(InquireError::OperationCanceled).log_err()?,
+        }
+    }
+
+    /// Runs the prompt and returns the name of the selected option, or `None`
+    /// if the user has pressed ESC.
+    pub fn prompt_skippable(self) -> Result<Option<String>> {
+        self.run()
+    }
+
+    /// Runs the prompt until the user selects an option or cancels it.
+    fn run(mut self) -> Result<Option<String>> {
+        let mut terminal = Terminal::enter().log_err()?;
+
+        let action = loop {
+            terminal
+                .draw(&self.lines(), self.cursor_column())
+                .log_err()?;
+
+            let Event::Key(key) = event::read().log_err()? else {
+                continue;
+            };
+
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+
+            match self.handle_key(key) {
+                Action::Continue => (),
+                Action::ShowDiff => {
+                    terminal.hide().log_err()?;
+                    diff::show_staged()?;
+                }
+                action => break action,
+            }
+        };
+
+        let answer = match action {
+            Action::Select(index) => self.name(index).to_owned(),
+            Action::Continue
+            | Action::Cancel
+            | Action::Interrupt
+            | Action::ShowDiff => String::from("<canceled>"),
+        };
+
+        terminal.finish(self.message, &answer).log_err()?;
+
+        match action {
+            Action::Select(_) => Ok(Some(answer)),
+            Action::Interrupt => {
+                Err(InquireError::OperationInterrupted).log_err()?
+            }
+            Action::Continue | Action::Cancel | Action::ShowDiff => Ok(None),
+        }
+    }
+
+    /// Handles a key press.
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Char('c') if control => return Action::Interrupt,
+            // NOTE: In raw mode, a line feed is read as Ctrl-J.
+            KeyCode::Enter => return self.selected(),
+            KeyCode::Char('j') if control => return self.selected(),
+            KeyCode::Esc => return Action::Cancel,
+            KeyCode::Char('d') if control => {
+                return self.select_and_skip_rest()
+            }
+            KeyCode::Char('v') if control => return Action::ShowDiff,
+            KeyCode::Up => self.move_cursor(-1),
+            KeyCode::Down | KeyCode::Tab => self.move_cursor(1),
+            KeyCode::PageUp => self.move_cursor(-self.page_offset()),
+            KeyCode::PageDown => self.move_cursor(self.page_offset()),
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.cursor = 0;
+            }
+            KeyCode::Char(character) if !control => {
+                self.filter.push(character);
+                self.cursor = 0;
+            }
+            _ => (),
+        }
+
+        self.adjust_scroll();
+        Action::Continue
+    }
+
+    /// Returns the action for the highlighted option, if any.
+    fn selected(&self) -> Action {
+        self.filtered()
+            .get(self.cursor)
+            .map_or(Action::Continue, |&index| Action::Select(index))
+    }
+
+    /// Selects the highlighted option and skips the remaining questions, if
+    /// enabled.
+    fn select_and_skip_rest(&self) -> Action {
+        let Some(skip_rest) = self.skip_rest else {
+            return Action::Continue;
+        };
+
+        let action = self.selected();
+
+        if matches!(action, Action::Select(_)) {
+            skip_rest.set(true);
+        }
+
+        action
+    }
+
+    /// Returns the indices of the options matching the filter.
+    fn filtered(&self) -> Vec<usize> {
+        self.options
+            .keys()
+            .enumerate()
+            .filter(|(_, name)| matches(&self.filter, name))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Moves the cursor by `offset` options, wrapping around the list.
+    fn move_cursor(&mut self, offset: isize) {
+        let len = self.filtered().len();
+
+        if len > 0 {
+            let len = isize::try_from(len).unwrap_or(isize::MAX);
+            let cursor = isize::try_from(self.cursor).unwrap_or_default();
+            let cursor = (cursor + offset).rem_euclid(len);
+            self.cursor = usize::try_from(cursor).unwrap_or_default();
+        }
+    }
+
+    /// Returns the number of options to skip for a page.
+    fn page_offset(&self) -> isize {
+        isize::try_from(self.page_size).unwrap_or(1)
+    }
+
+    /// Scrolls the list so that the cursor is visible.
+    fn adjust_scroll(&mut self) {
+        self.scroll = self
+            .scroll
+            .min(self.cursor)
+            .max((self.cursor + 1).saturating_sub(self.page_size));
+    }
+
+    /// Returns the name of the option at `index`.
+    fn name(&self, index: usize) -> &str {
+        self.options
+            .get_index(index)
+            .map(|(name, _)| name.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Returns the group of the option at `index`, if any.
+    fn group(&self, index: usize) -> Option<&str> {
+        self.groups
+            .and_then(|groups| groups.get(self.name(index)))
+            .map(String::as_str)
+    }
+
+    /// Returns the prompt line, without its styling.
+    fn prompt_line(&self) -> String {
+        format!("? {} {}", self.message, self.filter)
+    }
+
+    /// Returns the column of the cursor on the prompt line.
+    fn cursor_column(&self) -> usize {
+        display_width(&self.prompt_line())
+    }
+
+    /// Returns the lines to draw, without wrapping them.
+    fn lines(&self) -> Vec<String> {
+        let filtered = self.filtered();
+
+        let mut lines = vec![format!(
+            "{} {} {}",
+            "?".green(),
+            self.message.bold(),
+            self.filter
+        )];
+
+        if filtered.is_empty() {
+            lines.push(String::from("No matching option"));
+        }
+
+        let mut previous_group = None;
+
+        for (position, &index) in filtered
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(self.page_size)
+        {
+            let group = self.group(index);
+            if let Some(group) = group.filter(|_| group != previous_group) {
+                lines.push(format!("{}", format!("-- {group} --").dimmed()));
+            }
+            previous_group = group;
+
+            if position == self.cursor {
+                lines.push(format!(
+                    "{}",
+                    format!("> {}", self.name(index)).cyan()
+                ));
+            } else {
+                lines.push(format!("  {}", self.name(index)));
+            }
+        }
+
+        if let Some((_, description)) = filtered
+            .get(self.cursor)
+            .and_then(|&index| self.options.get_index(index))
+            .filter(|(_, description)| !description.is_empty())
+        {
+            lines.push(format!("{}", description.as_str().dimmed()));
+        }
+
+        if let Some(help_message) = self.help_message {
+            lines.push(format!("{}", help_message.cyan()));
+        }
+
+        lines.push(format!("{}", self.keys_help().cyan()));
+        lines
+    }
+
+    /// Returns the help for the keys.
+    fn keys_help(&self) -> String {
+        let skip_rest_help = self
+            .skip_rest
+            .map(|_| format!(", {SKIP_REST_HELP}"))
+            .unwrap_or_default();
+
+        format!("[{KEYS_HELP}{skip_rest_help}, {SHOW_DIFF_HELP}]")
+    }
+}
+
+/// Returns whether the `filter` matches the `name` of an option.
+///
+/// The characters of the filter must appear in order in the name, ignoring the
+/// case.
+fn matches(filter: &str, name: &str) -> bool {
+    let mut name = name.chars().flat_map(char::to_lowercase);
+
+    filter
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|wanted| name.any(|character| character == wanted))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    fn types() -> IndexMap<String, String> {
+        IndexMap::from([
+            (String::from("feat"), String::from("add a new feature")),
+            (String::from("fix"), String::from("patch a bug")),
+            (String::from("docs"), String::from("fix the documentation")),
+        ])
+    }
+
+    fn press(select: &mut Select<'_>, code: KeyCode) -> Action {
+        select.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn type_filter(select: &mut Select<'_>, filter: &str) {
+        for character in filter.chars() {
+            press(select, KeyCode::Char(character));
+        }
+    }
+
+    #[test]
+    fn filters_on_names_only() {
+        let types = types();
+        let mut select = Select::new("Commit type", &types, 0, 10);
+
+        type_filter(&mut select, "fix");
+
+        assert_eq!(select.filtered(), [1]);
+    }
+
+    #[test]
+    fn filters_with_subsequences() {
+        assert!(matches("ft", "feat"));
+        assert!(matches("FE", "feat"));
+        assert!(matches("", "feat"));
+        assert!(!matches("tf", "feat"));
+    }
+
+    #[test]
+    fn selects_the_highlighted_option() {
+        let types = types();
+        let mut select = Select::new("Commit type", &types, 1, 10);
+
+        press(&mut select, KeyCode::Down);
+
+        assert_eq!(press(&mut select, KeyCode::Enter), Action::Select(2));
+    }
+
+    #[test]
+    fn selects_and_skips_the_rest_on_ctrl_d() {
+        let types = types();
+        let skip_rest = Cell::new(false);
+        let mut select = Select::new("Commit type", &types, 1, 10)
+            .with_skip_rest(&skip_rest);
+
+        let action = select.handle_key(KeyEvent::new(
+            KeyCode::Char('d'),
+            KeyModifiers::CONTROL,
+        ));
+
+        assert_eq!(action, Action::Select(1));
+        assert!(skip_rest.get());
+    }
+
+    #[test]
+    fn ignores_ctrl_d_without_skip_rest() {
+        let types = types();
+        let mut select = Select::new("Commit type", &types, 1, 10);
+
+        let action = select.handle_key(KeyEvent::new(
+            KeyCode::Char('d'),
+            KeyModifiers::CONTROL,
+        ));
+
+        assert_eq!(action, Action::Continue);
+    }
+
+    #[test]
+    fn puts_the_cursor_after_wide_characters() {
+        let types = types();
+        let mut select = Select::new("Commit type", &types, 0, 10);
+
+        type_filter(&mut select, "修正");
+
+        assert_eq!(select.cursor_column(), "? Commit type ".len() + 4);
+    }
+
+    #[test]
+    fn wraps_around_the_list() {
+        let types = types();
+        let mut select = Select::new("Commit type", &types, 0, 10);
+
+        press(&mut select, KeyCode::Up);
+
+        assert_eq!(press(&mut select, KeyCode::Enter), Action::Select(2));
+    }
+
+    #[test]
+    fn does_not_select_anything_without_match() {
+        let types = types();
+        let mut select = Select::new("Commit type", &types, 0, 10);
+
+        type_filter(&mut select, "unknown");
+
+        assert_eq!(press(&mut select, KeyCode::Enter), Action::Continue);
+    }
+
+    #[test]
+    fn shows_the_description_of_the_highlighted_option_only() {
+        colored::control::set_override(false);
+        let types = types();
+        let mut select = Select::new("Commit type", &types, 1, 10);
+
+        let lines = select.lines();
+        assert!(lines.contains(&String::from("> fix")));
+        assert!(lines.contains(&String::from("patch a bug")));
+        assert!(!lines.contains(&String::from("add a new feature")));
+
+        press(&mut select, KeyCode::Up);
+
+        let lines = select.lines();
+        assert!(lines.contains(&String::from("> feat")));
+        assert!(lines.contains(&String::from("add a new feature")));
+    }
+
+    #[test]
+    fn separates_the_groups() {
+        colored::control::set_override(false);
+        let types = types();
+        let groups = IndexMap::from([
+            (String::from("feat"), String::from("Common")),
+            (String::from("fix"), String::from("Common")),
+            (String::from("docs"), String::from("Other")),
+        ]);
+        let select =
+            Select::new("Commit type", &types, 0, 10).with_groups(&groups);
+
+        assert_eq!(
+            select.lines()[1..5],
+            ["-- Common --", "> feat", "  fix", "-- Other --"]
+        );
+    }
+
+    #[test]
+    fn scrolls_to_the_cursor() {
+        let types = types();
+        let mut select = Select::new("Commit type", &types, 2, 2);
+
+        assert_eq!(select.scroll, 1);
+
+        press(&mut select, KeyCode::Down);
+
+        assert_eq!(select.scroll, 0);
+    }
+}
@@ -0,0 +1,149 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Early detection of SSH signing issues.
+//!
+//! When commits are signed with an SSH key, `ssh-keygen` asks for the
+//! passphrase of the key during `git commit`, that is after the wizard, unless
+//! the key is loaded in an agent. git-z detects this before asking anything, so
+//! that users do not lose the flow at the last step.
+//!
+//! The check is skipped when Git signs through a custom program, like the one of
+//! a password manager, which does not need an agent.
+
+use std::{env, path::Path};
+
+use crate::{config::Config, git, hint, warning};
+
+/// The environment variable pointing to the socket of the SSH agent.
+const SSH_AUTH_SOCK: &str = "SSH_AUTH_SOCK";
+
+/// Warns when commits are signed with SSH but no agent is available.
+#[tracing::instrument(level = "trace", skip(config))]
+pub fn warn_if_no_agent(config: &Config, extra_args: &[String]) {
+    if check_enabled(config)
+        && signs_with_ssh(extra_args)
+        && !uses_custom_program()
+        && !agent_available()
+    {
+        warning!("Commits are signed with SSH, but no SSH agent is available.");
+        hint! {"
+            The passphrase of your key may be asked after the wizard. To avoid
+            this, start an agent and load your key with `ssh-add`, or set
+            `commit.check_ssh_agent = false` in `git-z.toml` to disable this
+            check.
+        "};
+    }
+}
+
+/// Returns whether the check is enabled in the configuration.
+fn check_enabled(config: &Config) -> bool {
+    config
+        .commit
+        .as_ref()
+        .and_then(|commit| commit.check_ssh_agent)
+        .unwrap_or(true)
+}
+
+/// Returns whether Git signs through a custom program instead of `ssh-keygen`.
+fn uses_custom_program() -> bool {
+    git_config(&["gpg.ssh.program"]).is_some()
+}
+
+/// Returns whether `git commit` signs the commit with an SSH key.
+fn signs_with_ssh(extra_args: &[String]) -> bool {
+    let signs = signing_requested(extra_args).unwrap_or_else(|| {
+        git_config(&["--type=bool", "commit.gpgsign"]).as_deref()
+            == Some("true")
+    });
+
+    signs && git_config(&["gpg.format"]).as_deref() == Some("ssh")
+}
+
+/// Returns whether signing is requested or disabled in the arguments of
+/// `git commit`, the last one winning as in Git.
+fn signing_requested(extra_args: &[String]) -> Option<bool> {
+    extra_args.iter().rev().find_map(|arg| match arg.as_str() {
+        "--no-gpg-sign" => Some(false),
+        "--gpg-sign" => Some(true),
+        arg if arg.starts_with("--gpg-sign=") || arg.starts_with("-S") => {
+            Some(true)
+        }
+        _ => None,
+    })
+}
+
+/// Returns whether an SSH agent can be reached.
+#[cfg(unix)]
+fn agent_available() -> bool {
+    env::var_os(SSH_AUTH_SOCK).is_some_and(|socket| Path::new(&socket).exists())
+}
+
+/// Returns whether an SSH agent can be reached.
+///
+/// The OpenSSH agent for Windows listens on a named pipe without setting
+/// `SSH_AUTH_SOCK`, so it cannot be detected the same way.
+#[cfg(not(unix))]
+fn agent_available() -> bool {
+    true
+}
+
+/// Returns a value from the Git configuration, if set.
+///
+/// As the check is only advisory, errors are treated as if it was unset.
+fn git_config(args: &[&str]) -> Option<String> {
+    git::config(args).ok().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|&arg| arg.to_owned()).collect()
+    }
+
+    #[test]
+    fn detects_signing_requested_in_the_arguments() {
+        assert_eq!(signing_requested(&args(&["-S"])), Some(true));
+        assert_eq!(signing_requested(&args(&["-SABCDEF"])), Some(true));
+        assert_eq!(signing_requested(&args(&["--gpg-sign"])), Some(true));
+        assert_eq!(
+            signing_requested(&args(&["--gpg-sign=~/.ssh/id.pub"])),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn detects_signing_disabled_in_the_arguments() {
+        assert_eq!(signing_requested(&args(&["--no-gpg-sign"])), Some(false));
+        assert_eq!(
+            signing_requested(&args(&["-S", "--no-gpg-sign"])),
+            Some(false)
+        );
+        assert_eq!(
+            signing_requested(&args(&["--no-gpg-sign", "-S"])),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn defers_to_the_configuration_without_signing_arguments() {
+        assert_eq!(signing_requested(&args(&["--no-verify"])), None);
+        assert_eq!(signing_requested(&[]), None);
+    }
+}
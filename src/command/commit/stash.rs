@@ -0,0 +1,159 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Stashing of the unstaged changes while running the pre-commit hook.
+//!
+//! The unstaged changes to tracked files are saved as a patch in the git-z
+//! directory, then removed from the worktree, so that the hook only sees what
+//! is about to be committed. Untracked files are left as is.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use eyre::Result;
+
+use crate::{
+    commit_cache::gitz_dir, config::repo_root, git, tracing::LogResult as _,
+};
+
+use super::CommitError;
+
+/// The prefix of the files where the unstaged changes are saved.
+const PATCH_FILE_PREFIX: &str = "unstaged";
+
+/// The extension of the files where the unstaged changes are saved.
+const PATCH_FILE_EXTENSION: &str = "patch";
+
+/// Unstaged changes removed from the worktree.
+#[derive(Debug)]
+pub struct Stash {
+    /// The root of the worktree.
+    root: PathBuf,
+    /// The patch containing the unstaged changes.
+    patch: PathBuf,
+}
+
+impl Stash {
+    /// Saves the unstaged changes and removes them from the worktree.
+    ///
+    /// Returns `None` if there are no unstaged changes. A patch left by a
+    /// previous run which has not been restored is never overwritten: git-z
+    /// refuses to run until it has been applied and deleted.
+    #[tracing::instrument(name = "stash_unstaged", level = "trace")]
+    pub fn save() -> Result<Option<Self>> {
+        let root = repo_root()?;
+        // NOTE: The path returned by Git can be relative to the current
+        // directory.
+        let gitz_dir = env::current_dir()?.join(gitz_dir()?);
+        fs::create_dir_all(&gitz_dir)?;
+
+        if let Some(patch) = leftover_patch(&gitz_dir)? {
+            return Err(CommitError::UnstagedLeftover { patch }).log_err()?;
+        }
+
+        let patch = gitz_dir.join(patch_file_name());
+        let output = format!("--output={}", patch.display());
+        // NOTE: Force the prefixes and disable textconv so that the patch
+        // can be applied whatever the diff settings of the user.
+        git::run_in(
+            &root,
+            &[
+                "diff",
+                "--binary",
+                "--no-color",
+                "--no-ext-diff",
+                "--no-textconv",
+                "--src-prefix=a/",
+                "--dst-prefix=b/",
+                &output,
+            ],
+        )?;
+
+        let is_empty =
+            fs::metadata(&patch).map_or(true, |patch| patch.len() == 0);
+
+        if is_empty {
+            tracing::debug!("no unstaged changes to stash");
+            drop(fs::remove_file(&patch));
+            return Ok(None);
+        }
+
+        git::run_in(&root, &["checkout", "--", "."])?;
+
+        tracing::info!(?patch, "the unstaged changes have been stashed");
+        Ok(Some(Self { root, patch }))
+    }
+
+    /// Restores the unstaged changes in the worktree.
+    ///
+    /// If they conflict with changes made by the hook, the worktree is left as
+    /// the hook made it and the patch is kept for the user to apply.
+    #[tracing::instrument(name = "restore_unstaged", level = "trace")]
+    pub fn restore(self) -> Result<()> {
+        let patch = self.patch.display().to_string();
+
+        if git::run_in(&self.root, &["apply", "--whitespace=nowarn", &patch])
+            .is_err()
+        {
+            return Err(CommitError::UnstagedConflict { patch: self.patch })
+                .log_err()?;
+        }
+
+        fs::remove_file(&self.patch)?;
+
+        tracing::info!("the unstaged changes have been restored");
+        Ok(())
+    }
+}
+
+/// Returns a unique name for the patch of the unstaged changes.
+fn patch_file_name() -> String {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+
+    format!(
+        "{PATCH_FILE_PREFIX}-{}-{timestamp}.{PATCH_FILE_EXTENSION}",
+        process::id()
+    )
+}
+
+/// Returns a patch left by a previous run, if any.
+fn leftover_patch(gitz_dir: &Path) -> Result<Option<PathBuf>> {
+    for entry in fs::read_dir(gitz_dir)? {
+        let path = entry?.path();
+
+        let is_patch = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| {
+                name.starts_with(PATCH_FILE_PREFIX)
+                    && path
+                        .extension()
+                        .is_some_and(|ext| ext == PATCH_FILE_EXTENSION)
+            });
+
+        if is_patch {
+            return Ok(Some(path));
+        }
+    }
+
+    Ok(None)
+}
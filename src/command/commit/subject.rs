@@ -0,0 +1,139 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Decoration of the subject line with `templates.subject`.
+//!
+//! Some teams require a prefix like `[GH-42]` or a suffix like `(#123)` on the
+//! subject line. The commit template is still rendered and checked as usual,
+//! then its subject line is replaced with the one rendered by the subject
+//! template. As decorations make the subject longer, its length is checked
+//! after rendering, against `commit.max_subject_length`.
+
+use eyre::Result;
+use tera::{Context, Tera};
+use thiserror::Error;
+
+pub use crate::subject_template::{replace_subject, TEMPLATE_NAME};
+use crate::{config::Config, helpers::display_width, tracing::LogResult as _};
+
+use super::CommitError;
+
+/// Errors that can occur when decorating the subject line.
+#[derive(Debug, Error)]
+pub enum SubjectError {
+    /// The subject template renders nothing.
+    #[error("The subject template renders an empty subject")]
+    Empty,
+    /// The subject template renders several lines.
+    #[error("The subject template renders several lines")]
+    MultipleLines {
+        /// The rendered subject.
+        subject: String,
+    },
+    /// The decorated subject is too long.
+    #[error(
+        "The subject must not be longer than {max} characters, but is {length}"
+    )]
+    TooLong {
+        /// The rendered subject.
+        subject: String,
+        /// The length of the rendered subject.
+        length: usize,
+        /// The maximum length of the subject.
+        max: usize,
+    },
+}
+
+/// Replaces the subject line of a message with the one rendered by
+/// `templates.subject`, if set.
+pub fn decorate(
+    tera: &Tera,
+    config: &Config,
+    context: &Context,
+    message: String,
+) -> Result<String> {
+    if config.templates.subject.is_none() {
+        return Ok(message);
+    }
+
+    let subject = render(tera, context)?;
+    check(&subject, config.max_subject_length())?;
+
+    tracing::debug!(?subject, "decorating the subject line");
+    Ok(replace_subject(&message, &subject))
+}
+
+/// Renders the subject line.
+pub fn render(tera: &Tera, context: &Context) -> Result<String> {
+    let subject = tera
+        .render(TEMPLATE_NAME, context)
+        .map_err(CommitError::Template)
+        .log_err()?;
+
+    let subject = subject.trim().to_owned();
+
+    if subject.is_empty() {
+        Err(SubjectError::Empty).log_err()?
+    } else if subject.contains('\n') {
+        Err(SubjectError::MultipleLines { subject }).log_err()?
+    } else {
+        Ok(subject)
+    }
+}
+
+/// Checks the length of a rendered subject line.
+fn check(subject: &str, max: usize) -> Result<(), SubjectError> {
+    let length = display_width(subject);
+
+    if length > max {
+        Err(SubjectError::TooLong {
+            subject: subject.to_owned(),
+            length,
+            max,
+        })
+        .log_err()
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+    use crate::lint::MAX_SUBJECT_LEN;
+
+    #[test]
+    fn refuses_subjects_longer_than_the_limit() {
+        assert!(check(&"a".repeat(MAX_SUBJECT_LEN), MAX_SUBJECT_LEN).is_ok());
+        assert!(matches!(
+            check(&"a".repeat(MAX_SUBJECT_LEN + 1), MAX_SUBJECT_LEN),
+            Err(SubjectError::TooLong { length: 73, .. })
+        ));
+    }
+
+    #[test]
+    fn measures_subjects_in_columns() {
+        assert!(check(&"é".repeat(MAX_SUBJECT_LEN), MAX_SUBJECT_LEN).is_ok());
+        assert!(
+            check(&"e\u{301}".repeat(MAX_SUBJECT_LEN), MAX_SUBJECT_LEN).is_ok()
+        );
+        assert!(matches!(
+            check(&"修".repeat(MAX_SUBJECT_LEN / 2 + 1), MAX_SUBJECT_LEN),
+            Err(SubjectError::TooLong { length: 74, .. })
+        ));
+    }
+}
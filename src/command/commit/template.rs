@@ -0,0 +1,154 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Selection of the commit message template.
+//!
+//! A named template from `templates.named` can be selected explicitly with
+//! `--template`. Otherwise, the first pattern of `templates.branch_overrides`
+//! matching the current branch selects one. When nothing matches, the default
+//! `templates.commit` is used.
+//!
+//! When committing a merge, `templates.merge` is used instead if set.
+
+use eyre::{Context as _, Result};
+use regex::Regex;
+
+use crate::{config::Config, tracing::LogResult as _};
+
+use super::{current_branch, CommitError};
+
+/// Replaces the commit template with the selected named template, if any.
+#[tracing::instrument(level = "trace", skip(config))]
+pub fn select(config: &mut Config, name: Option<&str>) -> Result<()> {
+    let name = match name {
+        Some(name) => Some(name.to_owned()),
+        None => branch_override(config)?,
+    };
+
+    let Some(name) = name else {
+        return Ok(());
+    };
+
+    let template = config
+        .templates
+        .named
+        .as_ref()
+        .and_then(|named| named.get(&name))
+        .cloned()
+        .ok_or_else(|| CommitError::UnknownTemplate {
+            name: name.clone(),
+            available: config
+                .templates
+                .named
+                .iter()
+                .flat_map(|named| named.keys().cloned())
+                .collect(),
+        })
+        .log_err()?;
+
+    tracing::debug!(?name, "using a named template");
+    config.templates.commit = template;
+    Ok(())
+}
+
+/// Replaces the commit template with the merge template, if any.
+pub fn select_merge(config: &mut Config) {
+    if let Some(template) = config.templates.merge.clone() {
+        tracing::debug!("using the merge template");
+        config.templates.commit = template;
+    }
+}
+
+/// Returns the name of the template configured for the current branch.
+///
+/// There is no override on a detached `HEAD`.
+fn branch_override(config: &Config) -> Result<Option<String>> {
+    let Some(branch_overrides) = &config.templates.branch_overrides else {
+        return Ok(None);
+    };
+
+    let Some(branch) = current_branch()? else {
+        return Ok(None);
+    };
+
+    for (pattern, name) in branch_overrides {
+        if glob_regex(pattern)
+            .wrap_err_with(|| format!("Invalid branch pattern `{pattern}`"))
+            .log_err()?
+            .is_match(&branch)
+        {
+            return Ok(Some(name.clone()));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Builds a regex matching a whole branch name from a glob pattern.
+///
+/// `*` matches any sequence of characters but `/`, `**` matches any sequence of
+/// characters and `?` matches any character but `/`.
+fn glob_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(character) = chars.next() {
+        match character {
+            '*' if chars.next_if_eq(&'*').is_some() => regex.push_str(".*"),
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            _ => regex.push_str(&regex::escape(&character.to_string())),
+        }
+    }
+
+    regex.push('$');
+    Regex::new(&regex)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    fn matches(pattern: &str, branch: &str) -> bool {
+        glob_regex(pattern).unwrap().is_match(branch)
+    }
+
+    #[test]
+    fn matches_a_single_level_with_a_star() {
+        assert!(matches("hotfix/*", "hotfix/crash"));
+        assert!(!matches("hotfix/*", "hotfix/v1/crash"));
+        assert!(!matches("hotfix/*", "feature/hotfix/crash"));
+    }
+
+    #[test]
+    fn matches_several_levels_with_a_double_star() {
+        assert!(matches("hotfix/**", "hotfix/v1/crash"));
+        assert!(matches("**/hotfix", "team/a/hotfix"));
+    }
+
+    #[test]
+    fn matches_a_single_character_with_a_question_mark() {
+        assert!(matches("release-?", "release-1"));
+        assert!(!matches("release-?", "release-12"));
+    }
+
+    #[test]
+    fn matches_other_characters_literally() {
+        assert!(matches("release.1+x", "release.1+x"));
+        assert!(!matches("release.1", "release-1"));
+    }
+}
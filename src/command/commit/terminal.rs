@@ -0,0 +1,327 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Drawing of the custom prompts in the terminal.
+//!
+//! The prompts are drawn below the cursor, and redrawn in place after each key
+//! press, like the ones from Inquire.
+
+use std::{
+    io::{self, Stderr, Write as _},
+    mem,
+};
+
+use colored::Colorize as _;
+use crossterm::{
+    cursor, queue,
+    style::Print,
+    terminal::{self, ClearType},
+};
+
+use unicode_segmentation::UnicodeSegmentation as _;
+
+use crate::helpers::display_width;
+
+/// The width used when the size of the terminal is unknown.
+const DEFAULT_WIDTH: usize = 80;
+
+/// A guard keeping the terminal in raw mode while a prompt is displayed.
+pub struct Terminal {
+    /// The output of the terminal.
+    ///
+    /// The standard output is kept free for `--print-only`.
+    output: Stderr,
+    /// The number of lines drawn below the first one.
+    height: usize,
+    /// The line of the cursor, from the first one.
+    cursor_line: usize,
+}
+
+impl Terminal {
+    /// Switches the terminal to raw mode.
+    pub fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+
+        Ok(Self {
+            output: io::stderr(),
+            height: 0,
+            cursor_line: 0,
+        })
+    }
+
+    /// Draws the lines, replacing the previous ones.
+    ///
+    /// The cursor is left on the prompt line, at `column`.
+    pub fn draw(&mut self, lines: &[String], column: usize) -> io::Result<()> {
+        let width = terminal::size()
+            .map_or(DEFAULT_WIDTH, |(width, _)| usize::from(width))
+            .max(1);
+
+        self.clear()?;
+
+        let (lines, (cursor_line, cursor_column)) =
+            layout(lines, column, width);
+
+        for (number, line) in lines.iter().enumerate() {
+            if number > 0 {
+                queue!(self.output, Print("\r\n"))?;
+            }
+            queue!(self.output, Print(line))?;
+        }
+
+        self.height = lines.len().saturating_sub(1);
+        self.cursor_line = cursor_line;
+
+        if self.height > cursor_line {
+            let offset = to_u16(self.height - cursor_line);
+            queue!(self.output, cursor::MoveUp(offset))?;
+        }
+
+        queue!(self.output, cursor::MoveToColumn(to_u16(cursor_column)))?;
+        self.output.flush()
+    }
+
+    /// Replaces the prompt by its answer.
+    pub fn finish(&mut self, message: &str, answer: &str) -> io::Result<()> {
+        self.clear()?;
+        queue!(
+            self.output,
+            Print(format!("{} {message} {}\r\n", ">".green(), answer.cyan()))
+        )?;
+        self.output.flush()
+    }
+
+    /// Hides the prompt, to let another program use the terminal.
+    pub fn hide(&mut self) -> io::Result<()> {
+        self.clear()?;
+        self.output.flush()
+    }
+
+    /// Clears the lines previously drawn.
+    fn clear(&mut self) -> io::Result<()> {
+        if self.cursor_line > 0 {
+            queue!(self.output, cursor::MoveUp(to_u16(self.cursor_line)))?;
+        }
+
+        queue!(
+            self.output,
+            cursor::MoveToColumn(0),
+            terminal::Clear(ClearType::FromCursorDown)
+        )?;
+        self.height = 0;
+        self.cursor_line = 0;
+        Ok(())
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        if let Err(error) = terminal::disable_raw_mode() {
+            tracing::error!(?error, "failed to restore the terminal");
+        }
+    }
+}
+
+/// Lays the lines out on a terminal of `width` columns.
+///
+/// The first line is the prompt line: it is split on the width, like the
+/// terminal would do, so that the cursor at `column` can be placed on the right
+/// line. The other lines are wrapped at spaces. Returns the lines to draw and
+/// the position of the cursor, as a line and a column.
+fn layout(
+    lines: &[String],
+    column: usize,
+    width: usize,
+) -> (Vec<String>, (usize, usize)) {
+    let Some((prompt, others)) = lines.split_first() else {
+        return (vec![], (0, 0));
+    };
+
+    let mut lines = split(prompt, width);
+    let cursor = cursor_position(&lines, column, width);
+
+    // NOTE: At the end of a full line, the cursor goes on the next one.
+    if lines.len() <= cursor.0 {
+        lines.resize(cursor.0 + 1, String::new());
+    }
+
+    lines.extend(others.iter().flat_map(|line| wrap(line, width)));
+    (lines, cursor)
+}
+
+/// Splits a styled line in lines of at most `width` visible characters.
+///
+/// Lines are broken at spaces when possible, so that the terminal never wraps
+/// them by itself and the number of drawn lines stays known. Words longer than
+/// the width are split.
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut current = String::new();
+    let mut current_width = 0;
+
+    for word in line.split(' ') {
+        let mut word_width = visible_width(word);
+
+        if current_width > 0 && current_width + 1 + word_width > width {
+            lines.push(current);
+            current = String::new();
+            current_width = 0;
+        }
+
+        let mut word = word.to_owned();
+
+        if word_width > width {
+            let mut parts = split(&word, width);
+            word = parts.pop().unwrap_or_default();
+            word_width = visible_width(&word);
+            lines.extend(parts);
+        }
+
+        if current_width > 0 {
+            current.push(' ');
+            current_width += 1;
+        }
+
+        current.push_str(&word);
+        current_width += word_width;
+    }
+
+    lines.push(current);
+    lines
+}
+
+/// Returns the position of the `column` of a line split in `lines`.
+fn cursor_position(
+    lines: &[String],
+    column: usize,
+    width: usize,
+) -> (usize, usize) {
+    let mut remaining = column;
+
+    for (number, line) in lines.iter().enumerate() {
+        let line_width = visible_width(line);
+
+        if remaining < line_width
+            || (remaining == line_width && line_width < width)
+        {
+            return (number, remaining);
+        }
+
+        remaining -= line_width;
+    }
+
+    (lines.len(), remaining)
+}
+
+/// Splits a styled line every `width` visible characters.
+///
+/// A wide character that does not fit at the end of a line goes on the next
+/// one, like in the terminal.
+fn split(line: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut current = String::new();
+    let mut current_width = 0;
+    let mut in_escape = false;
+
+    for grapheme in line.graphemes(true) {
+        let grapheme_width = match grapheme {
+            "\x1b" => {
+                in_escape = true;
+                0
+            }
+            "m" if in_escape => {
+                in_escape = false;
+                0
+            }
+            _ if in_escape => 0,
+            _ => display_width(grapheme),
+        };
+
+        if current_width + grapheme_width > width {
+            lines.push(mem::take(&mut current));
+            current_width = 0;
+        }
+
+        current.push_str(grapheme);
+        current_width += grapheme_width;
+    }
+
+    lines.push(current);
+    lines
+}
+
+/// Returns the number of visible columns, ignoring ANSI escape sequences.
+fn visible_width(text: &str) -> usize {
+    let mut visible = String::with_capacity(text.len());
+    let mut in_escape = false;
+
+    for character in text.chars() {
+        match character {
+            '\x1b' => in_escape = true,
+            'm' if in_escape => in_escape = false,
+            _ if in_escape => (),
+            _ => visible.push(character),
+        }
+    }
+
+    display_width(&visible)
+}
+
+/// Converts a terminal coordinate, saturating on overflow.
+fn to_u16(value: usize) -> u16 {
+    u16::try_from(value).unwrap_or(u16::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    #[test]
+    fn wraps_long_lines_at_spaces() {
+        assert_eq!(wrap("add a new feature", 10), ["add a new", "feature"]);
+        assert_eq!(wrap("\x1b[2madd\x1b[0m a", 5), ["\x1b[2madd\x1b[0m a"]);
+    }
+
+    #[test]
+    fn wraps_wide_characters_on_their_width() {
+        assert_eq!(wrap("修正 バグ", 5), ["修正", "バグ"]);
+        assert_eq!(wrap("été hiver", 9), ["été hiver"]);
+    }
+
+    #[test]
+    fn splits_words_longer_than_the_width() {
+        assert_eq!(wrap("a abcdefgh", 4), ["a", "abcd", "efgh"]);
+        assert_eq!(wrap("修正修正修", 5), ["修正", "修正", "修"]);
+    }
+
+    #[test]
+    fn places_the_cursor_on_wrapped_prompt_lines() {
+        let lines = [String::from("? Scope 修正修正"), String::from("[help]")];
+
+        let (drawn, cursor) = layout(&lines, display_width(&lines[0]), 10);
+        assert_eq!(drawn, ["? Scope 修", "正修正", "[help]"]);
+        assert_eq!(cursor, (1, 6));
+
+        let (drawn, cursor) = layout(&lines, display_width(&lines[0]), 9);
+        assert_eq!(drawn, ["? Scope ", "修正修正", "[help]"]);
+        assert_eq!(cursor, (1, 8));
+
+        let (drawn, cursor) = layout(&lines, display_width(&lines[0]), 16);
+        assert_eq!(drawn, ["? Scope 修正修正", "", "[help]"]);
+        assert_eq!(cursor, (1, 0));
+    }
+}
@@ -0,0 +1,536 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! A text prompt with a live status line.
+//!
+//! It looks and behaves like the one from Inquire, but a status line computed
+//! from the input, like the gauge of the subject length, is updated after each
+//! key press. Suggestions can be picked with ↑↓ and tab, and the staged diff
+//! viewed with Ctrl-V.
+
+use std::{cell::Cell, fmt};
+
+use colored::Colorize as _;
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+};
+use eyre::Result;
+use inquire::{
+    validator::{ErrorMessage, Validation},
+    InquireError,
+};
+
+use crate::{
+    helpers::{display_width, pop_grapheme},
+    tracing::LogResult as _,
+};
+
+use super::{diff, plain::Validator, terminal::Terminal};
+
+/// The keys help for skipping the remaining questions.
+const SKIP_REST_HELP: &str = "ctrl-d to use the defaults for the rest";
+
+/// The keys help for viewing the staged diff.
+const SHOW_DIFF_HELP: &str = "ctrl-v to view the diff";
+
+/// A text prompt.
+pub struct Text<'a> {
+    /// The message of the prompt.
+    message: &'a str,
+    /// The text typed by the user.
+    input: String,
+    /// A text displayed while the input is empty.
+    placeholder: Option<&'a str>,
+    /// A message displayed below the input.
+    help_message: Option<&'a str>,
+    /// The validator of the answer.
+    validator: Option<Validator>,
+    /// The suggestions to pick from.
+    suggestions: &'a [String],
+    /// The position of the highlighted suggestion among the matching ones.
+    highlighted: Option<usize>,
+    /// A function computing the status line from the input.
+    status: Option<&'a dyn Fn(&str) -> String>,
+    /// The error to display after a failed validation.
+    error: Option<String>,
+    /// The flag to set when the user wants to skip the remaining questions.
+    skip_rest: Option<&'a Cell<bool>>,
+}
+
+/// The result of a key press.
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    /// The prompt continues.
+    Continue,
+    /// The user has submitted a valid answer.
+    Submit,
+    /// The user has pressed ESC.
+    Cancel,
+    /// The user has pressed Ctrl-C.
+    Interrupt,
+    /// The user wants to view the staged diff.
+    ShowDiff,
+}
+
+impl<'a> Text<'a> {
+    /// Builds a text prompt.
+    pub const fn new(message: &'a str) -> Self {
+        Self {
+            message,
+            input: String::new(),
+            placeholder: None,
+            help_message: None,
+            validator: None,
+            suggestions: &[],
+            highlighted: None,
+            status: None,
+            error: None,
+            skip_rest: None,
+        }
+    }
+
+    /// Sets the initial value of the input.
+    pub fn with_initial_value(mut self, initial_value: &str) -> Self {
+        initial_value.clone_into(&mut self.input);
+        self
+    }
+
+    /// Displays a text while the input is empty.
+    pub const fn with_placeholder(mut self, placeholder: &'a str) -> Self {
+        self.placeholder = Some(placeholder);
+        self
+    }
+
+    /// Displays a message below the input.
+    pub const fn with_help_message(mut self, help_message: &'a str) -> Self {
+        self.help_message = Some(help_message);
+        self
+    }
+
+    /// Validates the answer before accepting it.
+    pub const fn with_validator(mut self, validator: Validator) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// Offers suggestions matching the input.
+    pub const fn with_suggestions(mut self, suggestions: &'a [String]) -> Self {
+        self.suggestions = suggestions;
+        self
+    }
+
+    /// Displays a status line computed from the input, updated live.
+    pub const fn with_status(
+        mut self,
+        status: &'a dyn Fn(&str) -> String,
+    ) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Lets the user submit the answer with Ctrl-D and skip the remaining
+    /// questions, which sets `skip_rest`.
+    pub const fn with_skip_rest(mut self, skip_rest: &'a Cell<bool>) -> Self {
+        self.skip_rest = Some(skip_rest);
+        self
+    }
+
+    /// Runs the prompt and returns the answer.
+    ///
+    /// ESC cancels the operation.
+    pub fn prompt(self) -> Result<String> {
+        match self.run()? {
+            Some(answer) => Ok(answer),
+            None => Err(InquireError::OperationCanceled).log_err()?,
+        }
+    }
+
+    /// Runs the prompt and returns the answer, or `None` if the user has
+    /// pressed ESC.
+    pub fn prompt_skippable(self) -> Result<Option<String>> {
+        self.run()
+    }
+
+    /// Runs the prompt until the user submits or cancels it.
+    fn run(mut self) -> Result<Option<String>> {
+        let mut terminal = Terminal::enter().log_err()?;
+
+        let action = loop {
+            terminal
+                .draw(&self.lines(), self.cursor_column())
+                .log_err()?;
+
+            let Event::Key(key) = event::read().log_err()? else {
+                continue;
+            };
+
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+
+            match self.handle_key(key) {
+                Action::Continue => (),
+                Action::ShowDiff => {
+                    terminal.hide().log_err()?;
+                    diff::show_staged()?;
+                }
+                action => break action,
+            }
+        };
+
+        match action {
+            Action::Submit => {
+                terminal.finish(self.message, &self.input).log_err()?;
+                Ok(Some(self.input))
+            }
+            Action::Cancel => {
+                terminal.finish(self.message, "<canceled>").log_err()?;
+                Ok(None)
+            }
+            Action::Continue | Action::Interrupt | Action::ShowDiff => {
+                terminal.finish(self.message, "<canceled>").log_err()?;
+                Err(InquireError::OperationInterrupted).log_err()?
+            }
+        }
+    }
+
+    /// Handles a key press.
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Char('c') if control => return Action::Interrupt,
+            // NOTE: In raw mode, a line feed is read as Ctrl-J.
+            KeyCode::Enter => return self.submit(),
+            KeyCode::Char('j') if control => return self.submit(),
+            KeyCode::Esc => return Action::Cancel,
+            KeyCode::Char('d') if control => {
+                return self.submit_and_skip_rest()
+            }
+            KeyCode::Char('v') if control => return Action::ShowDiff,
+            KeyCode::Up => self.move_highlight(-1),
+            KeyCode::Down => self.move_highlight(1),
+            KeyCode::Tab => self.complete(),
+            KeyCode::Backspace => self.edit(pop_grapheme),
+            KeyCode::Char('u') if control => self.edit(String::clear),
+            KeyCode::Char(character) if !control => {
+                self.edit(|input| input.push(character));
+            }
+            _ => (),
+        }
+
+        Action::Continue
+    }
+
+    /// Validates the input and submits it if valid.
+    fn submit(&mut self) -> Action {
+        let validation = self.validator.map(|validator| validator(&self.input));
+
+        match validation {
+            None | Some(Ok(Validation::Valid)) => Action::Submit,
+            Some(Ok(Validation::Invalid(ErrorMessage::Custom(error)))) => {
+                self.error = Some(error);
+                Action::Continue
+            }
+            Some(Ok(Validation::Invalid(ErrorMessage::Default))) => {
+                self.error = Some(String::from("Invalid answer"));
+                Action::Continue
+            }
+            Some(Err(error)) => {
+                self.error = Some(error.to_string());
+                Action::Continue
+            }
+        }
+    }
+
+    /// Submits the input and skips the remaining questions, if enabled.
+    fn submit_and_skip_rest(&mut self) -> Action {
+        let Some(skip_rest) = self.skip_rest else {
+            return Action::Continue;
+        };
+
+        let action = self.submit();
+
+        if action == Action::Submit {
+            skip_rest.set(true);
+        }
+
+        action
+    }
+
+    /// Edits the input, which resets the highlighted suggestion.
+    fn edit(&mut self, edit: impl FnOnce(&mut String)) {
+        edit(&mut self.input);
+        self.highlighted = None;
+        self.error = None;
+    }
+
+    /// Moves the highlight by `offset` suggestions, wrapping around the list.
+    fn move_highlight(&mut self, offset: isize) {
+        let len = self.matching().len();
+
+        if len > 0 {
+            let len = isize::try_from(len).unwrap_or(isize::MAX);
+            let highlighted = self
+                .highlighted
+                .map_or(if offset > 0 { -1 } else { 0 }, |highlighted| {
+                    isize::try_from(highlighted).unwrap_or_default()
+                });
+            let highlighted = (highlighted + offset).rem_euclid(len);
+            self.highlighted = usize::try_from(highlighted).ok();
+        }
+    }
+
+    /// Replaces the input with the highlighted suggestion, if any.
+    fn complete(&mut self) {
+        let suggestion = self
+            .highlighted
+            .and_then(|highlighted| self.matching().get(highlighted).copied())
+            .map(ToOwned::to_owned);
+
+        if let Some(suggestion) = suggestion {
+            self.edit(|input| *input = suggestion);
+        }
+    }
+
+    /// Returns the suggestions matching the input, ignoring the case.
+    fn matching(&self) -> Vec<&'a str> {
+        let input = self.input.to_lowercase();
+
+        self.suggestions
+            .iter()
+            .filter(|suggestion| suggestion.to_lowercase().contains(&input))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Returns the column of the cursor on the prompt line.
+    fn cursor_column(&self) -> usize {
+        display_width(&format!("? {} {}", self.message, self.input))
+    }
+
+    /// Returns the lines to draw, without wrapping them.
+    fn lines(&self) -> Vec<String> {
+        let input = match self.placeholder {
+            Some(placeholder) if self.input.is_empty() => {
+                placeholder.dimmed().to_string()
+            }
+            _ => self.input.clone(),
+        };
+
+        let mut lines =
+            vec![format!("{} {} {input}", "?".green(), self.message.bold())];
+
+        if let Some(error) = &self.error {
+            lines.push(format!("{}", format!("# {error}").red()));
+        }
+
+        for (position, suggestion) in self.matching().into_iter().enumerate() {
+            if Some(position) == self.highlighted {
+                lines.push(format!("{}", format!("> {suggestion}").cyan()));
+            } else {
+                lines.push(format!("  {suggestion}"));
+            }
+        }
+
+        if let Some(status) = self.status {
+            lines.push(status(&self.input));
+        }
+
+        if let Some(help_message) = self.help_message {
+            lines.push(format!("{}", format!("[{help_message}]").cyan()));
+        }
+
+        lines.push(format!("{}", self.keys_help().cyan()));
+        lines
+    }
+
+    /// Returns the help for the keys.
+    fn keys_help(&self) -> String {
+        let skip_rest_help = self
+            .skip_rest
+            .map(|_| format!("{SKIP_REST_HELP}, "))
+            .unwrap_or_default();
+
+        format!("[{skip_rest_help}{SHOW_DIFF_HELP}]")
+    }
+}
+
+impl fmt::Debug for Text<'_> {
+    #[expect(
+        clippy::min_ident_chars,
+        reason = "This is the conventional name in `fmt` implementations."
+    )]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Text")
+            .field("message", &self.message)
+            .field("input", &self.input)
+            .field("highlighted", &self.highlighted)
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use inquire::CustomUserError;
+
+    use super::*;
+
+    fn suggestions() -> Vec<String> {
+        vec![String::from("add run"), String::from("update lib.rs")]
+    }
+
+    fn press(text: &mut Text<'_>, code: KeyCode) -> Action {
+        text.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn type_input(text: &mut Text<'_>, input: &str) {
+        for character in input.chars() {
+            press(text, KeyCode::Char(character));
+        }
+    }
+
+    fn validate_length(input: &str) -> Result<Validation, CustomUserError> {
+        if input.len() < 5 {
+            Ok(Validation::Invalid("Too short".into()))
+        } else {
+            Ok(Validation::Valid)
+        }
+    }
+
+    #[test]
+    fn edits_the_input() {
+        let mut text = Text::new("Short description").with_initial_value("ab");
+
+        type_input(&mut text, "cé");
+        press(&mut text, KeyCode::Backspace);
+
+        assert_eq!(text.input, "abc");
+
+        text.handle_key(KeyEvent::new(
+            KeyCode::Char('u'),
+            KeyModifiers::CONTROL,
+        ));
+
+        assert_eq!(text.input, "");
+    }
+
+    #[test]
+    fn refuses_an_invalid_answer() {
+        colored::control::set_override(false);
+        let mut text =
+            Text::new("Short description").with_validator(validate_length);
+
+        type_input(&mut text, "add");
+
+        assert_eq!(press(&mut text, KeyCode::Enter), Action::Continue);
+        assert!(text.lines().contains(&String::from("# Too short")));
+
+        type_input(&mut text, " a test");
+
+        assert_eq!(press(&mut text, KeyCode::Enter), Action::Submit);
+    }
+
+    #[test]
+    fn submits_and_skips_the_rest_on_ctrl_d() {
+        let skip_rest = Cell::new(false);
+        let mut text = Text::new("Short description")
+            .with_validator(validate_length)
+            .with_skip_rest(&skip_rest);
+        let ctrl_d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+
+        type_input(&mut text, "add");
+
+        assert_eq!(text.handle_key(ctrl_d), Action::Continue);
+        assert!(!skip_rest.get());
+
+        type_input(&mut text, " a test");
+
+        assert_eq!(text.handle_key(ctrl_d), Action::Submit);
+        assert!(skip_rest.get());
+    }
+
+    #[test]
+    fn ignores_ctrl_d_without_skip_rest() {
+        let mut text = Text::new("Short description");
+
+        let action = text.handle_key(KeyEvent::new(
+            KeyCode::Char('d'),
+            KeyModifiers::CONTROL,
+        ));
+
+        assert_eq!(action, Action::Continue);
+    }
+
+    #[test]
+    fn shows_the_diff_on_ctrl_v() {
+        let mut text = Text::new("Scope");
+
+        let action = text.handle_key(KeyEvent::new(
+            KeyCode::Char('v'),
+            KeyModifiers::CONTROL,
+        ));
+
+        assert_eq!(action, Action::ShowDiff);
+        assert_eq!(text.input, "");
+    }
+
+    #[test]
+    fn completes_the_highlighted_suggestion() {
+        let suggestions = suggestions();
+        let mut text =
+            Text::new("Short description").with_suggestions(&suggestions);
+
+        press(&mut text, KeyCode::Down);
+        press(&mut text, KeyCode::Down);
+        press(&mut text, KeyCode::Tab);
+
+        assert_eq!(text.input, "update lib.rs");
+    }
+
+    #[test]
+    fn only_shows_the_matching_suggestions() {
+        let suggestions = suggestions();
+        let mut text =
+            Text::new("Short description").with_suggestions(&suggestions);
+
+        type_input(&mut text, "LIB");
+
+        assert_eq!(text.matching(), ["update lib.rs"]);
+    }
+
+    #[test]
+    fn updates_the_status_line_with_the_input() {
+        colored::control::set_override(false);
+        let status = |input: &str| format!("{} characters", input.len());
+        let mut text = Text::new("Short description").with_status(&status);
+
+        type_input(&mut text, "add");
+
+        assert!(text.lines().contains(&String::from("3 characters")));
+    }
+
+    #[test]
+    fn puts_the_cursor_after_wide_characters() {
+        let mut text = Text::new("Scope");
+
+        type_input(&mut text, "修正");
+
+        assert_eq!(text.cursor_column(), "? Scope ".len() + 4);
+    }
+}
@@ -0,0 +1,450 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Detection of the ticket reference from the context of the commit.
+//!
+//! The sources are tried in order:
+//!
+//! 1. the name of the current branch,
+//! 2. the name of its upstream branch,
+//! 3. the `Refs:` trailer of the `HEAD` commit.
+//!
+//! When `HEAD` is detached, there is no branch to look at, so only the last
+//! source is used.
+//!
+//! With `ticket.placement = "trailer"`, the ticket is also added to the rendered
+//! message as a Git trailer, so that tools reading trailers find it.
+
+use eyre::{Context as _, Result};
+use regex::Regex;
+use thiserror::Error;
+
+use crate::{
+    config::{Config, TicketPlacement},
+    git::{self, GitError},
+    hint, issue_title,
+    lint::{find_ticket, ticket_regex},
+    subject_template,
+    tracing::LogResult as _,
+    warning,
+};
+
+use super::{current_branch, head_message};
+
+/// The name of the capture group of the branch patterns.
+const TICKET_GROUP: &str = "ticket";
+
+/// The token of the trailer referencing a ticket.
+const REFS_TRAILER: &str = "Refs:";
+
+/// The default token of the trailer added with `ticket.placement = "trailer"`.
+const DEFAULT_TRAILER_TOKEN: &str = "Refs";
+
+/// Errors that can occur when placing the ticket in a trailer.
+#[derive(Debug, Error)]
+pub enum TrailerError {
+    /// The configured trailer token is invalid.
+    #[error("`{token}` is not a valid trailer token")]
+    InvalidToken {
+        /// The configured token.
+        token: String,
+    },
+}
+
+/// The result of the ticket detection.
+#[derive(Debug, Default)]
+pub struct Detection {
+    /// The detected ticket reference, if any.
+    pub ticket: Option<String>,
+    /// Whether `HEAD` is detached.
+    pub detached_head: bool,
+}
+
+impl Detection {
+    /// Returns the detected ticket.
+    ///
+    /// When no ticket has been detected because `HEAD` is detached, a note is
+    /// printed to explain why.
+    pub fn into_ticket(self) -> Option<String> {
+        if self.detached_head && self.ticket.is_none() {
+            hint!(
+                "HEAD is detached: the ticket cannot be detected from the name \
+                    of the branch."
+            );
+        }
+
+        self.ticket
+    }
+}
+
+/// Fetches the title of a ticket with `ticket.title_command`, if configured.
+///
+/// The title is returned as a description, starting in lowercase. When the
+/// command fails, a warning is printed and no title is returned, so that the
+/// description can still be entered by hand.
+#[tracing::instrument(level = "trace", skip(config))]
+pub fn fetch_title(config: &Config, ticket: &str) -> Option<String> {
+    let command = config.ticket.as_ref()?.title_command.as_deref()?;
+
+    match issue_title::fetch(command, ticket) {
+        Ok(title) => title.map(|title| lowercase_first(&title)),
+        Err(error) => {
+            warning!("The title of {ticket} cannot be fetched: {error}.");
+            None
+        }
+    }
+}
+
+/// Returns the token of the trailer referencing the ticket, if the ticket is
+/// placed in a trailer.
+pub fn trailer_token(config: &Config) -> Option<&str> {
+    config
+        .ticket
+        .as_ref()
+        .filter(|ticket| ticket.placement == TicketPlacement::Trailer)
+        .map(|ticket| {
+            ticket.trailer.as_deref().unwrap_or(DEFAULT_TRAILER_TOKEN)
+        })
+}
+
+/// Checks the token of the trailer referencing the ticket, if any.
+pub fn check_trailer_token(config: &Config) -> Result<(), TrailerError> {
+    match trailer_token(config) {
+        Some(token) if !is_valid_trailer_token(token) => {
+            Err(TrailerError::InvalidToken {
+                token: token.to_owned(),
+            })
+            .log_err()
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns whether a trailer token is accepted by `git interpret-trailers`.
+///
+/// Git accepts any token without whitespace nor separator, but we restrict it
+/// to letters, digits and hyphens so that it is also a valid footer.
+pub fn is_valid_trailer_token(token: &str) -> bool {
+    !token.is_empty()
+        && !token.starts_with('-')
+        && token.chars().all(|character| {
+            character.is_ascii_alphanumeric() || character == '-'
+        })
+}
+
+/// Adds the trailer referencing the ticket to a rendered message, if the ticket
+/// is placed in a trailer.
+///
+/// Like Git, the trailer is appended to the last paragraph if it only contains
+/// trailers, or as a new paragraph otherwise. Nothing is added if a trailer
+/// with the same token already references the ticket, for instance when the
+/// template places it itself.
+pub fn add_trailer(
+    config: &Config,
+    ticket: Option<&str>,
+    message: String,
+) -> String {
+    let (Some(token), Some(ticket)) = (trailer_token(config), ticket) else {
+        return message;
+    };
+
+    let footers = subject_template::parse(config, &message)
+        .map(|commit| commit.footers)
+        .unwrap_or_default();
+
+    let already_placed = footers.iter().any(|footer| {
+        footer.token.eq_ignore_ascii_case(token)
+            && footer.value.split(',').any(|value| value.trim() == ticket)
+    });
+
+    if already_placed {
+        return message;
+    }
+
+    let separator = if footers.is_empty() { "\n\n" } else { "\n" };
+    format!("{}{separator}{token}: {ticket}\n", message.trim_end())
+}
+
+/// Puts the first letter of a title in lowercase, unless it is an acronym.
+fn lowercase_first(title: &str) -> String {
+    let mut chars = title.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(first), second) if !second.is_some_and(char::is_uppercase) => {
+            format!(
+                "{}{}",
+                first.to_lowercase(),
+                title.get(first.len_utf8()..).unwrap_or_default()
+            )
+        }
+        _ => title.to_owned(),
+    }
+}
+
+/// Detects the ticket reference from the context of the commit.
+#[tracing::instrument(level = "trace")]
+pub fn detect(
+    prefixes: &[String],
+    branch_patterns: &[String],
+) -> Result<Detection> {
+    let patterns = branch_patterns
+        .iter()
+        .map(|pattern| {
+            Regex::new(pattern)
+                .wrap_err_with(|| format!("Invalid branch pattern `{pattern}`"))
+        })
+        .collect::<Result<Vec<_>>>()
+        .log_err()?;
+
+    let current_branch = current_branch()?;
+    let detached_head = current_branch.is_none();
+
+    let mut ticket = match &current_branch {
+        Some(branch) => from_branch(prefixes, &patterns, branch)?,
+        None => None,
+    };
+
+    if ticket.is_none() && !detached_head {
+        if let Some(upstream) = upstream_branch()? {
+            ticket = from_branch(prefixes, &patterns, &upstream)?;
+        }
+    }
+
+    if ticket.is_none() {
+        ticket = head_message()?
+            .and_then(|message| from_trailer(prefixes, &message));
+    }
+
+    let detection = Detection {
+        ticket,
+        detached_head,
+    };
+
+    tracing::trace!(?detection);
+    Ok(detection)
+}
+
+/// Tries to extract a ticket reference from the name of a branch.
+///
+/// The configured patterns are tried first, then the valid prefixes.
+fn from_branch(
+    prefixes: &[String],
+    patterns: &[Regex],
+    branch: &str,
+) -> Result<Option<String>> {
+    if let Some(ticket) = patterns.iter().find_map(|pattern| {
+        pattern
+            .captures(branch)
+            .and_then(|captures| captures.name(TICKET_GROUP))
+            .map(|ticket| ticket.as_str().to_owned())
+    }) {
+        return Ok(Some(add_hash_prefix(prefixes, ticket)));
+    }
+
+    // Replace `#` with an empty string in the regex, as we want to match
+    // branches like `feature/23-name` when `#` is a valid prefix like for
+    // GitHub or GitLab issues.
+    let regex = ticket_regex(prefixes).replace('#', "");
+
+    let ticket = Regex::new(&regex)
+        .wrap_err("Impossible to build a regex from the list of prefixes")
+        .log_err()?
+        .captures(branch)
+        .map(|captures| add_hash_prefix(prefixes, captures[0].to_owned()));
+
+    Ok(ticket)
+}
+
+/// Tries to extract a ticket reference from the `Refs:` trailer of a message.
+fn from_trailer(prefixes: &[String], message: &str) -> Option<String> {
+    message
+        .lines()
+        .filter_map(|line| line.strip_prefix(REFS_TRAILER))
+        .find_map(|refs| find_ticket(prefixes, refs))
+}
+
+/// Adds a `#` in front of numeric tickets when `#` is a valid prefix.
+///
+/// If one of the valid prefixes is `#` and the ticket ID is only made of
+/// numbers, we are in the GitHub / GitLab style.
+fn add_hash_prefix(prefixes: &[String], ticket: String) -> String {
+    let is_numeric = !ticket.is_empty()
+        && ticket.chars().all(|character| character.is_ascii_digit());
+
+    if is_numeric && prefixes.contains(&String::from("#")) {
+        format!("#{ticket}")
+    } else {
+        ticket
+    }
+}
+
+/// Gets the name of the upstream of the current branch, if any.
+#[tracing::instrument(level = "trace")]
+fn upstream_branch() -> Result<Option<String>> {
+    let upstream_branch = match git::run_trimmed(&[
+        "rev-parse",
+        "--abbrev-ref",
+        "--symbolic-full-name",
+        "@{u}",
+    ]) {
+        Ok(upstream_branch) => upstream_branch,
+        // NOTE: Git fails when the current branch has no upstream.
+        Err(GitError::Failed(_)) => return Ok(None),
+        Err(error) => return Err(error.into()),
+    };
+
+    tracing::trace!(?upstream_branch);
+    Ok((!upstream_branch.is_empty()).then_some(upstream_branch))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+    use crate::config::Ticket;
+
+    fn prefixes() -> Vec<String> {
+        vec![String::from("#"), String::from("GH-")]
+    }
+
+    fn patterns(patterns: &[&str]) -> Vec<Regex> {
+        patterns
+            .iter()
+            .map(|pattern| Regex::new(pattern).unwrap())
+            .collect()
+    }
+
+    fn trailer_config(trailer: Option<&str>) -> Config {
+        Config {
+            ticket: Some(Ticket {
+                required: false,
+                prefixes: prefixes(),
+                branch_patterns: None,
+                title_command: None,
+                status_command: None,
+                closed_states: None,
+                placement: TicketPlacement::Trailer,
+                trailer: trailer.map(ToOwned::to_owned),
+            }),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn adds_the_ticket_as_a_trailer() {
+        let config = trailer_config(None);
+
+        assert_eq!(
+            add_trailer(&config, Some("#12"), String::from("fix: a bug\n")),
+            "fix: a bug\n\nRefs: #12\n"
+        );
+        assert_eq!(
+            add_trailer(
+                &config,
+                Some("#12"),
+                String::from("fix: a bug\n\nReviewed-by: Someone\n")
+            ),
+            "fix: a bug\n\nReviewed-by: Someone\nRefs: #12\n"
+        );
+    }
+
+    #[test]
+    fn does_not_add_the_trailer_twice() {
+        let config = trailer_config(Some("Closes"));
+        let message = String::from("fix: a bug\n\ncloses: #1, #12\n");
+
+        assert_eq!(add_trailer(&config, Some("#12"), message.clone()), message);
+        assert_eq!(
+            add_trailer(&config, Some("#13"), message),
+            "fix: a bug\n\ncloses: #1, #12\nCloses: #13\n"
+        );
+    }
+
+    #[test]
+    fn leaves_the_message_untouched_with_the_template_placement() {
+        let mut config = trailer_config(None);
+        if let Some(ticket) = &mut config.ticket {
+            ticket.placement = TicketPlacement::Template;
+        }
+
+        assert_eq!(
+            add_trailer(&config, Some("#12"), String::from("fix: a bug\n")),
+            "fix: a bug\n"
+        );
+    }
+
+    #[test]
+    fn accepts_only_valid_trailer_tokens() {
+        assert!(is_valid_trailer_token("Closes"));
+        assert!(is_valid_trailer_token("Jira-Ticket"));
+        assert!(!is_valid_trailer_token(""));
+        assert!(!is_valid_trailer_token("Ticket ID"));
+        assert!(!is_valid_trailer_token("Refs:"));
+        assert!(!is_valid_trailer_token("-Refs"));
+    }
+
+    #[test]
+    fn puts_the_first_letter_of_titles_in_lowercase() {
+        assert_eq!(lowercase_first("Crash on startup"), "crash on startup");
+        assert_eq!(lowercase_first("API is down"), "API is down");
+        assert_eq!(lowercase_first(""), "");
+    }
+
+    #[test]
+    fn extracts_the_ticket_from_prefixes() {
+        assert_eq!(
+            from_branch(&prefixes(), &[], "feature/GH-42-name").unwrap(),
+            Some(String::from("GH-42"))
+        );
+        assert_eq!(
+            from_branch(&prefixes(), &[], "feature/42-name").unwrap(),
+            Some(String::from("#42"))
+        );
+        assert_eq!(from_branch(&prefixes(), &[], "main").unwrap(), None);
+    }
+
+    #[test]
+    fn extracts_the_ticket_from_patterns_first() {
+        let patterns = patterns(&[r"^issue-(?<ticket>\d+)", r"^GH-\d+$"]);
+
+        assert_eq!(
+            from_branch(&prefixes(), &patterns, "issue-7-GH-42").unwrap(),
+            Some(String::from("#7"))
+        );
+    }
+
+    #[test]
+    fn ignores_patterns_without_ticket_group() {
+        let patterns = patterns(&[r"^release-(\d+)"]);
+
+        assert_eq!(
+            from_branch(&prefixes(), &patterns, "release-12").unwrap(),
+            Some(String::from("#12"))
+        );
+    }
+
+    #[test]
+    fn extracts_the_ticket_from_the_refs_trailer() {
+        let message =
+            "feat: add a feature\n\nSome text about #1.\n\nRefs: GH-42\n";
+
+        assert_eq!(
+            from_trailer(&prefixes(), message),
+            Some(String::from("GH-42"))
+        );
+        assert_eq!(from_trailer(&prefixes(), "feat: add #1\n"), None);
+    }
+}
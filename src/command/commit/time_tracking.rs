@@ -0,0 +1,434 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Tracking of the time spent on a commit.
+//!
+//! The time is measured from the start of the wizard, which is recorded in the
+//! commit cache so that aborted runs are accounted for. With
+//! `time_tracking.ask`, the user can correct it before it is added to the
+//! message as a trailer in the Jira format, like `1h 30m`. With
+//! `time_tracking.worklog_command`, it is also logged on the referenced ticket.
+
+use std::{sync::LazyLock, time::Duration};
+
+use eyre::Result;
+use inquire::{validator::Validation, CustomUserError, Text};
+use itertools::Itertools as _;
+use regex::Regex;
+use thiserror::Error;
+
+use crate::{
+    commit_cache::CommitCache, config::Config, lint::find_ticket, list_command,
+    subject_template, tracing::LogResult as _, warning,
+};
+
+use super::{plain, ticket::is_valid_trailer_token, WizardUi};
+
+/// The default token of the trailer containing the time spent.
+const DEFAULT_TRAILER_TOKEN: &str = "Time-spent";
+
+/// The variable containing the ticket in the environment of `worklog_command`.
+const TICKET_VAR: &str = "GITZ_TICKET";
+
+/// The variable containing the time spent in the environment of
+/// `worklog_command`.
+const TIME_SPENT_VAR: &str = "GITZ_TIME_SPENT";
+
+/// The regex matching a time spent in the Jira format.
+#[expect(clippy::unwrap_used, reason = "This regex is known to be valid.")]
+static TIME_SPENT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\d+[wdhm](?: \d+[wdhm])*$").unwrap());
+
+/// Errors that can occur when tracking the time spent.
+#[derive(Debug, Error)]
+pub enum TimeTrackingError {
+    /// The configured trailer token is invalid.
+    #[error("`{token}` is not a valid trailer token")]
+    InvalidToken {
+        /// The configured token.
+        token: String,
+    },
+}
+
+/// Records the start of the wizard in the cache, if the time is tracked.
+pub fn start_timer(config: &Config, cache: &mut CommitCache) {
+    if config.time_tracking.is_some() {
+        cache.start_timer();
+    }
+}
+
+/// Returns the time spent on the commit, if the time is tracked.
+///
+/// With `time_tracking.ask`, the user is asked for it, and can omit it.
+pub fn time_spent(
+    config: &Config,
+    cache: &CommitCache,
+    ui: WizardUi,
+) -> Result<Option<String>> {
+    let Some(time_tracking) = &config.time_tracking else {
+        return Ok(None);
+    };
+
+    check_trailer_token(config)?;
+
+    let measured = format_duration(cache.time_spent().unwrap_or_default());
+    tracing::debug!(?measured);
+
+    if time_tracking.ask {
+        ask_time_spent(&measured, ui)
+    } else {
+        Ok(Some(measured))
+    }
+}
+
+/// Adds the trailer containing the time spent to a rendered message.
+///
+/// A trailer with the same token in the footers, left by a previous run, is
+/// replaced. The body is kept as is, even if some lines look like the trailer.
+pub fn add_trailer(
+    config: &Config,
+    time_spent: Option<&str>,
+    message: String,
+) -> String {
+    let (Some(token), Some(time_spent)) = (trailer_token(config), time_spent)
+    else {
+        return message;
+    };
+
+    let has_trailer =
+        subject_template::parse(config, &message).is_ok_and(|commit| {
+            commit
+                .footers
+                .iter()
+                .any(|footer| footer.token.eq_ignore_ascii_case(token))
+        });
+
+    // NOTE: When the message has footers, they are its last paragraph.
+    let message = match message.trim_end().rsplit_once("\n\n") {
+        Some((rest, footers)) if has_trailer => {
+            let footers = footers
+                .lines()
+                .filter(|line| !is_trailer(line, token))
+                .join("\n");
+            format!("{rest}\n\n{footers}")
+        }
+        _ => message,
+    };
+
+    let has_footers = subject_template::parse(config, &message)
+        .is_ok_and(|commit| !commit.footers.is_empty());
+    let separator = if has_footers { "\n" } else { "\n\n" };
+
+    format!("{}{separator}{token}: {time_spent}\n", message.trim_end())
+}
+
+/// Adds the time spent to the trailer of a message being amended.
+///
+/// The time spent on the amended commit is kept, and the time spent on the
+/// amendment is added to it. A trailer that is not in the Jira format is kept
+/// as is.
+pub fn add_to_trailer(
+    config: &Config,
+    time_spent: Option<&str>,
+    message: String,
+) -> String {
+    let (Some(token), Some(time_spent)) = (trailer_token(config), time_spent)
+    else {
+        return message;
+    };
+
+    let existing =
+        subject_template::parse(config, &message)
+            .ok()
+            .and_then(|commit| {
+                commit
+                    .footers
+                    .into_iter()
+                    .find(|footer| footer.token.eq_ignore_ascii_case(token))
+                    .map(|footer| footer.value)
+            });
+
+    let Some(existing) = existing else {
+        return add_trailer(config, Some(time_spent), message);
+    };
+
+    match (parse_duration(&existing), parse_duration(time_spent)) {
+        (Some(existing), Some(time_spent)) => {
+            let total = format_duration(existing.saturating_add(time_spent));
+            add_trailer(config, Some(&total), message)
+        }
+        _ => message,
+    }
+}
+
+/// Logs the time spent on the ticket referenced in the message, if
+/// `time_tracking.worklog_command` is configured.
+///
+/// As the commit is already done, failures are reported as warnings.
+#[tracing::instrument(level = "trace", skip(config, message))]
+pub fn log_work(config: &Config, message: &str, time_spent: &str) {
+    let Some(command) = config
+        .time_tracking
+        .as_ref()
+        .and_then(|time_tracking| time_tracking.worklog_command.as_deref())
+    else {
+        return;
+    };
+
+    let Some(ticket) = config
+        .ticket
+        .as_ref()
+        .and_then(|ticket| find_ticket(&ticket.prefixes, message))
+    else {
+        warning!("The time spent cannot be logged: no ticket is referenced.");
+        return;
+    };
+
+    let vars = [(TICKET_VAR, ticket.as_str()), (TIME_SPENT_VAR, time_spent)];
+    match list_command::first_line(command, &vars) {
+        Ok(_) => tracing::info!(ticket, time_spent, "work logged"),
+        Err(error) => {
+            warning!("The time spent cannot be logged on {ticket}: {error}.");
+        }
+    }
+}
+
+/// Returns the token of the trailer containing the time spent, if the time is
+/// tracked.
+fn trailer_token(config: &Config) -> Option<&str> {
+    config.time_tracking.as_ref().map(|time_tracking| {
+        time_tracking
+            .trailer
+            .as_deref()
+            .unwrap_or(DEFAULT_TRAILER_TOKEN)
+    })
+}
+
+/// Checks the token of the trailer containing the time spent, if any.
+fn check_trailer_token(config: &Config) -> Result<(), TimeTrackingError> {
+    match trailer_token(config) {
+        Some(token) if !is_valid_trailer_token(token) => {
+            Err(TimeTrackingError::InvalidToken {
+                token: token.to_owned(),
+            })
+            .log_err()
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns whether a line is a trailer with the given token.
+fn is_trailer(line: &str, token: &str) -> bool {
+    line.split_once(':')
+        .is_some_and(|(key, _)| key.eq_ignore_ascii_case(token))
+}
+
+/// Asks the user for the time spent, prefilled with the measured one.
+fn ask_time_spent(measured: &str, ui: WizardUi) -> Result<Option<String>> {
+    let help_message = "In the Jira format, like 1h 30m.";
+
+    let time_spent = if ui == WizardUi::Plain {
+        plain::text(
+            "Time spent",
+            measured,
+            Some(help_message),
+            Some(validate_time_spent),
+            true,
+            None,
+        )?
+    } else {
+        Text::new("Time spent")
+            .with_initial_value(measured)
+            .with_validator(validate_time_spent)
+            .with_help_message(&format!(
+                "{help_message} Press ESC to omit the time spent."
+            ))
+            .prompt_skippable()
+            .log_err()?
+    };
+
+    tracing::debug!(?time_spent);
+    Ok(time_spent)
+}
+
+/// Validates the time spent.
+#[expect(
+    clippy::unnecessary_wraps,
+    reason = "The signature of the function is imposed by Inquire."
+)]
+fn validate_time_spent(
+    time_spent: &str,
+) -> Result<Validation, CustomUserError> {
+    if TIME_SPENT_REGEX.is_match(time_spent.trim()) {
+        Ok(Validation::Valid)
+    } else {
+        Ok(Validation::Invalid(
+            "The time spent must be in the form 1h 30m".into(),
+        ))
+    }
+}
+
+/// Formats a duration in the Jira format, rounded up to the minute.
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs().div_ceil(60).max(1);
+
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    }
+}
+
+/// Parses a duration in the Jira format.
+///
+/// Like Jira by default, a day is 8 hours and a week is 5 days.
+fn parse_duration(time_spent: &str) -> Option<Duration> {
+    let time_spent = time_spent.trim();
+
+    if !TIME_SPENT_REGEX.is_match(time_spent) {
+        return None;
+    }
+
+    let minutes = time_spent
+        .split(' ')
+        .map(|part| {
+            let (value, unit) = part.split_at(part.len().saturating_sub(1));
+            let factor = match unit {
+                "w" => 5 * 8 * 60,
+                "d" => 8 * 60,
+                "h" => 60,
+                _ => 1,
+            };
+            value.parse::<u64>().ok()?.checked_mul(factor)
+        })
+        .try_fold(0_u64, |total, minutes| total.checked_add(minutes?))?;
+
+    Some(Duration::from_secs(minutes.checked_mul(60)?))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    fn config_with_time_tracking() -> Config {
+        Config {
+            time_tracking: Some(toml::from_str("").unwrap()),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn formats_durations_in_the_jira_format() {
+        assert_eq!(format_duration(Duration::ZERO), "1m");
+        assert_eq!(format_duration(Duration::from_secs(125)), "3m");
+        assert_eq!(format_duration(Duration::from_secs(7200)), "2h");
+        assert_eq!(format_duration(Duration::from_secs(5400)), "1h 30m");
+    }
+
+    #[test]
+    fn validates_the_time_spent() {
+        assert!(matches!(
+            validate_time_spent("1d 2h 30m").unwrap(),
+            Validation::Valid
+        ));
+        assert!(matches!(
+            validate_time_spent("1.5 hours").unwrap(),
+            Validation::Invalid(_)
+        ));
+    }
+
+    #[test]
+    fn parses_durations_in_the_jira_format() {
+        assert_eq!(parse_duration("25m"), Some(Duration::from_secs(1500)));
+        assert_eq!(parse_duration("1h 30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("1w 1d"), Some(Duration::from_secs(172_800)));
+        assert_eq!(parse_duration("1.5 hours"), None);
+    }
+
+    #[test]
+    fn does_not_add_the_trailer_by_default() {
+        let message = String::from("feat: add a feature\n");
+        assert_eq!(
+            add_trailer(&Config::default(), Some("1h"), message.clone()),
+            message
+        );
+    }
+
+    #[test]
+    fn appends_the_trailer_to_the_existing_footers() {
+        let message = String::from("feat: add a feature\n\nRefs: #42\n");
+        assert_eq!(
+            add_trailer(&config_with_time_tracking(), Some("1h"), message),
+            "feat: add a feature\n\nRefs: #42\nTime-spent: 1h\n"
+        );
+    }
+
+    #[test]
+    fn keeps_body_lines_looking_like_the_trailer() {
+        let message = String::from(
+            "feat: add a feature\n\nTime-spent: mostly on tests.\n\nRefs: #42\n",
+        );
+        assert_eq!(
+            add_trailer(&config_with_time_tracking(), Some("1h"), message),
+            "feat: add a feature\n\nTime-spent: mostly on tests.\n\nRefs: #42\n\
+                Time-spent: 1h\n"
+        );
+    }
+
+    #[test]
+    fn replaces_the_trailer_of_a_previous_run() {
+        let message =
+            String::from("feat: add a feature\n\nRefs: #42\nTime-spent: 10m\n");
+        assert_eq!(
+            add_trailer(&config_with_time_tracking(), Some("25m"), message),
+            "feat: add a feature\n\nRefs: #42\nTime-spent: 25m\n"
+        );
+    }
+
+    #[test]
+    fn adds_the_time_spent_to_the_trailer_of_an_amended_message() {
+        let message =
+            String::from("feat: add a feature\n\nRefs: #42\nTime-spent: 1h\n");
+        assert_eq!(
+            add_to_trailer(&config_with_time_tracking(), Some("45m"), message),
+            "feat: add a feature\n\nRefs: #42\nTime-spent: 1h 45m\n"
+        );
+    }
+
+    #[test]
+    fn keeps_a_trailer_not_in_the_jira_format_when_amending() {
+        let message =
+            String::from("feat: add a feature\n\nTime-spent: a while\n");
+        assert_eq!(
+            add_to_trailer(
+                &config_with_time_tracking(),
+                Some("45m"),
+                message.clone()
+            ),
+            message
+        );
+    }
+
+    #[test]
+    fn adds_the_trailer_when_amending_a_message_without_it() {
+        let message = String::from("feat: add a feature\n");
+        assert_eq!(
+            add_to_trailer(&config_with_time_tracking(), Some("45m"), message),
+            "feat: add a feature\n\nTime-spent: 45m\n"
+        );
+    }
+}
@@ -0,0 +1,1175 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! A full-screen form to answer all the questions of the wizard at once.
+//!
+//! Contrary to the sequential prompts, all the fields are shown on one screen
+//! with a live preview of the rendered message, so that revising an earlier
+//! answer is only a keystroke away.
+
+use std::io::{self, Stderr, Write as _};
+
+use colored::Colorize as _;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::Print,
+    terminal::{self, ClearType},
+};
+use eyre::Result;
+use indexmap::IndexMap;
+use regex::Regex;
+
+use crate::{
+    commit_cache::CommitCache,
+    config::{Config, Question, ScopeList, Scopes, Step, Ticket, Type},
+    helpers::{display_width, isolate_bidi, pop_grapheme},
+    lint::{
+        self, ticket_regex, Violation, MAX_DESCRIPTION_LEN, MIN_DESCRIPTION_LEN,
+    },
+    tracing::LogResult as _,
+};
+
+use super::{diff, ticket_placeholder, CommitMessage};
+
+/// The width of the column of field labels.
+const LABEL_WIDTH: usize = 17;
+
+/// The width of the marker in front of the focused field.
+const MARKER_WIDTH: usize = 2;
+
+/// The number of lines before the first field.
+const HEADER_HEIGHT: usize = 2;
+
+/// The width of the gauge of the subject length.
+const GAUGE_WIDTH: usize = 24;
+
+/// The help message displayed at the bottom of the screen.
+const HELP_MESSAGE: &str = "Tab/↑↓ to move, ←→ to choose, Enter for the next \
+    field or to commit from the last one, Ctrl-D to commit with the current \
+    answers, Ctrl-V to view the staged diff, ESC to abort";
+
+/// The form.
+#[derive(Debug)]
+pub struct Form<'a> {
+    /// The steps of the wizard, in the configured order.
+    steps: Vec<Step>,
+    /// The valid commit types.
+    types: &'a IndexMap<String, Type>,
+    /// The index of the selected type.
+    r#type: usize,
+    /// The scope field.
+    scope: ScopeField<'a>,
+    /// Whether a scope is required.
+    scope_required: bool,
+    /// The description.
+    description: String,
+    /// The maximum length of the subject line.
+    max_subject_length: usize,
+    /// The breaking change description.
+    breaking_change: String,
+    /// The ticket field, if tickets are configured.
+    ticket: Option<TicketField<'a>>,
+    /// The index of the focused field.
+    focus: usize,
+    /// The likely breaking changes detected in the staged changes.
+    likely_breaking_changes: Vec<String>,
+    /// The error to display after a failed submission.
+    error: Option<String>,
+}
+
+/// The scope field, depending on the accepted scopes.
+#[derive(Debug)]
+enum ScopeField<'a> {
+    /// Scopes are not used.
+    None,
+    /// Any scope is accepted.
+    Any(String),
+    /// Only scopes from a list are accepted.
+    List {
+        /// The list of accepted scopes.
+        list: &'a ScopeList,
+        /// The index of the selected scope, if any.
+        selected: Option<usize>,
+    },
+}
+
+/// The ticket field.
+#[derive(Debug)]
+struct TicketField<'a> {
+    /// The ticket configuration.
+    config: &'a Ticket,
+    /// The ticket reference.
+    value: String,
+}
+
+/// A field of the form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    /// The commit type.
+    Type,
+    /// The scope.
+    Scope,
+    /// The short description.
+    Description,
+    /// The breaking change description.
+    BreakingChange,
+    /// The ticket reference.
+    Ticket,
+}
+
+/// The result of handling a key.
+#[derive(Debug)]
+enum Action {
+    /// Keep on editing.
+    Continue,
+    /// The form has been submitted with valid answers.
+    Submit,
+    /// The user has aborted.
+    Cancel,
+    /// The user wants to view the staged diff.
+    ShowDiff,
+}
+
+/// The outcome of the form.
+#[derive(Debug)]
+pub enum Outcome {
+    /// The form has been submitted with valid answers.
+    Submitted(CommitMessage),
+    /// The user has aborted, leaving the current answers.
+    Canceled(CommitMessage),
+}
+
+/// The terminal, switched to the alternate screen in raw mode.
+///
+/// It is restored to its previous state when dropped.
+#[derive(Debug)]
+struct Terminal {
+    /// The output of the terminal.
+    ///
+    /// The standard output is kept free for `--print-only`.
+    output: Stderr,
+}
+
+/// Runs the form until the user submits or aborts it.
+///
+/// The preview is rendered from the current answers after each key.
+pub fn run(
+    mut form: Form<'_>,
+    preview: &dyn Fn(&CommitMessage) -> String,
+) -> Result<Outcome> {
+    let mut terminal = Terminal::enter().log_err()?;
+
+    loop {
+        terminal.draw(&form, &preview(&form.answers())).log_err()?;
+
+        let Event::Key(key) = event::read().log_err()? else {
+            continue;
+        };
+
+        if key.kind == KeyEventKind::Release {
+            continue;
+        }
+
+        match form.handle_key(key) {
+            Action::Continue => (),
+            Action::Submit => return Ok(Outcome::Submitted(form.answers())),
+            Action::Cancel => return Ok(Outcome::Canceled(form.answers())),
+            Action::ShowDiff => terminal.show_diff().log_err()?,
+        }
+    }
+}
+
+impl<'a> Form<'a> {
+    /// Builds the form, prefilled with the answers from the cache.
+    pub fn new(
+        config: &'a Config,
+        cache: &CommitCache,
+        likely_breaking_changes: Vec<String>,
+        detected_ticket: Option<String>,
+    ) -> Self {
+        let r#type = cache
+            .r#type()
+            .and_then(|r#type| config.types.get_index_of(r#type))
+            .unwrap_or_default();
+
+        let scope = match &config.scopes {
+            None => ScopeField::None,
+            Some(Scopes::Any { .. }) => {
+                ScopeField::Any(cache.scope().unwrap_or_default().to_owned())
+            }
+            Some(Scopes::List { list, .. }) => ScopeField::List {
+                list,
+                selected: cache.scope().and_then(|scope| list.position(scope)),
+            },
+        };
+
+        let ticket = config.ticket.as_ref().map(|ticket| TicketField {
+            config: ticket,
+            value: cache
+                .ticket()
+                .map(ToOwned::to_owned)
+                .or(detected_ticket)
+                .unwrap_or_default(),
+        });
+
+        Self {
+            steps: config.wizard_steps(),
+            types: &config.types,
+            r#type,
+            scope,
+            scope_required: config
+                .scopes
+                .as_ref()
+                .is_some_and(Scopes::is_required),
+            description: cache.description().unwrap_or_default().to_owned(),
+            max_subject_length: config.max_subject_length(),
+            breaking_change: cache
+                .breaking_change()
+                .unwrap_or_default()
+                .to_owned(),
+            ticket,
+            focus: 0,
+            likely_breaking_changes,
+            error: None,
+        }
+    }
+
+    /// Returns the current answers.
+    fn answers(&self) -> CommitMessage {
+        let scope = match &self.scope {
+            _ if self.skips(Question::Scope) => None,
+            ScopeField::None => None,
+            ScopeField::Any(scope) => non_empty(scope),
+            ScopeField::List { list, selected } => {
+                selected.and_then(|index| list.get(index)).cloned()
+            }
+        };
+
+        let breaking_change = if self.skips(Question::BreakingChange) {
+            None
+        } else {
+            non_empty(&self.breaking_change)
+        };
+
+        let ticket = if self.skips(Question::Ticket) {
+            None
+        } else {
+            self.ticket
+                .as_ref()
+                .and_then(|ticket| non_empty(&ticket.value))
+        };
+
+        CommitMessage {
+            r#type: self
+                .types
+                .get_index(self.r#type)
+                .map(|(r#type, _)| r#type.clone())
+                .unwrap_or_default(),
+            scope,
+            description: self.description.clone(),
+            breaking_change,
+            ticket,
+        }
+    }
+
+    /// Returns whether the selected type skips the given question.
+    fn skips(&self, question: Question) -> bool {
+        self.types
+            .get_index(self.r#type)
+            .is_some_and(|(_, r#type)| r#type.skips(question))
+    }
+
+    /// Handles a key press.
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        self.error = None;
+
+        match key.code {
+            KeyCode::Esc => return Action::Cancel,
+            KeyCode::Char('c') if control => return Action::Cancel,
+            KeyCode::Tab | KeyCode::Down => self.focus_next(),
+            KeyCode::BackTab | KeyCode::Up => self.focus_previous(),
+            KeyCode::Enter if self.focus + 1 < self.fields().len() => {
+                self.focus_next();
+            }
+            KeyCode::Enter => return self.submit(),
+            // NOTE: The remaining fields keep their current answers, which come
+            // from the cache or the detection. Invalid ones are still focused.
+            KeyCode::Char('d') if control => return self.submit(),
+            KeyCode::Char('v') if control => return Action::ShowDiff,
+            KeyCode::Right => self.select_next(),
+            KeyCode::Left => self.select_previous(),
+            KeyCode::Backspace => {
+                if let Some(text) = self.focused_text() {
+                    pop_grapheme(text);
+                }
+            }
+            KeyCode::Char('u') if control => {
+                if let Some(text) = self.focused_text() {
+                    text.clear();
+                }
+            }
+            KeyCode::Char(char) if !control => {
+                if let Some(text) = self.focused_text() {
+                    text.push(char);
+                }
+            }
+            _ => (),
+        }
+
+        Action::Continue
+    }
+
+    /// Submits the form if the answers are valid.
+    ///
+    /// Otherwise, the first invalid field is focused and the error displayed.
+    fn submit(&mut self) -> Action {
+        match self.validate() {
+            Ok(()) => Action::Submit,
+            Err((field, error)) => {
+                self.focus = self
+                    .fields()
+                    .iter()
+                    .position(|candidate| *candidate == field)
+                    .unwrap_or_default();
+                self.error = Some(error);
+                Action::Continue
+            }
+        }
+    }
+
+    /// Validates the answers, returning the first invalid field if any.
+    fn validate(&self) -> Result<(), (Field, String)> {
+        if self.scope_required
+            && !self.skips(Question::Scope)
+            && self.answers().scope.is_none()
+        {
+            return Err((Field::Scope, Violation::MissingScope.to_string()));
+        }
+
+        if let Some(violation) = lint::check_description(&self.description) {
+            return Err((Field::Description, violation.to_string()));
+        }
+
+        if let Some(ticket) = &self.ticket {
+            if !self.skips(Question::Ticket) {
+                ticket.validate().map_err(|error| (Field::Ticket, error))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the fields of the form.
+    ///
+    /// The fields follow the order of the wizard. The fields for the questions
+    /// skipped by the selected type are hidden.
+    fn fields(&self) -> Vec<Field> {
+        self.steps
+            .iter()
+            .filter_map(|step| match step {
+                Step::Type => Some(Field::Type),
+                Step::Scope => (!matches!(self.scope, ScopeField::None)
+                    && !self.skips(Question::Scope))
+                .then_some(Field::Scope),
+                Step::Description => Some(Field::Description),
+                Step::BreakingChange => (!self.skips(Question::BreakingChange))
+                    .then_some(Field::BreakingChange),
+                Step::Ticket => (self.ticket.is_some()
+                    && !self.skips(Question::Ticket))
+                .then_some(Field::Ticket),
+            })
+            .collect()
+    }
+
+    /// Returns the focused field.
+    fn focused(&self) -> Field {
+        self.fields()
+            .get(self.focus)
+            .copied()
+            .unwrap_or(Field::Type)
+    }
+
+    /// Focuses the next field, wrapping around.
+    fn focus_next(&mut self) {
+        self.focus = (self.focus + 1) % self.fields().len();
+    }
+
+    /// Focuses the previous field, wrapping around.
+    fn focus_previous(&mut self) {
+        let len = self.fields().len();
+        self.focus = (self.focus + len - 1) % len;
+    }
+
+    /// Selects the next choice of the focused field.
+    fn select_next(&mut self) {
+        match (self.focused(), &mut self.scope) {
+            (Field::Type, _) if !self.types.is_empty() => {
+                self.r#type = (self.r#type + 1) % self.types.len();
+            }
+            (Field::Scope, ScopeField::List { list, selected }) => {
+                *selected = match *selected {
+                    None if !list.is_empty() => Some(0),
+                    Some(index) if index + 1 < list.len() => Some(index + 1),
+                    _ => None,
+                };
+            }
+            _ => (),
+        }
+    }
+
+    /// Selects the previous choice of the focused field.
+    fn select_previous(&mut self) {
+        match (self.focused(), &mut self.scope) {
+            (Field::Type, _) if !self.types.is_empty() => {
+                let len = self.types.len();
+                self.r#type = (self.r#type + len - 1) % len;
+            }
+            (Field::Scope, ScopeField::List { list, selected }) => {
+                *selected = match *selected {
+                    None => list.len().checked_sub(1),
+                    Some(index) => index.checked_sub(1),
+                };
+            }
+            _ => (),
+        }
+    }
+
+    /// Returns the text of the focused field, if it is a text field.
+    fn focused_text(&mut self) -> Option<&mut String> {
+        match (self.focused(), &mut self.scope) {
+            (Field::Scope, ScopeField::Any(scope)) => Some(scope),
+            (Field::Description, _) => Some(&mut self.description),
+            (Field::BreakingChange, _) => Some(&mut self.breaking_change),
+            (Field::Ticket, _) => {
+                self.ticket.as_mut().map(|ticket| &mut ticket.value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the lines to display.
+    fn lines(&self, preview: &str) -> Vec<String> {
+        let mut lines =
+            vec![format!("{}", "git z commit".bold()), String::new()];
+
+        for (index, field) in self.fields().into_iter().enumerate() {
+            let marker = if index == self.focus { "›" } else { " " };
+            let label = format!("{:LABEL_WIDTH$}", field.label());
+            let label = if index == self.focus {
+                label.bold()
+            } else {
+                label.normal()
+            };
+
+            lines.push(format!("{marker} {label}{}", self.value(field)));
+        }
+
+        if self.focused() == Field::Description {
+            let label = format!("{:LABEL_WIDTH$}", "Subject length");
+            let length =
+                display_width(preview.lines().next().unwrap_or_default());
+            lines.push(format!(
+                "  {}{}",
+                label.dimmed(),
+                gauge(length, self.max_subject_length)
+            ));
+        }
+
+        lines.push(String::new());
+
+        if !self.likely_breaking_changes.is_empty() {
+            lines.push(String::from(
+                "The staged changes look like a breaking change:",
+            ));
+            lines.extend(
+                self.likely_breaking_changes
+                    .iter()
+                    .map(|reason| format!("  - {reason}")),
+            );
+            lines.push(String::new());
+        }
+
+        if let Some(error) = &self.error {
+            lines.push(format!("{}", error.red()));
+            lines.push(String::new());
+        }
+
+        lines.push(format!("{}", "Preview".bold().underline()));
+        lines.extend(preview.lines().map(isolate_bidi));
+        lines.push(String::new());
+        lines.push(format!("{}", HELP_MESSAGE.dimmed()));
+
+        lines
+    }
+
+    /// Returns the value to display for a field.
+    fn value(&self, field: Field) -> String {
+        match field {
+            Field::Type => self
+                .types
+                .get_index(self.r#type)
+                .map(|(name, r#type)| {
+                    format!("‹ {name} › {}", r#type.description().dimmed())
+                })
+                .unwrap_or_default(),
+            Field::Scope => match &self.scope {
+                ScopeField::None => String::new(),
+                ScopeField::Any(scope) => isolate_bidi(scope),
+                ScopeField::List { list, selected } => {
+                    let scope = selected
+                        .and_then(|index| list.get(index))
+                        .map_or("no scope", String::as_str);
+
+                    match list.description(scope) {
+                        Some(description) => {
+                            format!("‹ {scope} › {}", description.dimmed())
+                        }
+                        None => format!("‹ {scope} ›"),
+                    }
+                }
+            },
+            Field::Description => {
+                let len = display_width(&self.description);
+                let count = format!("({len}/{MAX_DESCRIPTION_LEN})");
+                let count = if (MIN_DESCRIPTION_LEN..=MAX_DESCRIPTION_LEN)
+                    .contains(&len)
+                {
+                    count.dimmed()
+                } else {
+                    count.red()
+                };
+
+                format!("{}  {count}", isolate_bidi(&self.description))
+            }
+            Field::BreakingChange => isolate_bidi(&self.breaking_change),
+            Field::Ticket => self
+                .ticket
+                .as_ref()
+                .map(|ticket| ticket.value.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the position of the cursor, if the focused field is a text.
+    fn cursor(&self) -> Option<(usize, usize)> {
+        let text = match (self.focused(), &self.scope) {
+            (Field::Scope, ScopeField::Any(scope)) => scope,
+            (Field::Description, _) => &self.description,
+            (Field::BreakingChange, _) => &self.breaking_change,
+            (Field::Ticket, _) => &self.ticket.as_ref()?.value,
+            _ => return None,
+        };
+
+        let column = MARKER_WIDTH + LABEL_WIDTH + display_width(text);
+        Some((column, HEADER_HEIGHT + self.focus))
+    }
+}
+
+impl Field {
+    /// Returns the label of the field.
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Type => "Type",
+            Self::Scope => "Scope",
+            Self::Description => "Description",
+            Self::BreakingChange => "Breaking change",
+            Self::Ticket => "Ticket",
+        }
+    }
+}
+
+impl TicketField<'_> {
+    /// Validates the ticket reference.
+    fn validate(&self) -> Result<(), String> {
+        if self.value.is_empty() {
+            return if self.config.required {
+                Err(String::from("The issue / ticket number is required"))
+            } else {
+                Ok(())
+            };
+        }
+
+        let regex = format!("^{}$", ticket_regex(&self.config.prefixes));
+
+        if Regex::new(&regex).is_ok_and(|regex| regex.is_match(&self.value)) {
+            Ok(())
+        } else {
+            let placeholder =
+                ticket_placeholder(&self.config.prefixes).unwrap_or_default();
+
+            Err(format!(
+                "The issue / ticket number must be in the form {placeholder}"
+            ))
+        }
+    }
+}
+
+impl Terminal {
+    /// Switches the terminal to the alternate screen in raw mode.
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+
+        let mut output = io::stderr();
+        execute!(output, terminal::EnterAlternateScreen)?;
+
+        Ok(Self { output })
+    }
+
+    /// Shows the staged diff out of the alternate screen.
+    ///
+    /// The pager may quit by itself when the diff fits on the screen, so the
+    /// form is only shown again after a key press.
+    fn show_diff(&mut self) -> Result<()> {
+        execute!(self.output, cursor::Show, terminal::LeaveAlternateScreen)?;
+        diff::show_staged()?;
+
+        execute!(
+            self.output,
+            Print("Press any key to go back to the form.\r\n")
+        )?;
+
+        while !matches!(
+            event::read()?,
+            Event::Key(KeyEvent {
+                kind: KeyEventKind::Press,
+                ..
+            })
+        ) {}
+
+        execute!(self.output, terminal::EnterAlternateScreen)?;
+        Ok(())
+    }
+
+    /// Draws the form and its preview.
+    fn draw(&mut self, form: &Form<'_>, preview: &str) -> io::Result<()> {
+        queue!(
+            self.output,
+            terminal::Clear(ClearType::All),
+            cursor::MoveTo(0, 0)
+        )?;
+
+        for line in form.lines(preview) {
+            queue!(self.output, Print(line), Print("\r\n"))?;
+        }
+
+        match form.cursor() {
+            Some((column, row)) => queue!(
+                self.output,
+                cursor::MoveTo(to_u16(column), to_u16(row)),
+                cursor::Show
+            )?,
+            None => queue!(self.output, cursor::Hide)?,
+        }
+
+        self.output.flush()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let restored =
+            execute!(self.output, cursor::Show, terminal::LeaveAlternateScreen)
+                .and_then(|()| terminal::disable_raw_mode());
+
+        if let Err(error) = restored {
+            tracing::error!(?error, "failed to restore the terminal");
+        }
+    }
+}
+
+/// Returns a gauge of the length of the subject line.
+///
+/// The gauge is green within the limit, and red beyond.
+pub fn gauge(length: usize, max: usize) -> String {
+    let filled = (length.min(max) * GAUGE_WIDTH)
+        .checked_div(max)
+        .unwrap_or(GAUGE_WIDTH);
+    let bar =
+        format!("{}{}", "█".repeat(filled), "░".repeat(GAUGE_WIDTH - filled));
+    let count = format!("{length}/{max}");
+
+    if length > max {
+        format!("{}  {}", bar.red(), count.red())
+    } else {
+        format!("{}  {}", bar.green(), count.dimmed())
+    }
+}
+
+/// Converts an empty string to `None`.
+fn non_empty(text: &str) -> Option<String> {
+    (!text.is_empty()).then(|| text.to_owned())
+}
+
+/// Converts a terminal coordinate, saturating on overflow.
+fn to_u16(value: usize) -> u16 {
+    u16::try_from(value).unwrap_or(u16::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use indexmap::indexmap;
+    use indoc::indoc;
+
+    use super::*;
+    use crate::{
+        config::{DetailedType, ScopeOrder, TicketPlacement},
+        lint::MAX_SUBJECT_LEN,
+    };
+
+    fn press(form: &mut Form<'_>, code: KeyCode) -> Action {
+        form.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn type_text(form: &mut Form<'_>, text: &str) {
+        for char in text.chars() {
+            press(form, KeyCode::Char(char));
+        }
+    }
+
+    fn config_with_scope_list() -> Config {
+        Config {
+            scopes: Some(Scopes::List {
+                list: ScopeList::Names(vec![
+                    String::from("cli"),
+                    String::from("config"),
+                ]),
+                order: ScopeOrder::default(),
+                required: false,
+                from: None,
+                command: None,
+            }),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn prefills_the_answers_from_the_cache() {
+        let config = config_with_scope_list();
+        let mut cache = CommitCache::default();
+        cache.wizard_answers.r#type = Some(String::from("fix"));
+        cache.wizard_answers.scope = Some(String::from("config"));
+        cache.wizard_answers.description = Some(String::from("fix a bug"));
+
+        let form = Form::new(&config, &cache, vec![], None);
+        let answers = form.answers();
+
+        assert_eq!(answers.r#type, "fix");
+        assert_eq!(answers.scope.as_deref(), Some("config"));
+        assert_eq!(answers.description, "fix a bug");
+        assert_eq!(answers.breaking_change, None);
+    }
+
+    #[test]
+    fn cycles_through_the_types_and_scopes() {
+        let config = config_with_scope_list();
+        let mut form =
+            Form::new(&config, &CommitCache::default(), vec![], None);
+
+        press(&mut form, KeyCode::Right);
+        press(&mut form, KeyCode::Right);
+        press(&mut form, KeyCode::Left);
+        press(&mut form, KeyCode::Tab);
+        press(&mut form, KeyCode::Left);
+
+        let answers = form.answers();
+        assert_eq!(answers.r#type, "sec");
+        assert_eq!(answers.scope.as_deref(), Some("config"));
+
+        press(&mut form, KeyCode::Right);
+        assert_eq!(form.answers().scope, None);
+    }
+
+    #[test]
+    fn shows_the_description_of_the_selected_scope() {
+        colored::control::set_override(false);
+        let config = Config {
+            scopes: Some(Scopes::List {
+                list: ScopeList::Described(indexmap! {
+                    String::from("cli") => String::from("the command line"),
+                }),
+                order: ScopeOrder::default(),
+                required: false,
+                from: None,
+                command: None,
+            }),
+            ..Config::default()
+        };
+        let mut form =
+            Form::new(&config, &CommitCache::default(), vec![], None);
+
+        press(&mut form, KeyCode::Tab);
+        press(&mut form, KeyCode::Right);
+
+        assert_eq!(form.answers().scope.as_deref(), Some("cli"));
+        assert_eq!(form.value(Field::Scope), "‹ cli › the command line");
+    }
+
+    #[test]
+    fn edits_the_focused_text_field() {
+        let config = Config::default();
+        let mut form =
+            Form::new(&config, &CommitCache::default(), vec![], None);
+
+        press(&mut form, KeyCode::Down);
+        type_text(&mut form, "cli");
+        press(&mut form, KeyCode::Enter);
+        type_text(&mut form, "add a featurex");
+        press(&mut form, KeyCode::Backspace);
+
+        let answers = form.answers();
+        assert_eq!(answers.scope.as_deref(), Some("cli"));
+        assert_eq!(answers.description, "add a feature");
+        assert_eq!(form.cursor(), Some((MARKER_WIDTH + LABEL_WIDTH + 13, 4)));
+    }
+
+    #[test]
+    fn places_the_cursor_after_wide_characters() {
+        let config = Config::default();
+        let mut form =
+            Form::new(&config, &CommitCache::default(), vec![], None);
+
+        press(&mut form, KeyCode::Down);
+        press(&mut form, KeyCode::Enter);
+        type_text(&mut form, "修正する");
+
+        assert_eq!(form.cursor(), Some((MARKER_WIDTH + LABEL_WIDTH + 8, 4)));
+    }
+
+    #[test]
+    fn erases_whole_graphemes_on_backspace() {
+        let config = Config::default();
+        let mut form =
+            Form::new(&config, &CommitCache::default(), vec![], None);
+
+        press(&mut form, KeyCode::Down);
+        press(&mut form, KeyCode::Enter);
+        type_text(&mut form, "corrige l’e\u{301}te\u{301}");
+        press(&mut form, KeyCode::Backspace);
+
+        assert_eq!(form.answers().description, "corrige l’e\u{301}t");
+    }
+
+    #[test]
+    fn isolates_right_to_left_text_from_the_counter() {
+        let config = Config::default();
+        let mut form =
+            Form::new(&config, &CommitCache::default(), vec![], None);
+
+        press(&mut form, KeyCode::Down);
+        press(&mut form, KeyCode::Enter);
+        type_text(&mut form, "תיקון באג");
+
+        assert!(form
+            .value(Field::Description)
+            .starts_with("\u{2068}תיקון באג\u{2069}  "));
+    }
+
+    #[test]
+    fn shows_the_subject_length_while_typing_the_description() {
+        colored::control::set_override(false);
+        let config = config_with_scope_list();
+        let mut form =
+            Form::new(&config, &CommitCache::default(), vec![], None);
+
+        press(&mut form, KeyCode::Tab);
+        press(&mut form, KeyCode::Right);
+        assert!(!form.lines("").iter().any(|line| line.contains("Subject")));
+
+        press(&mut form, KeyCode::Tab);
+        type_text(&mut form, "add a flag");
+
+        let preview = "[#12] feat(cli): add a flag\n\nA longer body line.\n";
+        let gauge_line = format!(
+            "  {:LABEL_WIDTH$}{}",
+            "Subject length",
+            gauge(27, MAX_SUBJECT_LEN)
+        );
+        assert!(form.lines(preview).contains(&gauge_line));
+    }
+
+    #[test]
+    fn uses_the_configured_subject_length() {
+        let config = Config {
+            commit: Some(toml::from_str("max_subject_length = 20").unwrap()),
+            ..Config::default()
+        };
+        let form = Form::new(&config, &CommitCache::default(), vec![], None);
+
+        assert_eq!(form.max_subject_length, 20);
+    }
+
+    #[test]
+    fn fills_the_gauge_up_to_the_limit() {
+        colored::control::set_override(false);
+
+        assert_eq!(gauge(0, 72), format!("{}  0/72", "░".repeat(GAUGE_WIDTH)));
+        assert_eq!(
+            gauge(36, 72),
+            format!("{}{}  36/72", "█".repeat(12), "░".repeat(12))
+        );
+        assert_eq!(
+            gauge(80, 72),
+            format!("{}  80/72", "█".repeat(GAUGE_WIDTH))
+        );
+    }
+
+    #[test]
+    fn moves_to_the_next_field_on_enter() {
+        let config = Config::default();
+        let mut form =
+            Form::new(&config, &CommitCache::default(), vec![], None);
+
+        assert!(matches!(press(&mut form, KeyCode::Enter), Action::Continue));
+        assert_eq!(form.focused(), Field::Scope);
+    }
+
+    #[test]
+    fn focuses_the_invalid_field_on_submit() {
+        let config = Config::default();
+        let mut form =
+            Form::new(&config, &CommitCache::default(), vec![], None);
+
+        press(&mut form, KeyCode::BackTab);
+        assert_eq!(form.focused(), Field::BreakingChange);
+
+        assert!(matches!(press(&mut form, KeyCode::Enter), Action::Continue));
+        assert_eq!(form.focused(), Field::Description);
+        assert_eq!(
+            form.error.as_deref(),
+            Some("The description must be longer than 5 characters")
+        );
+    }
+
+    #[test]
+    fn submits_valid_answers() {
+        let config = Config::default();
+        let mut form =
+            Form::new(&config, &CommitCache::default(), vec![], None);
+
+        press(&mut form, KeyCode::Tab);
+        press(&mut form, KeyCode::Tab);
+        type_text(&mut form, "add a feature");
+        press(&mut form, KeyCode::Tab);
+
+        assert!(matches!(press(&mut form, KeyCode::Enter), Action::Submit));
+    }
+
+    #[test]
+    fn validates_the_ticket() {
+        let config = Config {
+            ticket: Some(Ticket {
+                required: true,
+                prefixes: vec![String::from("#")],
+                branch_patterns: None,
+                title_command: None,
+                status_command: None,
+                closed_states: None,
+                placement: TicketPlacement::default(),
+                trailer: None,
+            }),
+            ..Config::default()
+        };
+        let mut cache = CommitCache::default();
+        cache.wizard_answers.description = Some(String::from("add a feature"));
+        let mut form =
+            Form::new(&config, &cache, vec![], Some(String::from("GH-1")));
+
+        press(&mut form, KeyCode::BackTab);
+        assert_eq!(form.focused(), Field::Ticket);
+        assert!(matches!(press(&mut form, KeyCode::Enter), Action::Continue));
+        assert_eq!(
+            form.error.as_deref(),
+            Some("The issue / ticket number must be in the form #XXX")
+        );
+
+        form.handle_key(KeyEvent::new(
+            KeyCode::Char('u'),
+            KeyModifiers::CONTROL,
+        ));
+        assert!(matches!(press(&mut form, KeyCode::Enter), Action::Continue));
+        assert_eq!(
+            form.error.as_deref(),
+            Some("The issue / ticket number is required")
+        );
+
+        type_text(&mut form, "#12");
+        assert!(matches!(press(&mut form, KeyCode::Enter), Action::Submit));
+        assert_eq!(form.answers().ticket.as_deref(), Some("#12"));
+    }
+
+    #[test]
+    fn requires_a_scope_when_configured() {
+        let config = Config {
+            scopes: Some(Scopes::Any { required: true }),
+            ..Config::default()
+        };
+        let mut cache = CommitCache::default();
+        cache.wizard_answers.description = Some(String::from("add a feature"));
+        let mut form = Form::new(&config, &cache, vec![], None);
+
+        press(&mut form, KeyCode::BackTab);
+        assert!(matches!(press(&mut form, KeyCode::Enter), Action::Continue));
+        assert_eq!(form.focused(), Field::Scope);
+        assert_eq!(form.error.as_deref(), Some("A scope is required"));
+
+        type_text(&mut form, "cli");
+        press(&mut form, KeyCode::BackTab);
+        press(&mut form, KeyCode::BackTab);
+        assert!(matches!(press(&mut form, KeyCode::Enter), Action::Submit));
+    }
+
+    #[test]
+    fn hides_the_fields_skipped_by_the_type() {
+        let mut config = Config {
+            ticket: Some(Ticket {
+                required: true,
+                prefixes: vec![String::from("#")],
+                branch_patterns: None,
+                title_command: None,
+                status_command: None,
+                closed_states: None,
+                placement: TicketPlacement::default(),
+                trailer: None,
+            }),
+            ..Config::default()
+        };
+        config.types.insert(
+            String::from("wip"),
+            Type::Detailed(DetailedType {
+                description: String::from("work in progress"),
+                skip: vec![Question::Scope, Question::Ticket],
+                template: None,
+            }),
+        );
+        let mut cache = CommitCache::default();
+        cache.wizard_answers.r#type = Some(String::from("wip"));
+        cache.wizard_answers.scope = Some(String::from("cli"));
+        cache.wizard_answers.description = Some(String::from("try something"));
+        let mut form = Form::new(&config, &cache, vec![], None);
+
+        assert_eq!(
+            form.fields(),
+            vec![Field::Type, Field::Description, Field::BreakingChange]
+        );
+        assert_eq!(form.answers().scope, None);
+
+        press(&mut form, KeyCode::BackTab);
+        assert!(matches!(press(&mut form, KeyCode::Enter), Action::Submit));
+    }
+
+    #[test]
+    fn follows_the_order_of_the_wizard() {
+        let config = Config::from_toml(indoc! {r##"
+            version = "0.3-dev.0"
+
+            [types]
+            feat = "add a feature"
+
+            [scopes]
+            accept = "any"
+
+            [ticket]
+            required = false
+            prefixes = ["#"]
+
+            [wizard]
+            order = ["ticket", "breaking_change"]
+
+            [templates]
+            commit = "{{ type }}: {{ description }}"
+        "##})
+        .unwrap();
+        let form = Form::new(&config, &CommitCache::default(), vec![], None);
+
+        assert_eq!(
+            form.fields(),
+            vec![
+                Field::Ticket,
+                Field::BreakingChange,
+                Field::Type,
+                Field::Scope,
+                Field::Description,
+            ]
+        );
+        assert_eq!(form.focused(), Field::Ticket);
+    }
+
+    #[test]
+    fn submits_the_current_answers_on_ctrl_d() {
+        let config = Config::default();
+        let mut cache = CommitCache::default();
+        cache.wizard_answers.description = Some(String::from("fix a bug"));
+        let mut form = Form::new(&config, &cache, vec![], None);
+
+        let action = form.handle_key(KeyEvent::new(
+            KeyCode::Char('d'),
+            KeyModifiers::CONTROL,
+        ));
+
+        assert!(matches!(action, Action::Submit));
+        assert_eq!(form.answers().description, "fix a bug");
+    }
+
+    #[test]
+    fn shows_the_diff_on_ctrl_v_without_editing() {
+        let config = Config::default();
+        let mut cache = CommitCache::default();
+        cache.wizard_answers.description = Some(String::from("fix a bug"));
+        let mut form = Form::new(&config, &cache, vec![], None);
+        form.focus = form
+            .fields()
+            .iter()
+            .position(|field| *field == Field::Description)
+            .unwrap();
+
+        let action = form.handle_key(KeyEvent::new(
+            KeyCode::Char('v'),
+            KeyModifiers::CONTROL,
+        ));
+
+        assert!(matches!(action, Action::ShowDiff));
+        assert_eq!(form.answers().description, "fix a bug");
+    }
+
+    #[test]
+    fn keeps_the_validation_on_ctrl_d() {
+        let config = Config::default();
+        let mut form =
+            Form::new(&config, &CommitCache::default(), vec![], None);
+
+        let action = form.handle_key(KeyEvent::new(
+            KeyCode::Char('d'),
+            KeyModifiers::CONTROL,
+        ));
+
+        assert!(matches!(action, Action::Continue));
+        assert_eq!(form.focused(), Field::Description);
+        assert!(form.error.is_some());
+    }
+
+    #[test]
+    fn cancels_on_escape() {
+        let config = Config::default();
+        let mut form =
+            Form::new(&config, &CommitCache::default(), vec![], None);
+
+        assert!(matches!(press(&mut form, KeyCode::Esc), Action::Cancel));
+    }
+}
@@ -0,0 +1,251 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `config` subcommand.
+
+use clap::{Parser, Subcommand};
+use eyre::Result;
+use indexmap::IndexMap;
+
+use crate::{
+    config::{Config, Scopes},
+    hint, history, subject_template, success, warning,
+};
+
+use super::{
+    commit::build_and_check_template,
+    helpers::{complete_lists, ensure_in_git_repo, load_config},
+    revspec::{RevSpec, Revision},
+};
+
+/// The config command.
+#[derive(Debug, Parser)]
+pub struct ConfigCommand {
+    /// The action to run on the configuration.
+    #[command(subcommand)]
+    action: Action,
+}
+
+/// The actions on the configuration.
+#[derive(Debug, Subcommand)]
+enum Action {
+    /// Checks the configuration, and its scopes against the history.
+    Validate(Validate),
+}
+
+/// The arguments of `git z config validate`.
+#[derive(Debug, Parser)]
+struct Validate {
+    /// The commits to compare the scopes with.
+    #[command(flatten)]
+    revspec: RevSpec,
+    /// The number of uses from which an unconfigured scope is reported.
+    #[arg(long, value_name = "COUNT", default_value_t = 3)]
+    min_uses: usize,
+}
+
+/// The usage of the scopes in the history.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ScopeReport {
+    /// The configured scopes never used.
+    unused: Vec<String>,
+    /// The scopes used often but not configured, with their number of uses.
+    unconfigured: Vec<(String, usize)>,
+}
+
+impl super::Command for ConfigCommand {
+    #[tracing::instrument(name = "config", level = "trace", skip_all)]
+    fn run(&self) -> Result<()> {
+        tracing::info!(params = ?self, "running config");
+
+        match &self.action {
+            Action::Validate(validate) => validate.run(),
+        }
+    }
+}
+
+impl Validate {
+    /// Validates the configuration, then reports on the usage of its scopes.
+    ///
+    /// Besides loading the configuration, which compiles its regexes, its
+    /// templates are compiled and its lists are completed, so that any error is
+    /// caught here instead of in the middle of a commit.
+    fn run(&self) -> Result<()> {
+        ensure_in_git_repo()?;
+
+        let mut config = load_config()?;
+        build_and_check_template(&config)?;
+        complete_lists(&mut config)?;
+        success!("The configuration is valid.");
+
+        let Some(configured) = configured_scopes(&config) else {
+            tracing::debug!("any scope is accepted, skipping the statistics");
+            return Ok(());
+        };
+
+        let commits = self.revspec.commits(&[])?;
+        let report = scope_report(
+            &configured,
+            &count_scopes(&config, &commits),
+            self.min_uses,
+        );
+        tracing::debug!(?report);
+
+        print_report(&report, commits.len());
+        Ok(())
+    }
+}
+
+/// Returns the list of configured scopes, if any.
+fn configured_scopes(config: &Config) -> Option<Vec<String>> {
+    match config.scopes.as_ref()? {
+        Scopes::Any { .. } => None,
+        Scopes::List { list, .. } => Some(list.names()),
+    }
+}
+
+/// Counts the uses of each scope in conventional commits, from the most used.
+fn count_scopes(
+    config: &Config,
+    commits: &[Revision],
+) -> IndexMap<String, usize> {
+    let mut counts = history::count(commits.iter().filter_map(|commit| {
+        subject_template::parse(config, &commit.message).ok()?.scope
+    }));
+
+    counts.sort_by(|scope_a, count_a, scope_b, count_b| {
+        count_b.cmp(count_a).then_with(|| scope_a.cmp(scope_b))
+    });
+
+    counts
+}
+
+/// Compares the configured scopes with the ones used in the history.
+fn scope_report(
+    configured: &[String],
+    used: &IndexMap<String, usize>,
+    min_uses: usize,
+) -> ScopeReport {
+    let unused = configured
+        .iter()
+        .filter(|scope| !used.contains_key(*scope))
+        .cloned()
+        .collect();
+
+    let unconfigured = used
+        .iter()
+        .filter(|(scope, &count)| {
+            count >= min_uses && !configured.contains(scope)
+        })
+        .map(|(scope, &count)| (scope.clone(), count))
+        .collect();
+
+    ScopeReport {
+        unused,
+        unconfigured,
+    }
+}
+
+/// Prints the report on the usage of the scopes.
+fn print_report(report: &ScopeReport, commit_count: usize) {
+    if report.unused.is_empty() && report.unconfigured.is_empty() {
+        success!("The scopes match the ones used in {commit_count} commit(s).");
+        return;
+    }
+
+    if !report.unused.is_empty() {
+        warning!(
+            "Some configured scopes are not used in {commit_count} commit(s):"
+        );
+        for scope in &report.unused {
+            println!("  - {scope}");
+        }
+    }
+
+    if !report.unconfigured.is_empty() {
+        warning!("Some scopes are used in the history but not configured:");
+        for (scope, count) in &report.unconfigured {
+            println!("  - {scope} ({count} commit(s))");
+        }
+    }
+
+    hint!("You can update the list of scopes in `git-z.toml`.");
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    fn commit(message: &str) -> Revision {
+        Revision {
+            hash: String::from("0000000"),
+            message: String::from(message),
+            is_merge: false,
+            author: String::new(),
+            author_email: String::new(),
+            date: String::new(),
+        }
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|&name| name.to_owned()).collect()
+    }
+
+    #[test]
+    fn counts_the_scopes_from_the_most_used() {
+        let commits = [
+            commit("feat(api): add a route"),
+            commit("fix(cli): fix a flag"),
+            commit("fix(api): fix a route"),
+            commit("docs: update the README"),
+            commit("Not conventional"),
+        ];
+
+        let counts = count_scopes(&Config::default(), &commits);
+
+        assert_eq!(
+            counts.into_iter().collect::<Vec<_>>(),
+            vec![(String::from("api"), 2), (String::from("cli"), 1)]
+        );
+    }
+
+    #[test]
+    fn reports_unused_and_unconfigured_scopes() {
+        let used = IndexMap::from([
+            (String::from("cli"), 5),
+            (String::from("api"), 4),
+            (String::from("ui"), 1),
+        ]);
+
+        let report = scope_report(&names(&["api", "docs"]), &used, 3);
+
+        assert_eq!(
+            report,
+            ScopeReport {
+                unused: names(&["docs"]),
+                unconfigured: vec![(String::from("cli"), 5)],
+            }
+        );
+    }
+
+    #[test]
+    fn reports_nothing_when_the_scopes_match() {
+        let used = IndexMap::from([(String::from("api"), 4)]);
+        let report = scope_report(&names(&["api"]), &used, 3);
+        assert_eq!(report, ScopeReport::default());
+    }
+}
@@ -0,0 +1,215 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `debug` subcommand.
+//!
+//! It gathers helpers to diagnose the environment of users. They are not meant
+//! for everyday use, so the subcommand is hidden from the help.
+
+use std::{
+    fmt::Display,
+    fs,
+    io::{self, Read as _},
+    path::PathBuf,
+};
+
+use clap::{Parser, Subcommand};
+use eyre::Result;
+use thiserror::Error;
+
+use crate::{
+    commit_cache::{self, CommitCache, WizardAnswers},
+    config,
+    conventional_commit::{ConventionalCommit, ParseError},
+    git,
+    tracing::LogResult as _,
+};
+
+use super::{
+    commit::{
+        self, context::CommitContext, render_message, unedited_message,
+        CommitMessage, WizardUi,
+    },
+    helpers::{complete_lists, ensure_in_git_worktree, load_config},
+};
+
+/// The debug command.
+#[derive(Debug, Parser)]
+pub struct Debug {
+    /// The helper to run.
+    #[command(subcommand)]
+    helper: Helper,
+}
+
+/// The debug helpers.
+#[derive(Debug, Subcommand)]
+enum Helper {
+    /// Renders the commit template from the given answers.
+    RenderTemplate(RenderTemplate),
+    /// Parses a commit message and prints its parts.
+    ParseMessage(ParseMessage),
+    /// Shows the paths used by git-z.
+    ShowPaths,
+    /// Runs the wizard and prints the message, without committing.
+    FakeCommit(FakeCommit),
+}
+
+/// The arguments of `git z debug render-template`.
+#[derive(Debug, Parser)]
+struct RenderTemplate {
+    /// The type of commit [default: dummy].
+    #[arg(long = "type")]
+    r#type: Option<String>,
+    /// The scope of the commit.
+    #[arg(long)]
+    scope: Option<String>,
+    /// The short description [default: dummy commit].
+    #[arg(long)]
+    description: Option<String>,
+    /// The description of the breaking change.
+    #[arg(long)]
+    breaking_change: Option<String>,
+    /// The linked ticket.
+    #[arg(long)]
+    ticket: Option<String>,
+}
+
+/// The arguments of `git z debug parse-message`.
+#[derive(Debug, Parser)]
+struct ParseMessage {
+    /// The file containing the message [default: the standard input].
+    file: Option<PathBuf>,
+}
+
+/// The arguments of `git z debug fake-commit`.
+#[derive(Debug, Parser)]
+struct FakeCommit {
+    /// Use plain prompts, for screen readers and dumb terminals.
+    #[arg(long)]
+    plain: bool,
+}
+
+/// Usage errors of `git z debug`.
+#[derive(Debug, Error)]
+pub enum DebugError {
+    /// The commit message cannot be read.
+    #[error("Failed to read the commit message")]
+    ReadMessage(#[source] io::Error),
+    /// The commit message is not a valid conventional commit.
+    #[error("Invalid commit message")]
+    InvalidMessage(#[from] ParseError),
+}
+
+impl super::Command for Debug {
+    #[tracing::instrument(name = "debug", level = "trace", skip_all)]
+    fn run(&self) -> Result<()> {
+        tracing::info!(params = ?self, "running debug");
+
+        match &self.helper {
+            Helper::RenderTemplate(args) => render_template(args),
+            Helper::ParseMessage(args) => parse_message(args),
+            Helper::ShowPaths => {
+                show_paths();
+                Ok(())
+            }
+            Helper::FakeCommit(args) => fake_commit(args),
+        }
+    }
+}
+
+/// Renders the commit template from the answers given on the command line.
+fn render_template(args: &RenderTemplate) -> Result<()> {
+    ensure_in_git_worktree()?;
+    let config = load_config()?;
+
+    let answers = CommitMessage::from_answers(WizardAnswers {
+        r#type: args.r#type.clone(),
+        scope: args.scope.clone(),
+        description: args.description.clone(),
+        breaking_change: args.breaking_change.clone(),
+        ticket: args.ticket.clone(),
+    });
+
+    tracing::debug!(?answers);
+    print!("{}", render_message(&config, &answers)?);
+    Ok(())
+}
+
+/// Parses a commit message and prints its parts.
+fn parse_message(args: &ParseMessage) -> Result<()> {
+    let message = if let Some(file) = &args.file {
+        fs::read_to_string(file)
+    } else {
+        let mut message = String::new();
+        io::stdin().read_to_string(&mut message).map(|_| message)
+    }
+    .map_err(DebugError::ReadMessage)
+    .log_err()?;
+
+    let commit = ConventionalCommit::parse(&message)
+        .map_err(DebugError::InvalidMessage)
+        .log_err()?;
+
+    println!("{commit:#?}");
+    Ok(())
+}
+
+/// Shows the paths used by git-z.
+///
+/// The paths that cannot be resolved are shown with the reason instead, so
+/// that this works anywhere.
+fn show_paths() {
+    println!("git dir: {}", display_path(git::dir()));
+    println!("git-z dir: {}", display_path(commit_cache::gitz_dir()));
+    println!("config file: {}", display_path(config::config_file()));
+    println!(
+        "commit cache: {}",
+        display_path(commit_cache::commit_cache_file())
+    );
+}
+
+/// Runs the wizard and prints the message, without committing.
+///
+/// The answers are not saved in the commit cache and `COMMIT_EDITMSG` is left
+/// untouched, so this does not interfere with an aborted `git z commit`.
+fn fake_commit(args: &FakeCommit) -> Result<()> {
+    ensure_in_git_worktree()?;
+    let _lock = CommitCache::lock()?;
+    let mut config = load_config()?;
+    complete_lists(&mut config)?;
+
+    let ui = if args.plain {
+        WizardUi::Plain
+    } else {
+        WizardUi::Prompts
+    };
+
+    let message = commit::run_wizard(
+        &config,
+        &mut CommitCache::default(),
+        &mut CommitContext::ephemeral(),
+        ui,
+    )?;
+    println!("{}", unedited_message(&config, &message));
+    Ok(())
+}
+
+/// Displays a path, or the reason why it cannot be resolved.
+fn display_path<E: Display>(path: Result<PathBuf, E>) -> String {
+    match path {
+        Ok(path) => path.display().to_string(),
+        Err(error) => format!("<{error}>"),
+    }
+}
@@ -0,0 +1,213 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `export` subcommand.
+
+use std::io::{self, Write};
+
+use clap::{Parser, ValueEnum};
+use eyre::Result;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    command::helpers::load_config, config::Config, lint, subject_template,
+    tracing::LogResult as _,
+};
+
+use super::{
+    helpers::ensure_in_git_repo,
+    revspec::{RevSpec, Revision},
+};
+
+/// The export command.
+#[derive(Debug, Parser)]
+pub struct Export {
+    /// The range of commits to export.
+    #[command(flatten)]
+    revspec: RevSpec,
+    /// The output format.
+    #[arg(long, value_name = "FORMAT", default_value = "json")]
+    format: Format,
+}
+
+/// An output format.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    /// A JSON array of records.
+    Json,
+    /// A CSV table with a header, tickets being separated by spaces.
+    Csv,
+}
+
+/// The metadata of a conventional commit.
+#[derive(Debug, Serialize)]
+struct Record<'a> {
+    /// The hash of the commit.
+    hash: &'a str,
+    /// The type of commit.
+    r#type: String,
+    /// The scope of the commit, if any.
+    scope: Option<String>,
+    /// The short description.
+    description: String,
+    /// The tickets referenced in the message.
+    tickets: Vec<String>,
+    /// Whether the commit is a breaking change.
+    breaking: bool,
+    /// The name of the author.
+    author: &'a str,
+    /// The email of the author.
+    author_email: &'a str,
+    /// The date of the commit.
+    date: &'a str,
+}
+
+/// The columns of the CSV format.
+const CSV_HEADER: &str =
+    "hash,type,scope,description,tickets,breaking,author,author_email,date";
+
+/// Errors that can occur when exporting the commits.
+#[derive(Debug, Error)]
+pub enum ExportError {
+    /// The export cannot be written.
+    #[error("Failed to write the export")]
+    CannotWrite(#[source] io::Error),
+}
+
+impl super::Command for Export {
+    #[tracing::instrument(name = "export", level = "trace", skip_all)]
+    fn run(&self) -> Result<()> {
+        tracing::info!(params = ?self, "running export");
+
+        ensure_in_git_repo()?;
+
+        let config = load_config()?;
+        let commits = self.revspec.commits(&[])?;
+
+        let records = commits
+            .iter()
+            .filter_map(|commit| record(&config, commit))
+            .collect::<Vec<_>>();
+
+        tracing::debug!(
+            count = records.len(),
+            skipped = commits.len() - records.len(),
+            "exporting the conventional commits"
+        );
+
+        let mut stdout = io::stdout().lock();
+
+        match self.format {
+            Format::Json => write_json(&mut stdout, &records),
+            Format::Csv => write_csv(&mut stdout, &records),
+        }
+        .map_err(ExportError::CannotWrite)
+        .log_err()?;
+
+        Ok(())
+    }
+}
+
+/// Builds the record of a commit, if it is a conventional commit.
+fn record<'a>(config: &Config, commit: &'a Revision) -> Option<Record<'a>> {
+    let Ok(parsed) = subject_template::parse(config, &commit.message) else {
+        tracing::debug!(hash = ?commit.hash, "skipping a non-conventional commit");
+        return None;
+    };
+
+    let prefixes = config
+        .ticket
+        .as_ref()
+        .map(|ticket| ticket.prefixes.as_slice())
+        .unwrap_or_default();
+
+    let breaking = parsed.breaking
+        || parsed.footers.iter().any(|footer| {
+            footer.token == "BREAKING CHANGE"
+                || footer.token == "BREAKING-CHANGE"
+        });
+
+    Some(Record {
+        hash: &commit.hash,
+        r#type: parsed.r#type,
+        scope: parsed.scope,
+        description: parsed.description,
+        tickets: lint::find_tickets(prefixes, &commit.message),
+        breaking,
+        author: &commit.author,
+        author_email: &commit.author_email,
+        date: &commit.date,
+    })
+}
+
+/// Writes the records as a JSON array.
+fn write_json(
+    output: &mut impl Write,
+    records: &[Record<'_>],
+) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *output, records)?;
+    writeln!(output)
+}
+
+/// Writes the records as CSV.
+fn write_csv(
+    output: &mut impl Write,
+    records: &[Record<'_>],
+) -> io::Result<()> {
+    writeln!(output, "{CSV_HEADER}")?;
+
+    for record in records {
+        let fields = [
+            record.hash,
+            &record.r#type,
+            record.scope.as_deref().unwrap_or_default(),
+            &record.description,
+            &record.tickets.join(" "),
+            if record.breaking { "true" } else { "false" },
+            record.author,
+            record.author_email,
+            record.date,
+        ];
+
+        let line = fields.map(csv_field).join(",");
+        writeln!(output, "{line}")?;
+    }
+
+    Ok(())
+}
+
+/// Quotes a CSV field if needed.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    #[test]
+    fn quotes_the_csv_fields_when_needed() {
+        assert_eq!(csv_field("add a flag"), "add a flag");
+        assert_eq!(csv_field("add a, b"), "\"add a, b\"");
+        assert_eq!(csv_field("add \"a\""), "\"add \"\"a\"\"\"");
+    }
+}
@@ -0,0 +1,115 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The categories of failures and their exit code.
+//!
+//! All the subcommands map their errors to these categories, so scripts can
+//! rely on the same exit codes everywhere:
+//!
+//! | Category        | Exit code                                |
+//! |-----------------|------------------------------------------|
+//! | check failed    | 1                                        |
+//! | lint violations | number of invalid commits, capped at 63  |
+//! | usage error     | 64                                       |
+//! | invalid data    | 65                                       |
+//! | Git unavailable | 69                                       |
+//! | Git failure     | status code of Git, or 70 if it has none |
+//! | cannot create   | 73                                       |
+//! | I/O error       | 74                                       |
+//! | repository busy | 75                                       |
+//! | invalid config  | 78                                       |
+//! | user abort      | 130                                      |
+//!
+//! The codes from 64 to 78 come from `sysexits.h`. Lint violations are capped
+//! below this range so they cannot be mistaken for another failure.
+
+/// The maximum exit code for lint violations.
+const MAX_VIOLATIONS_CODE: i32 = 63;
+
+/// The exit code for a user abort, as for a shell interrupted by `SIGINT`.
+const USER_ABORT_CODE: i32 = 130;
+
+/// A category of failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// A check has not passed, like the size of the changes or a hook.
+    CheckFailed,
+    /// Some commits do not follow the convention.
+    Violations(usize),
+    /// The command is misused, or run in the wrong context.
+    Usage,
+    /// Some input data is invalid.
+    Data,
+    /// Git cannot be run.
+    GitUnavailable,
+    /// Git has failed, with its status code if any.
+    Git(Option<i32>),
+    /// A file cannot be created.
+    CannotCreate,
+    /// A file cannot be read or written.
+    Io,
+    /// The repository is in a state preventing the command to run.
+    Busy,
+    /// The configuration is invalid.
+    Config,
+    /// The user has aborted.
+    Aborted,
+}
+
+impl Failure {
+    /// Returns the exit code for the failure.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::CheckFailed => 1,
+            Self::Violations(count) => i32::try_from(count)
+                .unwrap_or(i32::MAX)
+                .clamp(1, MAX_VIOLATIONS_CODE),
+            Self::Usage => exitcode::USAGE,
+            Self::Data => exitcode::DATAERR,
+            Self::GitUnavailable => exitcode::UNAVAILABLE,
+            Self::Git(status_code) => status_code.unwrap_or(exitcode::SOFTWARE),
+            Self::CannotCreate => exitcode::CANTCREAT,
+            Self::Io => exitcode::IOERR,
+            Self::Busy => exitcode::TEMPFAIL,
+            Self::Config => exitcode::CONFIG,
+            Self::Aborted => USER_ABORT_CODE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    #[test]
+    fn uses_the_number_of_violations_as_exit_code() {
+        assert_eq!(Failure::Violations(1).exit_code(), 1);
+        assert_eq!(Failure::Violations(12).exit_code(), 12);
+    }
+
+    #[test]
+    fn caps_the_number_of_violations_below_sysexits() {
+        assert_eq!(Failure::Violations(64).exit_code(), 63);
+        assert_eq!(Failure::Violations(usize::MAX).exit_code(), 63);
+    }
+
+    #[test]
+    fn passes_the_status_code_of_git_through() {
+        assert_eq!(Failure::Git(Some(128)).exit_code(), 128);
+        assert_eq!(Failure::Git(None).exit_code(), exitcode::SOFTWARE);
+    }
+}
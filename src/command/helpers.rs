@@ -15,18 +15,21 @@
 
 //! Helpers for writing CLIs.
 
-use std::{io, process::Command};
+use std::{env, io, path::PathBuf, process::Command};
 
 use eyre::Result;
 use thiserror::Error;
 
 use crate::{
-    config::{Config, CONFIG_FILE_NAME, VERSION},
+    config::{Config, Scopes, Type, TypeList, CONFIG_FILE_NAME, VERSION},
     hint,
+    list_command::{self, Entry},
     tracing::LogResult as _,
-    warning,
+    warning, workspace,
 };
 
+use super::update::offer_update;
+
 /// Errors that can occur when not inside a Git worktree.
 #[derive(Debug, Error)]
 pub enum NotInGitWorktree {
@@ -41,6 +44,38 @@ pub enum NotInGitWorktree {
     NotInWorktree,
 }
 
+/// Error while changing to the directory given with `-C`.
+#[derive(Debug, Error)]
+#[error("Cannot change to {}", path.display())]
+pub struct ChangeDirError {
+    /// The directory.
+    pub path: PathBuf,
+    /// The error reported by the OS.
+    #[source]
+    pub source: io::Error,
+}
+
+/// Changes the current directory, as `git -C`.
+///
+/// Each directory is relative to the previous one, so that `-C a -C b` is
+/// equivalent to `-C a/b`. Empty directories are ignored, as by Git.
+#[tracing::instrument(level = "trace")]
+pub fn change_dirs(directories: &[PathBuf]) -> Result<(), ChangeDirError> {
+    for directory in directories
+        .iter()
+        .filter(|directory| !directory.as_os_str().is_empty())
+    {
+        env::set_current_dir(directory)
+            .map_err(|source| ChangeDirError {
+                path: directory.clone(),
+                source,
+            })
+            .log_err()?;
+    }
+
+    Ok(())
+}
+
 /// Ensures the command is run from a Git worktree.
 #[tracing::instrument(level = "trace")]
 pub fn ensure_in_git_worktree() -> Result<(), NotInGitWorktree> {
@@ -61,16 +96,136 @@ pub fn ensure_in_git_worktree() -> Result<(), NotInGitWorktree> {
     }
 }
 
+/// Ensures the command is run from a Git repository, bare or not.
+///
+/// This is enough for commands that only read the history.
+#[tracing::instrument(level = "trace")]
+pub fn ensure_in_git_repo() -> Result<(), NotInGitWorktree> {
+    let is_inside_work_tree = Command::new("git")
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .output()
+        .map_err(NotInGitWorktree::CannotRunGit)
+        .log_err()?;
+
+    if is_inside_work_tree.status.success() {
+        Ok(())
+    } else {
+        Err(NotInGitWorktree::NotInRepo).log_err()
+    }
+}
+
+/// The configuration is out of date in strict mode.
+#[derive(Debug, Error)]
+#[error("The configuration in {CONFIG_FILE_NAME} is out of date")]
+pub struct OutdatedConfig {
+    /// Whether the strict mode comes from `--strict`.
+    pub from_flag: bool,
+}
+
+/// What to do when the configuration is out of date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnOutdated {
+    /// Warn the user.
+    Warn,
+    /// Offer the user to update the configuration.
+    OfferUpdate,
+    /// Refuse to run.
+    Refuse,
+}
+
 /// Loads the configuration.
 #[tracing::instrument(level = "trace")]
 pub fn load_config() -> Result<Config> {
+    load_config_file(OnOutdated::Warn)
+}
+
+/// Completes the lists of the configuration from their external sources.
+///
+/// This runs commands and reads the workspace manifests, so only the commands
+/// using the lists call it. The sources are cleared once read, so that calling
+/// it again does nothing.
+///
+/// The types printed by `type_list.command` come after the ones from the
+/// configuration, which take precedence over duplicates. The scopes from the
+/// workspace come before the ones printed by `scopes.command`.
+#[tracing::instrument(level = "trace", skip(config))]
+pub fn complete_lists(config: &mut Config) -> Result<()> {
+    if let Some(TypeList { command, .. }) = &mut config.type_list {
+        if let Some(types_command) = command.as_deref() {
+            for Entry { name, description } in list_command::run(types_command)?
+            {
+                if config.types.contains_key(&name) {
+                    warning!(
+                        "`{types_command}` prints the type `{name}`, which is \
+                            already defined."
+                    );
+                    hint!("The definition from {CONFIG_FILE_NAME} is kept.");
+                } else {
+                    let description = description.unwrap_or_default();
+                    config.types.insert(name, Type::Simple(description));
+                }
+            }
+
+            *command = None;
+        }
+    }
+
+    if let Some(Scopes::List {
+        list,
+        from,
+        command,
+        ..
+    }) = &mut config.scopes
+    {
+        if let Some(source) = *from {
+            list.extend(workspace::members(source)?);
+            *from = None;
+        }
+
+        if let Some(scopes_command) = command.as_deref() {
+            list.extend(list_command::run(scopes_command)?);
+            *command = None;
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads the configuration file, handling an out-of-date one as requested.
+///
+/// Setting `meta.strict_version` in the configuration makes any command refuse
+/// to run with an out-of-date one. The lists are not completed from their
+/// external sources: see [`complete_lists`].
+#[tracing::instrument(level = "trace")]
+pub fn load_config_file(on_outdated: OnOutdated) -> Result<Config> {
     let config = Config::load()?;
 
-    if config.version != VERSION {
-        warning!("The configuration in {CONFIG_FILE_NAME} is out of date.");
-        hint!("You can update it by running `git z update`.");
+    if config.version == VERSION {
+        return Ok(config);
+    }
+
+    let strict_version = config
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.strict_version)
+        .unwrap_or(false);
+
+    if on_outdated == OnOutdated::Refuse {
+        return Err(OutdatedConfig { from_flag: true }).log_err()?;
     }
 
+    if strict_version {
+        return Err(OutdatedConfig { from_flag: false }).log_err()?;
+    }
+
+    if on_outdated == OnOutdated::OfferUpdate && offer_update(&config.version)?
+    {
+        return Ok(Config::load()?);
+    }
+
+    warning!("The configuration in {CONFIG_FILE_NAME} is out of date.");
+    hint!("You can update it by running `git z update`.");
+
     Ok(config)
 }
 
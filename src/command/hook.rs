@@ -0,0 +1,267 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `hook` subcommand.
+//!
+//! It implements server-side Git hooks, so that self-hosted Git servers can
+//! enforce the convention on pushes. Each hook is meant to be called from the
+//! script of the same name in the hooks directory of the bare repository.
+
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, BufRead as _},
+    path::PathBuf,
+};
+
+use clap::{Parser, Subcommand};
+use eyre::Result;
+use thiserror::Error;
+
+use crate::{
+    config::{self, Config},
+    lint::CheckOptions,
+    tracing::LogResult as _,
+};
+
+use super::{
+    helpers::{ensure_in_git_repo, load_config},
+    lint::print_violations,
+    revspec,
+};
+
+/// The hook command.
+#[derive(Debug, Parser)]
+pub struct Hook {
+    /// The hook to run.
+    #[command(subcommand)]
+    hook: ServerHook,
+}
+
+/// The server-side hooks.
+#[derive(Debug, Subcommand)]
+enum ServerHook {
+    /// Rejects pushes introducing commits not following the convention.
+    PreReceive(PreReceive),
+}
+
+/// The arguments of `git z hook pre-receive`.
+#[derive(Debug, Parser)]
+struct PreReceive {
+    /// The configuration to check the commits against [default: `git-z.toml`
+    /// in `HEAD`].
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Check merge commits even when Git or the forge generated their message.
+    #[arg(long)]
+    check_merge_messages: bool,
+    /// Accept and validate the ` (#123)` suffix added by squash-merges.
+    #[arg(long)]
+    squash_merges: bool,
+}
+
+/// An update of a ref, as received by the `pre-receive` hook.
+#[derive(Debug, PartialEq, Eq)]
+struct RefUpdate {
+    /// The new value of the ref.
+    new: String,
+    /// The name of the ref.
+    name: String,
+}
+
+/// Errors that can occur when running a hook.
+#[derive(Debug, Error)]
+pub enum HookError {
+    /// The ref updates cannot be read from the standard input.
+    #[error("Failed to read the ref updates from the standard input")]
+    CannotReadUpdates(#[source] io::Error),
+    /// A ref update is not in the `<old> <new> <ref>` format.
+    #[error("Invalid ref update: `{line}`")]
+    InvalidRefUpdate {
+        /// The line of the update.
+        line: String,
+    },
+    /// The configuration cannot be read.
+    #[error("Failed to read {path}")]
+    CannotReadConfig {
+        /// The path of the configuration.
+        path: PathBuf,
+        /// The error reported by the OS.
+        #[source]
+        source: io::Error,
+    },
+    /// Some pushed commits do not follow the convention.
+    #[error("{count} pushed commit(s) do not follow the convention")]
+    InvalidCommits {
+        /// The number of invalid commits.
+        count: usize,
+    },
+}
+
+impl super::Command for Hook {
+    #[tracing::instrument(name = "hook", level = "trace", skip_all)]
+    fn run(&self) -> Result<()> {
+        tracing::info!(params = ?self, "running hook");
+
+        match &self.hook {
+            ServerHook::PreReceive(pre_receive) => pre_receive.run(),
+        }
+    }
+}
+
+impl PreReceive {
+    /// Checks the commits introduced by the ref updates read from stdin.
+    fn run(&self) -> Result<()> {
+        ensure_in_git_repo()?;
+
+        let config = self.config()?;
+        let updates = read_updates()?;
+        tracing::debug!(?updates);
+
+        let mut checked = HashSet::new();
+        let mut invalid = 0;
+
+        for update in &updates {
+            for commit in revspec::new_commits(&update.new)? {
+                if !checked.insert(commit.hash.clone())
+                    || commit.is_skipped(self.check_options())
+                {
+                    continue;
+                }
+
+                let violations = commit.check(&config, self.check_options());
+
+                if !violations.is_empty() {
+                    tracing::debug!(hash = ?commit.hash, ?violations);
+                    invalid += 1;
+                    println!("{}:", update.name);
+                    print_violations(&commit, &violations);
+                }
+            }
+        }
+
+        if invalid == 0 {
+            Ok(())
+        } else {
+            Err(HookError::InvalidCommits { count: invalid }).log_err()?
+        }
+    }
+
+    /// Loads the configuration passed with `--config`, or the one in `HEAD`.
+    fn config(&self) -> Result<Config> {
+        let Some(path) = &self.config else {
+            return load_config();
+        };
+
+        let toml = fs::read_to_string(path)
+            .map_err(|source| HookError::CannotReadConfig {
+                path: path.clone(),
+                source,
+            })
+            .log_err()?;
+
+        Ok(Config::from_toml(&toml)
+            .map_err(config::LoadError::from)
+            .log_err()?)
+    }
+
+    /// Returns the options deciding how the commits are checked.
+    fn check_options(&self) -> CheckOptions {
+        CheckOptions {
+            check_merge_messages: self.check_merge_messages,
+            squash_merges: self.squash_merges,
+            skips_external_lists: false,
+        }
+    }
+}
+
+/// Reads the ref updates from the standard input.
+///
+/// Deleted refs are left out, as they introduce no commit.
+fn read_updates() -> Result<Vec<RefUpdate>, HookError> {
+    let mut updates = vec![];
+
+    for line in io::stdin().lock().lines() {
+        let line = line.map_err(HookError::CannotReadUpdates).log_err()?;
+
+        if let Some(update) = parse_update(&line)? {
+            updates.push(update);
+        }
+    }
+
+    Ok(updates)
+}
+
+/// Parses a ref update, returning `None` for deletions and empty lines.
+fn parse_update(line: &str) -> Result<Option<RefUpdate>, HookError> {
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+
+    match line.split_whitespace().collect::<Vec<_>>()[..] {
+        [_old, new, _name] if is_null_hash(new) => Ok(None),
+        [_old, new, name] => Ok(Some(RefUpdate {
+            new: new.to_owned(),
+            name: name.to_owned(),
+        })),
+        _ => Err(HookError::InvalidRefUpdate {
+            line: line.to_owned(),
+        })
+        .log_err(),
+    }
+}
+
+/// Returns whether a hash is the null one Git uses for missing refs.
+fn is_null_hash(hash: &str) -> bool {
+    hash.chars().all(|c| c == '0')
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    const NULL: &str = "0000000000000000000000000000000000000000";
+    const HASH: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+    #[test]
+    fn parses_ref_updates() {
+        assert_eq!(
+            parse_update(&format!("{NULL} {HASH} refs/heads/main")).unwrap(),
+            Some(RefUpdate {
+                new: String::from(HASH),
+                name: String::from("refs/heads/main"),
+            })
+        );
+    }
+
+    #[test]
+    fn ignores_deleted_refs_and_empty_lines() {
+        assert_eq!(
+            parse_update(&format!("{HASH} {NULL} refs/heads/main")).unwrap(),
+            None
+        );
+        assert_eq!(parse_update("").unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_malformed_ref_updates() {
+        assert!(matches!(
+            parse_update(HASH),
+            Err(HookError::InvalidRefUpdate { .. })
+        ));
+    }
+}
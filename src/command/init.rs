@@ -15,20 +15,46 @@
 
 //! The `init` subcommand.
 
-use std::fs;
+pub mod setup;
+pub mod sync;
+
+use std::{
+    collections::HashSet,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
 
 use askama::Template;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use eyre::Result;
-use inquire::Select;
+use indexmap::IndexMap;
+use inquire::{MultiSelect, Select};
 use thiserror::Error;
 
-use crate::{config::config_file, hint, success, tracing::LogResult as _};
+use crate::{
+    config::{self, config_file, FromTomlError, DEFAULT_TYPES},
+    conventional_commit::ConventionalCommit,
+    hint, history, list_command, success,
+    tracing::LogResult as _,
+    warning,
+};
+
+use self::setup::{CiProvider, Hook};
 
 use super::helpers::ensure_in_git_worktree;
 
+/// The maximum number of commits read to find the types and scopes in use.
+const HISTORY_COMMITS: usize = 1000;
+
+/// The description of the types found in the history without a default one.
+const UNKNOWN_TYPE_DESCRIPTION: &str = "TODO: describe this type";
+
 /// The init command.
 #[derive(Debug, Parser)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "These are independent command-line flags."
+)]
 pub struct Init {
     /// Use the default configuration.
     #[arg(long, short = 'd')]
@@ -36,6 +62,76 @@ pub struct Init {
     /// Force the init process.
     #[arg(long, short = 'f')]
     force: bool,
+    /// Propose the types and scopes used in the history of the repository.
+    ///
+    /// With `--sync-from`, only the scope list is proposed from the history.
+    #[arg(long, conflicts_with = "default")]
+    from_history: bool,
+    /// Copy the configuration of another repository, from its path or URL.
+    #[arg(
+        long,
+        value_name = "REPO",
+        conflicts_with_all = ["default", "preset", "scopes", "ticket", "types_file"],
+    )]
+    sync_from: Option<String>,
+    /// Start from a preset instead of asking questions.
+    #[arg(long, value_name = "NAME", conflicts_with_all = INTERACTIVE_FLAGS)]
+    preset: Option<Preset>,
+    /// Whether to ask for a scope, without asking questions.
+    #[arg(long, value_name = "KIND", conflicts_with_all = INTERACTIVE_FLAGS)]
+    scopes: Option<ScopesFlag>,
+    /// Whether to ask for a ticket, without asking questions.
+    #[arg(long, value_name = "KIND", conflicts_with_all = INTERACTIVE_FLAGS)]
+    ticket: Option<TicketFlag>,
+    /// Read the types from a file instead of using the default ones.
+    ///
+    /// The file contains one type per line, optionally followed by a tab and
+    /// its description.
+    #[arg(long, value_name = "PATH", conflicts_with_all = INTERACTIVE_FLAGS)]
+    types_file: Option<PathBuf>,
+    /// Install the commit-msg and prepare-commit-msg hooks calling git-z.
+    #[arg(long)]
+    install_hooks: bool,
+    /// Write a CI job running `git z lint` on pull requests.
+    #[arg(long, value_name = "PROVIDER")]
+    ci: Option<CiProvider>,
+}
+
+/// The flags running a wizard, which conflict with the non-interactive ones.
+const INTERACTIVE_FLAGS: [&str; 2] = ["default", "from_history"];
+
+/// A preset configuration.
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+enum Preset {
+    /// Ask for any scope and an optional ticket.
+    #[default]
+    Default,
+    /// Ask neither for a scope nor for a ticket.
+    Minimal,
+    /// Ask for a scope in a list and require a ticket.
+    Strict,
+}
+
+/// Whether to ask for a scope, as a command-line flag.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ScopesFlag {
+    /// Ask for a scope, accept any.
+    Any,
+    /// Ask for a scope in a list.
+    List,
+    /// Do not ask for a scope.
+    None,
+}
+
+/// Whether to ask for a ticket, as a command-line flag.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum TicketFlag {
+    /// Require a ticket number.
+    Required,
+    /// Ask for an optional ticket number.
+    Optional,
+    /// Do not ask for a ticket number.
+    None,
 }
 
 /// Usage errors of `git z init`.
@@ -44,18 +140,75 @@ pub enum InitError {
     /// A configuration already exists.
     #[error("There is already a git-z.toml in the current repository")]
     ExistingConfig,
+    /// The types file cannot be read.
+    #[error("Failed to read {path}")]
+    CannotReadTypesFile {
+        /// The path of the types file.
+        path: PathBuf,
+        /// The error reported by the OS.
+        #[source]
+        source: io::Error,
+    },
+    /// The types file does not contain any type.
+    #[error("There is no type in {path}")]
+    NoTypes {
+        /// The path of the types file.
+        path: PathBuf,
+    },
+    /// The types file contains the same type twice.
+    #[error("The type {name} is listed twice in {path}")]
+    DuplicateType {
+        /// The name of the duplicate type.
+        name: String,
+        /// The path of the types file.
+        path: PathBuf,
+    },
+    /// The generated configuration cannot be loaded.
+    #[error("The generated configuration is invalid")]
+    InvalidConfig(#[from] FromTomlError),
 }
 
 /// Parameters to generate a `git-z.toml`.
-#[derive(Debug, Default, Template)]
-#[template(path = "git-z.toml.jinja", syntax = "template")]
+#[derive(Debug, Template)]
+#[template(path = "git-z.toml.jinja", syntax = "template", escape = "none")]
 struct Config {
+    /// The types of commits.
+    types: Vec<TypeParam>,
     /// Whether to ask for a scope.
     scopes: Scopes,
+    /// The list of scopes, as TOML strings, or empty for the example one.
+    scope_list: Vec<String>,
     /// Whether to ask for a ticket.
     ticket: Ticket,
 }
 
+/// A type of commits, ready to be rendered.
+#[derive(Debug)]
+struct TypeParam {
+    /// The name of the type, as a TOML key.
+    key: String,
+    /// The description of the type, as a TOML string.
+    description: String,
+}
+
+/// The number of commits using each type and scope in the history.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Usage {
+    /// The number of commits for each type.
+    types: IndexMap<String, usize>,
+    /// The number of commits for each scope.
+    scopes: IndexMap<String, usize>,
+}
+
+/// A type or scope found in the history, offered for selection.
+#[derive(Debug)]
+struct Candidate {
+    /// The name of the type or scope.
+    name: String,
+    /// The number of commits using it.
+    count: usize,
+}
+
 /// Whether to ask for a scope.
 #[derive(Debug)]
 enum Scopes {
@@ -103,24 +256,129 @@ impl super::Command for Init {
             Err(InitError::ExistingConfig).log_err()?;
         }
 
-        let config = if self.default {
-            tracing::info!("using the default configuration");
-            Config::default()
+        let (config, is_interactive) = if let Some(repo) = &self.sync_from {
+            tracing::info!(repo, "copying the configuration");
+            (self.config_from_repo(repo)?, self.from_history)
         } else {
-            tracing::info!("customising the configuration");
-            Config::run_wizard()?
+            let (config, is_interactive) = self.generate_config()?;
+            let config = format!("{config}\n");
+            config::Config::from_toml(&config)
+                .map_err(InitError::from)
+                .log_err()?;
+            (config, is_interactive)
+        };
+
+        let hooks = if self.install_hooks {
+            Hook::ALL.to_vec()
+        } else if is_interactive {
+            Hook::run_wizard()?
+        } else {
+            vec![]
+        };
+
+        let ci = match self.ci {
+            Some(provider) => Some(provider),
+            None if is_interactive => CiProvider::run_wizard()?,
+            None => None,
         };
 
         tracing::info!(?config, "writing the configuration file");
-        fs::write(config_file, format!("{config}\n")).log_err()?;
+        fs::write(config_file, config).log_err()?;
 
         success!("A git-z.toml has been created!");
         hint!("You can now edit it to adjust the configuration.");
 
+        for hook in hooks {
+            hook.install()?;
+        }
+
+        if let Some(provider) = ci {
+            provider.write_job()?;
+        }
+
         Ok(())
     }
 }
 
+impl Init {
+    /// Generates the configuration, and returns whether questions were asked.
+    fn generate_config(&self) -> Result<(Config, bool)> {
+        if self.default {
+            tracing::info!("using the default configuration");
+            Ok((Config::default(), false))
+        } else if self.from_history {
+            tracing::info!("inferring the configuration from the history");
+            Ok((Config::run_wizard_from_history()?, true))
+        } else if self.has_non_interactive_flags() {
+            tracing::info!("using the configuration from the flags");
+            Ok((self.config_from_flags()?, false))
+        } else {
+            tracing::info!("customising the configuration");
+            Ok((Config::run_wizard()?, true))
+        }
+    }
+
+    /// Returns whether a flag answering the questions has been passed.
+    fn has_non_interactive_flags(&self) -> bool {
+        self.preset.is_some()
+            || self.scopes.is_some()
+            || self.ticket.is_some()
+            || self.types_file.is_some()
+    }
+
+    /// Reads the configuration of another repository.
+    ///
+    /// With `--from-history`, its scope list is replaced by the scopes selected
+    /// from the history.
+    fn config_from_repo(&self, repo: &str) -> Result<String> {
+        let config = sync::read_config(repo)?;
+
+        if !self.from_history {
+            return Ok(config);
+        }
+
+        // NOTE: The subjects may be decorated by the copied configuration.
+        let usage = Usage::from_commits(&history::conventional_commits(
+            &config::Config::from_toml(&config).map_err(InitError::from)?,
+            Some(HISTORY_COMMITS),
+        )?);
+        tracing::debug!(?usage);
+
+        let scopes = select_candidates(
+            "Which scopes do you want to keep?",
+            &usage.scopes,
+        )?;
+
+        if scopes.is_empty() {
+            warning!("No scope has been found, so the scope list is kept.");
+            Ok(config)
+        } else {
+            Ok(sync::replace_scope_list(repo, &config, &scopes)?)
+        }
+    }
+
+    /// Builds the configuration from the flags.
+    ///
+    /// The questions without a flag are answered by the preset.
+    fn config_from_flags(&self) -> Result<Config> {
+        let mut config = self.preset.unwrap_or_default().config();
+
+        if let Some(scopes) = self.scopes {
+            config.scopes = scopes.into();
+        }
+
+        if let Some(ticket) = self.ticket {
+            config.ticket = ticket.into();
+        }
+
+        if let Some(path) = &self.types_file {
+            config.types = read_types_file(path)?;
+        }
+
+        Ok(config)
+    }
+}
+
 impl Config {
     /// Runs the wizard to fill the parameters for the configuration.
     #[tracing::instrument(level = "trace")]
@@ -128,8 +386,111 @@ impl Config {
         Ok(Self {
             scopes: Scopes::run_wizard()?,
             ticket: Ticket::run_wizard()?,
+            ..Self::default()
         })
     }
+
+    /// Runs the wizard, proposing the types and scopes used in the history.
+    ///
+    /// The user can prune them before they are written. If no conventional
+    /// commit is found, the default types are used and the scopes are asked
+    /// for as usual.
+    #[tracing::instrument(level = "trace")]
+    fn run_wizard_from_history() -> Result<Self> {
+        // NOTE: With `--force`, the subjects may be decorated by the current
+        // configuration, which is ignored if invalid as it is being replaced.
+        let usage = Usage::from_commits(&history::conventional_commits(
+            &config::Config::load().unwrap_or_default(),
+            Some(HISTORY_COMMITS),
+        )?);
+        tracing::debug!(?usage);
+
+        let types = select_candidates(
+            "Which types do you want to keep?",
+            &usage.types,
+        )?;
+
+        let types = if types.is_empty() {
+            warning!("No type has been found, so the default ones are used.");
+            default_types()
+        } else {
+            types
+                .iter()
+                .map(|name| TypeParam::from_history(name))
+                .collect()
+        };
+
+        let scope_list = select_candidates(
+            "Which scopes do you want to keep?",
+            &usage.scopes,
+        )?;
+
+        let scopes = if scope_list.is_empty() {
+            Scopes::run_wizard()?
+        } else {
+            Scopes::Ask {
+                accept: AcceptScopes::List,
+            }
+        };
+
+        Ok(Self {
+            types,
+            scopes,
+            scope_list: scope_list
+                .iter()
+                .map(|scope| toml_string(scope))
+                .collect(),
+            ticket: Ticket::run_wizard()?,
+        })
+    }
+}
+
+impl TypeParam {
+    /// Builds a type from a name and a description.
+    fn new(name: &str, description: &str) -> Self {
+        Self {
+            key: toml_edit::Key::new(name).display_repr().into_owned(),
+            description: toml_string(description),
+        }
+    }
+
+    /// Builds a type found in the history, with its default description if
+    /// any.
+    fn from_history(name: &str) -> Self {
+        let description = DEFAULT_TYPES
+            .iter()
+            .find(|(default, _)| *default == name)
+            .map_or(UNKNOWN_TYPE_DESCRIPTION, |(_, description)| description);
+
+        Self::new(name, description)
+    }
+}
+
+impl Usage {
+    /// Counts the types and scopes of the commits.
+    ///
+    /// They are sorted by decreasing count.
+    fn from_commits(commits: &[ConventionalCommit]) -> Self {
+        Self {
+            types: history::count(
+                commits.iter().map(|commit| commit.r#type.clone()),
+            ),
+            scopes: history::count(
+                commits.iter().filter_map(|commit| commit.scope.clone()),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Candidate {
+    #[expect(
+        clippy::min_ident_chars,
+        reason = "This is the conventional name in `fmt` implementations."
+    )]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let commits = if self.count == 1 { "commit" } else { "commits" };
+        write!(f, "{} ({} {commits})", self.name, self.count)
+    }
 }
 
 impl Scopes {
@@ -189,6 +550,58 @@ impl Ticket {
     }
 }
 
+impl Preset {
+    /// Returns the configuration for the preset.
+    fn config(self) -> Config {
+        let (scopes, ticket) = match self {
+            Self::Default => (ScopesFlag::Any, TicketFlag::Optional),
+            Self::Minimal => (ScopesFlag::None, TicketFlag::None),
+            Self::Strict => (ScopesFlag::List, TicketFlag::Required),
+        };
+
+        Config {
+            scopes: scopes.into(),
+            ticket: ticket.into(),
+            ..Config::default()
+        }
+    }
+}
+
+impl From<ScopesFlag> for Scopes {
+    fn from(flag: ScopesFlag) -> Self {
+        match flag {
+            ScopesFlag::Any => Self::Ask {
+                accept: AcceptScopes::Any,
+            },
+            ScopesFlag::List => Self::Ask {
+                accept: AcceptScopes::List,
+            },
+            ScopesFlag::None => Self::DontAsk,
+        }
+    }
+}
+
+impl From<TicketFlag> for Ticket {
+    fn from(flag: TicketFlag) -> Self {
+        match flag {
+            TicketFlag::Required => Self::Ask { required: true },
+            TicketFlag::Optional => Self::Ask { required: false },
+            TicketFlag::None => Self::DontAsk,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            types: default_types(),
+            scopes: Scopes::default(),
+            scope_list: Vec::new(),
+            ticket: Ticket::default(),
+        }
+    }
+}
+
 impl Default for Scopes {
     fn default() -> Self {
         Self::Ask {
@@ -202,3 +615,186 @@ impl Default for Ticket {
         Self::Ask { required: false }
     }
 }
+
+/// Returns the default types.
+fn default_types() -> Vec<TypeParam> {
+    DEFAULT_TYPES
+        .iter()
+        .map(|(name, description)| TypeParam::new(name, description))
+        .collect()
+}
+
+/// Reads the types from a file.
+///
+/// The types without a description get their default one, if any. A type
+/// listed twice is an error, as it would be a duplicate key in the
+/// configuration.
+fn read_types_file(path: &Path) -> Result<Vec<TypeParam>> {
+    let content = fs::read_to_string(path)
+        .map_err(|source| InitError::CannotReadTypesFile {
+            path: path.to_owned(),
+            source,
+        })
+        .log_err()?;
+
+    let mut names = HashSet::new();
+    let mut types = Vec::new();
+
+    for entry in list_command::parse(&content) {
+        if !names.insert(entry.name.clone()) {
+            Err(InitError::DuplicateType {
+                name: entry.name.clone(),
+                path: path.to_owned(),
+            })
+            .log_err()?;
+        }
+
+        types.push(match entry.description {
+            Some(description) => TypeParam::new(&entry.name, &description),
+            None => TypeParam::from_history(&entry.name),
+        });
+    }
+
+    if types.is_empty() {
+        Err(InitError::NoTypes {
+            path: path.to_owned(),
+        })
+        .log_err()?;
+    }
+
+    Ok(types)
+}
+
+/// Lets the user select the candidates to keep, all of them being selected by
+/// default.
+///
+/// Returns an empty list without asking if there are no candidates.
+fn select_candidates(
+    message: &str,
+    counts: &IndexMap<String, usize>,
+) -> Result<Vec<String>> {
+    if counts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let candidates = counts
+        .iter()
+        .map(|(name, count)| Candidate {
+            name: name.clone(),
+            count: *count,
+        })
+        .collect();
+
+    let selected = MultiSelect::new(message, candidates)
+        .with_all_selected_by_default()
+        .prompt()
+        .log_err()?;
+
+    Ok(selected
+        .into_iter()
+        .map(|candidate| candidate.name)
+        .collect())
+}
+
+/// Formats a string as a TOML string.
+fn toml_string(value: &str) -> String {
+    toml_edit::Value::from(value).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use indexmap::indexmap;
+
+    use super::*;
+
+    /// A preset offered by the wizard, with its name.
+    type Preset<T> = (&'static str, fn() -> T);
+
+    /// The scope presets offered by the wizard.
+    const SCOPES_PRESETS: [Preset<Scopes>; 3] = [
+        ("scopes-any", || Scopes::Ask {
+            accept: AcceptScopes::Any,
+        }),
+        ("scopes-list", || Scopes::Ask {
+            accept: AcceptScopes::List,
+        }),
+        ("no-scopes", || Scopes::DontAsk),
+    ];
+
+    /// The ticket presets offered by the wizard.
+    const TICKET_PRESETS: [Preset<Ticket>; 3] = [
+        ("ticket-required", || Ticket::Ask { required: true }),
+        ("ticket-optional", || Ticket::Ask { required: false }),
+        ("no-ticket", || Ticket::DontAsk),
+    ];
+
+    #[test]
+    fn renders_the_default_config() {
+        insta::assert_snapshot!(Config::default().to_string());
+    }
+
+    #[test]
+    fn counts_the_types_and_scopes_by_decreasing_count() {
+        let subjects = "feat(cli): add a flag\n\
+            fix(config): patch a bug\n\
+            Update the README\n\
+            fix(cli): patch another bug\n\
+            fix: patch a third bug\n";
+
+        let commits = subjects
+            .lines()
+            .filter_map(|subject| ConventionalCommit::parse(subject).ok())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            Usage::from_commits(&commits),
+            Usage {
+                types: indexmap! {
+                    String::from("fix") => 3,
+                    String::from("feat") => 1,
+                },
+                scopes: indexmap! {
+                    String::from("cli") => 2,
+                    String::from("config") => 1,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn renders_the_config_inferred_from_the_history() {
+        let config = Config {
+            types: vec![
+                TypeParam::from_history("fix"),
+                TypeParam::from_history("hotfix"),
+            ],
+            scopes: Scopes::Ask {
+                accept: AcceptScopes::List,
+            },
+            scope_list: vec![toml_string("cli"), toml_string("the \"core\"")],
+            ..Config::default()
+        };
+
+        insta::assert_snapshot!(config.to_string());
+    }
+
+    #[test]
+    fn renders_the_config_for_every_preset() {
+        for (scopes_name, scopes) in SCOPES_PRESETS {
+            for (ticket_name, ticket) in TICKET_PRESETS {
+                let config = Config {
+                    scopes: scopes(),
+                    ticket: ticket(),
+                    ..Config::default()
+                };
+
+                insta::assert_snapshot!(
+                    format!("preset_{scopes_name}_{ticket_name}"),
+                    config.to_string()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Optional setup steps after writing the configuration.
+//!
+//! They install Git hooks calling git-z and a CI job linting the commits, from
+//! templates embedded in the binary. Existing files are never overwritten.
+
+use std::{env, fmt, fs, path::PathBuf};
+
+use clap::ValueEnum;
+use eyre::Result;
+use inquire::{MultiSelect, Select};
+
+use crate::{
+    config::repo_root, git, helpers::set_executable, hint, success,
+    tracing::LogResult as _, warning,
+};
+
+/// A Git hook calling git-z.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Hook {
+    /// Checks the commit messages.
+    CommitMsg,
+    /// Runs the wizard when committing without a message.
+    PrepareCommitMsg,
+}
+
+/// A CI provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CiProvider {
+    /// GitHub Actions.
+    Github,
+    /// GitLab CI.
+    Gitlab,
+}
+
+impl Hook {
+    /// All the hooks.
+    pub const ALL: [Self; 2] = [Self::CommitMsg, Self::PrepareCommitMsg];
+
+    /// Runs the wizard to select the hooks to install.
+    pub fn run_wizard() -> Result<Vec<Self>> {
+        let hooks = MultiSelect::new(
+            "Which Git hooks do you want to install?",
+            Self::ALL.to_vec(),
+        )
+        .prompt()
+        .log_err()?;
+
+        tracing::debug!(?hooks);
+        Ok(hooks)
+    }
+
+    /// Installs the hook, unless there is already one.
+    #[tracing::instrument(level = "trace")]
+    pub fn install(self) -> Result<()> {
+        let path = self.path()?;
+
+        if path.exists() {
+            warning!(
+                "There is already a `{}` hook, so it has not been installed.",
+                self.name()
+            );
+            return Ok(());
+        }
+
+        if let Some(hooks_dir) = path.parent() {
+            fs::create_dir_all(hooks_dir).log_err()?;
+        }
+
+        fs::write(&path, self.content()).log_err()?;
+        set_executable(&path).log_err()?;
+
+        success!("The `{}` hook has been installed.", self.name());
+        Ok(())
+    }
+
+    /// Returns the path where the hook is installed.
+    pub fn path(self) -> Result<PathBuf> {
+        // NOTE: Git resolves `hooks` to `core.hooksPath` if it is set, and the
+        // path can be relative to the current directory.
+        Ok(env::current_dir()?
+            .join(git::path("hooks")?)
+            .join(self.name()))
+    }
+
+    /// Returns the name of the hook.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::CommitMsg => "commit-msg",
+            Self::PrepareCommitMsg => "prepare-commit-msg",
+        }
+    }
+
+    /// Returns the content of the hook.
+    const fn content(self) -> &'static str {
+        match self {
+            Self::CommitMsg => {
+                include_str!("../../../templates/hooks/commit-msg")
+            }
+            Self::PrepareCommitMsg => {
+                include_str!("../../../templates/hooks/prepare-commit-msg")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Hook {
+    #[expect(
+        clippy::min_ident_chars,
+        reason = "This is the conventional name in `fmt` implementations."
+    )]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            Self::CommitMsg => "check the commit messages",
+            Self::PrepareCommitMsg => "run the wizard on `git commit`",
+        };
+
+        write!(f, "{}: {description}", self.name())
+    }
+}
+
+impl CiProvider {
+    /// Runs the wizard to select the CI provider to write a job for, if any.
+    pub fn run_wizard() -> Result<Option<Self>> {
+        let options = vec!["GitHub Actions", "GitLab CI", "No"];
+
+        let choice = Select::new(
+            "Do you want a CI job running `git z lint` on pull requests?",
+            options,
+        )
+        .with_starting_cursor(2)
+        .prompt()
+        .log_err()?;
+
+        let provider = match choice {
+            "GitHub Actions" => Some(Self::Github),
+            "GitLab CI" => Some(Self::Gitlab),
+            _ => None,
+        };
+
+        tracing::debug!(?provider);
+        Ok(provider)
+    }
+
+    /// Writes the CI job, unless the file already exists.
+    #[tracing::instrument(level = "trace")]
+    pub fn write_job(self) -> Result<()> {
+        let path = repo_root()?.join(self.path());
+
+        if path.exists() {
+            warning!(
+                "`{}` already exists, so the CI job has not been written.",
+                self.path()
+            );
+            return Ok(());
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).log_err()?;
+        }
+
+        fs::write(&path, self.content()).log_err()?;
+
+        success!("The CI job has been written to `{}`.", self.path());
+
+        if self == Self::Gitlab {
+            hint!("Include it from your `.gitlab-ci.yml` to enable it.");
+        }
+
+        Ok(())
+    }
+
+    /// Returns the path of the CI job, relative to the root of the repository.
+    const fn path(self) -> &'static str {
+        match self {
+            Self::Github => ".github/workflows/git-z.yml",
+            Self::Gitlab => ".gitlab/ci/git-z.yml",
+        }
+    }
+
+    /// Returns the content of the CI job.
+    const fn content(self) -> &'static str {
+        match self {
+            Self::Github => {
+                include_str!("../../../templates/ci/github-actions.yml")
+            }
+            Self::Gitlab => include_str!("../../../templates/ci/gitlab-ci.yml"),
+        }
+    }
+}
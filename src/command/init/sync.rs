@@ -0,0 +1,254 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Copy of the configuration of another repository.
+//!
+//! The configuration is read from the worktree of a local repository, or from
+//! the `HEAD` of a shallow clone for a Git URL. It is copied as is, comments
+//! included, but for the scope list which can be rewritten.
+
+use std::{env, fs, io, path::Path, process};
+
+use thiserror::Error;
+use toml_edit::{Array, DocumentMut, Item, Table};
+
+use crate::{
+    config::{Config, FromTomlError, CONFIG_FILE_NAME},
+    git::{self, GitError},
+    tracing::LogResult as _,
+};
+
+/// Errors that can occur when copying the configuration of another repository.
+#[derive(Debug, Error)]
+pub enum SyncError {
+    /// The repository has no configuration.
+    #[error("There is no {CONFIG_FILE_NAME} in {repo}")]
+    NoConfig {
+        /// The repository.
+        repo: String,
+    },
+    /// The configuration cannot be read.
+    #[error("Failed to read the {CONFIG_FILE_NAME} of {repo}")]
+    CannotRead {
+        /// The repository.
+        repo: String,
+        /// The error reported by the OS.
+        #[source]
+        source: io::Error,
+    },
+    /// An error has occurred while running Git.
+    #[error("Failed to read the remote configuration")]
+    Git(#[from] GitError),
+    /// The repository cannot be cloned.
+    #[error("Failed to clone {repo}")]
+    CannotClone {
+        /// The repository.
+        repo: String,
+        /// The standard error of Git.
+        stderr: String,
+    },
+    /// The configuration is invalid.
+    #[error("The {CONFIG_FILE_NAME} of {repo} is invalid")]
+    InvalidConfig {
+        /// The repository.
+        repo: String,
+        /// The error in the configuration.
+        #[source]
+        source: FromTomlError,
+    },
+    /// The configuration is not a proper TOML document.
+    #[error("The {CONFIG_FILE_NAME} of {repo} cannot be edited")]
+    CannotEdit {
+        /// The repository.
+        repo: String,
+        /// The parse error.
+        #[source]
+        source: toml_edit::TomlError,
+    },
+}
+
+/// Reads the configuration of another repository.
+///
+/// `repo` is either the path of a local repository or a Git URL. The
+/// configuration is checked, so that an invalid one is not copied.
+#[tracing::instrument(level = "trace")]
+pub fn read_config(repo: &str) -> Result<String, SyncError> {
+    let config = if Path::new(repo).is_dir() {
+        read_local(repo)?
+    } else {
+        read_remote(repo)?
+    };
+
+    Config::from_toml(&config)
+        .map_err(|source| SyncError::InvalidConfig {
+            repo: repo.to_owned(),
+            source,
+        })
+        .log_err()?;
+
+    Ok(config)
+}
+
+/// Replaces the scope list of a configuration, keeping its comments.
+///
+/// The scopes are then accepted from the list.
+pub fn replace_scope_list(
+    repo: &str,
+    config: &str,
+    scopes: &[String],
+) -> Result<String, SyncError> {
+    let mut document = config
+        .parse::<DocumentMut>()
+        .map_err(|source| SyncError::CannotEdit {
+            repo: repo.to_owned(),
+            source,
+        })
+        .log_err()?;
+
+    let table = document
+        .entry("scopes")
+        .or_insert_with(|| Item::Table(Table::new()));
+
+    table["accept"] = toml_edit::value("list");
+    table["list"] = toml_edit::value(scopes.iter().collect::<Array>());
+
+    Ok(document.to_string())
+}
+
+/// Reads the configuration from the worktree of a local repository.
+fn read_local(repo: &str) -> Result<String, SyncError> {
+    fs::read_to_string(Path::new(repo).join(CONFIG_FILE_NAME))
+        .map_err(|source| {
+            if source.kind() == io::ErrorKind::NotFound {
+                SyncError::NoConfig {
+                    repo: repo.to_owned(),
+                }
+            } else {
+                SyncError::CannotRead {
+                    repo: repo.to_owned(),
+                    source,
+                }
+            }
+        })
+        .log_err()
+}
+
+/// Reads the configuration from the `HEAD` of a remote repository.
+///
+/// The repository is cloned without checkout in a temporary directory, which is
+/// removed afterwards.
+fn read_remote(repo: &str) -> Result<String, SyncError> {
+    let clone_dir =
+        env::temp_dir().join(format!("git-z-sync-{}", process::id()));
+
+    let result = clone_and_show(repo, &clone_dir);
+
+    if let Err(error) = fs::remove_dir_all(&clone_dir) {
+        tracing::debug!(?error, "cannot remove the temporary clone");
+    }
+
+    result
+}
+
+/// Clones a repository and shows its configuration.
+fn clone_and_show(repo: &str, clone_dir: &Path) -> Result<String, SyncError> {
+    let clone_dir = clone_dir.to_string_lossy();
+
+    git::run(&[
+        "clone",
+        "--quiet",
+        "--depth=1",
+        "--no-checkout",
+        "--",
+        repo,
+        &clone_dir,
+    ])
+    .map_err(|error| match error {
+        GitError::Failed(stderr) => SyncError::CannotClone {
+            repo: repo.to_owned(),
+            stderr,
+        },
+        error => SyncError::Git(error),
+    })
+    .log_err()?;
+
+    let object = format!("HEAD:{CONFIG_FILE_NAME}");
+    git::run(&["-C", &clone_dir, "show", &object])
+        .map_err(|error| match error {
+            GitError::Failed(_) => SyncError::NoConfig {
+                repo: repo.to_owned(),
+            },
+            error => SyncError::Git(error),
+        })
+        .log_err()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn replaces_the_scope_list() {
+        let config = indoc! {r#"
+            version = "0.3-dev.0"
+
+            # The accepted scopes.
+            [scopes]
+            # What kind of scope to accept.
+            accept = "list"
+            list = ["api", "cli"]
+        "#};
+
+        let config = replace_scope_list(
+            "../other",
+            config,
+            &[String::from("core"), String::from("ui")],
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            indoc! {r#"
+                version = "0.3-dev.0"
+
+                # The accepted scopes.
+                [scopes]
+                # What kind of scope to accept.
+                accept = "list"
+                list = ["core", "ui"]
+            "#}
+        );
+    }
+
+    #[test]
+    fn accepts_the_scopes_from_the_new_list() {
+        let config = indoc! {r#"
+            version = "0.3-dev.0"
+
+            [scopes]
+            accept = "any"
+        "#};
+
+        let config =
+            replace_scope_list("../other", config, &[String::from("core")])
+                .unwrap();
+
+        assert!(config.contains("accept = \"list\"\nlist = [\"core\"]\n"));
+    }
+}
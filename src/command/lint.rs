@@ -0,0 +1,488 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `lint` subcommand.
+
+mod cache;
+mod repos;
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use colored::Colorize as _;
+use eyre::Result;
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
+use thiserror::Error;
+
+use crate::{
+    command::helpers::load_config,
+    config::{self, Config},
+    lint::{self, CheckOptions},
+    metrics, success,
+    tracing::LogResult as _,
+    workspace::WorkspaceError,
+};
+
+use self::cache::LintCache;
+
+use super::{
+    helpers::ensure_in_git_repo,
+    revspec::{RevSpec, Revision},
+};
+
+/// The lint command.
+#[derive(Debug, Parser)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "These are independent command-line flags."
+)]
+pub struct Lint {
+    /// The range of commits to check.
+    #[command(flatten)]
+    revspec: RevSpec,
+    /// Ignore the commits older than this one [default: `lint.baseline`].
+    #[arg(long, value_name = "COMMIT")]
+    baseline: Option<String>,
+    /// Check all the commits, even older than the baseline.
+    #[arg(long, conflicts_with = "baseline")]
+    no_baseline: bool,
+    /// Check merge commits even when Git or the forge generated their message.
+    #[arg(long)]
+    check_merge_messages: bool,
+    /// Accept and validate the ` (#123)` suffix added by squash-merges.
+    #[arg(long)]
+    squash_merges: bool,
+    /// Check the message in a file instead of commits, as in a commit-msg hook.
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["RevSpec", "baseline", "no_baseline"],
+    )]
+    message_file: Option<PathBuf>,
+    /// Check all the commits again instead of using the cached verdicts.
+    #[arg(long)]
+    no_cache: bool,
+    /// The number of threads checking the commits [default: number of CPUs].
+    #[arg(short, long, value_name = "N")]
+    jobs: Option<usize>,
+    /// Lint the repositories listed in a file, or matching a glob pattern.
+    #[arg(long, value_name = "FILE|GLOB", conflicts_with = "message_file")]
+    repos: Option<String>,
+    /// Lint all the repositories with this configuration instead of their own.
+    #[arg(long, value_name = "PATH", requires = "repos")]
+    common_config: Option<PathBuf>,
+}
+
+/// The result of linting the commits of a repository.
+#[derive(Debug, Default, Clone, Copy)]
+struct Report {
+    /// The number of commits checked.
+    checked: usize,
+    /// The number of commits not following the convention.
+    invalid: usize,
+}
+
+/// The line after which Git ignores the content of a message file.
+const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+/// Usage errors of `git z lint`.
+#[derive(Debug, Error)]
+pub enum LintError {
+    /// Some commits do not follow the convention.
+    #[error("{count} {} not follow the convention", commits_do(*.count))]
+    InvalidCommits {
+        /// The number of invalid commits.
+        count: usize,
+    },
+    /// The message file cannot be read.
+    #[error("Failed to read {path}")]
+    CannotReadMessageFile {
+        /// The path of the message file.
+        path: PathBuf,
+        /// The error reported by the OS.
+        #[source]
+        source: io::Error,
+    },
+    /// The message in the file does not follow the convention.
+    #[error("The commit message does not follow the convention")]
+    InvalidMessage,
+    /// The file listing the repositories cannot be read.
+    #[error("Failed to read {path}")]
+    CannotReadRepos {
+        /// The path of the file.
+        path: PathBuf,
+        /// The error reported by the OS.
+        #[source]
+        source: io::Error,
+    },
+    /// The glob pattern matching the repositories is invalid.
+    #[error("The repository pattern `{pattern}` is invalid")]
+    InvalidReposPattern {
+        /// The pattern.
+        pattern: String,
+        /// The error reported when expanding the pattern.
+        #[source]
+        source: WorkspaceError,
+    },
+    /// No repository is listed or matches the pattern.
+    #[error("No repository found in `{repos}`")]
+    NoRepos {
+        /// The file or glob pattern.
+        repos: String,
+    },
+    /// The common configuration cannot be read.
+    #[error("Failed to read {path}")]
+    CannotReadCommonConfig {
+        /// The path of the configuration.
+        path: PathBuf,
+        /// The error reported by the OS.
+        #[source]
+        source: io::Error,
+    },
+    /// The thread pool checking the commits cannot be started.
+    #[error("Failed to start the threads checking the commits")]
+    CannotStartThreads(#[source] rayon::ThreadPoolBuildError),
+    /// Some repositories cannot be linted.
+    #[error("{count} {} could not be linted", repositories(*.count))]
+    FailedRepos {
+        /// The number of repositories which cannot be linted.
+        count: usize,
+    },
+}
+
+impl super::Command for Lint {
+    #[tracing::instrument(name = "lint", level = "trace", skip_all)]
+    fn run(&self) -> Result<()> {
+        tracing::info!(params = ?self, "running lint");
+
+        if let Some(repos) = &self.repos {
+            return self.lint_repos(repos);
+        }
+
+        ensure_in_git_repo()?;
+
+        let config = load_config()?;
+
+        metrics::record(|metrics| metrics.lint_runs += 1);
+
+        if let Some(path) = &self.message_file {
+            return self.lint_message_file(&config, path);
+        }
+
+        let report = self.lint_commits(&config)?;
+
+        if report.invalid == 0 {
+            let count = report.checked;
+            let s = if count == 1 { "" } else { "s" };
+            success!(
+                "{count} commit{s} checked, all following the convention."
+            );
+            Ok(())
+        } else {
+            Err(LintError::InvalidCommits {
+                count: report.invalid,
+            })
+            .log_err()?
+        }
+    }
+}
+
+impl Lint {
+    /// Checks the commits of the current repository.
+    ///
+    /// The commits are checked in parallel, then their violations are printed
+    /// in the order of the history.
+    fn lint_commits(&self, config: &Config) -> Result<Report> {
+        let excluded = self
+            .baseline(config)
+            .map(|baseline| vec![format!("{baseline}^@")])
+            .unwrap_or_default();
+
+        let commits = self.revspec.commits(&excluded)?;
+        let mut cache = self.cache(config);
+        let options = self.check_options();
+
+        let checked: Vec<(&Revision, Vec<String>, bool)> =
+            self.thread_pool()?.install(|| {
+                commits
+                    .par_iter()
+                    .filter(|commit| !commit.is_skipped(options))
+                    .map(|commit| match cache.get(&commit.hash) {
+                        Some(violations) => (commit, violations.to_vec(), true),
+                        None => (commit, commit.check(config, options), false),
+                    })
+                    .collect()
+            });
+
+        let mut report = Report {
+            checked: checked.len(),
+            invalid: 0,
+        };
+
+        let mut fresh = 0_usize;
+
+        for (commit, violations, cached) in checked {
+            if !violations.is_empty() {
+                tracing::debug!(hash = ?commit.hash, ?violations, cached);
+                report.invalid += 1;
+                print_violations(commit, &violations);
+            }
+
+            // NOTE: The output of the list commands and the workspaces is not
+            // part of the configuration hash, so verdicts depending on them
+            // are not cached.
+            if !cached && !lint::uses_external_lists(config, &commit.message) {
+                fresh += 1;
+                cache.insert(commit.hash.clone(), violations);
+            }
+        }
+
+        tracing::debug!(fresh, "checked the commits");
+
+        if !self.no_cache && fresh > 0 {
+            drop(cache.save());
+        }
+
+        Ok(report)
+    }
+
+    /// Returns the cache of the verdicts valid for the configuration.
+    fn cache(&self, config: &Config) -> LintCache {
+        if self.no_cache {
+            LintCache::default()
+        } else {
+            LintCache::load(&cache::config_hash(config, &self.squash_merges))
+        }
+    }
+
+    /// Returns the options deciding how the commits are checked.
+    fn check_options(&self) -> CheckOptions {
+        CheckOptions {
+            check_merge_messages: self.check_merge_messages,
+            squash_merges: self.squash_merges,
+            skips_external_lists: false,
+        }
+    }
+
+    /// Builds the thread pool checking the commits.
+    fn thread_pool(&self) -> Result<ThreadPool> {
+        Ok(ThreadPoolBuilder::new()
+            .num_threads(self.jobs.unwrap_or_default())
+            .build()
+            .map_err(LintError::CannotStartThreads)
+            .log_err()?)
+    }
+
+    /// Checks the commits of several repositories and prints a summary.
+    ///
+    /// A repository which cannot be linted is reported in the summary, and the
+    /// other ones are still linted.
+    fn lint_repos(&self, repos: &str) -> Result<()> {
+        let paths = repos::paths(repos)?;
+        let common_config = self.common_config()?;
+        let current_dir = env::current_dir().log_err()?;
+
+        let mut results = vec![];
+
+        for path in paths {
+            println!("{}\n", path.display().to_string().bold());
+
+            let result = env::set_current_dir(current_dir.join(&path))
+                .map_err(Into::into)
+                .and_then(|()| self.lint_repo(common_config.as_deref()));
+
+            env::set_current_dir(&current_dir).log_err()?;
+            results.push((path, result));
+        }
+
+        println!("{}", "Summary:".bold());
+
+        let mut invalid_commits = 0_usize;
+        let mut failed_repos = 0_usize;
+
+        for (path, result) in &results {
+            let path = path.display();
+
+            match result {
+                Ok(report) if report.invalid == 0 => {
+                    let count = report.checked;
+                    let s = if count == 1 { "" } else { "s" };
+                    println!(
+                        "  {path}: {}",
+                        format!("{count} commit{s} checked, all following the convention.")
+                            .green()
+                    );
+                }
+                Ok(report) => {
+                    invalid_commits += report.invalid;
+                    println!(
+                        "  {path}: {}",
+                        format!(
+                            "{} of {} {} not follow the convention.",
+                            report.invalid,
+                            report.checked,
+                            commits_do(report.invalid)
+                        )
+                        .red()
+                    );
+                }
+                Err(error) => {
+                    failed_repos += 1;
+                    println!(
+                        "  {path}: {}",
+                        format!("failed to lint: {error}.").red()
+                    );
+                }
+            }
+        }
+
+        println!();
+
+        if invalid_commits > 0 {
+            Err(LintError::InvalidCommits {
+                count: invalid_commits,
+            })
+            .log_err()?
+        } else if failed_repos > 0 {
+            Err(LintError::FailedRepos {
+                count: failed_repos,
+            })
+            .log_err()?
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks the commits of the repository in the current directory.
+    fn lint_repo(&self, common_config: Option<&str>) -> Result<Report> {
+        ensure_in_git_repo()?;
+
+        let config = match common_config {
+            Some(toml) => {
+                Config::from_toml(toml).map_err(config::LoadError::from)?
+            }
+            None => load_config()?,
+        };
+
+        self.lint_commits(&config)
+    }
+
+    /// Reads the common configuration, if any.
+    ///
+    /// It is parsed once to report errors early, then for each repository.
+    fn common_config(&self) -> Result<Option<String>> {
+        let Some(path) = &self.common_config else {
+            return Ok(None);
+        };
+
+        let toml = fs::read_to_string(path)
+            .map_err(|source| LintError::CannotReadCommonConfig {
+                path: path.clone(),
+                source,
+            })
+            .log_err()?;
+
+        Config::from_toml(&toml)
+            .map_err(config::LoadError::from)
+            .log_err()?;
+
+        Ok(Some(toml))
+    }
+
+    /// Checks the message in a file.
+    ///
+    /// Like Git, the lines after the scissors are ignored.
+    fn lint_message_file(&self, config: &Config, path: &Path) -> Result<()> {
+        let content = fs::read_to_string(path)
+            .map_err(|source| LintError::CannotReadMessageFile {
+                path: path.to_owned(),
+                source,
+            })
+            .log_err()?;
+
+        let message = content
+            .split_once(SCISSORS)
+            .map_or(content.as_str(), |(message, _)| message);
+
+        if self.check_options().skips(message) {
+            tracing::debug!("skipping merge message");
+            return Ok(());
+        }
+
+        let violations = self.check_options().check(config, message);
+
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        tracing::debug!(?violations);
+
+        for violation in &violations {
+            eprintln!("  - {violation}.");
+        }
+
+        eprintln!();
+        Err(LintError::InvalidMessage).log_err()?
+    }
+
+    /// Returns the baseline to use, if any.
+    fn baseline<'a>(&'a self, config: &'a Config) -> Option<&'a str> {
+        if self.no_baseline {
+            None
+        } else {
+            self.baseline.as_deref().or_else(|| {
+                config
+                    .lint
+                    .as_ref()
+                    .and_then(|lint| lint.baseline.as_deref())
+            })
+        }
+    }
+}
+
+/// Prints the violations found in a commit.
+pub fn print_violations(commit: &Revision, violations: &[String]) {
+    let short_hash = commit.hash.get(..10).unwrap_or(&commit.hash);
+    let subject = commit.message.lines().next().unwrap_or_default();
+
+    println!("{} {subject}", short_hash.yellow());
+
+    for violation in violations {
+        println!("  - {violation}.");
+    }
+
+    println!();
+}
+
+/// Returns the proper wording for a number of repositories.
+fn repositories(count: usize) -> &'static str {
+    if count == 1 {
+        "repository"
+    } else {
+        "repositories"
+    }
+}
+
+/// Returns the proper wording for a number of commits.
+fn commits_do(count: usize) -> &'static str {
+    if count == 1 {
+        "commit does"
+    } else {
+        "commits do"
+    }
+}
@@ -0,0 +1,132 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The cache of the verdicts of `git z lint`.
+//!
+//! The violations found in each commit are stored in `.git/git-z/lint-cache`,
+//! keyed by commit hash, so that later runs only check the new commits. The
+//! cache also records a hash of the configuration and the lint options: when
+//! they change, it is discarded and all the commits are checked again. The
+//! verdicts depending on a list command or a workspace are not cached, since
+//! their output is not part of the hash.
+//!
+//! The cache is only an optimisation: when it cannot be read or written, the
+//! commits are checked as if there was no cache.
+
+use std::{collections::BTreeMap, fs};
+
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commit_cache::gitz_dir,
+    config::Config,
+    helpers::{fnv1a, write_atomically},
+    tracing::LogResult as _,
+};
+
+/// The name of the cache file in the git-z directory.
+const LINT_CACHE_FILE_NAME: &str = "lint-cache";
+
+/// The verdicts of the previous runs of `git z lint`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LintCache {
+    /// The hash of the configuration and options the verdicts are valid for.
+    config_hash: String,
+    /// The violations found in each commit, by commit hash.
+    verdicts: BTreeMap<String, Vec<String>>,
+}
+
+impl LintCache {
+    /// Loads the cache, or returns an empty one if it is not valid for
+    /// `config_hash`.
+    #[tracing::instrument(level = "trace")]
+    pub fn load(config_hash: &str) -> Self {
+        let cache = gitz_dir()
+            .ok()
+            .and_then(|dir| {
+                fs::read_to_string(dir.join(LINT_CACHE_FILE_NAME)).ok()
+            })
+            .and_then(|json| serde_json::from_str::<Self>(&json).ok())
+            .filter(|cache| cache.config_hash == config_hash);
+
+        if let Some(cache) = cache {
+            tracing::debug!(count = cache.verdicts.len(), "loaded the cache");
+            cache
+        } else {
+            tracing::debug!("starting from an empty cache");
+            Self {
+                config_hash: config_hash.to_owned(),
+                verdicts: BTreeMap::new(),
+            }
+        }
+    }
+
+    /// Returns the violations previously found in a commit, if any.
+    pub fn get(&self, hash: &str) -> Option<&[String]> {
+        self.verdicts.get(hash).map(Vec::as_slice)
+    }
+
+    /// Records the violations found in a commit.
+    pub fn insert(&mut self, hash: String, violations: Vec<String>) {
+        self.verdicts.insert(hash, violations);
+    }
+
+    /// Writes the cache in the git-z directory.
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn save(&self) -> Result<()> {
+        let gitz_dir = gitz_dir()?;
+        fs::create_dir_all(&gitz_dir).log_err()?;
+
+        let json = serde_json::to_string(self).log_err()?;
+        write_atomically(&gitz_dir.join(LINT_CACHE_FILE_NAME), &json)
+            .log_err()?;
+
+        Ok(())
+    }
+}
+
+/// Computes the hash of a configuration and the options affecting the verdicts.
+///
+/// The version of git-z is part of the hash, since the rules can evolve.
+pub fn config_hash(config: &Config, options: &impl Serialize) -> String {
+    let key =
+        serde_json::to_string(&(env!("CARGO_PKG_VERSION"), config, options))
+            .unwrap_or_default();
+
+    format!("{:016x}", fnv1a(key.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    #[test]
+    fn changes_the_config_hash_with_the_options() {
+        let config = Config::default();
+        assert_ne!(config_hash(&config, &false), config_hash(&config, &true));
+    }
+
+    #[test]
+    fn changes_the_config_hash_with_the_config() {
+        let config = Config::default();
+        let mut other = Config::default();
+        other.types.shift_remove("feat");
+
+        assert_ne!(config_hash(&config, &()), config_hash(&other, &()));
+    }
+}
@@ -0,0 +1,93 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The list of repositories linted by `git z lint --repos`.
+//!
+//! The repositories are either listed in a file, one per line, or matched by a
+//! glob pattern.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eyre::Result;
+
+use crate::{tracing::LogResult as _, workspace};
+
+use super::LintError;
+
+/// Returns the paths of the repositories to lint.
+///
+/// When `repos` is a file, each line is a path, relative to the directory of
+/// the file. Blank lines and lines starting with `#` are ignored. Otherwise,
+/// `repos` is a glob pattern matching directories.
+#[tracing::instrument(level = "trace")]
+pub fn paths(repos: &str) -> Result<Vec<PathBuf>> {
+    let path = Path::new(repos);
+
+    let paths = if path.is_file() {
+        from_file(path)?
+    } else {
+        from_pattern(repos)?
+    };
+
+    tracing::debug!(?paths);
+
+    if paths.is_empty() {
+        Err(LintError::NoRepos {
+            repos: repos.to_owned(),
+        })
+        .log_err()?;
+    }
+
+    Ok(paths)
+}
+
+/// Reads the paths of the repositories listed in a file.
+fn from_file(path: &Path) -> Result<Vec<PathBuf>> {
+    let content = fs::read_to_string(path)
+        .map_err(|source| LintError::CannotReadRepos {
+            path: path.to_owned(),
+            source,
+        })
+        .log_err()?;
+
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| dir.join(line))
+        .collect())
+}
+
+/// Expands a glob pattern into the matching directories.
+fn from_pattern(pattern: &str) -> Result<Vec<PathBuf>> {
+    let (root, relative) = match pattern.strip_prefix('/') {
+        Some(relative) => (Path::new("/"), relative),
+        None => (Path::new(""), pattern),
+    };
+
+    let dirs = workspace::expand(&root.join("."), relative)
+        .map_err(|source| LintError::InvalidReposPattern {
+            pattern: pattern.to_owned(),
+            source,
+        })
+        .log_err()?;
+
+    Ok(dirs.into_iter().map(|dir| root.join(dir)).collect())
+}
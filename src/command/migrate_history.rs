@@ -0,0 +1,366 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `migrate-history` subcommand.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{self, Path, PathBuf},
+    process::Command,
+};
+
+use clap::Parser;
+use colored::Colorize as _;
+use eyre::Result;
+use inquire::Confirm;
+use thiserror::Error;
+
+use crate::{
+    command::helpers::{complete_lists, load_config},
+    commit_cache::{gitz_dir, CommitCache, WizardState},
+    config::Config,
+    git::{self, GitError},
+    hint, lint,
+    reword::{merge_messages, prefilled_answers},
+    success,
+    tracing::LogResult as _,
+    warning,
+};
+
+use super::{
+    commit::{self, context::CommitContext, WizardUi},
+    helpers::ensure_in_git_worktree,
+    revspec::{RevSpec, Revision},
+};
+
+/// The migrate-history command.
+#[derive(Debug, Parser)]
+pub struct MigrateHistory {
+    /// The range of commits to rewrite.
+    #[command(flatten)]
+    revspec: RevSpec,
+}
+
+/// Usage errors of `git z migrate-history`.
+#[derive(Debug, Error)]
+pub enum MigrateHistoryError {
+    /// A commit is in progress with `git z commit`.
+    #[error("A commit is in progress")]
+    CommitInProgress,
+    /// The worktree has uncommitted changes.
+    #[error("The worktree contains uncommitted changes")]
+    DirtyWorktree,
+    /// A commit to rewrite is not part of the current branch.
+    #[error("The commit {hash} is not part of the current branch")]
+    NotInCurrentBranch {
+        /// The hash of the commit.
+        hash: String,
+    },
+    /// There are merge commits on top of the commits to rewrite.
+    #[error("The history to rewrite contains merge commits")]
+    MergeInHistory,
+    /// An error has occurred while running Git.
+    #[error("Failed to read the history")]
+    Git(#[from] GitError),
+    /// The instructions for the rebase cannot be written.
+    #[error("Failed to write the rebase instructions")]
+    WriteError(#[source] io::Error),
+    /// The rebase has failed.
+    #[error("The rebase has failed")]
+    RebaseFailed,
+}
+
+/// The name of the directory where to write the rebase instructions.
+const MIGRATE_DIR_NAME: &str = "migrate-history";
+
+impl super::Command for MigrateHistory {
+    #[tracing::instrument(name = "migrate_history", level = "trace", skip_all)]
+    fn run(&self) -> Result<()> {
+        tracing::info!(params = ?self, "running migrate-history");
+
+        ensure_in_git_worktree()?;
+        let _lock = CommitCache::lock()?;
+        ensure_no_commit_in_progress()?;
+        ensure_clean_worktree()?;
+
+        let mut config = load_config()?;
+        complete_lists(&mut config)?;
+
+        let commits = self
+            .revspec
+            .commits(&[])?
+            .into_iter()
+            .filter(|commit| {
+                !lint::check_message(&config, &commit.message).is_empty()
+            })
+            .collect::<Vec<_>>();
+
+        let Some(oldest) = commits.last() else {
+            success!("All the commits already follow the convention.");
+            return Ok(());
+        };
+
+        let history = history_since(&oldest.hash)?;
+        ensure_rewritable(&commits, &history)?;
+
+        if !confirm_rewrite(commits.len())? {
+            hint!("The history has been left untouched.");
+            return Ok(());
+        }
+
+        let result = rewrite_history(&config, &commits, &history);
+        let _ = CommitCache::discard().ok();
+        result
+    }
+}
+
+/// A commit in the history to rebase.
+#[derive(Debug)]
+struct HistoryEntry {
+    /// The hash of the commit.
+    hash: String,
+    /// The hashes of the parents of the commit.
+    parents: Vec<String>,
+}
+
+/// Asks for the new messages, then rebases the history to use them.
+#[tracing::instrument(level = "trace", skip_all)]
+fn rewrite_history(
+    config: &Config,
+    commits: &[Revision],
+    history: &[HistoryEntry],
+) -> Result<()> {
+    let mut new_messages = HashMap::new();
+
+    for (index, commit) in commits.iter().rev().enumerate() {
+        println!(
+            "\n[{}/{}] {} {}",
+            index + 1,
+            commits.len(),
+            short_hash(&commit.hash).yellow(),
+            commit.message.lines().next().unwrap_or_default()
+        );
+
+        let message = ask_new_message(config, commit)?;
+        new_messages.insert(commit.hash.clone(), message);
+    }
+
+    let migrate_dir = migrate_dir()?;
+    let result = rebase(history, &new_messages, &migrate_dir);
+    let _ = fs::remove_dir_all(&migrate_dir).ok();
+    result?;
+
+    success!("The history has been rewritten.");
+    hint! {"
+        If the branch has already been pushed, you need to force-push it, for
+        instance with `git push --force-with-lease`.
+    "};
+
+    Ok(())
+}
+
+/// Runs the wizard prefilled from the old message to build a new one.
+fn ask_new_message(config: &Config, commit: &Revision) -> Result<String> {
+    let mut cache = CommitCache {
+        wizard_state: WizardState::Ongoing,
+        wizard_answers: prefilled_answers(config, &commit.message),
+        ..CommitCache::default()
+    };
+
+    let rendered = commit::run_wizard(
+        config,
+        &mut cache,
+        &mut CommitContext::default(),
+        WizardUi::Prompts,
+    )?;
+    Ok(merge_messages(config, &rendered, &commit.message))
+}
+
+/// Rebases the history, replacing the messages of the rewritten commits.
+#[tracing::instrument(level = "trace", skip_all)]
+fn rebase(
+    history: &[HistoryEntry],
+    new_messages: &HashMap<String, String>,
+    migrate_dir: &Path,
+) -> Result<(), MigrateHistoryError> {
+    fs::create_dir_all(migrate_dir)
+        .map_err(MigrateHistoryError::WriteError)
+        .log_err()?;
+
+    let mut todo = vec![];
+
+    for entry in history {
+        todo.push(format!("pick {}", entry.hash));
+
+        if let Some(message) = new_messages.get(&entry.hash) {
+            let message_file = migrate_dir.join(&entry.hash);
+            fs::write(&message_file, message)
+                .map_err(MigrateHistoryError::WriteError)
+                .log_err()?;
+
+            todo.push(format!(
+                "exec git commit --amend --allow-empty --no-verify --quiet \
+                    --cleanup=verbatim --file={}",
+                shell_quote(&message_file.to_string_lossy())
+            ));
+        }
+    }
+
+    let todo_file = migrate_dir.join("git-rebase-todo");
+    fs::write(&todo_file, todo.join("\n"))
+        .map_err(MigrateHistoryError::WriteError)
+        .log_err()?;
+
+    let mut git_rebase = Command::new("git");
+    git_rebase
+        .args(["rebase", "--interactive", "--no-autosquash", "--quiet"])
+        .env(
+            "GIT_SEQUENCE_EDITOR",
+            format!("cp {}", shell_quote(&todo_file.to_string_lossy())),
+        );
+
+    match history.first().and_then(|entry| entry.parents.first()) {
+        Some(parent) => git_rebase.arg(parent),
+        None => git_rebase.arg("--root"),
+    };
+
+    tracing::debug!(?git_rebase, "calling git rebase");
+    let status = git_rebase.status().map_err(GitError::CannotRun).log_err()?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(MigrateHistoryError::RebaseFailed).log_err()
+    }
+}
+
+/// Ensures no commit is in progress with `git z commit`.
+fn ensure_no_commit_in_progress() -> Result<()> {
+    if CommitCache::load()?.wizard_state == WizardState::NotStarted {
+        Ok(())
+    } else {
+        Err(MigrateHistoryError::CommitInProgress).log_err()?
+    }
+}
+
+/// Ensures the worktree does not contain uncommitted changes.
+fn ensure_clean_worktree() -> Result<(), MigrateHistoryError> {
+    if git(&["status", "--porcelain", "--untracked-files=no"])?.is_empty() {
+        Ok(())
+    } else {
+        Err(MigrateHistoryError::DirtyWorktree).log_err()
+    }
+}
+
+/// Ensures the commits can be rewritten by rebasing the history.
+fn ensure_rewritable(
+    commits: &[Revision],
+    history: &[HistoryEntry],
+) -> Result<(), MigrateHistoryError> {
+    if history.iter().any(|entry| entry.parents.len() > 1) {
+        return Err(MigrateHistoryError::MergeInHistory).log_err();
+    }
+
+    for commit in commits {
+        if !history.iter().any(|entry| entry.hash == commit.hash) {
+            return Err(MigrateHistoryError::NotInCurrentBranch {
+                hash: short_hash(&commit.hash).to_owned(),
+            })
+            .log_err();
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists the commits from `oldest` to `HEAD`, from the oldest.
+#[tracing::instrument(level = "trace")]
+fn history_since(
+    oldest: &str,
+) -> Result<Vec<HistoryEntry>, MigrateHistoryError> {
+    let history = git(&[
+        "rev-list",
+        "--reverse",
+        "--topo-order",
+        "--parents",
+        "HEAD",
+        "--not",
+        &format!("{oldest}^@"),
+        "--",
+    ])?
+    .lines()
+    .filter_map(|line| {
+        let mut hashes = line.split_whitespace().map(ToOwned::to_owned);
+        hashes.next().map(|hash| HistoryEntry {
+            hash,
+            parents: hashes.collect(),
+        })
+    })
+    .collect::<Vec<_>>();
+
+    tracing::debug!(?history);
+    Ok(history)
+}
+
+/// Asks the user to confirm the rewrite of the history.
+fn confirm_rewrite(count: usize) -> Result<bool> {
+    let s = if count == 1 { "" } else { "s" };
+
+    warning! {"
+
+        Found {count} commit{s} not following the convention.
+
+        This command REWRITES THE HISTORY of the current branch: the hash of
+        these commits and of all the commits on top of them will change. Do not
+        use it on a branch other people have based their work on, unless you
+        have agreed to do so with them.
+
+        You will be asked for a new message for each commit, starting from the
+        oldest. The history is only rewritten after all the messages have been
+        entered.
+    "};
+
+    Ok(Confirm::new("Do you want to rewrite the history?")
+        .with_default(false)
+        .prompt()
+        .log_err()?)
+}
+
+/// Returns the path of the directory where to write the rebase instructions.
+fn migrate_dir() -> Result<PathBuf> {
+    let migrate_dir = gitz_dir()?.join(MIGRATE_DIR_NAME);
+
+    // NOTE: The path is used from the rebase, which can run from another
+    // directory, so it must be absolute.
+    Ok(path::absolute(migrate_dir)
+        .map_err(MigrateHistoryError::WriteError)
+        .log_err()?)
+}
+
+/// Runs a git command and returns its trimmed output.
+fn git(args: &[&str]) -> Result<String, MigrateHistoryError> {
+    Ok(git::run_trimmed(args)?)
+}
+
+/// Returns the short version of a commit hash.
+fn short_hash(hash: &str) -> &str {
+    hash.get(..10).unwrap_or(hash)
+}
+
+/// Quotes a string to be used as a single argument in a shell command.
+fn shell_quote(string: &str) -> String {
+    format!("'{}'", string.replace('\'', r"'\''"))
+}
@@ -0,0 +1,259 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Selection of commit ranges for the commands working on the history.
+
+use std::{
+    io::{BufRead as _, BufReader},
+    process::{Command, Stdio},
+};
+
+use clap::Args;
+use thiserror::Error;
+
+use crate::{
+    config::Config,
+    git::{self, GitError},
+    lint::CheckOptions,
+    tracing::LogResult as _,
+};
+
+/// Options to select a range of commits.
+#[derive(Debug, Args)]
+pub struct RevSpec {
+    /// The revision range to consider, like `main..HEAD` [default: HEAD].
+    #[arg(
+        value_name = "REVISION_RANGE",
+        conflicts_with_all = ["since_tag", "base"],
+    )]
+    range: Option<String>,
+    /// Only consider the commits since the latest tag.
+    #[arg(long, conflicts_with = "base")]
+    since_tag: bool,
+    /// Only consider the commits more recent than a date.
+    #[arg(long, value_name = "DATE")]
+    since: Option<String>,
+    /// Only consider the commits not merged yet in a base branch.
+    #[arg(long, value_name = "BRANCH")]
+    base: Option<String>,
+    /// Include merge commits (default).
+    #[arg(long, overrides_with = "no_merges")]
+    merges: bool,
+    /// Exclude merge commits.
+    #[arg(long, overrides_with = "merges")]
+    no_merges: bool,
+}
+
+/// A commit from the history.
+#[derive(Debug, Clone)]
+pub struct Revision {
+    /// The hash of the commit.
+    pub hash: String,
+    /// The raw message of the commit.
+    pub message: String,
+    /// Whether the commit is a merge commit.
+    pub is_merge: bool,
+    /// The name of the author.
+    pub author: String,
+    /// The email of the author.
+    pub author_email: String,
+    /// The date of the commit, in the strict ISO 8601 format.
+    pub date: String,
+}
+
+impl Revision {
+    /// Returns whether the commit is a merge left out of the check.
+    pub fn is_skipped(&self, options: CheckOptions) -> bool {
+        let skip = self.is_merge && options.skips(&self.message);
+
+        if skip {
+            tracing::debug!(hash = ?self.hash, "skipping merge commit");
+        }
+
+        skip
+    }
+
+    /// Checks the message of the commit, and returns the violations.
+    pub fn check(&self, config: &Config, options: CheckOptions) -> Vec<String> {
+        options
+            .check(config, &self.message)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+}
+
+/// The format of the records read from `git log`.
+const LOG_FORMAT: &str = "--format=%H %P%n%aN%n%aE%n%aI%n%B";
+
+/// Errors that can occur when resolving a range of commits.
+#[derive(Debug, Error)]
+pub enum RevSpecError {
+    /// An error has occurred while running Git.
+    #[error("Failed to list the commits to check")]
+    Git(#[from] GitError),
+    /// There is no tag to start the range from.
+    #[error("There is no tag reachable from HEAD")]
+    NoTag,
+}
+
+impl RevSpec {
+    /// Returns the revision range selected by the options.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub fn range(&self) -> Result<String, RevSpecError> {
+        let range = if let Some(range) = &self.range {
+            range.clone()
+        } else if self.since_tag {
+            let tag = git(&["describe", "--tags", "--abbrev=0"])
+                .map_err(|_error| RevSpecError::NoTag)
+                .log_err()?;
+            format!("{tag}..HEAD")
+        } else if let Some(base) = &self.base {
+            let merge_base = git(&["merge-base", base, "HEAD"])?;
+            format!("{merge_base}..HEAD")
+        } else {
+            String::from("HEAD")
+        };
+
+        tracing::debug!(?range);
+        Ok(range)
+    }
+
+    /// Returns the arguments to pass to `git log` to select the commits.
+    pub fn log_args(&self) -> Result<Vec<String>, RevSpecError> {
+        let mut args = vec![self.range()?];
+
+        if let Some(since) = &self.since {
+            args.push(format!("--since={since}"));
+        }
+
+        if self.no_merges {
+            args.push(String::from("--no-merges"));
+        }
+
+        Ok(args)
+    }
+
+    /// Lists the commits selected by the options, from the newest.
+    ///
+    /// Commits reachable from any of the `excluded` revisions are left out.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub fn commits(
+        &self,
+        excluded: &[String],
+    ) -> Result<Vec<Revision>, RevSpecError> {
+        let log_args = self.log_args()?;
+
+        let mut args = vec!["log", "-z", LOG_FORMAT];
+        args.extend(log_args.iter().map(String::as_str));
+
+        if !excluded.is_empty() {
+            args.push("--not");
+            args.extend(excluded.iter().map(String::as_str));
+        }
+
+        args.push("--");
+
+        let commits = git_log(&args)?;
+        tracing::debug!(count = commits.len(), "listed the commits");
+        Ok(commits)
+    }
+}
+
+/// Lists the commits reachable from `tip` but from none of the refs, from the
+/// newest.
+///
+/// In a `pre-receive` hook, these are the commits introduced by a push, as the
+/// refs are only updated once the hook has accepted it.
+#[tracing::instrument(level = "trace")]
+pub fn new_commits(tip: &str) -> Result<Vec<Revision>, RevSpecError> {
+    let commits =
+        git_log(&["log", "-z", LOG_FORMAT, tip, "--not", "--all", "--"])?;
+    tracing::debug!(count = commits.len(), "listed the new commits");
+    Ok(commits)
+}
+
+/// Runs `git log` and parses the commits as its output is read.
+///
+/// Reading the records one by one avoids holding the whole output of large
+/// histories in memory next to the parsed commits.
+#[tracing::instrument(level = "trace")]
+fn git_log(args: &[&str]) -> Result<Vec<Revision>, RevSpecError> {
+    let mut git = Command::new("git")
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(GitError::CannotRun)
+        .log_err()?;
+
+    let mut commits = vec![];
+
+    if let Some(stdout) = git.stdout.take() {
+        for record in BufReader::new(stdout).split(b'\0') {
+            let record = record.map_err(GitError::CannotRun).log_err()?;
+            let record = String::from_utf8(record)
+                .map_err(GitError::Encoding)
+                .log_err()?;
+
+            if let Some(commit) = parse_record(record.trim_start_matches('\n'))
+            {
+                commits.push(commit);
+            }
+        }
+    }
+
+    let output = git
+        .wait_with_output()
+        .map_err(GitError::CannotRun)
+        .log_err()?;
+
+    if output.status.success() {
+        Ok(commits)
+    } else {
+        Err(RevSpecError::from(GitError::Failed(
+            String::from_utf8(output.stderr)
+                .map_err(GitError::Encoding)
+                .log_err()?
+                .trim()
+                .to_owned(),
+        )))
+        .log_err()
+    }
+}
+
+/// Parses a record of `git log` in the [`LOG_FORMAT`].
+fn parse_record(record: &str) -> Option<Revision> {
+    let mut fields = record.splitn(5, '\n');
+    let mut hashes = fields.next()?.split_whitespace();
+    let author = fields.next()?.to_owned();
+    let author_email = fields.next()?.to_owned();
+    let date = fields.next()?.to_owned();
+    let message = fields.next().unwrap_or_default();
+
+    Some(Revision {
+        hash: hashes.next().unwrap_or_default().to_owned(),
+        message: message.trim_end().to_owned(),
+        is_merge: hashes.count() > 1,
+        author,
+        author_email,
+        date,
+    })
+}
+
+/// Runs a git command and returns its trimmed output.
+fn git(args: &[&str]) -> Result<String, RevSpecError> {
+    Ok(git::run_trimmed(args)?)
+}
@@ -0,0 +1,397 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `serve` subcommand.
+//!
+//! It exposes the steps of the wizard over JSON-RPC 2.0 on the standard input
+//! and output, so that editor integrations can build their own UI on top of
+//! git-z. Each request and response is a JSON object on its own line.
+//!
+//! The available methods are:
+//!
+//! - `list_types`, returning the commit types and their description,
+//! - `list_scopes`, returning the kind of accepted scopes and their list,
+//! - `validate_description`, checking a description like the wizard does,
+//! - `render_message`, rendering the commit message from the answers,
+//! - `commit`, rendering the commit message and calling `git commit`.
+
+use std::{
+    io::{self, BufRead as _, Write as _},
+    process::{Command, Stdio},
+};
+
+use clap::Parser;
+use eyre::{Report, Result};
+use itertools::Itertools as _;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    command::helpers::{complete_lists, load_config},
+    config::{Config, Scopes},
+    lint,
+    tracing::LogResult as _,
+};
+
+use super::{
+    commit::{render_message, unedited_message, CommitMessage},
+    helpers::ensure_in_git_worktree,
+};
+
+/// The version of JSON-RPC implemented by the server.
+const JSONRPC_VERSION: &str = "2.0";
+
+/// The arguments making `git commit` interact with the user.
+///
+/// The standard input and output are used by the protocol, so they are refused.
+const INTERACTIVE_ARGS: &[&str] =
+    &["-e", "--edit", "--interactive", "-p", "--patch"];
+
+/// The serve command.
+#[derive(Debug, Parser)]
+pub struct Serve;
+
+/// A JSON-RPC request.
+#[derive(Debug, Deserialize)]
+struct Request {
+    /// The version of the protocol.
+    jsonrpc: String,
+    /// The ID of the request, absent for notifications.
+    id: Option<Value>,
+    /// The method to call.
+    method: String,
+    /// The parameters of the method.
+    #[serde(default)]
+    params: Value,
+}
+
+/// A JSON-RPC response.
+#[derive(Debug, Serialize)]
+struct Response {
+    /// The version of the protocol.
+    jsonrpc: &'static str,
+    /// The ID of the request.
+    id: Value,
+    /// The result of the method, on success.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    /// The error, on failure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+/// A JSON-RPC error.
+#[derive(Debug, Serialize)]
+struct RpcError {
+    /// The error code.
+    code: i64,
+    /// A short description of the error.
+    message: String,
+    /// Additional information about the error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
+/// The parameters of `validate_description`.
+#[derive(Debug, Deserialize)]
+struct ValidateDescriptionParams {
+    /// The description to validate.
+    description: String,
+}
+
+/// The parameters of `commit`.
+#[derive(Debug, Deserialize)]
+struct CommitParams {
+    /// The answers to the wizard questions.
+    #[serde(flatten)]
+    answers: CommitMessage,
+    /// Extra arguments to be passed to `git commit`.
+    #[serde(default)]
+    extra_args: Vec<String>,
+}
+
+impl super::Command for Serve {
+    #[tracing::instrument(name = "serve", level = "trace", skip_all)]
+    fn run(&self) -> Result<()> {
+        tracing::info!(params = ?self, "running serve");
+
+        ensure_in_git_worktree()?;
+
+        let mut config = load_config()?;
+        let mut stdout = io::stdout().lock();
+
+        for line in io::stdin().lock().lines() {
+            let line = line.log_err()?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if let Some(response) = handle_line(&mut config, &line) {
+                let response = serde_json::to_string(&response).log_err()?;
+                writeln!(stdout, "{response}").log_err()?;
+                stdout.flush().log_err()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl RpcError {
+    /// The JSON sent is not valid.
+    const PARSE_ERROR: i64 = -32_700;
+    /// The JSON sent is not a valid request.
+    const INVALID_REQUEST: i64 = -32_600;
+    /// The method does not exist.
+    const METHOD_NOT_FOUND: i64 = -32_601;
+    /// The parameters of the method are invalid.
+    const INVALID_PARAMS: i64 = -32_602;
+    /// An internal error has occurred.
+    const INTERNAL_ERROR: i64 = -32_603;
+    /// The answers do not produce a valid commit message.
+    const INVALID_MESSAGE: i64 = -32_000;
+    /// Git has returned an error.
+    const GIT_ERROR: i64 = -32_001;
+
+    /// Builds an error without additional data.
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+impl Response {
+    /// Builds a successful response.
+    const fn success(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION,
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    /// Builds an error response.
+    const fn error(id: Value, error: RpcError) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION,
+            id,
+            result: None,
+            error: Some(error),
+        }
+    }
+}
+
+impl From<Report> for RpcError {
+    fn from(report: Report) -> Self {
+        Self::new(Self::INTERNAL_ERROR, report.chain().join(": "))
+    }
+}
+
+/// Handles a line of input, returning the response to send if any.
+#[tracing::instrument(level = "trace", skip(config))]
+fn handle_line(config: &mut Config, line: &str) -> Option<Response> {
+    let request = match serde_json::from_str::<Request>(line) {
+        Ok(request) => request,
+        Err(error) => {
+            let code = if error.is_syntax() || error.is_eof() {
+                RpcError::PARSE_ERROR
+            } else {
+                RpcError::INVALID_REQUEST
+            };
+
+            return Some(Response::error(
+                Value::Null,
+                RpcError::new(code, error.to_string()),
+            ));
+        }
+    };
+
+    let result = if request.jsonrpc == JSONRPC_VERSION {
+        handle_request(config, &request.method, request.params)
+    } else {
+        Err(RpcError::new(
+            RpcError::INVALID_REQUEST,
+            format!("Unsupported JSON-RPC version {}", request.jsonrpc),
+        ))
+    };
+
+    tracing::debug!(?result);
+
+    // NOTE: Requests without ID are notifications, which get no response.
+    let id = request.id?;
+    Some(match result {
+        Ok(result) => Response::success(id, result),
+        Err(error) => Response::error(id, error),
+    })
+}
+
+/// Handles a request and returns its result.
+///
+/// The lists of the configuration are completed by the first method using
+/// them, so that a session which does not need them runs no command.
+fn handle_request(
+    config: &mut Config,
+    method: &str,
+    params: Value,
+) -> Result<Value, RpcError> {
+    match method {
+        "list_types" => {
+            complete_lists(config)?;
+            Ok(list_types(config))
+        }
+        "list_scopes" => {
+            complete_lists(config)?;
+            Ok(list_scopes(config))
+        }
+        "validate_description" => {
+            Ok(validate_description(&parse_params(params)?))
+        }
+        "render_message" => {
+            complete_lists(config)?;
+            render(config, &parse_params(params)?)
+        }
+        "commit" => {
+            complete_lists(config)?;
+            commit(config, &parse_params(params)?)
+        }
+        _ => Err(RpcError::new(
+            RpcError::METHOD_NOT_FOUND,
+            format!("Unknown method {method}"),
+        )),
+    }
+}
+
+/// Parses the parameters of a method.
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|error| {
+        RpcError::new(RpcError::INVALID_PARAMS, error.to_string())
+    })
+}
+
+/// Lists the commit types and their description.
+fn list_types(config: &Config) -> Value {
+    config
+        .types
+        .iter()
+        .map(|(name, r#type)| {
+            json!({ "name": name, "description": r#type.description() })
+        })
+        .collect()
+}
+
+/// Lists the accepted scopes.
+fn list_scopes(config: &Config) -> Value {
+    match &config.scopes {
+        None => json!({ "accept": "none" }),
+        Some(Scopes::Any { required }) => {
+            json!({ "accept": "any", "required": required })
+        }
+        Some(Scopes::List { list, required, .. }) => {
+            json!({
+                "accept": "list",
+                "list": list.names(),
+                "required": required,
+            })
+        }
+    }
+}
+
+/// Validates a commit description.
+fn validate_description(params: &ValidateDescriptionParams) -> Value {
+    match lint::check_description(&params.description) {
+        Some(violation) => {
+            json!({ "valid": false, "reason": violation.to_string() })
+        }
+        None => json!({ "valid": true }),
+    }
+}
+
+/// Renders the commit message from the answers.
+fn render(config: &Config, answers: &CommitMessage) -> Result<Value, RpcError> {
+    let message = render_and_check(config, answers)?;
+    Ok(json!({ "message": message }))
+}
+
+/// Renders the commit message from the answers and calls `git commit`.
+fn commit(config: &Config, params: &CommitParams) -> Result<Value, RpcError> {
+    if let Some(arg) = params
+        .extra_args
+        .iter()
+        .find(|arg| INTERACTIVE_ARGS.contains(&arg.as_str()))
+    {
+        return Err(RpcError::new(
+            RpcError::INVALID_PARAMS,
+            format!("`{arg}` cannot be passed to `git commit` from the server"),
+        ));
+    }
+
+    let message = render_and_check(config, &params.answers)?;
+
+    // NOTE: The standard input and output are used by the protocol, so Git
+    // must neither open an editor, read from them nor print anything to them.
+    let git_commit = Command::new("git")
+        .arg("commit")
+        .args(&params.extra_args)
+        .args(["-m", &unedited_message(config, &message)])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(Report::from)
+        .log_err()?;
+
+    let output = String::from_utf8_lossy(&git_commit.stdout).into_owned();
+
+    if git_commit.status.success() {
+        Ok(json!({ "message": message, "output": output }))
+    } else {
+        Err(RpcError {
+            code: RpcError::GIT_ERROR,
+            message: String::from("Git has returned an error"),
+            data: Some(json!({
+                "status_code": git_commit.status.code(),
+                "output": output,
+                "error": String::from_utf8_lossy(&git_commit.stderr),
+            })),
+        })
+    }
+}
+
+/// Renders the commit message, checking it follows the convention.
+fn render_and_check(
+    config: &Config,
+    answers: &CommitMessage,
+) -> Result<String, RpcError> {
+    let message = render_message(config, answers)?;
+    let violations = lint::check_message(config, &message);
+
+    if violations.is_empty() {
+        Ok(message)
+    } else {
+        Err(RpcError {
+            code: RpcError::INVALID_MESSAGE,
+            message: String::from("The commit message is invalid"),
+            data: Some(json!({
+                "message": message,
+                "violations": violations
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>(),
+            })),
+        })
+    }
+}
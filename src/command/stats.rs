@@ -0,0 +1,102 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `stats` subcommand.
+//!
+//! With `--self`, it shows the usage metrics recorded locally when they are
+//! enabled. They are never transmitted anywhere.
+
+use std::time::Duration;
+
+use clap::Parser;
+use colored::Colorize as _;
+use eyre::Result;
+
+use crate::{
+    hint,
+    metrics::{self, Metrics, METRICS_GIT_CONFIG},
+    warning,
+};
+
+use super::helpers::ensure_in_git_repo;
+
+/// The stats command.
+#[derive(Debug, Parser)]
+pub struct Stats {
+    /// Show your own usage of git-z in this repository, recorded locally.
+    #[arg(long = "self", required = true)]
+    own: bool,
+}
+
+impl super::Command for Stats {
+    #[tracing::instrument(name = "stats", level = "trace", skip_all)]
+    fn run(&self) -> Result<()> {
+        tracing::info!(params = ?self, "running stats");
+
+        ensure_in_git_repo()?;
+
+        if !metrics::is_enabled() {
+            warning!("The usage metrics are not enabled.");
+            hint!(
+                "You can enable them by running `git config --global \
+                {METRICS_GIT_CONFIG} true`. They are only written locally, \
+                and never transmitted."
+            );
+        }
+
+        match Metrics::load()? {
+            Some(metrics) => print_metrics(&metrics),
+            None => println!("No usage has been recorded yet."),
+        }
+
+        Ok(())
+    }
+}
+
+/// Prints the usage metrics.
+fn print_metrics(metrics: &Metrics) {
+    let average = metrics
+        .average_wizard_duration()
+        .map_or_else(|| String::from("-"), format_duration);
+
+    println!("{}", "Usage of git-z in this repository".bold());
+    println!("  Commits with the wizard:  {}", metrics.wizard_commits);
+    println!("  Average wizard duration:  {average}");
+    println!("  Runs of git z lint:       {}", metrics.lint_runs);
+}
+
+/// Formats a duration in minutes and seconds.
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+
+    if seconds < 60 {
+        format!("{seconds}s")
+    } else {
+        format!("{}min {:02}s", seconds / 60, seconds % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2min 05s");
+    }
+}
@@ -0,0 +1,314 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `tickets` subcommand.
+//!
+//! It lists the tickets referenced in a range of commits, so that release
+//! managers can see what is shipped. With `--check-status`, the state of each
+//! ticket is fetched with `ticket.status_command`, and the command fails if
+//! some of them are not known to be closed.
+
+use clap::Parser;
+use colored::Colorize as _;
+use eyre::Result;
+use indexmap::IndexMap;
+use thiserror::Error;
+
+use crate::{
+    lint::find_tickets, list_command, tracing::LogResult as _, warning,
+};
+
+use super::{
+    helpers::{ensure_in_git_repo, load_config},
+    revspec::{RevSpec, Revision},
+};
+
+/// The variable containing the ticket in the environment of `status_command`.
+const TICKET_VAR: &str = "GITZ_TICKET";
+
+/// The default states printed by `status_command` for closed tickets.
+const DEFAULT_CLOSED_STATES: &[&str] =
+    &["closed", "done", "resolved", "merged", "completed"];
+
+/// The tickets command.
+#[derive(Debug, Parser)]
+pub struct Tickets {
+    /// The range of commits to look at.
+    #[command(flatten)]
+    revspec: RevSpec,
+    /// Check the state of each ticket with `ticket.status_command`, and fail if
+    /// some are not known to be closed.
+    #[arg(long)]
+    check_status: bool,
+}
+
+/// The state of a ticket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// The ticket is still open.
+    Open,
+    /// The ticket is closed.
+    Closed,
+    /// The state of the ticket cannot be fetched.
+    Unknown,
+}
+
+/// Errors that can occur when listing the tickets.
+#[derive(Debug, Error)]
+pub enum TicketsError {
+    /// There are no ticket prefixes in the configuration.
+    #[error("No ticket is configured")]
+    NoTicketConfig,
+    /// There is no command to fetch the state of the tickets.
+    #[error("No `ticket.status_command` is configured")]
+    NoStatusCommand,
+    /// Some tickets are still open.
+    #[error("{count} referenced ticket(s) are still open")]
+    OpenTickets {
+        /// The number of open tickets.
+        count: usize,
+    },
+    /// The state of some tickets cannot be fetched.
+    #[error("The state of {count} referenced ticket(s) is unknown")]
+    UnknownStates {
+        /// The number of tickets in an unknown state.
+        count: usize,
+    },
+}
+
+impl super::Command for Tickets {
+    #[tracing::instrument(name = "tickets", level = "trace", skip_all)]
+    fn run(&self) -> Result<()> {
+        tracing::info!(params = ?self, "running tickets");
+
+        ensure_in_git_repo()?;
+
+        let config = load_config()?;
+        let ticket_config = config
+            .ticket
+            .as_ref()
+            .ok_or(TicketsError::NoTicketConfig)
+            .log_err()?;
+
+        let status_command = if self.check_status {
+            let command = ticket_config
+                .status_command
+                .as_deref()
+                .ok_or(TicketsError::NoStatusCommand)
+                .log_err()?;
+            Some(command)
+        } else {
+            None
+        };
+
+        let commits = self.revspec.commits(&[])?;
+        let tickets = group_by_ticket(&ticket_config.prefixes, &commits);
+        tracing::debug!(count = tickets.len(), "found tickets");
+
+        if tickets.is_empty() {
+            println!("No ticket referenced in {} commit(s).", commits.len());
+            return Ok(());
+        }
+
+        let closed_states = ticket_config.closed_states.as_ref().map_or_else(
+            || {
+                DEFAULT_CLOSED_STATES
+                    .iter()
+                    .map(|&state| state.to_owned())
+                    .collect()
+            },
+            Clone::clone,
+        );
+
+        let mut open = 0;
+        let mut unknown = 0;
+
+        for (ticket, commits) in &tickets {
+            let state = status_command
+                .map(|command| fetch_state(command, ticket, &closed_states));
+
+            match state {
+                Some(State::Open) => open += 1,
+                Some(State::Unknown) => unknown += 1,
+                Some(State::Closed) | None => (),
+            }
+
+            print_ticket(ticket, state, commits);
+        }
+
+        if open > 0 {
+            Err(TicketsError::OpenTickets { count: open }).log_err()?
+        } else if unknown > 0 {
+            Err(TicketsError::UnknownStates { count: unknown }).log_err()?
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Groups the commits by the tickets they reference, in order of appearance.
+///
+/// A commit referencing several tickets is listed under each of them.
+fn group_by_ticket<'a>(
+    prefixes: &[String],
+    commits: &'a [Revision],
+) -> IndexMap<String, Vec<&'a Revision>> {
+    let mut tickets = IndexMap::<String, Vec<&Revision>>::new();
+
+    for commit in commits {
+        for ticket in find_tickets(prefixes, &commit.message) {
+            tickets.entry(ticket).or_default().push(commit);
+        }
+    }
+
+    tickets
+}
+
+/// Fetches the state of a ticket with `status_command`.
+///
+/// When the command fails, a warning is printed and the state is unknown, so
+/// that the other tickets can still be checked.
+#[tracing::instrument(level = "trace")]
+fn fetch_state(command: &str, ticket: &str, closed_states: &[String]) -> State {
+    match list_command::first_line(command, &[(TICKET_VAR, ticket)]) {
+        Ok(Some(state)) => parse_state(&state, closed_states),
+        Ok(None) => State::Unknown,
+        Err(error) => {
+            warning!("The state of {ticket} cannot be fetched: {error}.");
+            State::Unknown
+        }
+    }
+}
+
+/// Parses the state printed by `status_command`.
+///
+/// Only the closed states are known for sure: any other state, like `To Do` or
+/// `Reopened`, is considered open.
+fn parse_state(state: &str, closed_states: &[String]) -> State {
+    if closed_states
+        .iter()
+        .any(|closed| state.trim().eq_ignore_ascii_case(closed))
+    {
+        State::Closed
+    } else {
+        State::Open
+    }
+}
+
+/// Prints a ticket with the commits referencing it.
+fn print_ticket(ticket: &str, state: Option<State>, commits: &[&Revision]) {
+    match state {
+        None => println!("{}", ticket.bold()),
+        Some(State::Open) => {
+            println!("{} {}", ticket.bold(), "(open)".red());
+        }
+        Some(State::Closed) => {
+            println!("{} {}", ticket.bold(), "(closed)".green());
+        }
+        Some(State::Unknown) => {
+            println!("{} {}", ticket.bold(), "(unknown)".yellow());
+        }
+    }
+
+    for commit in commits {
+        let short_hash = commit.hash.get(..10).unwrap_or(&commit.hash);
+        let subject = commit.message.lines().next().unwrap_or_default();
+        println!("  {} {subject}", short_hash.yellow());
+    }
+
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    fn commit(hash: &str, message: &str) -> Revision {
+        Revision {
+            hash: String::from(hash),
+            message: String::from(message),
+            is_merge: false,
+            author: String::new(),
+            author_email: String::new(),
+            date: String::new(),
+        }
+    }
+
+    #[test]
+    fn groups_the_commits_by_ticket() {
+        let prefixes = vec![String::from("#"), String::from("GH-")];
+        let commits = [
+            commit("c", "fix: fix a crash\n\nRefs: #2, GH-3"),
+            commit("b", "feat: add a flag\n\nRefs: #1"),
+            commit("a", "feat: add a route\n\nRefs: #2"),
+            commit("0", "chore: bump the version"),
+        ];
+
+        let tickets = group_by_ticket(&prefixes, &commits);
+        let hashes = tickets
+            .iter()
+            .map(|(ticket, commits)| {
+                let hashes = commits.iter().map(|commit| commit.hash.as_str());
+                (ticket.as_str(), hashes.collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            hashes,
+            vec![
+                ("#2", vec!["c", "a"]),
+                ("GH-3", vec!["c"]),
+                ("#1", vec!["b"]),
+            ]
+        );
+    }
+
+    fn default_closed_states() -> Vec<String> {
+        DEFAULT_CLOSED_STATES
+            .iter()
+            .map(|&state| state.to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn parses_the_state_of_tickets() {
+        let closed_states = default_closed_states();
+
+        assert_eq!(parse_state("OPEN", &closed_states), State::Open);
+        assert_eq!(parse_state("opened\n", &closed_states), State::Open);
+        assert_eq!(parse_state("CLOSED", &closed_states), State::Closed);
+        assert_eq!(parse_state("Done\n", &closed_states), State::Closed);
+        assert_eq!(parse_state("merged", &closed_states), State::Closed);
+    }
+
+    #[test]
+    fn considers_any_other_state_as_open() {
+        let closed_states = default_closed_states();
+
+        assert_eq!(parse_state("To Do", &closed_states), State::Open);
+        assert_eq!(parse_state("In Progress", &closed_states), State::Open);
+        assert_eq!(parse_state("Reopened", &closed_states), State::Open);
+    }
+
+    #[test]
+    fn uses_the_configured_closed_states() {
+        let closed_states = vec![String::from("Won't Do")];
+
+        assert_eq!(parse_state("won't do", &closed_states), State::Closed);
+        assert_eq!(parse_state("closed", &closed_states), State::Open);
+    }
+}
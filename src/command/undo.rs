@@ -0,0 +1,141 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `undo` subcommand.
+
+use clap::Parser;
+use eyre::Result;
+use inquire::Confirm;
+use thiserror::Error;
+
+use crate::{
+    command::helpers::load_config,
+    commit_cache::{CommitCache, WizardState},
+    git::{self, GitError},
+    hint, reword, success,
+    tracing::LogResult as _,
+    warning,
+};
+
+use super::helpers::ensure_in_git_worktree;
+
+/// The undo command.
+#[derive(Debug, Parser)]
+pub struct Undo;
+
+/// Usage errors of `git z undo`.
+#[derive(Debug, Error)]
+pub enum UndoError {
+    /// A commit is in progress with `git z commit`.
+    #[error("A commit is in progress")]
+    CommitInProgress,
+    /// There is no commit yet.
+    #[error("There is no commit to undo")]
+    NoCommit,
+    /// The last commit is the first one of the history.
+    #[error("The last commit is the root commit")]
+    RootCommit,
+    /// The last commit is a merge commit.
+    #[error("The last commit is a merge commit")]
+    MergeCommit,
+    /// An error has occurred while running Git.
+    #[error("Failed to undo the last commit")]
+    Git(#[from] GitError),
+}
+
+impl super::Command for Undo {
+    #[tracing::instrument(name = "undo", level = "trace", skip_all)]
+    fn run(&self) -> Result<()> {
+        tracing::info!(params = ?self, "running undo");
+
+        ensure_in_git_worktree()?;
+        let _lock = CommitCache::lock()?;
+
+        let mut cache = CommitCache::load()?;
+        if cache.wizard_state != WizardState::NotStarted {
+            return Err(UndoError::CommitInProgress).log_err()?;
+        }
+
+        ensure_undoable()?;
+
+        if is_pushed()? && !confirm_undo_pushed()? {
+            hint!("The last commit has been left untouched.");
+            return Ok(());
+        }
+
+        let config = load_config()?;
+        let message = git(&["log", "-1", "--format=%B", "HEAD"])?;
+        let subject = message.lines().next().unwrap_or_default().to_owned();
+
+        git(&["reset", "--soft", "HEAD~1"])?;
+
+        cache.wizard_answers = reword::prefilled_answers(&config, &message);
+        cache.old_message = Some(message);
+        cache.mark_wizard_as_ongoing();
+        cache.save()?;
+
+        success!("`{subject}` has been undone, its changes are staged again.");
+        hint! {"
+            Once you have fixed things, run `git z commit` and reuse the
+            answers to commit again. The body and trailers of the undone
+            commit are kept.
+        "};
+
+        Ok(())
+    }
+}
+
+/// Ensures the last commit can be undone with a soft reset.
+fn ensure_undoable() -> Result<(), UndoError> {
+    if !git::succeeds(&["rev-parse", "--verify", "--quiet", "HEAD"])? {
+        return Err(UndoError::NoCommit).log_err();
+    }
+
+    let parents = git(&["rev-list", "--parents", "-n", "1", "HEAD"])?
+        .split_whitespace()
+        .count()
+        .saturating_sub(1);
+
+    match parents {
+        0 => Err(UndoError::RootCommit).log_err(),
+        1 => Ok(()),
+        _ => Err(UndoError::MergeCommit).log_err(),
+    }
+}
+
+/// Returns whether the last commit is on a remote branch.
+fn is_pushed() -> Result<bool, UndoError> {
+    let remote_branches = git(&["branch", "--remotes", "--contains", "HEAD"])?;
+    tracing::debug!(?remote_branches);
+    Ok(!remote_branches.is_empty())
+}
+
+/// Asks the user to confirm undoing a commit already pushed.
+fn confirm_undo_pushed() -> Result<bool> {
+    warning! {"
+        The last commit has already been pushed. Undoing it rewrites the
+        history, so you will need to force-push the branch afterwards.
+    "};
+
+    Ok(Confirm::new("Do you want to undo it anyway?")
+        .with_default(false)
+        .prompt()
+        .log_err()?)
+}
+
+/// Runs a git command and returns its trimmed output.
+fn git(args: &[&str]) -> Result<String, UndoError> {
+    Ok(git::run_trimmed(args)?)
+}
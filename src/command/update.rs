@@ -16,14 +16,15 @@
 //! The `update` subcommand.
 
 use clap::Parser;
+use colored::Colorize as _;
 use eyre::Result;
 use inquire::Confirm;
 use thiserror::Error;
 
 use crate::{
     config::{
-        updater::{AskForTicket, ConfigUpdater, Init},
-        VERSION,
+        updater::{self, AskForTicket, ConfigUpdater, Init, Updated},
+        CONFIG_FILE_NAME, VERSION,
     },
     error, hint, success,
     tracing::LogResult as _,
@@ -33,7 +34,11 @@ use super::helpers::ensure_in_git_worktree;
 
 /// The update command.
 #[derive(Debug, Parser)]
-pub struct Update;
+pub struct Update {
+    /// Restore the configuration saved before the latest update.
+    #[arg(long)]
+    rollback: bool,
+}
 
 /// Usage errors of `git z init`.
 #[derive(Debug, Error)]
@@ -61,30 +66,108 @@ impl super::Command for Update {
 
         ensure_in_git_worktree()?;
 
+        if self.rollback {
+            let backup_file = updater::rollback()?;
+            tracing::debug!(?backup_file, "restored the backup");
+            success!("The configuration has been restored from its backup.");
+            return Ok(());
+        }
+
         let updater = ConfigUpdater::load()?;
 
-        match updater.config_version() {
-            VERSION => success!("The configuration is already up to date."),
-            "0.1" => update_from_v0_1(updater)?,
-            version @ ("0.2-dev.0" | "0.2-dev.1" | "0.2-dev.2"
-            | "0.2-dev.3") => Err(UpdateError::UnsupportedDevelopmentVersion {
+        if updater.config_version() == VERSION {
+            success!("The configuration is already up to date.");
+        } else {
+            update(updater)?.save()?;
+            success!("The configuration has been updated.");
+        }
+
+        Ok(())
+    }
+}
+
+/// Offers to update an out-of-date configuration before running a command.
+///
+/// The changes are shown before saving them. Returns whether the configuration
+/// has been updated.
+#[tracing::instrument(level = "trace")]
+pub fn offer_update(version: &str) -> Result<bool> {
+    let update_now = Confirm::new(&format!(
+        "Your {CONFIG_FILE_NAME} is from version {version}. Do you want to \
+            update it now?"
+    ))
+    .with_help_message("You can also update it later by running `git z update`")
+    .with_default(true)
+    .prompt()
+    .log_err()?;
+
+    tracing::debug!(?update_now);
+    if !update_now {
+        return Ok(false);
+    }
+
+    let updater = update(ConfigUpdater::load()?)?;
+    print_diff(&updater.diff());
+
+    let save = Confirm::new("Do you want to save these changes?")
+        .with_default(true)
+        .prompt()
+        .log_err()?;
+
+    tracing::debug!(?save);
+    if save {
+        updater.save()?;
+        success!("The configuration has been updated.");
+    }
+
+    Ok(save)
+}
+
+/// Updates the configuration to the current version.
+fn update(updater: ConfigUpdater<Init>) -> Result<ConfigUpdater<Updated>> {
+    match updater.config_version() {
+        "0.2" => update_from_v0_2(updater),
+        "0.1" => update_from_v0_1(updater),
+        version @ ("0.2-dev.0" | "0.2-dev.1" | "0.2-dev.2" | "0.2-dev.3") => {
+            Err(UpdateError::UnsupportedDevelopmentVersion {
                 version: version.to_owned(),
                 gitz_version: String::from("0.2.0"),
             })
-            .log_err()?,
-            version => Err(UpdateError::UnsupportedVersion {
-                version: version.to_owned(),
-            })
-            .log_err()?,
+            .log_err()?
         }
+        version => Err(UpdateError::UnsupportedVersion {
+            version: version.to_owned(),
+        })
+        .log_err()?,
+    }
+}
 
-        Ok(())
+/// Prints a diff, with the added and removed lines in colour.
+fn print_diff(diff: &str) {
+    eprintln!();
+
+    for line in diff.lines() {
+        if line.starts_with("+++") || line.starts_with("---") {
+            eprintln!("{}", line.bold());
+        } else if line.starts_with('+') {
+            eprintln!("{}", line.green());
+        } else if line.starts_with('-') {
+            eprintln!("{}", line.red());
+        } else if line.starts_with("@@") {
+            eprintln!("{}", line.cyan());
+        } else {
+            eprintln!("{line}");
+        }
     }
+
+    eprintln!();
 }
 
 /// Updates the configuration from version 0.1.
 #[tracing::instrument(level = "trace", skip_all)]
-fn update_from_v0_1(updater: ConfigUpdater<Init>) -> Result<()> {
+fn update_from_v0_1(
+    updater: ConfigUpdater<Init>,
+) -> Result<ConfigUpdater<Updated>> {
     tracing::info!("updating from version 0.1");
 
     let switch_scopes_to_any = ask_scopes_any(&updater)?;
@@ -95,16 +178,20 @@ fn update_from_v0_1(updater: ConfigUpdater<Init>) -> Result<()> {
         AskForTicket::DontAsk => false,
     };
 
-    updater
-        .update_from_v0_1(
-            switch_scopes_to_any,
-            ask_for_ticket,
-            empty_prefix_to_hash,
-        )?
-        .save()?;
+    Ok(updater.update_from_v0_1(
+        switch_scopes_to_any,
+        ask_for_ticket,
+        empty_prefix_to_hash,
+    )?)
+}
 
-    success!("The configuration has been updated.");
-    Ok(())
+/// Updates the configuration from version 0.2.
+#[tracing::instrument(level = "trace", skip_all)]
+fn update_from_v0_2(
+    updater: ConfigUpdater<Init>,
+) -> Result<ConfigUpdater<Updated>> {
+    tracing::info!("updating from version 0.2");
+    Ok(updater.update_from_v0_2()?)
 }
 
 /// Asks the user whether to switch the scopes to `"any"`.
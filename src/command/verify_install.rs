@@ -0,0 +1,285 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `verify-install` subcommand.
+//!
+//! It is meant to be run by onboarding scripts, like a `direnv` or devcontainer
+//! setup: it checks git-z can be run by Git and its hooks, and that it
+//! satisfies the configuration, then exits with a non-zero code if it cannot.
+
+use std::{env, fs};
+
+use clap::{Parser, ValueEnum};
+use colored::Colorize as _;
+use eyre::Result;
+use is_executable::IsExecutable as _;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    config::{config_file, Config, FromTomlError, LoadError},
+    min_version,
+    tracing::LogResult as _,
+};
+
+use super::{helpers::ensure_in_git_worktree, init::setup::Hook};
+
+/// The name of the git-z executable, looked up in the `PATH`.
+const EXECUTABLE: &str = "git-z";
+
+/// The command a hook must run to call git-z.
+const HOOK_COMMAND: &str = "git z";
+
+/// The verify-install command.
+#[derive(Debug, Parser)]
+pub struct VerifyInstall {
+    /// Require a Git hook calling git-z to be installed.
+    #[arg(long = "hook", value_name = "HOOK")]
+    hooks: Vec<Hook>,
+    /// The output format.
+    #[arg(long, value_name = "FORMAT", default_value = "text")]
+    format: Format,
+}
+
+/// An output format.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    /// One line per check.
+    Text,
+    /// A JSON object with the result of each check.
+    Json,
+}
+
+/// The result of the checks.
+#[derive(Debug, Serialize)]
+struct Report {
+    /// The version of git-z.
+    version: &'static str,
+    /// Whether all the checks have passed.
+    ok: bool,
+    /// The checks.
+    checks: Vec<Check>,
+}
+
+/// The result of a check.
+#[derive(Debug, Serialize)]
+struct Check {
+    /// The name of the check.
+    name: String,
+    /// The status of the check.
+    status: Status,
+    /// Details about the status.
+    detail: String,
+}
+
+/// The status of a check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    /// The check has passed.
+    Ok,
+    /// The check has failed.
+    Failed,
+    /// There was nothing to check.
+    Skipped,
+}
+
+/// Errors that can occur when verifying the installation.
+#[derive(Debug, Error)]
+pub enum VerifyInstallError {
+    /// Some checks have failed.
+    #[error("{count} check(s) of the installation have failed")]
+    ChecksFailed {
+        /// The number of failed checks.
+        count: usize,
+    },
+}
+
+impl super::Command for VerifyInstall {
+    #[tracing::instrument(name = "verify-install", level = "trace", skip_all)]
+    fn run(&self) -> Result<()> {
+        tracing::info!(params = ?self, "running verify-install");
+
+        ensure_in_git_worktree()?;
+
+        let mut checks = vec![check_path()];
+
+        match Config::load() {
+            Ok(config) => {
+                checks.push(check_config_found());
+                checks.push(check_min_version(&config));
+            }
+            Err(LoadError::InvalidConfig(FromTomlError::MinVersion(error))) => {
+                checks.push(Check::skipped(
+                    "config",
+                    "it cannot be checked by this version of git-z",
+                ));
+                checks.push(Check::failed("min_version", error.to_string()));
+            }
+            Err(error) => {
+                checks.push(Check::failed("config", format!("{error:#}")));
+                checks.push(Check::skipped(
+                    "min_version",
+                    "the configuration is invalid",
+                ));
+            }
+        }
+
+        for hook in Hook::ALL {
+            checks.push(check_hook(hook, self.hooks.contains(&hook))?);
+        }
+
+        let count = checks
+            .iter()
+            .filter(|check| check.status == Status::Failed)
+            .count();
+
+        let report = Report {
+            version: min_version::CURRENT,
+            ok: count == 0,
+            checks,
+        };
+
+        tracing::debug!(?report);
+
+        match self.format {
+            Format::Text => print_text(&report),
+            Format::Json => println!("{}", serde_json::to_string(&report)?),
+        }
+
+        if report.ok {
+            Ok(())
+        } else {
+            Err(VerifyInstallError::ChecksFailed { count }).log_err()?
+        }
+    }
+}
+
+impl Check {
+    /// Builds a passed check.
+    fn ok(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, Status::Ok, detail)
+    }
+
+    /// Builds a failed check.
+    fn failed(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, Status::Failed, detail)
+    }
+
+    /// Builds a skipped check.
+    fn skipped(name: &str, detail: impl Into<String>) -> Self {
+        Self::new(name, Status::Skipped, detail)
+    }
+
+    /// Builds a check.
+    fn new(name: &str, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_owned(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Checks git-z can be found in the `PATH`, as `git z` requires it.
+fn check_path() -> Check {
+    let executable = format!("{EXECUTABLE}{}", env::consts::EXE_SUFFIX);
+
+    let found = env::var_os("PATH").and_then(|path| {
+        env::split_paths(&path)
+            .map(|dir| dir.join(&executable))
+            .find(|candidate| candidate.is_executable())
+    });
+
+    match found {
+        Some(path) => Check::ok("path", path.display().to_string()),
+        None => Check::failed(
+            "path",
+            format!(
+                "{executable} is not in the PATH, so Git cannot run `git z`"
+            ),
+        ),
+    }
+}
+
+/// Reports where the configuration has been loaded from.
+fn check_config_found() -> Check {
+    match config_file() {
+        Ok(path) if path.is_file() => {
+            Check::ok("config", path.display().to_string())
+        }
+        _ => Check::skipped(
+            "config",
+            "no configuration file, the default one is used",
+        ),
+    }
+}
+
+/// Reports the minimum version of a configuration, which has been checked on
+/// loading.
+fn check_min_version(config: &Config) -> Check {
+    match min_version::required(config) {
+        Some(required) => Check::ok(
+            "min_version",
+            format!("git-z {} >= {required}", min_version::CURRENT),
+        ),
+        None => Check::skipped("min_version", "no minimum version is set"),
+    }
+}
+
+/// Checks a Git hook calling git-z is installed.
+///
+/// A missing hook or a hook not calling git-z is only a failure when the hook
+/// is required. A hook calling git-z but that Git cannot run always is.
+fn check_hook(hook: Hook, required: bool) -> Result<Check> {
+    let name = format!("hook.{}", hook.name());
+    let path = hook.path()?;
+    let not_ok = |detail: &str| {
+        if required {
+            Check::failed(&name, detail)
+        } else {
+            Check::skipped(&name, detail)
+        }
+    };
+
+    if !path.is_file() {
+        return Ok(not_ok("not installed"));
+    }
+
+    let calls_git_z = fs::read_to_string(&path)
+        .is_ok_and(|content| content.contains(HOOK_COMMAND));
+
+    Ok(match (calls_git_z, path.is_executable()) {
+        (true, true) => Check::ok(&name, path.display().to_string()),
+        (true, false) => Check::failed(
+            &name,
+            format!("{} is not executable, so Git ignores it", path.display()),
+        ),
+        (false, _) => not_ok("installed, but does not call git-z"),
+    })
+}
+
+/// Prints the report with one line per check.
+fn print_text(report: &Report) {
+    for check in &report.checks {
+        let status = match check.status {
+            Status::Ok => "ok".green().bold(),
+            Status::Failed => "failed".red().bold(),
+            Status::Skipped => "skipped".dimmed(),
+        };
+
+        println!("{status:>7} {}: {}", check.name, check.detail);
+    }
+}
@@ -0,0 +1,231 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The `verify-message` subcommand.
+//!
+//! It is meant for merge bots and server-side hooks: it checks a raw message
+//! read from the standard input with the same rules as `git z lint`, but
+//! without needing a repository, and prints a structured verdict.
+
+use std::{
+    fs,
+    io::{self, Read as _},
+    path::PathBuf,
+};
+
+use clap::{Parser, ValueEnum};
+use eyre::Result;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    config::{self, Config},
+    lint::{CheckOptions, Violation},
+    min_version,
+    tracing::LogResult as _,
+};
+
+/// The verify-message command.
+#[derive(Debug, Parser)]
+pub struct VerifyMessage {
+    /// The configuration to check the message against [default: the default
+    /// configuration].
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Check the message even when Git or the forge generated it for a merge.
+    #[arg(long)]
+    check_merge_messages: bool,
+    /// Accept and validate the ` (#123)` suffix added by squash-merges.
+    #[arg(long)]
+    squash_merges: bool,
+    /// The output format.
+    #[arg(long, value_name = "FORMAT", default_value = "json")]
+    format: Format,
+}
+
+/// An output format.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    /// One line per violation.
+    Text,
+    /// A JSON object with the verdict and the violations.
+    Json,
+}
+
+/// The verdict on a message.
+#[derive(Debug, Serialize)]
+struct Report {
+    /// The version of git-z.
+    version: &'static str,
+    /// The verdict.
+    verdict: Verdict,
+    /// The violations of the convention.
+    violations: Vec<ReportedViolation>,
+}
+
+/// The verdict on a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Verdict {
+    /// The message follows the convention.
+    Valid,
+    /// The message does not follow the convention.
+    Invalid,
+    /// The message has been generated for a merge, and is not checked.
+    Skipped,
+    /// The message only uses types or scopes from external lists, which are
+    /// not read.
+    Unverifiable,
+}
+
+/// A violation of the convention.
+#[derive(Debug, Serialize)]
+struct ReportedViolation {
+    /// The stable identifier of the violation.
+    code: &'static str,
+    /// The description of the violation.
+    message: String,
+}
+
+/// Errors that can occur when verifying a message.
+#[derive(Debug, Error)]
+pub enum VerifyMessageError {
+    /// The message cannot be read from the standard input.
+    #[error("Failed to read the message from the standard input")]
+    CannotReadMessage(#[source] io::Error),
+    /// The configuration cannot be read.
+    #[error("Failed to read {path}")]
+    CannotReadConfig {
+        /// The path of the configuration.
+        path: PathBuf,
+        /// The error reported by the OS.
+        #[source]
+        source: io::Error,
+    },
+    /// The message does not follow the convention.
+    #[error("The commit message does not follow the convention")]
+    InvalidMessage,
+    /// The message cannot be verified without running the list commands.
+    #[error("The commit message cannot be fully verified")]
+    UnverifiableMessage,
+}
+
+impl super::Command for VerifyMessage {
+    #[tracing::instrument(name = "verify-message", level = "trace", skip_all)]
+    fn run(&self) -> Result<()> {
+        tracing::info!(params = ?self, "running verify-message");
+
+        let config = self.config()?;
+
+        let mut message = String::new();
+        io::stdin()
+            .read_to_string(&mut message)
+            .map_err(VerifyMessageError::CannotReadMessage)
+            .log_err()?;
+
+        let report = self.verify(&config, &message);
+        tracing::debug!(?report);
+
+        match self.format {
+            Format::Text => print_text(&report),
+            Format::Json => println!("{}", serde_json::to_string(&report)?),
+        }
+
+        match report.verdict {
+            Verdict::Invalid => {
+                Err(VerifyMessageError::InvalidMessage).log_err()?
+            }
+            Verdict::Unverifiable => {
+                Err(VerifyMessageError::UnverifiableMessage).log_err()?
+            }
+            Verdict::Valid | Verdict::Skipped => Ok(()),
+        }
+    }
+}
+
+impl VerifyMessage {
+    /// Loads the configuration passed with `--config`, or the default one.
+    ///
+    /// The lists provided by external commands or workspaces are not
+    /// completed, as they depend on a repository.
+    fn config(&self) -> Result<Config> {
+        let Some(path) = &self.config else {
+            return Ok(Config::default());
+        };
+
+        let toml = fs::read_to_string(path)
+            .map_err(|source| VerifyMessageError::CannotReadConfig {
+                path: path.clone(),
+                source,
+            })
+            .log_err()?;
+
+        Ok(Config::from_toml(&toml)
+            .map_err(config::LoadError::from)
+            .log_err()?)
+    }
+
+    /// Checks a message and builds the report.
+    fn verify(&self, config: &Config, message: &str) -> Report {
+        let options = CheckOptions {
+            check_merge_messages: self.check_merge_messages,
+            squash_merges: self.squash_merges,
+            // NOTE: Running the commands from the given configuration would
+            // make the verdict depend on the current directory.
+            skips_external_lists: true,
+        };
+
+        let (verdict, violations) = if options.skips(message) {
+            (Verdict::Skipped, vec![])
+        } else {
+            let violations = options.check(config, message);
+
+            let verdict = if violations.is_empty() {
+                Verdict::Valid
+            } else if violations.iter().all(Violation::is_unverifiable) {
+                Verdict::Unverifiable
+            } else {
+                Verdict::Invalid
+            };
+
+            (verdict, violations)
+        };
+
+        Report {
+            version: min_version::CURRENT,
+            verdict,
+            violations: violations
+                .iter()
+                .map(ReportedViolation::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<&Violation> for ReportedViolation {
+    fn from(violation: &Violation) -> Self {
+        Self {
+            code: violation.code(),
+            message: violation.to_string(),
+        }
+    }
+}
+
+/// Prints the violations with one line each.
+fn print_text(report: &Report) {
+    for violation in &report.violations {
+        println!("{}: {}.", violation.code, violation.message);
+    }
+}
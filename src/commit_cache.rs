@@ -15,12 +15,23 @@
 
 //! Cache for aborted commits.
 
-use std::{fs, io, path::PathBuf, process::Command};
-
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use fs4::fs_std::FileExt as _;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::tracing::LogResult as _;
+use crate::{
+    git::{self, GitError},
+    helpers::fnv1a,
+    tracing::LogResult as _,
+};
 
 /// The commit cache.
 #[derive(Debug, Serialize, Deserialize)]
@@ -29,6 +40,18 @@ pub struct CommitCache {
     pub version: String,
     /// The state of the wizard.
     pub wizard_state: WizardState,
+    /// The extra arguments passed to `git commit`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_args: Vec<String>,
+    /// The ID of the tree of the staged changes when the wizard has completed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staged_tree: Option<String>,
+    /// When the wizard has started, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<u64>,
+    /// The message being reworded, whose body and trailers are kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_message: Option<String>,
     /// The answers to the wizard questions.
     pub wizard_answers: WizardAnswers,
 }
@@ -63,6 +86,37 @@ pub struct WizardAnswers {
     pub ticket: Option<String>,
 }
 
+/// A lock on the commit cache, released when dropped.
+#[derive(Debug)]
+pub struct CommitCacheLock {
+    /// The locked file.
+    #[expect(
+        dead_code,
+        reason = "The lock is held as long as the file is open."
+    )]
+    file: File,
+}
+
+/// Errors that can occur when locking the commit cache.
+#[derive(Debug, Error)]
+pub enum LockError {
+    /// The path of the git-z directory cannot be resolved.
+    #[error("Failed to get the path of the git-z directory")]
+    GitZDir(#[from] GitZDirError),
+    /// Error while creating the git-z directory.
+    #[error("Failed to create the git-z directory")]
+    CreateDir(#[source] io::Error),
+    /// Error while opening the lock file.
+    #[error("Failed to open the commit cache lock file")]
+    Open(#[source] io::Error),
+    /// Error while locking the lock file.
+    #[error("Failed to lock the commit cache")]
+    Lock(#[source] io::Error),
+    /// The commit cache is already locked by another process.
+    #[error("Another git z command is already running in this repository")]
+    AlreadyLocked,
+}
+
 /// Errors that can occur when loading the commit cache.
 #[derive(Debug, Error)]
 pub enum LoadError {
@@ -89,6 +143,9 @@ pub enum SaveError {
     /// Error while writing the commit cache file.
     #[error("Failed to write the commit cache")]
     Write(#[source] io::Error),
+    /// Error while replacing the commit cache file with the new one.
+    #[error("Failed to replace the commit cache")]
+    Replace(#[source] io::Error),
 }
 
 /// Errors that can occur when discarding the commit cache.
@@ -129,27 +186,21 @@ pub enum CommitCacheFileError {
 pub enum GitZDirError {
     /// An error has occurred while getting the path of the Git directory.
     #[error("Failed to get the path of the Git directory")]
-    GitDirError(#[from] GitDirError),
-}
-
-/// Errors that can occur when getting the Git directory.
-#[derive(Debug, Error)]
-pub enum GitDirError {
-    /// The `git` command cannot be run.
-    #[error("Failed to run the git command")]
-    CannotRunGit(#[source] io::Error),
-    /// Git has returned an error.
-    #[error("{0}")]
-    GitError(String),
-    /// The output of the git command is not proper UTF-8.
-    #[error("The output of the git command is not proper UTF-8")]
-    EncodingError(#[source] std::string::FromUtf8Error),
+    GitDirError(#[from] GitError),
+    /// An error has occurred while reading the Git configuration.
+    #[error("Failed to read `{HOME_GIT_CONFIG}` from the Git configuration")]
+    GitConfigError(#[source] GitError),
 }
 
 /// A minimal commit cache to get the version.
 ///
 /// The format of the commit cache can evolve with time. It is versioned so that
-/// git-z can invalidate any cache produced by an incompatible version.
+/// git-z can invalidate any cache produced by an incompatible version:
+///
+/// * a new minor version only adds optional fields, so caches with the same
+///   major version are loaded, ignoring the fields they do not know about;
+/// * a new major version is incompatible, so caches with another major version
+///   are discarded.
 #[derive(Debug, Serialize, Deserialize)]
 struct MinimalCommitCache {
     /// The version of the commit cache.
@@ -159,23 +210,72 @@ struct MinimalCommitCache {
 /// The name of the git-z directory.
 const GITZ_DIR_NAME: &str = "git-z";
 
+/// The environment variable overriding the path of the git-z directory.
+pub const HOME_ENV: &str = "GIT_Z_HOME";
+
+/// The Git configuration key overriding the path of the git-z directory.
+pub const HOME_GIT_CONFIG: &str = "git-z.home";
+
 /// The name of the commit cache file.
 const COMMIT_CACHE_FILE_NAME: &str = "commit-cache.toml";
 
-/// The current version of the config cache.
-const VERSION: &str = "0.1";
+/// The name of the temporary file used to write the commit cache.
+const COMMIT_CACHE_TMP_FILE_NAME: &str = "commit-cache.toml.tmp";
+
+/// The name of the file used to lock the commit cache.
+const COMMIT_CACHE_LOCK_FILE_NAME: &str = "commit-cache.lock";
+
+/// The current version of the commit cache.
+const VERSION: &str = "0.2";
 
 impl Default for CommitCache {
     fn default() -> Self {
         Self {
             version: String::from(VERSION),
             wizard_state: WizardState::default(),
+            extra_args: Vec::new(),
+            staged_tree: None,
+            started_at: None,
+            old_message: None,
             wizard_answers: WizardAnswers::default(),
         }
     }
 }
 
 impl CommitCache {
+    /// Locks the commit cache for the current process.
+    ///
+    /// Until the returned lock is dropped, any other process trying to lock the
+    /// commit cache fails instead of interleaving its changes with ours.
+    #[tracing::instrument(name = "lock_cache", level = "trace")]
+    pub fn lock() -> Result<CommitCacheLock, LockError> {
+        let gitz_dir = gitz_dir()?;
+        fs::create_dir_all(&gitz_dir)
+            .map_err(LockError::CreateDir)
+            .log_err()?;
+
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(lock_file()?)
+            .map_err(LockError::Open)
+            .log_err()?;
+
+        match file.try_lock_exclusive() {
+            Ok(()) => {
+                tracing::debug!("commit cache locked");
+                Ok(CommitCacheLock { file })
+            }
+            Err(error)
+                if error.kind() == fs4::lock_contended_error().kind() =>
+            {
+                Err(LockError::AlreadyLocked).log_err()
+            }
+            Err(error) => Err(LockError::Lock(error)).log_err(),
+        }
+    }
+
     /// Loads the commit cache of the repo or fallbacks to the default.
     #[tracing::instrument(name = "load_cache", level = "trace")]
     pub fn load() -> Result<Self, LoadError> {
@@ -257,79 +357,108 @@ impl CommitCache {
         ticket
     }
 
+    /// Gets the extra arguments passed to `git commit`.
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn extra_args(&self) -> &[String] {
+        let extra_args = &self.extra_args;
+        tracing::trace!(?extra_args);
+        extra_args
+    }
+
+    /// Gets the ID of the tree of the staged changes when the wizard has
+    /// completed.
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub fn staged_tree(&self) -> Option<&str> {
+        let staged_tree = self.staged_tree.as_deref();
+        tracing::trace!(?staged_tree);
+        staged_tree
+    }
+
+    /// Returns the time spent since the wizard has started, if known.
+    pub fn time_spent(&self) -> Option<Duration> {
+        let elapsed = unix_time()?.saturating_sub(self.started_at?);
+        Some(Duration::from_secs(elapsed))
+    }
+
+    /// Records when the wizard starts, unless a previous run already has.
+    ///
+    /// Like the extra arguments, it is only saved along with the next answer.
+    pub fn start_timer(&mut self) {
+        if self.started_at.is_none() {
+            self.started_at = unix_time();
+        }
+    }
+
     /// Resets the commit cache and discards it from the repo.
+    ///
+    /// The start of the wizard is kept, as the time spent on the discarded
+    /// answers is still spent on the commit. The reworded message is kept as
+    /// well, as it is still the one being reworded.
     #[tracing::instrument(level = "trace", skip(self))]
     pub fn reset(&mut self) -> Result<(), DiscardError> {
         tracing::debug!("resetting the commit cache");
+        self.extra_args = Vec::new();
+        self.staged_tree = None;
         self.wizard_answers = WizardAnswers::default();
         self.wizard_state = WizardState::default();
         Self::discard()
     }
 
+    /// Sets the extra arguments passed to `git commit`.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub fn set_extra_args(&mut self, extra_args: &[String]) {
+        self.extra_args = extra_args.to_vec();
+    }
+
     /// Sets the answer for the type.
     #[tracing::instrument(level = "trace", skip(self))]
-    pub fn set_type(&mut self, r#type: &str) -> Result<(), SaveError> {
+    pub fn set_type(&mut self, r#type: &str) {
         self.wizard_state = WizardState::Ongoing;
         self.wizard_answers.r#type = Some(r#type.to_owned());
-        self.save()
     }
 
     /// Sets the answer for the scope.
     #[tracing::instrument(level = "trace", skip(self))]
-    pub fn set_scope(&mut self, scope: Option<&str>) -> Result<(), SaveError> {
+    pub fn set_scope(&mut self, scope: Option<&str>) {
         self.wizard_state = WizardState::Ongoing;
         self.wizard_answers.scope = scope.map(ToOwned::to_owned);
-        self.save()
     }
 
     /// Sets the answer for the description.
     #[tracing::instrument(level = "trace", skip(self))]
-    pub fn set_description(
-        &mut self,
-        description: &str,
-    ) -> Result<(), SaveError> {
+    pub fn set_description(&mut self, description: &str) {
         self.wizard_state = WizardState::Ongoing;
         self.wizard_answers.description = Some(description.to_owned());
-        self.save()
     }
 
     /// Sets the answer for the breaking change.
     #[tracing::instrument(level = "trace", skip(self))]
-    pub fn set_breaking_change(
-        &mut self,
-        breaking_change: Option<&str>,
-    ) -> Result<(), SaveError> {
+    pub fn set_breaking_change(&mut self, breaking_change: Option<&str>) {
         self.wizard_state = WizardState::Ongoing;
         self.wizard_answers.breaking_change =
             breaking_change.map(ToOwned::to_owned);
-        self.save()
     }
 
     /// Sets the answer for the ticket.
     #[tracing::instrument(level = "trace", skip(self))]
-    pub fn set_ticket(
-        &mut self,
-        ticket: Option<&str>,
-    ) -> Result<(), SaveError> {
+    pub fn set_ticket(&mut self, ticket: Option<&str>) {
         self.wizard_state = WizardState::Ongoing;
         self.wizard_answers.ticket = ticket.map(ToOwned::to_owned);
-        self.save()
     }
 
     /// Marks the wizard as ongoing.
     #[tracing::instrument(level = "trace", skip(self))]
-    pub fn mark_wizard_as_ongoing(&mut self) -> Result<(), SaveError> {
+    pub fn mark_wizard_as_ongoing(&mut self) {
         tracing::trace!("marking the wizard as ongoing");
         self.wizard_state = WizardState::Ongoing;
-        self.save()
     }
 
-    /// Marks the wizard as completed.
+    /// Marks the wizard as completed with the given staged tree.
     #[tracing::instrument(level = "trace", skip(self))]
-    pub fn mark_wizard_as_completed(&mut self) -> Result<(), SaveError> {
+    pub fn mark_wizard_as_completed(&mut self, staged_tree: &str) {
         tracing::debug!("marking the wizard as completed");
         self.wizard_state = WizardState::Completed;
-        self.save()
+        self.staged_tree = Some(staged_tree.to_owned());
     }
 
     /// Discards the current commit cache from the repo.
@@ -343,12 +472,22 @@ impl CommitCache {
     }
 
     /// Saves the commit cache to the repo.
+    ///
+    /// The setters only update the cache in memory, so this must be called
+    /// after each answer for it to be reused if the wizard is aborted. As long
+    /// as the wizard is not started, nothing is saved, so that no cache is
+    /// created before the first answer.
     #[expect(
         clippy::unwrap_in_result,
         reason = "The expect in this function should not actually panic."
     )]
     #[tracing::instrument(level = "trace", skip_all)]
-    fn save(&self) -> Result<(), SaveError> {
+    pub fn save(&self) -> Result<(), SaveError> {
+        if self.wizard_state == WizardState::NotStarted {
+            tracing::trace!("not saving the commit cache before any answer");
+            return Ok(());
+        }
+
         tracing::trace!(?self, "saving the commit cache");
 
         #[expect(
@@ -359,27 +498,44 @@ impl CommitCache {
         let commit_cache = toml::to_string(self)
             .expect("Failed to serialise the commit cache");
 
-        fs::create_dir_all(gitz_dir()?)
+        let gitz_dir = gitz_dir()?;
+        fs::create_dir_all(&gitz_dir)
             .map_err(SaveError::CreateDir)
             .log_err()?;
-        fs::write(commit_cache_file()?, commit_cache)
+
+        // NOTE: The cache is first written to a temporary file, then renamed.
+        // This way, an interrupted write cannot leave a corrupt cache behind:
+        // either the old or the new cache is in place.
+        let tmp_file = gitz_dir.join(COMMIT_CACHE_TMP_FILE_NAME);
+        write_synced(&tmp_file, &commit_cache)
             .map_err(SaveError::Write)
             .log_err()?;
+        fs::rename(&tmp_file, commit_cache_file()?)
+            .map_err(SaveError::Replace)
+            .log_err()?;
 
         Ok(())
     }
 
     /// Builds a commit cache from its TOML representation.
+    ///
+    /// Caches from another minor version are migrated to the current one.
     #[tracing::instrument(level = "trace", skip_all)]
-    fn from_toml(toml: &str) -> Result<Self, FromTomlError> {
+    pub fn from_toml(toml: &str) -> Result<Self, FromTomlError> {
         let minimal_cache: MinimalCommitCache = toml::from_str(toml)
             .map_err(FromTomlError::ParseError)
             .log_err()?;
 
-        if minimal_cache.version.as_str() == VERSION {
-            let cache = toml::from_str(toml)
+        if major_version(&minimal_cache.version) == major_version(VERSION) {
+            let mut cache: Self = toml::from_str(toml)
                 .map_err(FromTomlError::ParseError)
                 .log_err()?;
+
+            if cache.version != VERSION {
+                tracing::debug!(from = ?cache.version, "migrating the cache");
+                cache.version = String::from(VERSION);
+            }
+
             Ok(cache)
         } else {
             Err(FromTomlError::UnsupportedVersion {
@@ -389,48 +545,84 @@ impl CommitCache {
     }
 }
 
+/// Returns the current time, in seconds since the Unix epoch.
+fn unix_time() -> Option<u64> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+    Some(now.as_secs())
+}
+
+/// Returns the major part of a commit cache version.
+fn major_version(version: &str) -> &str {
+    version.split_once('.').map_or(version, |(major, _)| major)
+}
+
+/// Writes a file and waits for its content to reach the disk.
+fn write_synced(path: &Path, contents: &str) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()
+}
+
+/// Returns the path of the file used to lock the commit cache.
+pub fn lock_file() -> Result<PathBuf, GitZDirError> {
+    Ok(gitz_dir()?.join(COMMIT_CACHE_LOCK_FILE_NAME))
+}
+
 /// Returns the path of the commit cache file.
-fn commit_cache_file() -> Result<PathBuf, CommitCacheFileError> {
+pub fn commit_cache_file() -> Result<PathBuf, CommitCacheFileError> {
     Ok(gitz_dir()?.join(COMMIT_CACHE_FILE_NAME))
 }
 
 /// Returns the path of the git-z directory.
-fn gitz_dir() -> Result<PathBuf, GitZDirError> {
-    Ok(git_dir()?.join(GITZ_DIR_NAME))
+///
+/// This is where git-z stores all its state, like caches and backups. It
+/// defaults to `git-z` in the Git directory, but can be overridden by the
+/// `GIT_Z_HOME` environment variable or the `git-z.home` Git configuration, in
+/// this order of precedence. A relative override is resolved from the Git
+/// directory. An absolute one can be shared by several repositories, so each
+/// repository or worktree gets its own subdirectory in it.
+#[tracing::instrument(level = "trace")]
+pub fn gitz_dir() -> Result<PathBuf, GitZDirError> {
+    let home = match env::var_os(HOME_ENV).filter(|home| !home.is_empty()) {
+        Some(home) => Some(PathBuf::from(home)),
+        None => git::config(&["--path", HOME_GIT_CONFIG])
+            .map_err(GitZDirError::GitConfigError)?
+            .map(PathBuf::from),
+    };
+
+    match home {
+        Some(home) if home.is_absolute() => Ok(home.join(repo_dir_name()?)),
+        Some(home) => Ok(git::dir()?.join(home)),
+        None => Ok(git::path(GITZ_DIR_NAME)?),
+    }
 }
 
-/// Returns the path of the Git directory.
-#[tracing::instrument(level = "trace")]
-fn git_dir() -> Result<PathBuf, GitDirError> {
-    let git_rev_parse = Command::new("git")
-        .args(["rev-parse", "--git-dir"])
-        .output()
-        .map_err(GitDirError::CannotRunGit)
-        .log_err()?;
-
-    if git_rev_parse.status.success() {
-        Ok(String::from_utf8(git_rev_parse.stdout)
-            .map_err(GitDirError::EncodingError)
-            .log_err()?
-            .trim()
-            .into())
+/// Returns the name of the subdirectory of the current repository or worktree
+/// in a shared git-z home.
+///
+/// It is made of the name of the worktree for readability, and of a hash of the
+/// absolute Git directory to keep it unique.
+fn repo_dir_name() -> Result<String, GitError> {
+    let git_dir = git::dir()?;
+
+    let name = if git_dir.ends_with(".git") {
+        git_dir.parent().and_then(Path::file_name)
     } else {
-        Err(GitDirError::GitError(
-            String::from_utf8(git_rev_parse.stderr)
-                .map_err(GitDirError::EncodingError)
-                .log_err()?
-                .trim()
-                .to_owned(),
-        ))
-        .log_err()
-    }
+        git_dir.file_name()
+    };
+
+    let name = name.unwrap_or_default().to_string_lossy();
+    let hash = fnv1a(git_dir.as_os_str().as_encoded_bytes());
+
+    Ok(format!("{name}-{hash:016x}"))
 }
 
 #[cfg(test)]
 mod test {
     #![allow(clippy::pedantic, clippy::restriction)]
 
-    use indoc::formatdoc;
+    use indoc::{formatdoc, indoc};
+    use proptest::prelude::*;
 
     use super::*;
 
@@ -454,6 +646,10 @@ mod test {
         let commit_cache = CommitCache {
             version: String::from(VERSION),
             wizard_state: WizardState::Ongoing,
+            extra_args: vec![],
+            staged_tree: None,
+            started_at: None,
+            old_message: None,
             wizard_answers: WizardAnswers {
                 r#type: Some(String::from("feat")),
                 scope: None,
@@ -482,6 +678,12 @@ mod test {
         let commit_cache = CommitCache {
             version: String::from(VERSION),
             wizard_state: WizardState::Completed,
+            extra_args: vec![],
+            staged_tree: Some(String::from(
+                "4b825dc642cb6eb9a060e54bf8d69288fbee4904",
+            )),
+            started_at: None,
+            old_message: None,
             wizard_answers: WizardAnswers::default(),
         };
 
@@ -490,9 +692,171 @@ mod test {
             formatdoc! {r##"
                 version = "{VERSION}"
                 wizard_state = "completed"
+                staged_tree = "4b825dc642cb6eb9a060e54bf8d69288fbee4904"
+
+                [wizard_answers]
+            "##}
+        );
+    }
+
+    #[test]
+    fn toml_representation_with_extra_args() {
+        let commit_cache = CommitCache {
+            version: String::from(VERSION),
+            wizard_state: WizardState::Ongoing,
+            extra_args: vec![String::from("--amend"), String::from("-s")],
+            staged_tree: None,
+            started_at: None,
+            old_message: None,
+            wizard_answers: WizardAnswers::default(),
+        };
+
+        assert_eq!(
+            toml::to_string(&commit_cache).unwrap(),
+            formatdoc! {r##"
+                version = "{VERSION}"
+                wizard_state = "ongoing"
+                extra_args = ["--amend", "-s"]
 
                 [wizard_answers]
             "##}
         );
     }
+
+    #[test]
+    fn keeps_the_start_of_an_aborted_wizard() {
+        let mut commit_cache = CommitCache {
+            started_at: Some(42),
+            ..CommitCache::default()
+        };
+
+        commit_cache.start_timer();
+
+        assert_eq!(commit_cache.started_at, Some(42));
+    }
+
+    #[test]
+    fn migrates_a_cache_from_version_0_1() {
+        let commit_cache = CommitCache::from_toml(indoc! {r##"
+            version = "0.1"
+            wizard_state = "ongoing"
+            extra_args = ["-s"]
+
+            [wizard_answers]
+            type = "feat"
+            ticket = "#23"
+        "##})
+        .unwrap();
+
+        assert_eq!(commit_cache.version, VERSION);
+        assert_eq!(commit_cache.wizard_state, WizardState::Ongoing);
+        assert_eq!(commit_cache.extra_args, vec![String::from("-s")]);
+        assert_eq!(commit_cache.r#type(), Some("feat"));
+        assert_eq!(commit_cache.ticket(), Some("#23"));
+    }
+
+    #[test]
+    fn ignores_unknown_fields_from_a_newer_minor_version() {
+        let commit_cache = CommitCache::from_toml(indoc! {r##"
+            version = "0.42"
+            wizard_state = "ongoing"
+            some_new_field = "value"
+
+            [wizard_answers]
+            type = "feat"
+            some_new_answer = "value"
+        "##})
+        .unwrap();
+
+        assert_eq!(commit_cache.version, VERSION);
+        assert_eq!(commit_cache.r#type(), Some("feat"));
+    }
+
+    #[test]
+    fn rejects_a_cache_from_another_major_version() {
+        let result = CommitCache::from_toml(indoc! {r##"
+            version = "1.0"
+            wizard_state = "ongoing"
+
+            [wizard_answers]
+        "##});
+
+        assert!(matches!(
+            result,
+            Err(FromTomlError::UnsupportedVersion { version }) if version == "1.0"
+        ));
+    }
+
+    fn wizard_state() -> impl Strategy<Value = WizardState> {
+        prop_oneof![
+            Just(WizardState::NotStarted),
+            Just(WizardState::Ongoing),
+            Just(WizardState::Completed),
+        ]
+    }
+
+    fn wizard_answers() -> impl Strategy<Value = WizardAnswers> {
+        (
+            proptest::option::of(any::<String>()),
+            proptest::option::of(any::<String>()),
+            proptest::option::of(any::<String>()),
+            proptest::option::of(any::<String>()),
+            proptest::option::of(any::<String>()),
+        )
+            .prop_map(
+                |(r#type, scope, description, breaking_change, ticket)| {
+                    WizardAnswers {
+                        r#type,
+                        scope,
+                        description,
+                        breaking_change,
+                        ticket,
+                    }
+                },
+            )
+    }
+
+    fn commit_cache() -> impl Strategy<Value = CommitCache> {
+        (
+            wizard_state(),
+            proptest::collection::vec(any::<String>(), 0..4),
+            proptest::option::of("[0-9a-f]{40}"),
+            proptest::option::of(any::<u32>().prop_map(u64::from)),
+            proptest::option::of(any::<String>()),
+            wizard_answers(),
+        )
+            .prop_map(
+                |(
+                    wizard_state,
+                    extra_args,
+                    staged_tree,
+                    started_at,
+                    old_message,
+                    wizard_answers,
+                )| CommitCache {
+                    version: String::from(VERSION),
+                    wizard_state,
+                    extra_args,
+                    staged_tree,
+                    started_at,
+                    old_message,
+                    wizard_answers,
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn from_toml_does_not_panic_on_arbitrary_input(toml in any::<String>()) {
+            drop(CommitCache::from_toml(&toml));
+        }
+
+        #[test]
+        fn loads_back_any_saved_cache(commit_cache in commit_cache()) {
+            let toml = toml::to_string(&commit_cache).unwrap();
+            let loaded = CommitCache::from_toml(&toml).unwrap();
+
+            prop_assert_eq!(toml::to_string(&loaded).unwrap(), toml);
+        }
+    }
 }
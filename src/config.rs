@@ -17,8 +17,11 @@
 
 pub mod updater;
 
+mod diagnostic;
+
 mod v0_1;
 mod v0_2;
+mod v0_3;
 
 // NOTE: When you switch to a new version:
 //
@@ -32,15 +35,30 @@ mod v0_2;
 // - write an updater in `ConfigUpdater`,
 // - update the previous updaters as well,
 // - update `git z update`.
-pub use v0_2::{Config, Scopes, Templates, Ticket};
+pub use v0_3::{
+    BreakingChangeRule, Config, DetailedType, Question, ScopeList, ScopeOrder,
+    ScopeSource, Scopes, SecretRule, Severity, Step, Suggestions, Templates,
+    Ticket, TicketPlacement, Type, TypeList, TypeOrder,
+};
+
+pub use diagnostic::Diagnostic;
 
-use std::{fs, io, path::PathBuf, process::Command};
+use std::{fs, io, path::PathBuf};
 
-use indexmap::{indexmap, IndexMap};
-use serde::{Deserialize, Serialize};
+use indexmap::IndexMap;
+use itertools::Itertools as _;
+use regex::Regex;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::tracing::LogResult as _;
+use crate::{
+    git::{self, GitError},
+    lint::MAX_SUBJECT_LEN,
+    list_command::Entry,
+    min_version::{self, MinVersionError},
+    tracing::LogResult as _,
+    warning,
+};
 
 /// Errors that can occur when loading the configuration.
 #[derive(Debug, Error)]
@@ -54,6 +72,9 @@ pub enum LoadError {
     /// The configuration is invalid.
     #[error("Invalid configuration in {CONFIG_FILE_NAME}")]
     InvalidConfig(#[from] FromTomlError),
+    /// An error has occurred while reading the configuration from `HEAD`.
+    #[error("Failed to read {CONFIG_FILE_NAME} from HEAD")]
+    BareRepoError(#[from] GitError),
 }
 
 /// Errors that can occur when parsing the TOML.
@@ -75,7 +96,27 @@ pub enum FromTomlError {
     },
     /// The configuration file cannot be parsed.
     #[error("Failed to parse into a valid configuration")]
-    ParseError(#[source] toml::de::Error),
+    ParseError(Diagnostic),
+    /// A key of the configuration is invalid.
+    #[error("Invalid configuration at `{path}`")]
+    InvalidKey {
+        /// The path of the invalid key.
+        path: String,
+        /// The diagnostic pointing to the error in the source.
+        diagnostic: Diagnostic,
+    },
+    /// The configuration requires a newer version of git-z.
+    #[error(transparent)]
+    MinVersion(#[from] MinVersionError),
+    /// A regex of the configuration is invalid.
+    #[error("Invalid regex at `{key}`")]
+    InvalidRegex {
+        /// The path of the key containing the regex.
+        key: String,
+        /// The error returned by the regex parser.
+        #[source]
+        source: regex::Error,
+    },
 }
 
 /// Errors that can occur when building the config file path.
@@ -83,21 +124,7 @@ pub enum FromTomlError {
 pub enum ConfigFileError {
     /// An error has occurred while getting the root of the Git repository.
     #[error("Failed to get the Git repo root")]
-    RepoRootError(#[from] RepoRootError),
-}
-
-/// Errors that can occur when getting the Git repo root.
-#[derive(Debug, Error)]
-pub enum RepoRootError {
-    /// The `git` command cannot be run.
-    #[error("Failed to run the git command")]
-    CannotRunGit(#[source] io::Error),
-    /// Git has returned an error.
-    #[error("{0}")]
-    GitError(String),
-    /// The output of the git command is not proper UTF-8.
-    #[error("The output of the git command is not proper UTF-8")]
-    EncodingError(#[source] std::string::FromUtf8Error),
+    RepoRootError(#[from] GitError),
 }
 
 /// A minimal configuration to get the version.
@@ -109,50 +136,116 @@ pub enum RepoRootError {
 struct MinimalConfig {
     /// The version of the configuration.
     version: String,
+    /// Settings about the configuration itself.
+    meta: Option<MinimalMeta>,
+}
+
+/// The settings of a minimal configuration.
+///
+/// The minimum version of git-z is read before the rest of the configuration,
+/// so that an older git-z can tell it is too old instead of failing to parse
+/// newer keys.
+#[derive(Debug, Serialize, Deserialize)]
+struct MinimalMeta {
+    /// The minimum version of git-z required by the configuration.
+    min_version: Option<String>,
 }
 
 /// The name of the configuration file.
 pub const CONFIG_FILE_NAME: &str = "git-z.toml";
 
 /// The current version of the configuration file.
-pub const VERSION: &str = "0.2";
+pub const VERSION: &str = "0.3-dev.0";
+
+/// The default types of commits and their description.
+pub const DEFAULT_TYPES: [(&str, &str); 17] = [
+    (
+        "feat",
+        "add a new feature in the code (including tests for the feature)",
+    ),
+    (
+        "sec",
+        "patch a security issue (including updating a dependency for security)",
+    ),
+    ("fix", "patch a bug in the code"),
+    ("perf", "enhance the performance of the code"),
+    (
+        "refactor",
+        "restructure the code without changing its external behaviour",
+    ),
+    (
+        "test",
+        "add, update (including refactoring) or remove tests only",
+    ),
+    (
+        "docs",
+        "update the documentation only (including README and alike)",
+    ),
+    (
+        "style",
+        "update the style, like running a code formatter or changing headers",
+    ),
+    (
+        "deps",
+        "add, update or remove external dependencies used by the code",
+    ),
+    (
+        "build",
+        "update the toolchain, build scripts or package definitions",
+    ),
+    ("env", "update the development environment"),
+    ("ide", "update the IDE configuration"),
+    (
+        "ci",
+        "update the CI configuration (including local check scripts)",
+    ),
+    ("revert", "revert a previous commit"),
+    (
+        "chore",
+        "update or remove something that is not covered by any other type",
+    ),
+    ("wip", "work in progress / to be rebased and squashed later"),
+    (
+        "debug",
+        "commit used for debugging purposes, not to be integrated",
+    ),
+];
 
 /// The default commit message template.
 const DEFAULT_TEMPLATE: &str = include_str!("../templates/COMMIT_EDITMSG");
 
 impl Default for Config {
     fn default() -> Self {
-        let default_types = indexmap! {
-            "feat" => "add a new feature in the code (including tests for the feature)",
-            "sec" => "patch a security issue (including updating a dependency for security)",
-            "fix" => "patch a bug in the code",
-            "perf" => "enhance the performance of the code",
-            "refactor" => "restructure the code without changing its external behaviour",
-            "test" => "add, update (including refactoring) or remove tests only",
-            "docs" => "update the documentation only (including README and alike)",
-            "style" => "update the style, like running a code formatter or changing headers",
-            "deps" => "add, update or remove external dependencies used by the code",
-            "build" => "update the toolchain, build scripts or package definitions",
-            "env" => "update the development environment",
-            "ide" => "update the IDE configuration",
-            "ci" => "update the CI configuration (including local check scripts)",
-            "revert" => "revert a previous commit",
-            "chore" => "update or remove something that is not covered by any other type",
-            "wip" => "work in progress / to be rebased and squashed later",
-            "debug" => "commit used for debugging purposes, not to be integrated",
-        };
-
         Self {
             version: String::from(VERSION),
-            types: default_types
+            types: DEFAULT_TYPES
                 .into_iter()
-                .map(|(key, value)| (String::from(key), String::from(value)))
+                .map(|(key, value)| {
+                    (String::from(key), Type::Simple(String::from(value)))
+                })
                 .collect(),
-            scopes: Some(Scopes::Any),
+            type_list: None,
+            scopes: Some(Scopes::Any { required: false }),
             ticket: None,
             templates: Templates {
                 commit: String::from(DEFAULT_TEMPLATE),
+                strip_comments: None,
+                named: None,
+                branch_overrides: None,
+                merge: None,
+                subject: None,
             },
+            scope_paths: None,
+            lint: None,
+            commit: None,
+            wizard: None,
+            hooks: None,
+            suggestions: None,
+            breaking_change: None,
+            secrets: None,
+            time_tracking: None,
+            template_vars: None,
+            meta: None,
         }
     }
 }
@@ -161,6 +254,10 @@ impl Config {
     /// Loads the configuration of the repo or fallbacks to the default.
     #[tracing::instrument(name = "load_config", level = "trace")]
     pub fn load() -> Result<Self, LoadError> {
+        if is_bare_repository()? {
+            return Self::load_from_head();
+        }
+
         let config_file = config_file()?;
 
         match fs::read_to_string(&config_file) {
@@ -188,26 +285,128 @@ impl Config {
         }
     }
 
+    /// Loads the configuration committed in `HEAD` or fallbacks to the default.
+    ///
+    /// This is used in bare repositories, which have no worktree to read the
+    /// configuration from.
+    fn load_from_head() -> Result<Self, LoadError> {
+        if let Some(config) = read_from_head()? {
+            tracing::info!("loading the configuration from HEAD");
+            let config = Self::from_toml(&config)?;
+            tracing::debug!(?config);
+            Ok(config)
+        } else {
+            tracing::info!(
+                "no configuration file in HEAD, using the default config"
+            );
+            Ok(Self::default())
+        }
+    }
+
+    /// Returns the maximum length of the subject line.
+    pub fn max_subject_length(&self) -> usize {
+        self.commit
+            .as_ref()
+            .and_then(|commit| commit.max_subject_length)
+            .unwrap_or(MAX_SUBJECT_LEN)
+    }
+
+    /// Returns the steps of the wizard, in the configured order.
+    ///
+    /// The steps missing from `wizard.order` come after the listed ones, in
+    /// their default order.
+    pub fn wizard_steps(&self) -> Vec<Step> {
+        let order = self
+            .wizard
+            .as_ref()
+            .and_then(|wizard| wizard.order.as_deref())
+            .unwrap_or_default();
+
+        order.iter().chain(Step::ALL).copied().unique().collect()
+    }
+
+    /// Checks that the regexes of the configuration are valid.
+    ///
+    /// They are only compiled when used, so an invalid one would otherwise be
+    /// noticed in the middle of a commit.
+    fn check_regexes(&self) -> Result<(), FromTomlError> {
+        let branch_patterns = self
+            .ticket
+            .iter()
+            .flat_map(|ticket| ticket.branch_patterns.iter().flatten())
+            .enumerate()
+            .map(|(index, regex)| {
+                (format!("ticket.branch_patterns[{index}]"), regex)
+            });
+
+        let scope_paths = self
+            .scope_paths
+            .iter()
+            .flatten()
+            .map(|(scope, regex)| (format!("scope_paths.{scope}"), regex));
+
+        let breaking_change_rules = self
+            .breaking_change
+            .iter()
+            .flat_map(|breaking_change| {
+                breaking_change.rules.iter().enumerate()
+            })
+            .flat_map(|(index, rule)| {
+                [("path", &rule.path), ("pattern", &rule.pattern)]
+                    .into_iter()
+                    .filter_map(move |(name, regex)| {
+                        let key =
+                            format!("breaking_change.rules[{index}].{name}");
+                        Some((key, regex.as_ref()?))
+                    })
+            });
+
+        let secret_rules = self
+            .secrets
+            .iter()
+            .flat_map(|secrets| secrets.rules.iter().enumerate())
+            .map(|(index, rule)| {
+                (format!("secrets.rules[{index}].pattern"), &rule.pattern)
+            });
+
+        for (key, regex) in branch_patterns
+            .chain(scope_paths)
+            .chain(breaking_change_rules)
+            .chain(secret_rules)
+        {
+            if let Err(source) = Regex::new(regex) {
+                return Err(FromTomlError::InvalidRegex { key, source })
+                    .log_err();
+            }
+        }
+
+        Ok(())
+    }
+
     /// Builds the configuration from its TOML representation.
+    ///
+    /// Its regexes are checked too, so that an invalid one is reported with the
+    /// other configuration errors.
     #[tracing::instrument(level = "trace", skip_all)]
     pub fn from_toml(toml: &str) -> Result<Self, FromTomlError> {
         let minimal_config: MinimalConfig = toml::from_str(toml)
-            .map_err(FromTomlError::ParseError)
+            .map_err(|error| {
+                FromTomlError::ParseError(Diagnostic::new(toml, &error))
+            })
             .log_err()?;
 
-        match minimal_config.version.as_str() {
-            VERSION => {
-                let config = toml::from_str(toml)
-                    .map_err(FromTomlError::ParseError)
-                    .log_err()?;
-                Ok(config)
-            }
-            "0.1" => {
-                let config: v0_1::Config = toml::from_str(toml)
-                    .map_err(FromTomlError::ParseError)
-                    .log_err()?;
-                Ok(config.into())
-            }
+        if let Some(required) =
+            minimal_config.meta.and_then(|meta| meta.min_version)
+        {
+            min_version::check(&required).log_err()?;
+        }
+
+        let config: Self = match minimal_config.version.as_str() {
+            // NOTE: The latest version denies unknown fields, while previous
+            // ones only warn about them so that they can still be updated.
+            VERSION => deserialize(toml),
+            "0.2" => Ok(deserialize::<v0_2::Config>(toml)?.into()),
+            "0.1" => Ok(deserialize::<v0_1::Config>(toml)?.into()),
             version @ ("0.2-dev.0" | "0.2-dev.1" | "0.2-dev.2"
             | "0.2-dev.3") => {
                 Err(FromTomlError::UnsupportedDevelopmentVersion {
@@ -220,8 +419,46 @@ impl Config {
                 version: version.to_owned(),
             })
             .log_err(),
-        }
+        }?;
+
+        config.check_regexes().log_err()?;
+        Ok(config)
+    }
+}
+
+/// Deserialises a configuration, reporting the path of invalid keys.
+///
+/// Unknown keys are ignored with a warning, unless the configuration type
+/// denies them.
+fn deserialize<T: DeserializeOwned>(toml: &str) -> Result<T, FromTomlError> {
+    let mut unknown_keys = vec![];
+    let mut on_unknown_key = |path: serde_ignored::Path<'_>| {
+        unknown_keys.push(path.to_string());
+    };
+
+    let deserializer = serde_ignored::Deserializer::new(
+        toml::Deserializer::new(toml),
+        &mut on_unknown_key,
+    );
+
+    let config = serde_path_to_error::deserialize(deserializer)
+        .map_err(|error| {
+            let path = error.path().to_string();
+            let diagnostic = Diagnostic::new(toml, error.inner());
+
+            if path == "." {
+                FromTomlError::ParseError(diagnostic)
+            } else {
+                FromTomlError::InvalidKey { path, diagnostic }
+            }
+        })
+        .log_err()?;
+
+    for key in unknown_keys {
+        warning!("Unknown key `{key}` in {CONFIG_FILE_NAME} has been ignored.");
     }
+
+    Ok(config)
 }
 
 /// Returns the path of the configuration file.
@@ -231,27 +468,172 @@ pub fn config_file() -> Result<PathBuf, ConfigFileError> {
 
 /// Returns the path of the root of the current Git repository.
 #[tracing::instrument(level = "trace")]
-fn repo_root() -> Result<PathBuf, RepoRootError> {
-    let git_rev_parse = Command::new("git")
-        .args(["rev-parse", "--show-toplevel"])
-        .output()
-        .map_err(RepoRootError::CannotRunGit)
-        .log_err()?;
+pub fn repo_root() -> Result<PathBuf, GitError> {
+    Ok(git::run_trimmed(&["rev-parse", "--show-toplevel"])?.into())
+}
+
+/// Returns whether the current Git repository is bare.
+#[tracing::instrument(level = "trace")]
+fn is_bare_repository() -> Result<bool, GitError> {
+    Ok(git::run_trimmed(&["rev-parse", "--is-bare-repository"])? == "true")
+}
 
-    if git_rev_parse.status.success() {
-        Ok(String::from_utf8(git_rev_parse.stdout)
-            .map_err(RepoRootError::EncodingError)
-            .log_err()?
-            .trim()
-            .into())
+/// Reads the configuration file committed in `HEAD`, if any.
+#[tracing::instrument(level = "trace")]
+fn read_from_head() -> Result<Option<String>, GitError> {
+    let object = format!("HEAD:{CONFIG_FILE_NAME}");
+
+    if git::succeeds(&["rev-parse", "--verify", "--quiet", &object])? {
+        Ok(Some(git::run(&["show", &object])?))
     } else {
-        Err(RepoRootError::GitError(
-            String::from_utf8(git_rev_parse.stderr)
-                .map_err(RepoRootError::EncodingError)
-                .log_err()?
-                .trim()
-                .to_owned(),
-        ))
+        Ok(None)
+    }
+}
+
+impl Scopes {
+    /// Returns whether a scope is required.
+    pub const fn is_required(&self) -> bool {
+        match self {
+            Self::Any { required } | Self::List { required, .. } => *required,
+        }
+    }
+}
+
+impl ScopeList {
+    /// Returns the number of scopes.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Names(names) => names.len(),
+            Self::Described(scopes) => scopes.len(),
+        }
+    }
+
+    /// Returns whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the name of the scope at the given index.
+    pub fn get(&self, index: usize) -> Option<&String> {
+        match self {
+            Self::Names(names) => names.get(index),
+            Self::Described(scopes) => {
+                scopes.get_index(index).map(|(name, _)| name)
+            }
+        }
+    }
+
+    /// Returns the names of the scopes.
+    pub fn names(&self) -> Vec<String> {
+        match self {
+            Self::Names(names) => names.clone(),
+            Self::Described(scopes) => scopes.keys().cloned().collect(),
+        }
+    }
+
+    /// Returns the index of a scope.
+    pub fn position(&self, scope: &str) -> Option<usize> {
+        match self {
+            Self::Names(names) => names.iter().position(|name| name == scope),
+            Self::Described(scopes) => scopes.get_index_of(scope),
+        }
+    }
+
+    /// Returns whether the list contains a scope.
+    pub fn contains(&self, scope: &str) -> bool {
+        self.position(scope).is_some()
+    }
+
+    /// Returns the description of a scope, if any.
+    pub fn description(&self, scope: &str) -> Option<&str> {
+        match self {
+            Self::Names(_) => None,
+            Self::Described(scopes) => scopes
+                .get(scope)
+                .map(String::as_str)
+                .filter(|description| !description.is_empty()),
+        }
+    }
+
+    /// Adds scopes at the end of the list, skipping the ones already present.
+    pub fn extend(&mut self, entries: Vec<Entry>) {
+        for Entry { name, description } in entries {
+            if self.contains(&name) {
+                continue;
+            }
+
+            match (&mut *self, description) {
+                (Self::Names(names), None) => names.push(name),
+                (Self::Names(names), Some(description)) => {
+                    let mut scopes: IndexMap<String, String> = names
+                        .drain(..)
+                        .map(|name| (name, String::new()))
+                        .collect();
+                    scopes.insert(name, description);
+                    *self = Self::Described(scopes);
+                }
+                (Self::Described(scopes), description) => {
+                    scopes.insert(name, description.unwrap_or_default());
+                }
+            }
+        }
+    }
+}
+
+impl Default for ScopeList {
+    fn default() -> Self {
+        Self::Names(vec![])
+    }
+}
+
+impl Step {
+    /// All the steps, in their default order.
+    pub const ALL: &[Self] = &[
+        Self::Type,
+        Self::Scope,
+        Self::Description,
+        Self::BreakingChange,
+        Self::Ticket,
+    ];
+
+    /// Returns the question asked by the step, if it can be skipped.
+    pub const fn question(self) -> Option<Question> {
+        match self {
+            Self::Type | Self::Description => None,
+            Self::Scope => Some(Question::Scope),
+            Self::BreakingChange => Some(Question::BreakingChange),
+            Self::Ticket => Some(Question::Ticket),
+        }
+    }
+}
+
+impl Type {
+    /// Returns the description of the type.
+    pub fn description(&self) -> &str {
+        match self {
+            Self::Simple(description)
+            | Self::Detailed(DetailedType { description, .. }) => description,
+        }
+    }
+
+    /// Returns whether the wizard skips the given question for this type.
+    pub fn skips(&self, question: Question) -> bool {
+        match self {
+            Self::Simple(_) => false,
+            Self::Detailed(DetailedType { skip, .. }) => {
+                skip.contains(&question)
+            }
+        }
+    }
+
+    /// Returns the named template to use for this type, if any.
+    pub fn template(&self) -> Option<&str> {
+        match self {
+            Self::Simple(_) => None,
+            Self::Detailed(DetailedType { template, .. }) => {
+                template.as_deref()
+            }
+        }
     }
 }
 
@@ -260,29 +642,130 @@ impl From<v0_1::Config> for Config {
         Self {
             version: old.version,
             types: split_types_and_docs(&old.types),
-            scopes: Some(Scopes::List { list: old.scopes }),
+            type_list: None,
+            scopes: Some(Scopes::List {
+                list: ScopeList::Names(old.scopes),
+                from: None,
+                command: None,
+                order: ScopeOrder::default(),
+                required: false,
+            }),
             ticket: Some(Ticket {
                 required: true,
                 prefixes: old.ticket_prefixes,
+                branch_patterns: None,
+                title_command: None,
+                status_command: None,
+                closed_states: None,
+                placement: TicketPlacement::default(),
+                trailer: None,
             }),
             templates: Templates {
                 commit: old.template,
+                strip_comments: None,
+                named: None,
+                branch_overrides: None,
+                merge: None,
+                subject: None,
+            },
+            scope_paths: None,
+            lint: None,
+            commit: None,
+            wizard: None,
+            hooks: None,
+            suggestions: None,
+            breaking_change: None,
+            secrets: None,
+            time_tracking: None,
+            template_vars: None,
+            meta: None,
+        }
+    }
+}
+
+impl From<v0_2::Config> for Config {
+    fn from(old: v0_2::Config) -> Self {
+        Self {
+            version: old.version,
+            types: old
+                .types
+                .into_iter()
+                .map(|(name, description)| (name, Type::Simple(description)))
+                .collect(),
+            type_list: None,
+            scopes: old.scopes.map(Into::into),
+            ticket: old.ticket.map(Into::into),
+            templates: old.templates.into(),
+            scope_paths: None,
+            lint: None,
+            commit: None,
+            wizard: None,
+            hooks: None,
+            suggestions: None,
+            breaking_change: None,
+            secrets: None,
+            time_tracking: None,
+            template_vars: None,
+            meta: None,
+        }
+    }
+}
+
+impl From<v0_2::Scopes> for Scopes {
+    fn from(old: v0_2::Scopes) -> Self {
+        match old {
+            v0_2::Scopes::Any => Self::Any { required: false },
+            v0_2::Scopes::List { list } => Self::List {
+                list: ScopeList::Names(list),
+                from: None,
+                command: None,
+                order: ScopeOrder::default(),
+                required: false,
             },
         }
     }
 }
 
+impl From<v0_2::Ticket> for Ticket {
+    fn from(old: v0_2::Ticket) -> Self {
+        Self {
+            required: old.required,
+            prefixes: old.prefixes,
+            branch_patterns: None,
+            title_command: None,
+            status_command: None,
+            closed_states: None,
+            placement: TicketPlacement::default(),
+            trailer: None,
+        }
+    }
+}
+
+impl From<v0_2::Templates> for Templates {
+    fn from(old: v0_2::Templates) -> Self {
+        Self {
+            commit: old.commit,
+            strip_comments: None,
+            named: None,
+            branch_overrides: None,
+            merge: None,
+            subject: None,
+        }
+    }
+}
+
 /// Splits the types from their documentation.
 ///
 /// In the config version 0.1, the list of types is just a list of strings. The
 /// documentation for each type is simply separated from the type itself by a
 /// space, which is kind of a hack. This function splits the types from their
 /// documentation, putting them in two separate strings.
-fn split_types_and_docs(types: &[String]) -> IndexMap<String, String> {
+fn split_types_and_docs(types: &[String]) -> IndexMap<String, Type> {
     types
         .iter()
         .map(AsRef::as_ref)
         .map(split_type_and_doc)
+        .map(|(ty, doc)| (ty, Type::Simple(doc)))
         .collect()
 }
 
@@ -293,3 +776,78 @@ fn split_type_and_doc(type_and_doc: &str) -> (String, String) {
     let doc = split.next().unwrap_or_default().trim().to_owned();
     (ty, doc)
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use proptest::{prelude::*, sample::Index};
+
+    use super::*;
+
+    /// Reads the configurations from `tests/res/config` to seed the tests.
+    fn fixtures() -> Vec<String> {
+        let fixtures_dir =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/res/config");
+
+        fs::read_dir(fixtures_dir)
+            .unwrap()
+            .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect()
+    }
+
+    /// Replaces a random part of a fixture with some random text.
+    fn altered_fixture() -> impl Strategy<Value = String> {
+        (
+            proptest::sample::select(fixtures()),
+            any::<Index>(),
+            0..32_usize,
+            "\\PC{0,16}",
+        )
+            .prop_map(|(fixture, position, length, replacement)| {
+                let mut chars = fixture.chars().collect::<Vec<_>>();
+                let start = position.index(chars.len() + 1);
+                let end = (start + length).min(chars.len());
+                chars.splice(start..end, replacement.chars());
+                chars.into_iter().collect()
+            })
+    }
+
+    #[test]
+    fn extending_a_scope_list_skips_duplicates_and_keeps_descriptions() {
+        let mut list = ScopeList::Names(vec![String::from("api")]);
+
+        list.extend(vec![
+            Entry {
+                name: String::from("api"),
+                description: Some(String::from("duplicate")),
+            },
+            Entry {
+                name: String::from("cli"),
+                description: Some(String::from("the command line")),
+            },
+        ]);
+
+        assert_eq!(list.names(), vec!["api", "cli"]);
+        assert_eq!(list.description("api"), None);
+        assert_eq!(list.description("cli"), Some("the command line"));
+    }
+
+    proptest! {
+        #[test]
+        fn from_toml_does_not_panic_on_arbitrary_input(toml in any::<String>()) {
+            if let Err(error) = Config::from_toml(&toml) {
+                drop(format!("{error}: {error:?}"));
+            }
+        }
+
+        #[test]
+        fn from_toml_does_not_panic_on_altered_configs(
+            toml in altered_fixture(),
+        ) {
+            if let Err(error) = Config::from_toml(&toml) {
+                drop(format!("{error}: {error:?}"));
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Diagnostics pointing to the source of configuration errors.
+
+use std::fmt;
+
+use colored::Colorize as _;
+
+use super::CONFIG_FILE_NAME;
+
+/// A diagnostic pointing to a location in the configuration file.
+#[derive(Debug)]
+pub struct Diagnostic {
+    /// The error message.
+    message: String,
+    /// The location of the error, if known.
+    location: Option<Location>,
+}
+
+/// A location in the configuration file.
+#[derive(Debug)]
+struct Location {
+    /// The line number, starting at 1.
+    line_number: usize,
+    /// The column, starting at 1.
+    column: usize,
+    /// The content of the line.
+    line: String,
+    /// The number of characters to underline.
+    length: usize,
+}
+
+impl Diagnostic {
+    /// Builds a diagnostic from a TOML error and the source it comes from.
+    pub fn new(source: &str, error: &toml::de::Error) -> Self {
+        Self {
+            message: error.message().trim().to_owned(),
+            location: error
+                .span()
+                .and_then(|span| Location::new(source, span.start, span.end)),
+        }
+    }
+}
+
+impl Location {
+    /// Builds a location from a byte span in the source.
+    fn new(source: &str, start: usize, end: usize) -> Option<Self> {
+        let before = source.get(..start)?;
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        let line_end = source
+            .get(start..)?
+            .find('\n')
+            .map_or(source.len(), |index| start + index);
+
+        let line = source.get(line_start..line_end)?.trim_end_matches('\r');
+        let length = source
+            .get(start..end.clamp(start, line_end))
+            .map_or(0, |spanned| spanned.chars().count());
+
+        Some(Self {
+            line_number: before.matches('\n').count() + 1,
+            column: before.get(line_start..)?.chars().count() + 1,
+            line: line.to_owned(),
+            length: length.max(1),
+        })
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    #[expect(
+        clippy::min_ident_chars,
+        reason = "This is the conventional name in `fmt` implementations."
+    )]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(location) = &self.location else {
+            return write!(f, "{}", self.message);
+        };
+
+        let Location {
+            line_number,
+            column,
+            line,
+            length,
+        } = location;
+
+        let gutter = " ".repeat(line_number.to_string().len());
+        let underline = format!(
+            "{}{}",
+            " ".repeat(column - 1),
+            "^".repeat(*length).red().bold()
+        );
+
+        writeln!(f, "{gutter}--> {CONFIG_FILE_NAME}:{line_number}:{column}")?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{line_number} | {line}")?;
+        write!(f, "{gutter} | {underline} {}", self.message.red().bold())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use indoc::indoc;
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    #[expect(
+        dead_code,
+        reason = "The field is only deserialised to produce the errors."
+    )]
+    struct Test {
+        key: String,
+    }
+
+    fn diagnostic(source: &str) -> String {
+        colored::control::set_override(false);
+        let error = toml::from_str::<Test>(source).unwrap_err();
+        Diagnostic::new(source, &error).to_string()
+    }
+
+    #[test]
+    fn points_to_the_offending_key() {
+        let source = indoc! {r#"
+            key = "value"
+            unknown = "value"
+        "#};
+
+        assert_eq!(
+            diagnostic(source),
+            indoc! {"
+                 --> git-z.toml:2:1
+                  |
+                2 | unknown = \"value\"
+                  | ^^^^^^^ unknown field `unknown`, expected `key`"
+            }
+        );
+    }
+
+    #[test]
+    fn points_to_the_offending_value() {
+        let source = "key = 42\n";
+
+        assert_eq!(
+            diagnostic(source),
+            indoc! {"
+                 --> git-z.toml:1:7
+                  |
+                1 | key = 42
+                  |       ^^ invalid type: integer `42`, expected a string"
+            }
+        );
+    }
+}
@@ -17,22 +17,38 @@
 
 mod common;
 mod from_v0_1;
+mod from_v0_2;
 
-use std::{fs, io, marker::PhantomData};
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write as _},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
 
+use similar::TextDiff;
 use thiserror::Error;
 use toml_edit::DocumentMut;
 
-use crate::tracing::LogResult as _;
+use crate::{
+    commit_cache::{gitz_dir, GitZDirError},
+    tracing::LogResult as _,
+};
 
 use super::{
     config_file, Config, ConfigFileError, FromTomlError, CONFIG_FILE_NAME,
 };
 
+/// The name of the directory storing the backups, in the git-z directory.
+const BACKUPS_DIR_NAME: &str = "backups";
+
 /// A configuration updater.
 #[must_use]
 #[derive(Debug)]
 pub struct ConfigUpdater<State> {
+    /// The original configuration file.
+    original_toml: String,
     /// The parsed configuration.
     parsed_config: Config,
     /// The editable TOML document.
@@ -102,11 +118,51 @@ pub enum SaveError {
     /// The path of the configuration file cannot be resolved.
     #[error("Failed to get the configuration file path")]
     ConfigFileError(#[from] ConfigFileError),
+    /// Error while backing up the original configuration file.
+    #[error("Failed to back up {CONFIG_FILE_NAME}")]
+    Backup(#[from] BackupError),
     /// Error while writing the configuration file.
     #[error("Failed to write {CONFIG_FILE_NAME}")]
     WriteError(#[source] io::Error),
 }
 
+/// Errors that can occur when backing up the configuration.
+#[derive(Debug, Error)]
+pub enum BackupError {
+    /// The path of the git-z directory cannot be resolved.
+    #[error("Failed to get the path of the git-z directory")]
+    GitZDir(#[from] GitZDirError),
+    /// Error while creating the backups directory.
+    #[error("Failed to create the backups directory")]
+    CreateDir(#[source] io::Error),
+    /// Error while writing the backup file.
+    #[error("Failed to write the backup file")]
+    Write(#[source] io::Error),
+}
+
+/// Errors that can occur when rolling back the configuration.
+#[derive(Debug, Error)]
+pub enum RollbackError {
+    /// The path of the configuration file cannot be resolved.
+    #[error("Failed to get the configuration file path")]
+    ConfigFileError(#[from] ConfigFileError),
+    /// The path of the git-z directory cannot be resolved.
+    #[error("Failed to get the path of the git-z directory")]
+    GitZDir(#[from] GitZDirError),
+    /// There is no backup to restore.
+    #[error("No backup of {CONFIG_FILE_NAME} to restore")]
+    NoBackup,
+    /// Error while listing the backups.
+    #[error("Failed to list the backups")]
+    ReadDir(#[source] io::Error),
+    /// Error while restoring the backup.
+    #[error("Failed to restore {CONFIG_FILE_NAME}")]
+    Restore(#[source] io::Error),
+    /// Error while deleting the restored backup.
+    #[error("Failed to delete the restored backup")]
+    Delete(#[source] io::Error),
+}
+
 impl ConfigUpdater<Init> {
     /// Loads the configuration into the updater.
     #[tracing::instrument(name = "load_config", level = "trace")]
@@ -123,6 +179,7 @@ impl ConfigUpdater<Init> {
                 tracing::debug!(?parsed_config);
 
                 Ok(Self {
+                    original_toml: toml,
                     parsed_config,
                     toml_config,
                     _state: PhantomData,
@@ -173,7 +230,27 @@ impl ConfigUpdater<Init> {
             empty_prefix_to_hash,
         );
 
+        from_v0_2::update(&mut self.toml_config);
+
+        Ok(ConfigUpdater {
+            original_toml: self.original_toml,
+            parsed_config: self.parsed_config,
+            toml_config: self.toml_config,
+            _state: PhantomData,
+        })
+    }
+
+    /// Updates the configuration from version 0.2.
+    pub fn update_from_v0_2(
+        mut self,
+    ) -> Result<ConfigUpdater<Updated>, UpdateError> {
+        self.check_version("0.2")?;
+
+        tracing::debug!("updating the configuration");
+        from_v0_2::update(&mut self.toml_config);
+
         Ok(ConfigUpdater {
+            original_toml: self.original_toml,
             parsed_config: self.parsed_config,
             toml_config: self.toml_config,
             _state: PhantomData,
@@ -197,11 +274,23 @@ impl ConfigUpdater<Init> {
 }
 
 impl ConfigUpdater<Updated> {
+    /// Returns the changes to the configuration file as a unified diff.
+    pub fn diff(&self) -> String {
+        let updated_toml = self.toml_config.to_string();
+
+        TextDiff::from_lines(&self.original_toml, &updated_toml)
+            .unified_diff()
+            .header(CONFIG_FILE_NAME, CONFIG_FILE_NAME)
+            .to_string()
+    }
+
     /// Writes the updated configuration to the configuration file.
     #[tracing::instrument(level = "trace", skip_all)]
     pub fn save(self) -> Result<(), SaveError> {
         tracing::info!("saving the configuration");
 
+        backup(&self.original_toml)?;
+
         fs::write(config_file()?, self.toml_config.to_string())
             .map_err(SaveError::WriteError)
             .log_err()?;
@@ -209,3 +298,105 @@ impl ConfigUpdater<Updated> {
         Ok(())
     }
 }
+
+/// Restores the latest backup of the configuration.
+///
+/// The backup is deleted once restored, so that successive rollbacks go back
+/// further in time. Returns the path of the restored backup.
+#[tracing::instrument(level = "trace")]
+pub fn rollback() -> Result<PathBuf, RollbackError> {
+    tracing::info!("rolling back the configuration");
+
+    let backups_dir = gitz_dir()?.join(BACKUPS_DIR_NAME);
+    let backup_file = latest_backup(&backups_dir)?;
+    tracing::debug!(?backup_file);
+
+    fs::copy(&backup_file, config_file()?)
+        .map_err(RollbackError::Restore)
+        .log_err()?;
+
+    fs::remove_file(&backup_file)
+        .map_err(RollbackError::Delete)
+        .log_err()?;
+
+    Ok(backup_file)
+}
+
+/// Writes the original configuration in a new backup file.
+#[tracing::instrument(level = "trace", skip_all)]
+fn backup(original_toml: &str) -> Result<(), BackupError> {
+    let backups_dir = gitz_dir()?.join(BACKUPS_DIR_NAME);
+
+    fs::create_dir_all(&backups_dir)
+        .map_err(BackupError::CreateDir)
+        .log_err()?;
+
+    let mut timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+
+    // NOTE: The backup is created only if its name is not taken, so that two
+    // saves within the same tick of the clock cannot overwrite each other.
+    let (backup_file, mut file) = loop {
+        let backup_file =
+            backups_dir.join(format!("{CONFIG_FILE_NAME}.{timestamp}"));
+
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&backup_file)
+        {
+            Ok(file) => break (backup_file, file),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+                timestamp = timestamp.saturating_add(1);
+            }
+            Err(error) => return Err(BackupError::Write(error)).log_err(),
+        }
+    };
+
+    tracing::debug!(?backup_file);
+
+    file.write_all(original_toml.as_bytes())
+        .map_err(BackupError::Write)
+        .log_err()?;
+
+    Ok(())
+}
+
+/// Finds the backup with the most recent timestamp.
+fn latest_backup(backups_dir: &Path) -> Result<PathBuf, RollbackError> {
+    let entries = match fs::read_dir(backups_dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            return Err(RollbackError::NoBackup).log_err();
+        }
+        Err(error) => return Err(RollbackError::ReadDir(error)).log_err(),
+    };
+
+    let prefix = format!("{CONFIG_FILE_NAME}.");
+    let mut latest = None;
+
+    for entry in entries {
+        let path = entry.map_err(RollbackError::ReadDir).log_err()?.path();
+        let timestamp = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(&prefix))
+            .and_then(|timestamp| timestamp.parse::<u128>().ok());
+
+        if let Some(timestamp) = timestamp {
+            if latest
+                .as_ref()
+                .is_none_or(|(latest, _)| timestamp > *latest)
+            {
+                latest = Some((timestamp, path));
+            }
+        }
+    }
+
+    latest
+        .map(|(_, path)| path)
+        .ok_or(RollbackError::NoBackup)
+        .log_err()
+}
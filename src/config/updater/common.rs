@@ -27,8 +27,6 @@
 use indoc::indoc;
 use toml_edit::{DocumentMut, Item};
 
-use crate::config::VERSION;
-
 /// The new documentation for `types`.
 pub const TYPES_DOC: &str = indoc! {"
 
@@ -99,8 +97,233 @@ pub const TEMPLATES_COMMIT_DOC: &str = indoc! {"
     #   - ticket (optional): the ticket reference
 "};
 
+/// The documentation for `commit`.
+pub const COMMIT_DOC: &str = indoc! {"
+
+    # The configuration of `git z commit`.
+    #
+    # This table is optional.
+    # [commit]
+    # # Whether to open the commit message in an editor before committing.
+    # edit = true
+    # # Whether to show a preview of the commit message after each answer.
+    # preview = true
+    # # Whether to add a `Generated-by` trailer with the version of git-z. The
+    # # commit template can place it itself with the `generated_by` variable.
+    # tool_footer = false
+    # # The maximum length of the subject line. The commit form shows a gauge of
+    # # the subject length while typing the description.
+    # max_subject_length = 72
+    # # Whether to warn before the wizard when commits are signed with SSH but no
+    # # agent is available. The check is skipped if `gpg.ssh.program` is set.
+    # check_ssh_agent = true
+"};
+
+/// The documentation for `commit.size`.
+pub const COMMIT_SIZE_DOC: &str = indoc! {r#"
+
+    # Limits on the size of the staged changes in `git z commit`.
+    #
+    # This table is optional: if omitted, the size of the changes is not checked.
+    # [commit.size]
+    # # The maximum number of changed files.
+    # max_files = 20
+    # # The maximum number of changed lines.
+    # max_lines = 500
+    # # What to do when a limit is exceeded.
+    # #
+    # # Can be one of: "warning", "error". With "error", the commit is refused unless
+    # # you confirm you want to commit anyway.
+    # severity = "warning"
+"#};
+
+/// The documentation for `lint`.
+pub const LINT_DOC: &str = indoc! {r#"
+
+    # The configuration of `git z lint`.
+    #
+    # This table is optional.
+    # [lint]
+    # # The commit from which the convention has been adopted.
+    # #
+    # # Older commits are not checked. Any revision understood by Git is valid, like
+    # # a commit hash or a tag.
+    # baseline = "v1.0.0"
+"#};
+
+/// The documentation for `breaking_change`.
+pub const BREAKING_CHANGE_DOC: &str = indoc! {r#"
+
+    # The help with breaking changes in `git z commit`.
+    #
+    # This table is optional.
+    # [breaking_change]
+    # # A command printing a suggested description of the breaking change. When it
+    # # is set, `git z commit` asks whether the commit is a breaking change, and
+    # # pre-fills its description with the output of the command. The command
+    # # receives the staged diff on its standard input.
+    # suggestion_command = "./scripts/api-diff.sh"
+    #
+    # # Rules to detect likely breaking changes from the staged changes. When a rule
+    # # matches, `git z commit` suggests that the commit is a breaking change.
+    # [[breaking_change.rules]]
+    # # A regex matched against the path of the changed files (optional).
+    # path = '^src/api/'
+    # # A regex matched against the removed lines (optional).
+    # pattern = '^\s*pub fn '
+    # # The reason shown to the user when the rule matches.
+    # description = "a public function has been changed or removed"
+"#};
+
+/// The documentation for `secrets`.
+pub const SECRETS_DOC: &str = indoc! {r#"
+
+    # The scan of the staged changes for secrets in `git z commit`.
+    #
+    # Built-in rules detect common API keys and private keys. This table is
+    # optional.
+    # [secrets]
+    # # Whether to disable the built-in rules.
+    # disable_default_rules = false
+    #
+    # [[secrets.rules]]
+    # # The description of the secret detected by the rule.
+    # description = "internal token"
+    # # A regex matched against the added lines.
+    # pattern = '\bint_[0-9a-f]{8}\b'
+"#};
+
+/// The documentation for `time_tracking`.
+pub const TIME_TRACKING_DOC: &str = indoc! {r#"
+
+    # The tracking of the time spent on each commit in `git z commit`.
+    #
+    # The time is measured from the start of the wizard, and added to the message
+    # as a trailer in the Jira format, like `Time-spent: 1h 30m`. This table is
+    # optional: without it, the time is not tracked.
+    # [time_tracking]
+    # # Set to true to ask for the time spent, prefilled with the measured one.
+    # ask = false
+    # # The token of the trailer.
+    # trailer = "Time-spent"
+    # # A command logging the time spent in the forge. It receives the referenced
+    # # ticket as `GITZ_TICKET` and the time spent as `GITZ_TIME_SPENT`.
+    # worklog_command = "./scripts/log-work.sh"
+"#};
+
+/// The documentation for `scope_paths`.
+pub const SCOPE_PATHS_DOC: &str = indoc! {"
+
+    # The mapping from scopes to the paths they cover, as regexes.
+    #
+    # When the staged changes span several scopes, `git z commit` offers to split
+    # them into one commit per scope. This table is optional.
+    # [scope_paths]
+    # api = '^crates/api/'
+    # cli = '^crates/cli/'
+"};
+
+/// The documentation for `type_list`.
+pub const TYPE_LIST_DOC: &str = indoc! {r#"
+
+    # How to list the types in `git z commit`.
+    #
+    # This table is optional.
+    # [type_list]
+    # # The order of the types in each group.
+    # #
+    # # Can be one of: "config", "alphabetical", "recent". With "recent", the types
+    # # used in the most recent commits come first.
+    # order = "config"
+    # # A command printing more types, one per line, optionally followed by a tab and
+    # # a description. It is run from the root of the repository, and its types come
+    # # after the ones from `[types]`.
+    # command = "./scripts/list-types.sh"
+    #
+    # # Groups of types, displayed under a separator in the order they appear here.
+    # # Types not listed in any group are shown last, under "Other".
+    # [type_list.groups]
+    # Common = ["feat", "fix", "docs"]
+    # Maintenance = ["refactor", "deps", "build", "ci", "chore"]
+    # Temporary = ["wip", "debug"]
+"#};
+
+/// The documentation for `wizard`.
+pub const WIZARD_DOC: &str = indoc! {r#"
+
+    # The configuration of the wizard of `git z commit`.
+    #
+    # This table is optional.
+    # [wizard]
+    # # The order in which to ask the questions, among "type", "scope",
+    # # "description", "breaking_change" and "ticket". The questions not listed
+    # # here are asked last, in this default order.
+    # order = ["type", "ticket", "scope", "description", "breaking_change"]
+    # # Set to true to pre-select a type guessed from the staged changes, like
+    # # `test` when only tests are staged.
+    # suggest_type = false
+"#};
+
+/// The documentation for `hooks`.
+pub const HOOKS_DOC: &str = indoc! {"
+
+    # The configuration of the Git hooks run by git-z.
+    #
+    # This table is optional.
+    # [hooks]
+    # # Set to true to run the pre-commit hook before the wizard, so that a failing
+    # # check does not waste your answers. `git z commit --no-verify` skips it.
+    # # run_pre_commit = false
+    # # The time after which the pre-commit hook is stopped, in seconds.
+    # timeout = 300
+"};
+
+/// The documentation for `suggestions`.
+pub const SUGGESTIONS_DOC: &str = indoc! {r#"
+
+    # The suggestions offered by the wizard of `git z commit`.
+    #
+    # This table is optional.
+    # [suggestions]
+    # # Set to true to suggest short descriptions generated from the staged changes,
+    # # like the names of the files and functions they touch.
+    # descriptions = false
+    # # A command printing more suggested descriptions, one per line. It receives the
+    # # staged diff on its standard input and is run from the root of the repository.
+    # description_command = "./scripts/suggest-descriptions.sh"
+"#};
+
+/// The documentation for the `template_vars` table.
+pub const TEMPLATE_VARS_DOC: &str = indoc! {r#"
+
+    # Custom variables for the commit templates, available as `vars`.
+    #
+    # They let a template shared between repositories render some sections
+    # depending on the repository, like `{% if vars.hotfix_footer %}`. The
+    # templates can also access the `[ticket]` table as `config.ticket`. This table
+    # is optional.
+    # [template_vars]
+    # team = "core"
+    # hotfix_footer = true
+"#};
+
+/// The documentation for `meta`.
+pub const META_DOC: &str = indoc! {"
+
+    # Settings about the configuration itself.
+    #
+    # This table is optional.
+    # [meta]
+    # # Whether `git z commit` refuses to run until an out-of-date configuration is
+    # # updated with `git z update`, instead of only warning about it.
+    # strict_version = true
+    # # The minimum version of git-z required by the configuration. Older versions
+    # # refuse to run any command, so that the team uses the features it relies on.
+    # min_version = \"0.3\"
+"};
+
 /// Updates the version.
-pub fn update_version(toml_config: &mut DocumentMut) {
-    let version = toml_config.get_mut("version").expect("No `version` key");
-    *version = Item::Value(VERSION.into());
+pub fn update_version(toml_config: &mut DocumentMut, version: &str) {
+    let old_version = toml_config.get_mut("version").expect("No `version` key");
+    *old_version = Item::Value(version.into());
 }
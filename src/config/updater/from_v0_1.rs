@@ -63,7 +63,7 @@ pub fn update(
     ask_for_ticket: AskForTicket,
     empty_prefix_to_hash: bool,
 ) {
-    common::update_version(toml_config);
+    common::update_version(toml_config, "0.2");
     update_types(toml_config);
     update_scopes(toml_config, switch_scopes_to_any);
 
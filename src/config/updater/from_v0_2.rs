@@ -0,0 +1,83 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Configuration updater from version 0.2.
+
+use toml_edit::DocumentMut;
+
+use super::common;
+use crate::config::VERSION;
+
+/// Updates the configuration from version 0.2.
+pub fn update(toml_config: &mut DocumentMut) {
+    common::update_version(toml_config, VERSION);
+    add_doc(toml_config, common::COMMIT_DOC);
+    add_doc(toml_config, common::COMMIT_SIZE_DOC);
+    add_doc(toml_config, common::LINT_DOC);
+    add_doc(toml_config, common::BREAKING_CHANGE_DOC);
+    add_doc(toml_config, common::SECRETS_DOC);
+    add_doc(toml_config, common::TIME_TRACKING_DOC);
+    add_doc(toml_config, common::SCOPE_PATHS_DOC);
+    add_doc(toml_config, common::TYPE_LIST_DOC);
+    add_doc(toml_config, common::WIZARD_DOC);
+    add_doc(toml_config, common::HOOKS_DOC);
+    add_doc(toml_config, common::SUGGESTIONS_DOC);
+    add_doc(toml_config, common::TEMPLATE_VARS_DOC);
+    add_doc(toml_config, common::META_DOC);
+}
+
+/// Adds the documentation for an optional table at the end of the file.
+fn add_doc(toml_config: &mut DocumentMut, doc: &str) {
+    let trailing = toml_config.trailing().as_str().unwrap_or_default();
+    let trailing = format!("{trailing}{doc}");
+    toml_config.set_trailing(trailing);
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    const V0_2_STANDARD: &str =
+        include_str!("../../../tests/res/config/v0_2_standard.toml");
+
+    const V0_2_USER_COMMENTS: &str =
+        include_str!("../../../tests/res/config/v0_2_user-comments.toml");
+
+    const V0_3_STANDARD: &str =
+        include_str!("../../../tests/res/config/v0_3_standard.toml");
+
+    const V0_3_USER_COMMENTS: &str =
+        include_str!("../../../tests/res/config/v0_3_user-comments.toml");
+
+    #[test]
+    fn update_works_with_standard_config() {
+        let mut toml_config = V0_2_STANDARD.parse().unwrap();
+
+        update(&mut toml_config);
+
+        assert_eq!(toml_config.to_string(), V0_3_STANDARD);
+    }
+
+    #[test]
+    fn update_preserves_user_comments() {
+        let mut toml_config = V0_2_USER_COMMENTS.parse().unwrap();
+
+        update(&mut toml_config);
+
+        assert_eq!(toml_config.to_string(), V0_3_USER_COMMENTS);
+    }
+}
@@ -0,0 +1,416 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Configuration for git-z, version 0.3.
+
+// NOTE: This version is still in development. New optional fields can be added
+// to it until it is released. Once released, never update the fields of the
+// types defined in this file: create a new version instead.
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/// The git-z configuration.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The version of the configuration.
+    pub version: String,
+    /// The valid commit types.
+    pub types: IndexMap<String, Type>,
+    /// How to list the types in the wizard.
+    pub type_list: Option<TypeList>,
+    /// The accepted scopes.
+    pub scopes: Option<Scopes>,
+    /// The ticket reference configuration.
+    pub ticket: Option<Ticket>,
+    /// The templates.
+    pub templates: Templates,
+    /// The mapping from scopes to regexes matching the paths they cover.
+    pub scope_paths: Option<IndexMap<String, String>>,
+    /// The configuration of `git z lint`.
+    pub lint: Option<Lint>,
+    /// The configuration of `git z commit`.
+    pub commit: Option<Commit>,
+    /// The configuration of the wizard.
+    pub wizard: Option<Wizard>,
+    /// The configuration of the hooks run by git-z.
+    pub hooks: Option<Hooks>,
+    /// The suggestions offered by the wizard.
+    pub suggestions: Option<Suggestions>,
+    /// The rules to detect likely breaking changes.
+    pub breaking_change: Option<BreakingChange>,
+    /// The configuration of the secret scan.
+    pub secrets: Option<Secrets>,
+    /// The tracking of the time spent on each commit.
+    pub time_tracking: Option<TimeTracking>,
+    /// Custom variables for the commit templates.
+    pub template_vars: Option<IndexMap<String, toml::Value>>,
+    /// Settings about the configuration itself.
+    pub meta: Option<Meta>,
+}
+
+/// Settings about the configuration itself.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Meta {
+    /// Whether to refuse to commit with an out-of-date configuration.
+    pub strict_version: Option<bool>,
+    /// The minimum version of git-z required by the configuration.
+    pub min_version: Option<String>,
+}
+
+/// A commit type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Type {
+    /// A type with only a description.
+    Simple(String),
+    /// A type with options.
+    Detailed(DetailedType),
+}
+
+/// A commit type with options.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DetailedType {
+    /// The description of the type.
+    pub description: String,
+    /// The questions of the wizard to skip for this type.
+    #[serde(default)]
+    pub skip: Vec<Question>,
+    /// The named template to use for this type.
+    pub template: Option<String>,
+}
+
+/// A question of the wizard that can be skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Question {
+    /// The scope.
+    Scope,
+    /// The description of the breaking change.
+    BreakingChange,
+    /// The ticket reference.
+    Ticket,
+}
+
+/// How to list the types in the wizard.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TypeList {
+    /// The order of the types in each group.
+    #[serde(default)]
+    pub order: TypeOrder,
+    /// Groups of types, displayed under a separator.
+    pub groups: Option<IndexMap<String, Vec<String>>>,
+    /// A command printing more accepted types.
+    pub command: Option<String>,
+}
+
+/// The order of the types in the wizard.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeOrder {
+    /// The order of the configuration.
+    #[default]
+    Config,
+    /// The alphabetical order.
+    Alphabetical,
+    /// The types used in the most recent commits first.
+    Recent,
+}
+
+/// Types of accepted scopes.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "accept", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Scopes {
+    /// Accepts any arbitrary scope.
+    Any {
+        /// Whether a scope is required.
+        #[serde(default)]
+        required: bool,
+    },
+    /// Accepts only scopes from a list.
+    List {
+        /// The list of accepted scopes.
+        #[serde(default)]
+        list: ScopeList,
+        /// A workspace whose packages are more accepted scopes.
+        from: Option<ScopeSource>,
+        /// A command printing more accepted scopes.
+        command: Option<String>,
+        /// How to order the list in the wizard.
+        #[serde(default)]
+        order: ScopeOrder,
+        /// Whether a scope is required.
+        #[serde(default)]
+        required: bool,
+    },
+}
+
+/// A list of scopes.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ScopeList {
+    /// Scopes without description.
+    Names(Vec<String>),
+    /// Scopes and their description.
+    Described(IndexMap<String, String>),
+}
+
+/// A workspace providing scopes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScopeSource {
+    /// The members of the Cargo workspace.
+    CargoWorkspace,
+    /// The workspaces of the npm package.
+    NpmWorkspace,
+    /// The packages of the pnpm workspace.
+    Pnpm,
+}
+
+/// The order of the scopes in the wizard.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ScopeOrder {
+    /// The order of the configuration.
+    #[default]
+    Config,
+    /// The scopes used the most in the recent commits first.
+    Frequency,
+}
+
+/// Ticket reference configuration.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Ticket {
+    /// Whether the ticket reference is required.
+    pub required: bool,
+    /// The valid ticket prefixes.
+    pub prefixes: Vec<String>,
+    /// Regexes with a `ticket` group to extract the ticket from branch names.
+    pub branch_patterns: Option<Vec<String>>,
+    /// A command printing the title of a ticket, for `commit --fixes` and the
+    /// `issue_title` template function.
+    pub title_command: Option<String>,
+    /// A command printing the state of a ticket, for `tickets --check-status`.
+    pub status_command: Option<String>,
+    /// The states printed by `status_command` for closed tickets.
+    pub closed_states: Option<Vec<String>>,
+    /// Where to place the ticket reference in the message.
+    #[serde(default)]
+    pub placement: TicketPlacement,
+    /// The token of the trailer referencing the ticket.
+    pub trailer: Option<String>,
+}
+
+/// Where to place the ticket reference in the message.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum TicketPlacement {
+    /// Where the commit template puts it.
+    #[default]
+    Template,
+    /// In a Git trailer, added after rendering the template.
+    Trailer,
+}
+
+/// Templates.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Templates {
+    /// The commit message template.
+    pub commit: String,
+    /// Whether to strip the comments when the message is not edited.
+    pub strip_comments: Option<bool>,
+    /// Alternative commit message templates, by name.
+    pub named: Option<IndexMap<String, String>>,
+    /// The named template to use on the branches matching a glob pattern.
+    pub branch_overrides: Option<IndexMap<String, String>>,
+    /// The commit message template for merge commits.
+    pub merge: Option<String>,
+    /// The template of the subject line, replacing the one of the message.
+    pub subject: Option<String>,
+}
+
+/// The configuration of `git z lint`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Lint {
+    /// The commit from which the convention has been adopted.
+    ///
+    /// Commits older than this one are not checked.
+    pub baseline: Option<String>,
+}
+
+/// The configuration of `git z commit`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Commit {
+    /// Whether to open the commit message in an editor before committing.
+    pub edit: Option<bool>,
+    /// Whether to show a preview of the commit message after each answer.
+    pub preview: Option<bool>,
+    /// Whether to add a `Generated-by` trailer with the version of git-z.
+    pub tool_footer: Option<bool>,
+    /// The limits on the size of the staged changes.
+    pub size: Option<CommitSize>,
+    /// The maximum length of the subject line, in terminal columns.
+    pub max_subject_length: Option<usize>,
+    /// Whether to warn when commits are signed with SSH but no agent is
+    /// available.
+    pub check_ssh_agent: Option<bool>,
+}
+
+/// The configuration of the wizard.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Wizard {
+    /// The order in which to ask the questions.
+    pub order: Option<Vec<Step>>,
+    /// Whether to suggest a type from the staged changes.
+    #[serde(default)]
+    pub suggest_type: bool,
+}
+
+/// A step of the wizard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Step {
+    /// The commit type.
+    Type,
+    /// The scope.
+    Scope,
+    /// The short description.
+    Description,
+    /// The description of the breaking change.
+    BreakingChange,
+    /// The ticket reference.
+    Ticket,
+}
+
+/// The configuration of the hooks run by git-z.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Hooks {
+    /// Whether to run the pre-commit hook before the wizard.
+    #[serde(default)]
+    pub run_pre_commit: bool,
+    /// The time after which a hook is stopped, in seconds.
+    pub timeout: Option<u64>,
+}
+
+/// The suggestions offered by the wizard.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Suggestions {
+    /// Whether to suggest descriptions generated from the staged changes.
+    #[serde(default)]
+    pub descriptions: bool,
+    /// A command printing more suggested descriptions from the staged diff.
+    pub description_command: Option<String>,
+}
+
+/// Limits on the size of the staged changes.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CommitSize {
+    /// The maximum number of changed files.
+    pub max_files: Option<usize>,
+    /// The maximum number of changed lines.
+    pub max_lines: Option<usize>,
+    /// What to do when a limit is exceeded.
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+/// The severity of a check.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Only print a warning.
+    #[default]
+    Warning,
+    /// Refuse to continue, unless the user explicitly confirms.
+    Error,
+}
+
+/// The rules to detect likely breaking changes from the staged changes.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BreakingChange {
+    /// The rules.
+    #[serde(default)]
+    pub rules: Vec<BreakingChangeRule>,
+    /// A command printing a suggested description of the breaking change.
+    pub suggestion_command: Option<String>,
+}
+
+/// A rule to detect a likely breaking change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BreakingChangeRule {
+    /// A regex matching the path of the files to consider.
+    pub path: Option<String>,
+    /// A regex matching a removed line in the considered files.
+    pub pattern: Option<String>,
+    /// A description of what the rule detects.
+    pub description: String,
+}
+
+/// The configuration of the secret scan of the staged changes.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Secrets {
+    /// Whether to disable the built-in rules.
+    #[serde(default)]
+    pub disable_default_rules: bool,
+    /// Additional rules.
+    #[serde(default)]
+    pub rules: Vec<SecretRule>,
+}
+
+/// The tracking of the time spent on each commit.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimeTracking {
+    /// Whether to ask for the time spent, prefilled with the measured one.
+    #[serde(default)]
+    pub ask: bool,
+    /// The token of the trailer containing the time spent.
+    pub trailer: Option<String>,
+    /// A command logging the time spent on the ticket in the forge.
+    pub worklog_command: Option<String>,
+}
+
+/// A rule to detect a secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SecretRule {
+    /// A description of the secret detected by the rule.
+    pub description: String,
+    /// A regex matching an added line containing the secret.
+    pub pattern: String,
+}
@@ -0,0 +1,351 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Parser for conventional commit messages.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+use thiserror::Error;
+
+/// A parsed conventional commit message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConventionalCommit {
+    /// The type of commit.
+    pub r#type: String,
+    /// The optional scope of the commit.
+    pub scope: Option<String>,
+    /// Whether the header is marked as a breaking change with `!`.
+    pub breaking: bool,
+    /// The short commit description.
+    pub description: String,
+    /// The optional body of the commit message.
+    pub body: Option<String>,
+    /// The footers of the commit message.
+    pub footers: Vec<Footer>,
+}
+
+/// A footer in a conventional commit message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Footer {
+    /// The token of the footer.
+    pub token: String,
+    /// The value of the footer.
+    pub value: String,
+}
+
+/// Errors that can occur when parsing a conventional commit message.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParseError {
+    /// The message is empty.
+    #[error("The commit message is empty")]
+    EmptyMessage,
+    /// The header does not follow the `type(scope)!: description` format.
+    #[error(
+        "The first line does not follow the `type(scope)!: description` format"
+    )]
+    InvalidHeader,
+    /// The header is not followed by an empty line.
+    #[error("The first line must be followed by an empty line")]
+    MissingBlankLine,
+}
+
+/// The regex matching a conventional commit header.
+#[expect(clippy::unwrap_used, reason = "This regex is known to be valid.")]
+static HEADER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?<type>[\w-]+)(?:\((?<scope>[^()\s][^()]*)\))?(?<breaking>!)?: (?<description>\S.*)$",
+    )
+    .unwrap()
+});
+
+/// The regex matching the first line of a footer.
+#[expect(clippy::unwrap_used, reason = "This regex is known to be valid.")]
+static FOOTER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?<token>BREAKING[ -]CHANGE|[\w-]+)(?:: (?<value>.*)| (?<hash_value>#.*))$",
+    )
+    .unwrap()
+});
+
+impl ConventionalCommit {
+    /// Parses a commit message.
+    ///
+    /// Lines starting with `#` are considered as comments and ignored.
+    #[tracing::instrument(level = "trace")]
+    pub fn parse(message: &str) -> Result<Self, ParseError> {
+        let message = message
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut lines = message.trim_start_matches('\n').lines();
+        let header = lines.next().ok_or(ParseError::EmptyMessage)?;
+
+        let captures = HEADER_REGEX
+            .captures(header)
+            .ok_or(ParseError::InvalidHeader)?;
+
+        let rest = lines.collect::<Vec<_>>();
+        if rest.first().is_some_and(|line| !line.is_empty()) {
+            return Err(ParseError::MissingBlankLine);
+        }
+
+        let (body, footers) = split_body_and_footers(&rest.join("\n"));
+
+        Ok(Self {
+            r#type: captures["type"].to_owned(),
+            scope: captures.name("scope").map(|s| s.as_str().to_owned()),
+            breaking: captures.name("breaking").is_some(),
+            description: captures["description"].to_owned(),
+            body,
+            footers,
+        })
+    }
+}
+
+/// Returns the token of a footer, if the line starts one.
+pub fn footer_token(line: &str) -> Option<&str> {
+    FOOTER_REGEX
+        .captures(line)
+        .and_then(|captures| captures.name("token"))
+        .map(|token| token.as_str())
+}
+
+/// Splits the remaining of a message into the body and the footers.
+///
+/// The footers are the last paragraph of the message, if its first line is a
+/// footer. Lines not starting with a footer token are considered to be the
+/// continuation of the previous footer.
+fn split_body_and_footers(rest: &str) -> (Option<String>, Vec<Footer>) {
+    let rest = rest.trim_matches('\n');
+    let (body, last_paragraph) = match rest.rsplit_once("\n\n") {
+        Some((body, last_paragraph)) => (Some(body), last_paragraph),
+        None => (None, rest),
+    };
+
+    let starts_with_footer = last_paragraph
+        .lines()
+        .next()
+        .is_some_and(|line| FOOTER_REGEX.is_match(line));
+
+    let footers = if starts_with_footer {
+        parse_footers(last_paragraph)
+    } else {
+        vec![]
+    };
+
+    let body = if footers.is_empty() { Some(rest) } else { body };
+
+    let body = body
+        .map(|body| body.trim_matches('\n').to_owned())
+        .filter(|body| !body.is_empty());
+
+    (body, footers)
+}
+
+/// Parses a paragraph of footers.
+fn parse_footers(paragraph: &str) -> Vec<Footer> {
+    let mut footers: Vec<Footer> = vec![];
+
+    for line in paragraph.lines() {
+        match (FOOTER_REGEX.captures(line), footers.last_mut()) {
+            (Some(captures), _) => {
+                let value = captures
+                    .name("value")
+                    .or_else(|| captures.name("hash_value"))
+                    .map(|value| value.as_str().to_owned())
+                    .unwrap_or_default();
+
+                footers.push(Footer {
+                    token: captures["token"].to_owned(),
+                    value,
+                });
+            }
+            (None, Some(footer)) => {
+                footer.value.push('\n');
+                footer.value.push_str(line);
+            }
+            (None, None) => (),
+        }
+    }
+
+    footers
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use indoc::indoc;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn parses_a_simple_header() {
+        let commit = ConventionalCommit::parse("feat: add a feature").unwrap();
+
+        assert_eq!(commit.r#type, "feat");
+        assert_eq!(commit.scope, None);
+        assert!(!commit.breaking);
+        assert_eq!(commit.description, "add a feature");
+        assert_eq!(commit.body, None);
+        assert!(commit.footers.is_empty());
+    }
+
+    #[test]
+    fn parses_a_header_with_scope_and_breaking_mark() {
+        let commit =
+            ConventionalCommit::parse("fix(config)!: change the format")
+                .unwrap();
+
+        assert_eq!(commit.r#type, "fix");
+        assert_eq!(commit.scope.as_deref(), Some("config"));
+        assert!(commit.breaking);
+    }
+
+    #[test]
+    fn parses_the_body_and_footers() {
+        let message = indoc! {"
+            feat(cli): add a flag
+
+            This is a body.
+
+            With two paragraphs.
+
+            Refs: #23
+            BREAKING CHANGE: the flag changes
+              the behaviour
+        "};
+
+        let commit = ConventionalCommit::parse(message).unwrap();
+
+        assert_eq!(
+            commit.body.as_deref(),
+            Some("This is a body.\n\nWith two paragraphs.")
+        );
+        assert_eq!(
+            commit.footers,
+            vec![
+                Footer {
+                    token: String::from("Refs"),
+                    value: String::from("#23"),
+                },
+                Footer {
+                    token: String::from("BREAKING CHANGE"),
+                    value: String::from("the flag changes\n  the behaviour"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_footers_without_body() {
+        let message = indoc! {"
+            fix: patch a bug
+
+            Closes #42
+        "};
+
+        let commit = ConventionalCommit::parse(message).unwrap();
+
+        assert_eq!(commit.body, None);
+        assert_eq!(
+            commit.footers,
+            vec![Footer {
+                token: String::from("Closes"),
+                value: String::from("#42"),
+            }]
+        );
+    }
+
+    #[test]
+    fn ignores_commented_lines() {
+        let message = indoc! {"
+            docs: update the readme
+
+            # Feel free to enter a longer description here.
+
+        "};
+
+        let commit = ConventionalCommit::parse(message).unwrap();
+
+        assert_eq!(commit.body, None);
+        assert!(commit.footers.is_empty());
+    }
+
+    #[test]
+    fn refuses_an_invalid_header() {
+        assert_eq!(
+            ConventionalCommit::parse("Add a feature"),
+            Err(ParseError::InvalidHeader)
+        );
+        assert_eq!(
+            ConventionalCommit::parse("feat:add a feature"),
+            Err(ParseError::InvalidHeader)
+        );
+        assert_eq!(
+            ConventionalCommit::parse("feat(): add a feature"),
+            Err(ParseError::InvalidHeader)
+        );
+    }
+
+    #[test]
+    fn refuses_an_empty_message() {
+        assert_eq!(
+            ConventionalCommit::parse("\n# comment\n"),
+            Err(ParseError::EmptyMessage)
+        );
+    }
+
+    #[test]
+    fn refuses_a_body_directly_after_the_header() {
+        assert_eq!(
+            ConventionalCommit::parse("feat: add a feature\nbody"),
+            Err(ParseError::MissingBlankLine)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn does_not_panic_on_arbitrary_input(message in any::<String>()) {
+            drop(ConventionalCommit::parse(&message));
+        }
+
+        #[test]
+        fn parses_any_valid_header(
+            r#type in "[a-z][a-z-]{0,10}",
+            scope in proptest::option::of("[a-z][a-z0-9/-]{0,10}"),
+            breaking in any::<bool>(),
+            description in "[a-z]([a-zA-Z0-9 ,.'-]{0,40}[a-z0-9])?",
+        ) {
+            let header = format!(
+                "{type}{scope}{breaking}: {description}",
+                scope = scope.as_ref().map(|scope| format!("({scope})")).unwrap_or_default(),
+                breaking = if breaking { "!" } else { "" },
+            );
+
+            let commit = ConventionalCommit::parse(&header).unwrap();
+
+            prop_assert_eq!(commit.r#type, r#type);
+            prop_assert_eq!(commit.scope, scope);
+            prop_assert_eq!(commit.breaking, breaking);
+            prop_assert_eq!(commit.description, description);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Heuristics to suggest short descriptions from the staged changes.
+//!
+//! The candidates name the definitions added, updated or removed, then the
+//! files touched. Only the ones passing the description checks are kept.
+
+use std::sync::LazyLock;
+
+use indexmap::IndexSet;
+use regex::Regex;
+
+use crate::{lint, staged::StagedPatch};
+
+/// The maximum number of suggestions.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// The maximum number of files named in a suggestion.
+const MAX_NAMED_FILES: usize = 3;
+
+/// The regex matching the definition of a function or a type.
+#[expect(clippy::unwrap_used, reason = "This regex is known to be valid.")]
+static DEFINITION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\b(?:fn|def|func|function|class|struct|enum|trait|interface)\s+(?<name>[A-Za-z_]\w*)",
+    )
+    .unwrap()
+});
+
+/// Suggests short descriptions from the staged changes.
+pub fn suggest(patch: &StagedPatch) -> Vec<String> {
+    let mut candidates = IndexSet::new();
+
+    for file in &patch.files {
+        let added = definitions(&file.added_lines);
+        let removed = definitions(&file.removed_lines);
+
+        for name in &added {
+            if removed.contains(name) {
+                candidates.insert(format!("update {name}"));
+            } else {
+                candidates.insert(format!("add {name}"));
+            }
+        }
+
+        for name in removed.difference(&added) {
+            candidates.insert(format!("remove {name}"));
+        }
+    }
+
+    if let Some(files) = describe_files(patch) {
+        candidates.insert(files);
+    }
+
+    let suggestions: Vec<String> = candidates
+        .into_iter()
+        .filter(|candidate| lint::check_description(candidate).is_none())
+        .take(MAX_SUGGESTIONS)
+        .collect();
+
+    tracing::debug!(?suggestions, "suggested descriptions");
+    suggestions
+}
+
+/// Returns the names defined in the lines.
+fn definitions(lines: &[String]) -> IndexSet<&str> {
+    lines
+        .iter()
+        .flat_map(|line| DEFINITION_REGEX.captures_iter(line))
+        .filter_map(|captures| captures.name("name"))
+        .map(|name| name.as_str())
+        .collect()
+}
+
+/// Describes the files touched, like `update foo.rs and bar.rs`.
+fn describe_files(patch: &StagedPatch) -> Option<String> {
+    let names: IndexSet<&str> = patch
+        .files
+        .iter()
+        .map(|file| file.path.rsplit('/').next().unwrap_or(&file.path))
+        .collect();
+
+    let names: Vec<&str> = names.into_iter().collect();
+
+    match names.as_slice() {
+        [] => None,
+        [name] => Some(format!("update {name}")),
+        [names @ .., last] if names.len() < MAX_NAMED_FILES => {
+            Some(format!("update {} and {last}", names.join(", ")))
+        }
+        [first, second, others @ ..] => Some(format!(
+            "update {first}, {second} and {} other files",
+            others.len()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+    use crate::staged::FilePatch;
+
+    fn file(path: &str, added: &[&str], removed: &[&str]) -> FilePatch {
+        FilePatch {
+            path: String::from(path),
+            added_lines: added.iter().map(|line| String::from(*line)).collect(),
+            removed_lines: removed
+                .iter()
+                .map(|line| String::from(*line))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn names_the_definitions_added_updated_and_removed() {
+        let patch = StagedPatch {
+            files: vec![file(
+                "src/lib.rs",
+                &["pub fn run(force: bool) {}", "struct Options {"],
+                &["pub fn run() {}", "fn legacy() {}"],
+            )],
+        };
+
+        assert_eq!(
+            suggest(&patch),
+            vec![
+                "update run",
+                "add Options",
+                "remove legacy",
+                "update lib.rs"
+            ]
+        );
+    }
+
+    #[test]
+    fn names_up_to_three_files() {
+        let patch = StagedPatch {
+            files: vec![
+                file("src/a.rs", &[], &[]),
+                file("src/b.rs", &[], &[]),
+                file("src/c.rs", &[], &[]),
+            ],
+        };
+
+        assert_eq!(suggest(&patch), vec!["update a.rs, b.rs and c.rs"]);
+    }
+
+    #[test]
+    fn counts_the_other_files() {
+        let patch = StagedPatch {
+            files: vec![
+                file("a.rs", &[], &[]),
+                file("b.rs", &[], &[]),
+                file("c.rs", &[], &[]),
+                file("d.rs", &[], &[]),
+            ],
+        };
+
+        assert_eq!(
+            suggest(&patch),
+            vec!["update a.rs, b.rs and 2 other files"]
+        );
+    }
+
+    #[test]
+    fn skips_the_invalid_descriptions() {
+        let patch = StagedPatch {
+            files: vec![file(
+                "README.md",
+                &["function a_very_long_function_name_that_does_not_fit_at_all() {}"],
+                &[],
+            )],
+        };
+
+        assert_eq!(suggest(&patch), vec!["update README.md"]);
+    }
+
+    #[test]
+    fn does_not_suggest_anything_without_staged_changes() {
+        assert!(suggest(&StagedPatch::default()).is_empty());
+    }
+}
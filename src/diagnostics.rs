@@ -0,0 +1,200 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Diagnostics about the environment of git-z.
+//!
+//! They are meant to be included in bug reports, so gathering them never fails:
+//! what cannot be determined is simply left out.
+
+use std::{fs, path::PathBuf};
+
+use serde::Serialize;
+
+use crate::{
+    config::{self, Config},
+    git,
+};
+
+/// The Git hooks git-z interacts with.
+const HOOKS: [&str; 3] = ["pre-commit", "prepare-commit-msg", "commit-msg"];
+
+/// The sections every configuration has, which are not optional features.
+const REQUIRED_SECTIONS: [&str; 4] =
+    ["version", "types", "scopes", "templates"];
+
+/// The keys of the `templates` section every configuration has.
+const REQUIRED_TEMPLATES: [&str; 1] = ["commit"];
+
+/// Diagnostics about the environment of git-z.
+#[derive(Debug, Serialize)]
+pub struct Diagnostics {
+    /// The path of the configuration file.
+    config_path: Option<PathBuf>,
+    /// Whether the configuration file exists.
+    config_found: bool,
+    /// The version of the configuration.
+    config_version: Option<String>,
+    /// The version control system git-z runs.
+    backend: &'static str,
+    /// The version of the backend.
+    backend_version: Option<String>,
+    /// The optional features configured in the repository.
+    integrations: Vec<String>,
+    /// The installed Git hooks.
+    hooks: Vec<&'static str>,
+}
+
+/// The diagnostics, under their own table.
+#[derive(Debug, Serialize)]
+struct Report<'a> {
+    /// The diagnostics.
+    diagnostics: &'a Diagnostics,
+}
+
+impl Diagnostics {
+    /// Gathers the diagnostics from the current directory.
+    #[tracing::instrument(level = "trace")]
+    pub fn gather() -> Self {
+        let config_path = config::config_file().ok();
+        let config = config_path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok());
+
+        let diagnostics = Self {
+            config_found: config.is_some(),
+            config_version: config.as_deref().and_then(config_version),
+            config_path,
+            backend: "git",
+            backend_version: git::run_trimmed(&["--version"]).ok().map(
+                |version| version.trim_start_matches("git version ").to_owned(),
+            ),
+            integrations: config
+                .as_deref()
+                .and_then(|config| Config::from_toml(config).ok())
+                .map(|config| integrations(&config))
+                .unwrap_or_default(),
+            hooks: installed_hooks(),
+        };
+
+        tracing::debug!(?diagnostics);
+        diagnostics
+    }
+
+    /// Renders the diagnostics as a TOML table.
+    pub fn to_toml(&self) -> String {
+        toml::to_string(&Report { diagnostics: self }).unwrap_or_else(|error| {
+            format!("# Failed to render the diagnostics: {error}\n")
+        })
+    }
+}
+
+/// Reads the version from a configuration, even if the rest is invalid.
+fn config_version(config: &str) -> Option<String> {
+    toml::from_str::<toml::Table>(config)
+        .ok()?
+        .get("version")?
+        .as_str()
+        .map(ToOwned::to_owned)
+}
+
+/// Lists the optional features enabled in the configuration.
+///
+/// They are read from the serialised configuration, so that any section added
+/// to it is listed without having to be declared here.
+fn integrations(config: &Config) -> Vec<String> {
+    let Ok(table) = toml::Table::try_from(config) else {
+        return vec![];
+    };
+
+    let templates = table
+        .get("templates")
+        .and_then(toml::Value::as_table)
+        .into_iter()
+        .flat_map(|templates| templates.keys())
+        .filter(|key| !REQUIRED_TEMPLATES.contains(&key.as_str()))
+        .map(|key| format!("templates.{key}"));
+
+    table
+        .keys()
+        .filter(|key| !REQUIRED_SECTIONS.contains(&key.as_str()))
+        .cloned()
+        .chain(templates)
+        .collect()
+}
+
+/// Lists the Git hooks installed in the repository.
+fn installed_hooks() -> Vec<&'static str> {
+    // NOTE: Git resolves `hooks` to `core.hooksPath` if it is set.
+    let Ok(hooks_dir) = git::path("hooks") else {
+        return vec![];
+    };
+
+    HOOKS
+        .into_iter()
+        .filter(|hook| hooks_dir.join(hook).is_file())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    #[test]
+    fn lists_no_integration_for_the_default_config() {
+        assert!(integrations(&Config::default()).is_empty());
+    }
+
+    #[test]
+    fn lists_every_optional_section() {
+        let config = Config::from_toml(
+            r#"
+            version = "0.3-dev.0"
+
+            [types]
+            feat = "A new feature"
+
+            [templates]
+            commit = "{{ type }}: {{ description }}"
+            named = { short = "{{ description }}" }
+
+            [wizard]
+            suggest_type = true
+
+            [suggestions]
+            descriptions = true
+
+            [time_tracking]
+            ask = true
+
+            [meta]
+            strict_version = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            integrations(&config),
+            [
+                "meta",
+                "suggestions",
+                "time_tracking",
+                "wizard",
+                "templates.named"
+            ]
+        );
+    }
+}
@@ -0,0 +1,41 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Entry points for the fuzz targets.
+//!
+//! They expose the parsers to the `fuzz` crate without making them part of the
+//! API. Their result is discarded: the fuzzer is only looking for panics.
+
+use crate::{
+    commit_cache::CommitCache, config::Config,
+    conventional_commit::ConventionalCommit,
+};
+
+/// Parses a configuration, rendering the error if any.
+pub fn config_from_toml(toml: &str) {
+    if let Err(error) = Config::from_toml(toml) {
+        drop(error.to_string());
+    }
+}
+
+/// Parses a commit cache.
+pub fn commit_cache_from_toml(toml: &str) {
+    drop(CommitCache::from_toml(toml));
+}
+
+/// Parses a conventional commit message.
+pub fn parse_conventional_commit(message: &str) {
+    drop(ConventionalCommit::parse(message));
+}
@@ -0,0 +1,156 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Helpers to run Git.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+    string::FromUtf8Error,
+};
+
+use thiserror::Error;
+
+use crate::tracing::LogResult as _;
+
+/// Errors that can occur when running Git.
+#[derive(Debug, Error)]
+pub enum GitError {
+    /// The `git` command cannot be run.
+    #[error("Failed to run the git command")]
+    CannotRun(#[source] io::Error),
+    /// Git has returned an error.
+    #[error("{0}")]
+    Failed(String),
+    /// The output of the git command is not proper UTF-8.
+    #[error("The output of the git command is not proper UTF-8")]
+    Encoding(#[source] FromUtf8Error),
+}
+
+/// Runs a git command and returns its standard output.
+#[tracing::instrument(level = "trace")]
+pub fn run(args: &[&str]) -> Result<String, GitError> {
+    output(Command::new("git").args(args))
+}
+
+/// Runs a git command in the given directory and returns its standard output.
+#[tracing::instrument(level = "trace")]
+pub fn run_in(dir: &Path, args: &[&str]) -> Result<String, GitError> {
+    output(Command::new("git").current_dir(dir).args(args))
+}
+
+/// Runs a git command and returns its trimmed standard output.
+pub fn run_trimmed(args: &[&str]) -> Result<String, GitError> {
+    Ok(run(args)?.trim().to_owned())
+}
+
+/// Runs a git command attached to the terminal.
+///
+/// This is meant for interactive commands, so their output is not captured.
+#[tracing::instrument(level = "trace")]
+pub fn run_attached(args: &[&str]) -> Result<(), GitError> {
+    let status = Command::new("git")
+        .args(args)
+        .status()
+        .map_err(GitError::CannotRun)
+        .log_err()?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(GitError::Failed(format!(
+            "`git {}` has failed",
+            args.join(" ")
+        )))
+        .log_err()
+    }
+}
+
+/// Runs a git command and returns whether it succeeds.
+#[tracing::instrument(level = "trace")]
+pub fn succeeds(args: &[&str]) -> Result<bool, GitError> {
+    let status = Command::new("git")
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(GitError::CannotRun)
+        .log_err()?;
+
+    Ok(status.success())
+}
+
+/// Returns a value from the Git configuration, if set.
+///
+/// The arguments are passed to `git config --get`, so that options like
+/// `--path` or `--type=bool` can be given before the key.
+#[tracing::instrument(level = "trace")]
+pub fn config(args: &[&str]) -> Result<Option<String>, GitError> {
+    let git_config = Command::new("git")
+        .args(["config", "--get"])
+        .args(args)
+        .output()
+        .map_err(GitError::CannotRun)
+        .log_err()?;
+
+    if git_config.status.code() == Some(1) {
+        // NOTE: Git exits with 1 when the key is not set.
+        return Ok(None);
+    }
+
+    let value = checked_stdout(git_config)?;
+    let value = value.trim();
+    Ok((!value.is_empty()).then(|| value.to_owned()))
+}
+
+/// Returns the absolute path of the Git directory.
+#[tracing::instrument(level = "trace")]
+pub fn dir() -> Result<PathBuf, GitError> {
+    Ok(run_trimmed(&["rev-parse", "--absolute-git-dir"])?.into())
+}
+
+/// Returns the path of a file in the Git directory.
+///
+/// The path is resolved by Git, so that environment overrides like `GIT_DIR`
+/// and non-standard layouts like linked worktrees are taken into account.
+#[tracing::instrument(level = "trace")]
+pub fn path(path: &str) -> Result<PathBuf, GitError> {
+    Ok(run_trimmed(&["rev-parse", "--git-path", path])?.into())
+}
+
+/// Runs a prepared git command and returns its standard output.
+fn output(command: &mut Command) -> Result<String, GitError> {
+    let output = command.output().map_err(GitError::CannotRun).log_err()?;
+    checked_stdout(output)
+}
+
+/// Returns the standard output of Git, or its standard error if it has failed.
+fn checked_stdout(output: Output) -> Result<String, GitError> {
+    if output.status.success() {
+        String::from_utf8(output.stdout)
+            .map_err(GitError::Encoding)
+            .log_err()
+    } else {
+        Err(GitError::Failed(
+            String::from_utf8(output.stderr)
+                .map_err(GitError::Encoding)
+                .log_err()?
+                .trim()
+                .to_owned(),
+        ))
+        .log_err()
+    }
+}
@@ -15,6 +15,21 @@
 
 //! General helpers.
 
+use std::{fs, io, path::Path, process};
+
+use unicode_segmentation::UnicodeSegmentation as _;
+use unicode_width::UnicodeWidthStr as _;
+
+/// The maximum width of a grapheme in a terminal.
+const MAX_GRAPHEME_WIDTH: usize = 2;
+
+/// The character opening a bidirectional isolate, with its direction guessed
+/// from its first strong character.
+const FIRST_STRONG_ISOLATE: char = '\u{2068}';
+
+/// The character closing a bidirectional isolate.
+const POP_DIRECTIONAL_ISOLATE: char = '\u{2069}';
+
 /// Uncapitalises the first character in s.
 pub fn uncapitalise(s: &str) -> String {
     let mut chars = s.chars();
@@ -23,3 +38,181 @@ pub fn uncapitalise(s: &str) -> String {
         Some(c) => c.to_lowercase().collect::<String>() + chars.as_str(),
     }
 }
+
+/// Returns the number of columns taken by s in a terminal.
+///
+/// Each grapheme is measured as a whole, so that accented letters count as one
+/// column whatever their normalisation, and emoji sequences joined by a ZWJ as
+/// a single wide character.
+pub fn display_width(s: &str) -> usize {
+    s.graphemes(true)
+        .map(|grapheme| grapheme.width().min(MAX_GRAPHEME_WIDTH))
+        .sum()
+}
+
+/// Pads s with spaces up to the given number of columns.
+pub fn pad(s: &str, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(s));
+    format!("{s}{}", " ".repeat(padding))
+}
+
+/// Removes the last grapheme of s.
+///
+/// This is what users expect from a backspace, even when the grapheme is made
+/// of several characters, like a letter with a combining accent.
+pub fn pop_grapheme(s: &mut String) {
+    let start = s
+        .grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(index, _)| index);
+
+    s.truncate(start);
+}
+
+/// Isolates s from the surrounding text if it contains right-to-left text.
+///
+/// In a terminal supporting bidirectional text, a right-to-left text is
+/// otherwise reordered along with what surrounds it, like a label or a length
+/// counter. The isolate keeps it in its own run, and is invisible.
+pub fn isolate_bidi(s: &str) -> String {
+    if s.chars().any(is_right_to_left) {
+        format!("{FIRST_STRONG_ISOLATE}{s}{POP_DIRECTIONAL_ISOLATE}")
+    } else {
+        s.to_owned()
+    }
+}
+
+/// Returns whether a character is from a right-to-left script.
+///
+/// This covers the blocks of the Hebrew, Arabic, Syriac, Thaana and N’Ko
+/// scripts, with their presentation forms, which is enough to detect the text
+/// to isolate.
+fn is_right_to_left(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0590}'..='\u{08FF}'
+            | '\u{FB1D}'..='\u{FDFF}'
+            | '\u{FE70}'..='\u{FEFF}'
+            | '\u{10800}'..='\u{10FFF}'
+            | '\u{1E800}'..='\u{1EFFF}'
+    )
+}
+
+/// Computes the 64-bit FNV-1a hash of some bytes.
+///
+/// Unlike the hasher of the standard library, its output is stable across
+/// versions of Rust, hence suitable for a file on disk.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Replaces the content of a file atomically.
+///
+/// The content is first written to a temporary file next to it, which is then
+/// renamed. This way, an interrupted write cannot leave a partial file behind,
+/// and concurrent writers cannot interleave their contents.
+pub fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let mut tmp_file = path.as_os_str().to_owned();
+    tmp_file.push(format!(".{}.tmp", process::id()));
+
+    fs::write(&tmp_file, contents)?;
+    fs::rename(&tmp_file, path).inspect_err(|_| {
+        if let Err(error) = fs::remove_file(&tmp_file) {
+            tracing::debug!(?error, "cannot remove the temporary file");
+        }
+    })
+}
+
+/// Sets a file as executable.
+#[cfg(unix)]
+pub fn set_executable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+/// Sets a file as executable.
+///
+/// Git runs hooks through `sh` on Windows, so there is nothing to do.
+#[cfg(not(unix))]
+#[expect(clippy::unnecessary_wraps, reason = "Same API as on Unix.")]
+pub fn set_executable(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    #[test]
+    fn computes_the_reference_fnv1a_hashes() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn display_width_counts_ascii_characters() {
+        assert_eq!(display_width("add a flag"), 10);
+    }
+
+    #[test]
+    fn display_width_counts_accented_letters_once() {
+        assert_eq!(display_width("corrige l’été"), 13);
+        assert_eq!(display_width("e\u{301}t\u{301}e\u{301}"), 3);
+    }
+
+    #[test]
+    fn display_width_counts_wide_characters_twice() {
+        assert_eq!(display_width("修正"), 4);
+        assert_eq!(display_width("fix 🐛"), 6);
+    }
+
+    #[test]
+    fn display_width_counts_emoji_sequences_once() {
+        assert_eq!(display_width("👩\u{200d}💻"), 2);
+        assert_eq!(display_width("👍🏽"), 2);
+    }
+
+    #[test]
+    fn display_width_ignores_bidi_isolates() {
+        assert_eq!(display_width(&isolate_bidi("תיקון באג")), 9);
+    }
+
+    #[test]
+    fn pop_grapheme_removes_whole_graphemes() {
+        let mut text = String::from("e\u{301}te\u{301}");
+        pop_grapheme(&mut text);
+        assert_eq!(text, "e\u{301}t");
+
+        let mut text = String::from("ok 👩\u{200d}💻");
+        pop_grapheme(&mut text);
+        assert_eq!(text, "ok ");
+
+        let mut text = String::new();
+        pop_grapheme(&mut text);
+        assert_eq!(text, "");
+    }
+
+    #[test]
+    fn isolate_bidi_isolates_right_to_left_text() {
+        assert_eq!(isolate_bidi("תיקון באג"), "\u{2068}תיקון באג\u{2069}");
+        assert_eq!(isolate_bidi("إصلاح خطأ"), "\u{2068}إصلاح خطأ\u{2069}");
+    }
+
+    #[test]
+    fn isolate_bidi_leaves_left_to_right_text_untouched() {
+        assert_eq!(isolate_bidi("fix a bug"), "fix a bug");
+        assert_eq!(isolate_bidi("修正 été"), "修正 été");
+    }
+
+    #[test]
+    fn pad_aligns_on_columns() {
+        assert_eq!(pad("fix", 5), "fix  ");
+        assert_eq!(pad("修正", 5), "修正 ");
+        assert_eq!(pad("feature", 5), "feature");
+    }
+}
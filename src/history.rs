@@ -0,0 +1,143 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Conventional commits of the history, for the features learning from it.
+
+use indexmap::IndexMap;
+
+use crate::{
+    config::Config,
+    conventional_commit::ConventionalCommit,
+    git::{self, GitError},
+    subject_template,
+};
+
+/// Returns the conventional commits in the history of `HEAD`, the most recent
+/// first.
+///
+/// Only the subjects are parsed, and the merge commits are skipped. Subjects
+/// decorated with `templates.subject` are read as the conventional ones.
+/// Without `limit`, the whole history is read. There is no history before the
+/// first commit.
+#[tracing::instrument(level = "trace", skip(config))]
+pub fn conventional_commits(
+    config: &Config,
+    limit: Option<usize>,
+) -> Result<Vec<ConventionalCommit>, GitError> {
+    let max_count = limit.map(|limit| format!("--max-count={limit}"));
+    let mut args = vec!["log", "--no-merges", "--format=%s"];
+    args.extend(max_count.as_deref());
+
+    let subjects = match git::run(&args) {
+        Ok(subjects) => subjects,
+        Err(error) => {
+            // NOTE: `git log` fails when there is no commit yet.
+            if git::succeeds(&["rev-parse", "--verify", "--quiet", "HEAD"])? {
+                return Err(error);
+            }
+
+            tracing::debug!("no history to read");
+            return Ok(vec![]);
+        }
+    };
+
+    let commits = parse_subjects(config, &subjects);
+    tracing::trace!(?commits);
+    Ok(commits)
+}
+
+/// Counts the occurrences of each value, the most frequent first.
+///
+/// Values with the same count keep the order of their first occurrence.
+pub fn count(
+    values: impl IntoIterator<Item = String>,
+) -> IndexMap<String, usize> {
+    let mut counts = IndexMap::<String, usize>::new();
+
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+
+    counts.sort_by(|_, count_a, _, count_b| count_b.cmp(count_a));
+    counts
+}
+
+/// Parses the conventional commits from a list of subjects.
+fn parse_subjects(config: &Config, subjects: &str) -> Vec<ConventionalCommit> {
+    subjects
+        .lines()
+        .filter_map(|subject| subject_template::parse(config, subject).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use indexmap::indexmap;
+
+    use super::*;
+    use crate::config::Templates;
+
+    #[test]
+    fn parses_only_the_conventional_commits() {
+        let subjects = "feat(cli): add a flag\n\
+            Update the README\n\
+            fix: patch a bug\n";
+
+        let types = parse_subjects(&Config::default(), subjects)
+            .into_iter()
+            .map(|commit| commit.r#type)
+            .collect::<Vec<_>>();
+
+        assert_eq!(types, ["feat", "fix"]);
+    }
+
+    #[test]
+    fn parses_the_decorated_subjects() {
+        let config = Config {
+            templates: Templates {
+                subject: Some(String::from(
+                    "{% if ticket %}[{{ ticket }}] {% endif %}{{ type }}\
+                        {% if scope %}({{ scope }}){% endif %}: \
+                        {{ description }}",
+                )),
+                ..Config::default().templates
+            },
+            ..Config::default()
+        };
+
+        let scopes = parse_subjects(&config, "[#13] feat(api): add a thing\n")
+            .into_iter()
+            .map(|commit| commit.scope)
+            .collect::<Vec<_>>();
+
+        assert_eq!(scopes, [Some(String::from("api"))]);
+    }
+
+    #[test]
+    fn counts_the_values_by_decreasing_count() {
+        let values = ["config", "cli", "api", "cli"].map(String::from);
+
+        assert_eq!(
+            count(values),
+            indexmap! {
+                String::from("cli") => 2,
+                String::from("config") => 1,
+                String::from("api") => 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,249 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Titles of the issues, fetched with `ticket.title_command`.
+//!
+//! Fetching a title usually requires to reach the forge, so the titles are
+//! cached in the git-z directory. Once fetched, a title is then available
+//! offline.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use eyre::Result;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use tera::Value;
+
+use crate::{
+    commit_cache,
+    list_command::{self, ListCommandError},
+};
+
+/// The name of the Tera function returning the title of an issue.
+pub const FUNCTION_NAME: &str = "issue_title";
+
+/// The variable containing the ticket in the environment of `title_command`.
+const TICKET_VAR: &str = "GITZ_TICKET";
+
+/// The name of the issue title cache file.
+const ISSUE_TITLES_FILE_NAME: &str = "issue-titles.toml";
+
+/// The cached titles of the issues.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IssueTitles {
+    /// The title of each issue.
+    titles: IndexMap<String, String>,
+}
+
+/// The `issue_title(id)` function of the templates.
+///
+/// It returns the title of the issue, or an empty string when it cannot be
+/// fetched, so that the template can fall back to the ID alone.
+#[derive(Debug)]
+pub struct IssueTitle {
+    /// The command printing the title of an issue, if any.
+    command: Option<String>,
+    /// Whether to only return the titles already in the cache.
+    cached_only: bool,
+    /// The titles already looked up, `None` when they cannot be fetched.
+    titles: Mutex<HashMap<String, Option<String>>>,
+}
+
+impl IssueTitle {
+    /// Creates the function, fetching the titles with `command`.
+    ///
+    /// Without a command, no title is ever returned.
+    pub fn new(command: Option<&str>) -> Self {
+        Self {
+            command: command.map(ToOwned::to_owned),
+            cached_only: false,
+            titles: Mutex::default(),
+        }
+    }
+
+    /// Creates the function for the previews, returning only cached titles.
+    ///
+    /// Previews are rendered from partial answers, like `#4` while typing
+    /// `#42`, so they must neither reach the forge nor fill the cache.
+    pub fn cached(command: Option<&str>) -> Self {
+        Self {
+            cached_only: true,
+            ..Self::new(command)
+        }
+    }
+
+    /// Returns the title of an issue, looking it up only once.
+    fn title(&self, ticket: &str) -> Option<String> {
+        let command = self.command.as_deref()?;
+
+        // NOTE: The templates can be rendered several times, so failures are
+        // remembered too, and only traced.
+        let mut titles = self.titles.lock().ok()?;
+        titles
+            .entry(ticket.to_owned())
+            .or_insert_with(|| {
+                if self.cached_only {
+                    return cached(ticket);
+                }
+
+                fetch(command, ticket).unwrap_or_else(|error| {
+                    tracing::warn!(
+                        ?error,
+                        "cannot fetch the title of {ticket}"
+                    );
+                    None
+                })
+            })
+            .clone()
+    }
+}
+
+impl tera::Function for IssueTitle {
+    fn call(&self, args: &HashMap<String, Value>) -> tera::Result<Value> {
+        let ticket = match args.get("id") {
+            Some(Value::String(ticket)) => ticket.clone(),
+            Some(Value::Number(ticket)) => ticket.to_string(),
+            Some(Value::Null) => return Ok(Value::from("")),
+            Some(_) => {
+                return Err(tera::Error::msg(format!(
+                    "`{FUNCTION_NAME}` expects `id` to be a string"
+                )));
+            }
+            None => {
+                return Err(tera::Error::msg(format!(
+                    "`{FUNCTION_NAME}` expects an `id` argument"
+                )));
+            }
+        };
+
+        Ok(Value::from(self.title(&ticket).unwrap_or_default()))
+    }
+}
+
+/// Returns the cached title of an issue, if any.
+#[tracing::instrument(level = "trace")]
+pub fn cached(ticket: &str) -> Option<String> {
+    let (_, mut cache) = load();
+    cache.titles.swap_remove(ticket)
+}
+
+/// Fetches the title of an issue with `command`, using the cache if possible.
+///
+/// Titles are added to the cache once fetched. When the cache cannot be read
+/// or written, the command is run anyway.
+#[tracing::instrument(level = "trace")]
+pub fn fetch(
+    command: &str,
+    ticket: &str,
+) -> Result<Option<String>, ListCommandError> {
+    let (cache_file, mut cache) = load();
+
+    if let Some(title) = cache.titles.get(ticket) {
+        tracing::debug!(?title, "using the cached issue title");
+        return Ok(Some(title.clone()));
+    }
+
+    let title = list_command::first_line(command, &[(TICKET_VAR, ticket)])?;
+
+    if let (Some(title), Some(cache_file)) = (&title, &cache_file) {
+        cache.titles.insert(ticket.to_owned(), title.clone());
+
+        if let Err(error) = save(cache_file, &cache) {
+            tracing::warn!(?error, "cannot save the issue title cache");
+        }
+    }
+
+    Ok(title)
+}
+
+/// Loads the issue title cache, returning the path of its file if any.
+fn load() -> (Option<PathBuf>, IssueTitles) {
+    let cache_file = commit_cache::gitz_dir()
+        .ok()
+        .map(|gitz_dir| gitz_dir.join(ISSUE_TITLES_FILE_NAME));
+
+    let cache = cache_file
+        .as_ref()
+        .and_then(|cache_file| fs::read_to_string(cache_file).ok())
+        .and_then(|cache| toml::from_str::<IssueTitles>(&cache).ok())
+        .unwrap_or_default();
+
+    (cache_file, cache)
+}
+
+/// Saves the issue title cache.
+fn save(cache_file: &Path, cache: &IssueTitles) -> Result<()> {
+    if let Some(gitz_dir) = cache_file.parent() {
+        fs::create_dir_all(gitz_dir)?;
+    }
+
+    fs::write(cache_file, toml::to_string(cache)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use tera::Function as _;
+
+    use super::*;
+
+    fn call(function: &IssueTitle, id: Option<Value>) -> tera::Result<Value> {
+        let args = id
+            .map(|id| HashMap::from([(String::from("id"), id)]))
+            .unwrap_or_default();
+        function.call(&args)
+    }
+
+    #[test]
+    fn returns_an_empty_title_without_command() {
+        let function = IssueTitle::new(None);
+        assert_eq!(
+            call(&function, Some(Value::from("#42"))).unwrap(),
+            Value::from("")
+        );
+    }
+
+    #[test]
+    fn returns_an_empty_title_without_ticket() {
+        let function = IssueTitle::new(Some("echo Title"));
+        assert_eq!(
+            call(&function, Some(Value::Null)).unwrap(),
+            Value::from("")
+        );
+    }
+
+    #[test]
+    fn does_not_fetch_the_titles_for_the_previews() {
+        let function = IssueTitle::cached(Some("echo Title"));
+        assert_eq!(
+            call(&function, Some(Value::from("#0"))).unwrap(),
+            Value::from("")
+        );
+    }
+
+    #[test]
+    fn requires_an_id() {
+        let function = IssueTitle::new(None);
+        assert!(call(&function, None).is_err());
+        assert!(call(&function, Some(Value::Bool(true))).is_err());
+    }
+}
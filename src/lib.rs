@@ -15,11 +15,38 @@
 
 //! A Git extension to go beyond.
 
+mod breaking_change;
 mod command;
 mod commit_cache;
 mod config;
+mod conventional_commit;
+mod description_suggestion;
+mod diagnostics;
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing;
+mod git;
 mod helpers;
+mod history;
+mod issue_title;
+mod lint;
+mod list_command;
+mod metrics;
+mod min_version;
+mod prefetch;
+mod reword;
+mod scope_frequency;
+mod scope_paths;
+mod secrets;
+mod sections;
+mod staged;
+mod subject_template;
 mod tracing;
+mod type_list;
+mod type_suggestion;
+mod workspace;
 
 #[doc(hidden)]
 pub use command::GitZ;
+#[doc(hidden)]
+pub use config::Config;
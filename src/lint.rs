@@ -0,0 +1,885 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Rules to check commit messages against the configuration.
+
+use std::{
+    collections::HashMap,
+    env, fmt,
+    path::PathBuf,
+    sync::{LazyLock, Mutex, MutexGuard, PoisonError},
+};
+
+use itertools::Itertools as _;
+use regex::Regex;
+use thiserror::Error;
+
+use crate::{
+    config::{Config, Question, ScopeSource, Scopes},
+    conventional_commit::{ConventionalCommit, ParseError},
+    helpers::display_width,
+    list_command::{self, Entry},
+    subject_template, warning, workspace,
+};
+
+/// The minimum length of a commit description.
+pub const MIN_DESCRIPTION_LEN: usize = 5;
+
+/// The maximum length of a commit description.
+pub const MAX_DESCRIPTION_LEN: usize = 50;
+
+/// The default maximum length of a subject line.
+pub const MAX_SUBJECT_LEN: usize = 72;
+
+/// A violation of the commit convention.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum Violation {
+    /// The message is not a conventional commit.
+    #[error("{0}")]
+    NotConventional(#[from] ParseError),
+    /// The type is not part of the configured types.
+    #[error("The type `{0}` is not a valid commit type")]
+    UnknownType(String),
+    /// The scope is not part of the configured list.
+    #[error("The scope `{0}` is not part of the list of valid scopes")]
+    UnknownScope(String),
+    /// The description is too short.
+    #[error("The description must be longer than 5 characters")]
+    DescriptionTooShort,
+    /// The description is too long.
+    #[error("The description must not be longer than 50 characters")]
+    DescriptionTooLong,
+    /// The description starts with an uppercase letter.
+    #[error("The description must start in lowercase")]
+    DescriptionNotLowercase,
+    /// A scope is required but missing.
+    #[error("A scope is required")]
+    MissingScope,
+    /// A ticket reference is required but missing.
+    #[error("The commit message does not reference any ticket")]
+    MissingTicket,
+    /// The pull request number appended by a squash-merge is malformed.
+    #[error("The pull request number must be appended as ` (#123)`")]
+    InvalidPullRequestSuffix,
+    /// The type can only be provided by an external list, which is not read.
+    #[error("The type `{0}` can only be verified against `type_list.command`")]
+    UnverifiableType(String),
+    /// The scope can only be provided by an external list, which is not read.
+    #[error(
+        "The scope `{0}` can only be verified against `scopes.command` or the \
+            workspace"
+    )]
+    UnverifiableScope(String),
+}
+
+impl Violation {
+    /// Returns a stable identifier of the violation, for tools.
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::NotConventional(_) => "not_conventional",
+            Self::UnknownType(_) => "unknown_type",
+            Self::UnknownScope(_) => "unknown_scope",
+            Self::DescriptionTooShort => "description_too_short",
+            Self::DescriptionTooLong => "description_too_long",
+            Self::DescriptionNotLowercase => "description_not_lowercase",
+            Self::MissingScope => "missing_scope",
+            Self::MissingTicket => "missing_ticket",
+            Self::InvalidPullRequestSuffix => "invalid_pull_request_suffix",
+            Self::UnverifiableType(_) => "unverifiable_type",
+            Self::UnverifiableScope(_) => "unverifiable_scope",
+        }
+    }
+
+    /// Returns whether the violation only means the message cannot be fully
+    /// verified.
+    pub const fn is_unverifiable(&self) -> bool {
+        matches!(self, Self::UnverifiableType(_) | Self::UnverifiableScope(_))
+    }
+}
+
+/// The regex matching the pull request number appended by squash-merges.
+#[expect(clippy::unwrap_used, reason = "This regex is known to be valid.")]
+static PULL_REQUEST_SUFFIX_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r" \(#\d+\)$").unwrap());
+
+/// The regex matching something looking like a pull request number at the end
+/// of a subject.
+///
+/// Only the parenthesised form is matched, so that a ticket reference ending a
+/// subject, like `#123`, is not mistaken for a malformed suffix.
+#[expect(clippy::unwrap_used, reason = "This regex is known to be valid.")]
+static PULL_REQUEST_LIKE_SUFFIX_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\([^()]*#[^()]*\)\s*$").unwrap());
+
+/// The regex matching the messages generated by Git or forges for merges.
+#[expect(clippy::unwrap_used, reason = "This regex is known to be valid.")]
+static MERGE_MESSAGE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^Merge (?:branch|branches|remote-tracking branch|tag|commit|pull request|request) ",
+    )
+    .unwrap()
+});
+
+/// The names read from the external sources of the lists, by directory and
+/// source.
+///
+/// Many messages can be checked in a row, so each source is read at most once
+/// per directory. The directory is part of the key since `git z lint --repos`
+/// checks several repositories in a row, each with its own lists.
+static LISTED_NAMES: LazyLock<Mutex<ListedNames>> =
+    LazyLock::new(Mutex::default);
+
+/// The names read from the external sources, by directory and source.
+type ListedNames = HashMap<(PathBuf, ListSource), Vec<String>>;
+
+/// An external source of a list of names.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ListSource {
+    /// A list command.
+    Command(String),
+    /// The members of a workspace.
+    Workspace(ScopeSource),
+}
+
+/// Checks a commit message against the configuration.
+///
+/// A subject line decorated with `templates.subject` is checked as the
+/// conventional one it has been rendered from. Returns the list of violations,
+/// which is empty when the message is valid.
+pub fn check_message(config: &Config, message: &str) -> Vec<Violation> {
+    check_message_with(config, message, true)
+}
+
+/// Checks a commit message against the configuration, optionally leaving out
+/// the external sources of the lists.
+///
+/// When they are left out, the types and scopes only such a source could
+/// provide are reported as unverifiable, without running any command.
+#[tracing::instrument(level = "trace", skip(config))]
+fn check_message_with(
+    config: &Config,
+    message: &str,
+    reads_external_lists: bool,
+) -> Vec<Violation> {
+    let message = subject_template::undecorate(config, message);
+    let message = message.as_ref();
+
+    let commit = match ConventionalCommit::parse(message) {
+        Ok(commit) => commit,
+        Err(error) => return vec![error.into()],
+    };
+
+    let mut violations = vec![];
+
+    let r#type = config.types.get(&commit.r#type);
+
+    if r#type.is_none() {
+        let has_type_command = config
+            .type_list
+            .as_ref()
+            .is_some_and(|type_list| type_list.command.is_some());
+
+        let violation = if !reads_external_lists && has_type_command {
+            Some(Violation::UnverifiableType(commit.r#type.clone()))
+        } else if !is_listed_type(config, &commit.r#type) {
+            Some(Violation::UnknownType(commit.r#type.clone()))
+        } else {
+            None
+        };
+
+        violations.extend(violation);
+    }
+
+    if let (
+        Some(Scopes::List {
+            list,
+            from,
+            command,
+            ..
+        }),
+        Some(scope),
+    ) = (&config.scopes, &commit.scope)
+    {
+        let has_external_list = from.is_some() || command.is_some();
+
+        let violation = if list.contains(scope) {
+            None
+        } else if !reads_external_lists && has_external_list {
+            Some(Violation::UnverifiableScope(scope.clone()))
+        } else if !is_listed_scope(*from, command.as_deref(), scope) {
+            Some(Violation::UnknownScope(scope.clone()))
+        } else {
+            None
+        };
+
+        violations.extend(violation);
+    }
+
+    let skips_scope =
+        r#type.is_some_and(|r#type| r#type.skips(Question::Scope));
+
+    if config.scopes.as_ref().is_some_and(Scopes::is_required)
+        && !skips_scope
+        && commit.scope.is_none()
+    {
+        violations.push(Violation::MissingScope);
+    }
+
+    violations.extend(check_description(&commit.description));
+
+    let skips_ticket =
+        r#type.is_some_and(|r#type| r#type.skips(Question::Ticket));
+
+    if let Some(ticket) = &config.ticket {
+        if ticket.required
+            && !skips_ticket
+            && !references_ticket(&ticket.prefixes, message)
+        {
+            violations.push(Violation::MissingTicket);
+        }
+    }
+
+    tracing::trace!(?violations);
+    violations
+}
+
+/// Returns whether a scope is provided by the workspace or `scopes.command`.
+fn is_listed_scope(
+    from: Option<ScopeSource>,
+    command: Option<&str>,
+    scope: &str,
+) -> bool {
+    from.is_some_and(|source| lists(ListSource::Workspace(source), scope))
+        || command.is_some_and(|command| {
+            lists(ListSource::Command(command.to_owned()), scope)
+        })
+}
+
+/// Returns whether a type is printed by `type_list.command`, if any.
+///
+/// The command is only run for types missing from the configuration.
+fn is_listed_type(config: &Config, r#type: &str) -> bool {
+    config
+        .type_list
+        .as_ref()
+        .and_then(|type_list| type_list.command.as_deref())
+        .is_some_and(|command| {
+            lists(ListSource::Command(command.to_owned()), r#type)
+        })
+}
+
+/// Returns whether the verdict on a message depends on an external list.
+///
+/// This is the case when its type or scope is missing from the configuration
+/// but a list command or a workspace can provide it: the verdict can then change
+/// while the configuration stays the same.
+pub fn uses_external_lists(config: &Config, message: &str) -> bool {
+    let message = subject_template::undecorate(config, message);
+
+    let Ok(commit) = ConventionalCommit::parse(&message) else {
+        return false;
+    };
+
+    let lists_type = !config.types.contains_key(&commit.r#type)
+        && config
+            .type_list
+            .as_ref()
+            .is_some_and(|type_list| type_list.command.is_some());
+
+    let lists_scope = match (&config.scopes, &commit.scope) {
+        (
+            Some(Scopes::List {
+                list,
+                from,
+                command,
+                ..
+            }),
+            Some(scope),
+        ) => !list.contains(scope) && (from.is_some() || command.is_some()),
+        _ => false,
+    };
+
+    lists_type || lists_scope
+}
+
+/// Returns whether an external source lists the given name.
+///
+/// The source is read the first time it is needed in the current directory
+/// only. If it cannot be read, a warning is printed and it is considered empty,
+/// so that the check fails instead of being skipped.
+fn lists(source: ListSource, name: &str) -> bool {
+    let key = (env::current_dir().unwrap_or_default(), source);
+    let is_listed =
+        |names: &Vec<String>| names.iter().any(|listed| listed == name);
+
+    if let Some(names) = listed_names().get(&key) {
+        return is_listed(names);
+    }
+
+    // NOTE: The lock is not held while reading the source, so that other
+    // threads can still use the names already read. If several threads read
+    // the same source at once, the first result is kept.
+    let names = match key.1.read() {
+        Ok(entries) => entries.into_iter().map(|entry| entry.name).collect(),
+        Err(error) => {
+            warning!("The names from {} cannot be read: {error}.", key.1);
+            vec![]
+        }
+    };
+
+    is_listed(listed_names().entry(key).or_insert(names))
+}
+
+/// Locks the names read from the external sources.
+fn listed_names() -> MutexGuard<'static, ListedNames> {
+    LISTED_NAMES.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl ListSource {
+    /// Reads the entries from the source.
+    fn read(&self) -> Result<Vec<Entry>, String> {
+        match self {
+            Self::Command(command) => {
+                list_command::run(command).map_err(|error| error.to_string())
+            }
+            Self::Workspace(source) => {
+                workspace::members(*source).map_err(|error| error.to_string())
+            }
+        }
+    }
+}
+
+impl fmt::Display for ListSource {
+    #[expect(
+        clippy::min_ident_chars,
+        reason = "This is the conventional name in `fmt` implementations."
+    )]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Command(command) => write!(f, "`{command}`"),
+            Self::Workspace(source) => {
+                let name = match source {
+                    ScopeSource::CargoWorkspace => "Cargo",
+                    ScopeSource::NpmWorkspace => "npm",
+                    ScopeSource::Pnpm => "pnpm",
+                };
+
+                write!(f, "the {name} workspace")
+            }
+        }
+    }
+}
+
+/// How the messages are checked.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CheckOptions {
+    /// Whether to check the messages generated by Git or a forge for a merge.
+    pub check_merge_messages: bool,
+    /// Whether to accept and validate the suffix added by squash-merges.
+    pub squash_merges: bool,
+    /// Whether to leave out the list commands and workspaces, which depend on
+    /// a repository.
+    pub skips_external_lists: bool,
+}
+
+impl CheckOptions {
+    /// Returns whether a message is left out of the check.
+    pub fn skips(self, message: &str) -> bool {
+        !self.check_merge_messages && is_merge_message(message)
+    }
+
+    /// Checks a message, and returns the violations.
+    pub fn check(self, config: &Config, message: &str) -> Vec<Violation> {
+        let check = |message: &str| {
+            check_message_with(config, message, !self.skips_external_lists)
+        };
+
+        if self.squash_merges {
+            check_squash_merge(message, check)
+        } else {
+            check(message)
+        }
+    }
+}
+
+/// Checks a commit message created by a squash-merge.
+///
+/// Forges like GitHub append the number of the pull request to the subject, as
+/// in `feat: add a feature (#123)`. This suffix is validated, then stripped
+/// before checking the message with `check`.
+fn check_squash_merge(
+    message: &str,
+    check: impl Fn(&str) -> Vec<Violation>,
+) -> Vec<Violation> {
+    let (subject, rest) = message.split_once('\n').unwrap_or((message, ""));
+    let subject = subject.trim_end();
+
+    if PULL_REQUEST_SUFFIX_REGEX.is_match(subject) {
+        let subject = PULL_REQUEST_SUFFIX_REGEX.replace(subject, "");
+        check(&format!("{subject}\n{rest}"))
+    } else if PULL_REQUEST_LIKE_SUFFIX_REGEX.is_match(subject) {
+        let subject = PULL_REQUEST_LIKE_SUFFIX_REGEX.replace(subject, "");
+        let mut violations = check(&format!("{}\n{rest}", subject.trim_end()));
+        violations.push(Violation::InvalidPullRequestSuffix);
+        violations
+    } else {
+        check(message)
+    }
+}
+
+/// Returns whether a message has been generated by Git or a forge for a merge.
+pub fn is_merge_message(message: &str) -> bool {
+    MERGE_MESSAGE_REGEX.is_match(message)
+}
+
+/// Checks the description of a commit.
+pub fn check_description(description: &str) -> Option<Violation> {
+    let width = display_width(description);
+
+    if width < MIN_DESCRIPTION_LEN {
+        Some(Violation::DescriptionTooShort)
+    } else if width > MAX_DESCRIPTION_LEN {
+        Some(Violation::DescriptionTooLong)
+    } else if description.chars().next().is_some_and(char::is_uppercase) {
+        Some(Violation::DescriptionNotLowercase)
+    } else {
+        None
+    }
+}
+
+/// Builds a regex to match valid tickets from the list of valid prefixes.
+pub fn ticket_regex(prefixes: &[String]) -> String {
+    let prefixes = prefixes.join("|");
+    format!("(?:{prefixes})\\d+")
+}
+
+/// Finds the first ticket referenced in the message, if any.
+pub fn find_ticket(prefixes: &[String], message: &str) -> Option<String> {
+    let regex =
+        Regex::new(&format!(r"(?:^|\W)({})\b", ticket_regex(prefixes))).ok()?;
+
+    message
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .find_map(|line| regex.captures(line))
+        .map(|captures| captures[1].to_owned())
+}
+
+/// Finds all the tickets referenced in the message, without duplicates.
+pub fn find_tickets(prefixes: &[String], message: &str) -> Vec<String> {
+    let Ok(regex) =
+        Regex::new(&format!(r"(?:^|\W)({})\b", ticket_regex(prefixes)))
+    else {
+        return vec![];
+    };
+
+    message
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .flat_map(|line| regex.captures_iter(line))
+        .filter_map(|captures| captures.get(1))
+        .map(|ticket| ticket.as_str().to_owned())
+        .unique()
+        .collect()
+}
+
+/// Returns whether the message references a ticket.
+fn references_ticket(prefixes: &[String], message: &str) -> bool {
+    find_ticket(prefixes, message).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use indoc::indoc;
+
+    use super::*;
+    use crate::config::{
+        DetailedType, ScopeList, ScopeOrder, Ticket, TicketPlacement, Type,
+        TypeList,
+    };
+
+    fn check_squash_merge_message(
+        config: &Config,
+        message: &str,
+    ) -> Vec<Violation> {
+        CheckOptions {
+            squash_merges: true,
+            ..CheckOptions::default()
+        }
+        .check(config, message)
+    }
+
+    fn config() -> Config {
+        Config {
+            scopes: Some(Scopes::List {
+                list: ScopeList::Names(vec![
+                    String::from("cli"),
+                    String::from("config"),
+                ]),
+                order: ScopeOrder::default(),
+                required: false,
+                from: None,
+                command: None,
+            }),
+            ticket: Some(Ticket {
+                required: true,
+                prefixes: vec![String::from("#"), String::from("GH-")],
+                branch_patterns: None,
+                title_command: None,
+                status_command: None,
+                closed_states: None,
+                placement: TicketPlacement::default(),
+                trailer: None,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn accepts_a_valid_message() {
+        let message = indoc! {"
+            feat(cli): add a new flag
+
+            Refs: #23
+        "};
+
+        assert_eq!(check_message(&config(), message), vec![]);
+    }
+
+    #[test]
+    fn reports_non_conventional_messages() {
+        assert_eq!(
+            check_message(&config(), "Add a new flag"),
+            vec![Violation::NotConventional(ParseError::InvalidHeader)]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_types_and_scopes() {
+        let message = "feature(cmd): add a new flag\n\nRefs: GH-12";
+
+        assert_eq!(
+            check_message(&config(), message),
+            vec![
+                Violation::UnknownType(String::from("feature")),
+                Violation::UnknownScope(String::from("cmd")),
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_scopes_against_a_list_with_descriptions() {
+        let config = Config::from_toml(indoc! {r#"
+            version = "0.3-dev.0"
+
+            [types]
+            feat = "add a feature"
+
+            [scopes]
+            accept = "list"
+
+            [scopes.list]
+            cli = "the command line interface"
+            config = "the configuration"
+
+            [templates]
+            commit = "{{ type }}: {{ description }}"
+        "#})
+        .unwrap();
+
+        assert_eq!(check_message(&config, "feat(cli): add a new flag"), vec![]);
+        assert_eq!(
+            check_message(&config, "feat(cmd): add a new flag"),
+            vec![Violation::UnknownScope(String::from("cmd"))]
+        );
+    }
+
+    #[test]
+    fn checks_unknown_scopes_against_the_scopes_command() {
+        let mut config = config();
+        if let Some(Scopes::List { command, .. }) = &mut config.scopes {
+            *command = Some(String::from("echo cmd"));
+        }
+
+        assert_eq!(
+            check_message(&config, "feat(cmd): add a new flag\n\nRefs: #1"),
+            vec![]
+        );
+        assert_eq!(
+            check_message(&config, "feat(api): add a new route\n\nRefs: #1"),
+            vec![Violation::UnknownScope(String::from("api"))]
+        );
+    }
+
+    #[test]
+    fn checks_unknown_types_against_the_types_command() {
+        let config = Config {
+            types: [(
+                String::from("feat"),
+                Type::Simple(String::from("add a new feature")),
+            )]
+            .into_iter()
+            .collect(),
+            type_list: Some(TypeList {
+                command: Some(String::from("echo chore")),
+                ..Default::default()
+            }),
+            ..config()
+        };
+
+        assert_eq!(
+            check_message(
+                &config,
+                "chore(cli): update a dependency\n\nRefs: #1"
+            ),
+            vec![]
+        );
+        assert_eq!(
+            check_message(&config, "wip(cli): save the work\n\nRefs: #1"),
+            vec![Violation::UnknownType(String::from("wip"))]
+        );
+    }
+
+    #[test]
+    fn reports_names_from_external_lists_as_unverifiable_when_skipped() {
+        let mut config = Config {
+            type_list: Some(TypeList {
+                command: Some(String::from("echo custom")),
+                ..Default::default()
+            }),
+            ..config()
+        };
+        if let Some(Scopes::List { command, .. }) = &mut config.scopes {
+            *command = Some(String::from("echo cmd"));
+        }
+
+        let options = CheckOptions {
+            skips_external_lists: true,
+            ..CheckOptions::default()
+        };
+
+        assert_eq!(
+            options.check(&config, "custom(cmd): save the work\n\nRefs: #1"),
+            vec![
+                Violation::UnverifiableType(String::from("custom")),
+                Violation::UnverifiableScope(String::from("cmd")),
+            ]
+        );
+        assert_eq!(
+            options.check(&config, "feat(cli): add a new flag\n\nRefs: #1"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn uses_external_lists_only_for_unlisted_names() {
+        let mut with_command = config();
+        if let Some(Scopes::List { command, .. }) = &mut with_command.scopes {
+            *command = Some(String::from("echo cmd"));
+        }
+
+        assert!(!uses_external_lists(&with_command, "feat(cli): add a flag"));
+        assert!(uses_external_lists(&with_command, "feat(cmd): add a flag"));
+        assert!(!uses_external_lists(&config(), "feat(cmd): add a flag"));
+    }
+
+    #[test]
+    fn reports_invalid_descriptions() {
+        assert_eq!(
+            check_message(&config(), "feat: add\n\nRefs: #1"),
+            vec![Violation::DescriptionTooShort]
+        );
+        assert_eq!(
+            check_message(&config(), "feat: Add a new flag\n\nRefs: #1"),
+            vec![Violation::DescriptionNotLowercase]
+        );
+    }
+
+    #[test]
+    fn measures_descriptions_in_columns() {
+        let accented = "corrige l’été et la rentrée des élèves à l’école";
+        assert!(accented.len() > MAX_DESCRIPTION_LEN);
+        assert_eq!(check_description(accented), None);
+
+        assert_eq!(
+            check_description("été"),
+            Some(Violation::DescriptionTooShort)
+        );
+        assert_eq!(check_description("修正する"), None);
+        assert_eq!(
+            check_description(&"修".repeat(26)),
+            Some(Violation::DescriptionTooLong)
+        );
+        assert_eq!(check_description(&"🐛".repeat(25)), None);
+    }
+
+    #[test]
+    fn reports_missing_tickets_when_required() {
+        assert_eq!(
+            check_message(&config(), "feat: add a new flag"),
+            vec![Violation::MissingTicket]
+        );
+    }
+
+    #[test]
+    fn accepts_missing_tickets_when_not_required() {
+        let mut config = config();
+        config.ticket = Some(Ticket {
+            required: false,
+            prefixes: vec![String::from("#")],
+            branch_patterns: None,
+            title_command: None,
+            status_command: None,
+            closed_states: None,
+            placement: TicketPlacement::default(),
+            trailer: None,
+        });
+
+        assert_eq!(check_message(&config, "feat: add a new flag"), vec![]);
+    }
+
+    #[test]
+    fn reports_missing_scopes_when_required() {
+        let mut config = config();
+        config.scopes = Some(Scopes::Any { required: true });
+
+        assert_eq!(
+            check_message(&config, "feat: add a new flag\n\nRefs: #1"),
+            vec![Violation::MissingScope]
+        );
+        assert_eq!(
+            check_message(&config, "feat(cli): add a new flag\n\nRefs: #1"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn accepts_missing_tickets_for_types_skipping_them() {
+        let mut config = config();
+        config.types.insert(
+            String::from("wip"),
+            Type::Detailed(DetailedType {
+                description: String::from("work in progress"),
+                skip: vec![Question::Ticket],
+                template: None,
+            }),
+        );
+
+        assert_eq!(check_message(&config, "wip: add a new flag"), vec![]);
+    }
+
+    #[test]
+    fn finds_the_first_referenced_ticket() {
+        let prefixes = [String::from("#"), String::from("GH-")];
+
+        assert_eq!(
+            find_ticket(&prefixes, "fix: patch a bug\n\nRefs: GH-12, #13"),
+            Some(String::from("GH-12"))
+        );
+        assert_eq!(find_ticket(&prefixes, "fix: patch bug 12"), None);
+    }
+
+    #[test]
+    fn finds_all_the_referenced_tickets() {
+        let prefixes = [String::from("#"), String::from("GH-")];
+        let message = "fix: patch a bug (#13)\n\nRefs: GH-12, #13 #14";
+
+        assert_eq!(
+            find_tickets(&prefixes, message),
+            vec![
+                String::from("#13"),
+                String::from("GH-12"),
+                String::from("#14")
+            ]
+        );
+    }
+
+    #[test]
+    fn strips_the_pull_request_number_of_squash_merges() {
+        let message =
+            "feat(cli): add a flag to be squash-merged (#123)\n\nRefs: #23";
+
+        assert_eq!(check_squash_merge_message(&config(), message), vec![]);
+    }
+
+    #[test]
+    fn reports_malformed_pull_request_numbers() {
+        for subject in [
+            "feat(cli): add a new flag (# 123)",
+            "feat(cli): add a new flag (#abc)",
+        ] {
+            let message = format!("{subject}\n\nRefs: #23");
+
+            assert_eq!(
+                check_squash_merge_message(&config(), &message),
+                vec![Violation::InvalidPullRequestSuffix],
+                "{subject}"
+            );
+        }
+    }
+
+    #[test]
+    fn accepts_a_ticket_reference_ending_the_subject() {
+        let message = "fix(cli): handle the crash #123\n\nRefs: #23";
+
+        assert_eq!(check_squash_merge_message(&config(), message), vec![]);
+    }
+
+    #[test]
+    fn checks_squash_merges_without_pull_request_number() {
+        let message = "feat(cli): add a new flag\n\nRefs: #23";
+
+        assert_eq!(check_squash_merge_message(&config(), message), vec![]);
+    }
+
+    #[test]
+    fn recognises_merge_messages() {
+        assert!(is_merge_message("Merge branch 'feature'"));
+        assert!(is_merge_message(
+            "Merge pull request #12 from user/feature\n\nfeat: add a feature"
+        ));
+        assert!(!is_merge_message("feat: merge two configurations"));
+    }
+
+    #[test]
+    fn skips_merge_messages_unless_asked_to_check_them() {
+        let message = "Merge branch 'feature'";
+
+        assert!(CheckOptions::default().skips(message));
+        assert!(!CheckOptions {
+            check_merge_messages: true,
+            ..CheckOptions::default()
+        }
+        .skips(message));
+    }
+
+    #[test]
+    fn checks_the_squash_merge_suffix_only_when_asked() {
+        let message = "feat(cli): add a new flag (#abc)\n\nRefs: #23";
+        let options = CheckOptions {
+            squash_merges: true,
+            ..CheckOptions::default()
+        };
+
+        assert!(options
+            .check(&config(), message)
+            .contains(&Violation::InvalidPullRequestSuffix));
+        assert!(!CheckOptions::default()
+            .check(&config(), message)
+            .contains(&Violation::InvalidPullRequestSuffix));
+    }
+}
@@ -0,0 +1,329 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Lists provided by external commands.
+//!
+//! The command is run by `sh` from the root of the repository. It prints one
+//! entry per line, optionally followed by a tab and its description.
+
+use std::{
+    io::{self, Read, Write as _},
+    panic,
+    process::{Child, Command, ExitStatus, Stdio},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+use crate::{config::repo_root, tracing::LogResult as _};
+
+/// The interval at which a command with a timeout is polled.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// An entry of a list.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// The name of the entry.
+    pub name: String,
+    /// The description of the entry, if any.
+    pub description: Option<String>,
+}
+
+/// Errors that can occur when running a list command.
+#[derive(Debug, Error)]
+pub enum ListCommandError {
+    /// The command cannot be run.
+    #[error("Failed to run `{command}`")]
+    CannotRun {
+        /// The command.
+        command: String,
+        /// The error reported by the OS.
+        #[source]
+        source: io::Error,
+    },
+    /// The command has failed.
+    #[error("`{command}` has failed")]
+    Failed {
+        /// The command.
+        command: String,
+        /// The standard error of the command.
+        stderr: String,
+    },
+    /// The command has not finished in time.
+    #[error("`{command}` has not finished within {}s", timeout.as_secs())]
+    TimedOut {
+        /// The command.
+        command: String,
+        /// The time the command was given.
+        timeout: Duration,
+    },
+    /// The output of the command is not proper UTF-8.
+    #[error("The output of `{command}` is not proper UTF-8")]
+    EncodingError {
+        /// The command.
+        command: String,
+        /// The decoding error.
+        #[source]
+        source: std::string::FromUtf8Error,
+    },
+}
+
+/// Runs a list command and parses its output.
+#[tracing::instrument(level = "trace")]
+pub fn run(command: &str) -> Result<Vec<Entry>, ListCommandError> {
+    let entries = parse(&output(command, None, &[], None)?);
+    tracing::debug!(?entries);
+    Ok(entries)
+}
+
+/// Runs a list command with some input and returns the lines it prints.
+///
+/// The input is written to the standard input of the command. Blank lines are
+/// ignored. The command is killed if it has not finished within `timeout`.
+#[tracing::instrument(level = "trace", skip(input))]
+pub fn run_with_input(
+    command: &str,
+    input: &str,
+    timeout: Duration,
+) -> Result<Vec<String>, ListCommandError> {
+    let lines: Vec<String> = output(command, Some(input), &[], Some(timeout))?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(ToOwned::to_owned)
+        .collect();
+
+    tracing::debug!(?lines);
+    Ok(lines)
+}
+
+/// Runs a command with some environment variables and returns the first line
+/// it prints, if any.
+#[tracing::instrument(level = "trace")]
+pub fn first_line(
+    command: &str,
+    vars: &[(&str, &str)],
+) -> Result<Option<String>, ListCommandError> {
+    let line = output(command, None, vars, None)?
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(ToOwned::to_owned);
+
+    tracing::debug!(?line);
+    Ok(line)
+}
+
+/// Runs a command and returns its standard output.
+fn output(
+    command: &str,
+    input: Option<&str>,
+    vars: &[(&str, &str)],
+    timeout: Option<Duration>,
+) -> Result<String, ListCommandError> {
+    let mut sh = Command::new("sh");
+    sh.args(["-c", command])
+        .envs(vars.iter().copied())
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    // NOTE: There is no root in bare repositories, where the command is then
+    // run from the current directory.
+    if let Ok(root) = repo_root() {
+        sh.current_dir(root);
+    }
+
+    let cannot_run = |source| ListCommandError::CannotRun {
+        command: command.to_owned(),
+        source,
+    };
+
+    let mut child = sh.spawn().map_err(cannot_run).log_err()?;
+
+    // NOTE: The input is written and the output read from other threads so
+    // that a command printing a lot before reading all its input cannot block
+    // on a full pipe, and so that it can be killed on timeout.
+    if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+        let input = input.to_owned();
+        thread::spawn(move || {
+            if let Err(error) = stdin.write_all(input.as_bytes()) {
+                tracing::debug!(?error, "the input has not been read");
+            }
+        });
+    }
+
+    let stdout = child.stdout.take().map(read_in_background);
+    let stderr = child.stderr.take().map(read_in_background);
+
+    let status = match timeout {
+        Some(timeout) => wait_with_timeout(&mut child, command, timeout)?,
+        None => child.wait().map_err(cannot_run).log_err()?,
+    };
+
+    let stdout = join_output(stdout).map_err(cannot_run).log_err()?;
+    let stderr = join_output(stderr).map_err(cannot_run).log_err()?;
+
+    if !status.success() {
+        return Err(ListCommandError::Failed {
+            command: command.to_owned(),
+            stderr: String::from_utf8_lossy(&stderr).trim().to_owned(),
+        })
+        .log_err();
+    }
+
+    String::from_utf8(stdout)
+        .map_err(|source| ListCommandError::EncodingError {
+            command: command.to_owned(),
+            source,
+        })
+        .log_err()
+}
+
+/// Reads all the output of a command in a background thread.
+fn read_in_background(
+    mut reader: impl Read + Send + 'static,
+) -> JoinHandle<io::Result<Vec<u8>>> {
+    thread::spawn(move || {
+        let mut buffer = vec![];
+        reader.read_to_end(&mut buffer)?;
+        Ok(buffer)
+    })
+}
+
+/// Waits for the output read in background.
+fn join_output(
+    handle: Option<JoinHandle<io::Result<Vec<u8>>>>,
+) -> io::Result<Vec<u8>> {
+    handle.map_or_else(
+        || Ok(vec![]),
+        |handle| {
+            handle
+                .join()
+                .unwrap_or_else(|panic| panic::resume_unwind(panic))
+        },
+    )
+}
+
+/// Waits for the command to end, killing it after the timeout.
+fn wait_with_timeout(
+    child: &mut Child,
+    command: &str,
+    timeout: Duration,
+) -> Result<ExitStatus, ListCommandError> {
+    let cannot_run = |source| ListCommandError::CannotRun {
+        command: command.to_owned(),
+        source,
+    };
+
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if let Some(status) = child.try_wait().map_err(cannot_run).log_err()? {
+            return Ok(status);
+        }
+
+        if Instant::now() >= deadline {
+            child.kill().map_err(cannot_run).log_err()?;
+            child.wait().map_err(cannot_run).log_err()?;
+            return Err(ListCommandError::TimedOut {
+                command: command.to_owned(),
+                timeout,
+            })
+            .log_err();
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Parses the output of a list command.
+///
+/// Blank lines are ignored.
+pub fn parse(output: &str) -> Vec<Entry> {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| match line.split_once('\t') {
+            Some((name, description)) => Entry {
+                name: name.trim().to_owned(),
+                description: Some(description.trim().to_owned())
+                    .filter(|description| !description.is_empty()),
+            },
+            None => Entry {
+                name: line.trim().to_owned(),
+                description: None,
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    fn entry(name: &str, description: Option<&str>) -> Entry {
+        Entry {
+            name: String::from(name),
+            description: description.map(String::from),
+        }
+    }
+
+    #[test]
+    fn parses_one_entry_per_line() {
+        assert_eq!(
+            parse("api\ncli\n"),
+            vec![entry("api", None), entry("cli", None)]
+        );
+    }
+
+    #[test]
+    fn parses_the_descriptions_after_a_tab() {
+        assert_eq!(
+            parse("api\tthe HTTP API\ncli\t\n"),
+            vec![entry("api", Some("the HTTP API")), entry("cli", None)]
+        );
+    }
+
+    #[test]
+    fn ignores_blank_lines() {
+        assert_eq!(parse("\napi\n  \n"), vec![entry("api", None)]);
+    }
+
+    #[test]
+    fn passes_the_input_to_the_command() {
+        assert_eq!(
+            run_with_input("tr a-z A-Z", "api\ncli\n", Duration::from_secs(5))
+                .unwrap(),
+            vec!["API", "CLI"]
+        );
+    }
+
+    #[test]
+    fn kills_the_command_after_the_timeout() {
+        let start = Instant::now();
+        let result = run_with_input("sleep 10", "", Duration::from_millis(100));
+
+        assert!(matches!(result, Err(ListCommandError::TimedOut { .. })));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,187 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Local usage metrics.
+//!
+//! When enabled by the `GIT_Z_METRICS` environment variable or the
+//! `git-z.metrics` Git configuration, git-z counts how it is used in a metrics
+//! file in the git-z directory. The metrics are never transmitted: they are
+//! only shown by `git z stats --self`.
+
+use std::{env, fs, io, path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    commit_cache::{self, GitZDirError},
+    git,
+    tracing::LogResult as _,
+};
+
+/// The environment variable enabling the metrics.
+pub const METRICS_ENV: &str = "GIT_Z_METRICS";
+
+/// The Git configuration enabling the metrics.
+pub const METRICS_GIT_CONFIG: &str = "git-z.metrics";
+
+/// The name of the metrics file.
+const METRICS_FILE_NAME: &str = "metrics.toml";
+
+/// The usage metrics.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Metrics {
+    /// The number of commits made with the wizard.
+    pub wizard_commits: u64,
+    /// The total time spent in the wizard, in seconds.
+    pub wizard_seconds: u64,
+    /// The number of runs of `git z lint`.
+    pub lint_runs: u64,
+}
+
+/// Errors that can occur when reading or writing the metrics.
+#[derive(Debug, Error)]
+pub enum MetricsError {
+    /// The path of the git-z directory cannot be resolved.
+    #[error("Failed to get the path of the git-z directory")]
+    GitZDir(#[from] GitZDirError),
+    /// The metrics file cannot be read or written.
+    #[error("Failed to access the metrics file")]
+    Io(#[from] io::Error),
+    /// The metrics file is invalid.
+    #[error("The metrics file is invalid")]
+    Parse(#[from] toml::de::Error),
+    /// The metrics cannot be serialised.
+    #[error("Failed to serialise the metrics")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+impl Metrics {
+    /// Loads the metrics, or returns `None` if none have been recorded.
+    #[tracing::instrument(level = "trace")]
+    pub fn load() -> Result<Option<Self>, MetricsError> {
+        match fs::read_to_string(metrics_file()?) {
+            Ok(metrics) => Ok(Some(toml::from_str(&metrics).log_err()?)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error).log_err()?,
+        }
+    }
+
+    /// Returns the average time spent in the wizard, if it has been used.
+    pub fn average_wizard_duration(&self) -> Option<Duration> {
+        self.wizard_seconds
+            .checked_div(self.wizard_commits)
+            .map(Duration::from_secs)
+    }
+
+    /// Saves the metrics.
+    fn save(&self) -> Result<(), MetricsError> {
+        let metrics_file = metrics_file()?;
+
+        if let Some(gitz_dir) = metrics_file.parent() {
+            fs::create_dir_all(gitz_dir).log_err()?;
+        }
+
+        fs::write(metrics_file, toml::to_string(self).log_err()?).log_err()?;
+        Ok(())
+    }
+}
+
+/// Returns whether the metrics are enabled.
+///
+/// The environment variable takes precedence over the Git configuration.
+#[tracing::instrument(level = "trace")]
+pub fn is_enabled() -> bool {
+    if let Some(value) = env::var_os(METRICS_ENV) {
+        return is_true(&value.to_string_lossy());
+    }
+
+    match git::config(&["--type=bool", METRICS_GIT_CONFIG]) {
+        Ok(value) => value.is_some_and(|value| is_true(&value)),
+        Err(error) => {
+            tracing::debug!(?error, "cannot read the Git configuration");
+            false
+        }
+    }
+}
+
+/// Updates the metrics, if they are enabled.
+///
+/// As the metrics are only a convenience, failures are only traced.
+pub fn record(update: impl FnOnce(&mut Metrics)) {
+    if !is_enabled() {
+        return;
+    }
+
+    let result = Metrics::load().and_then(|metrics| {
+        let mut metrics = metrics.unwrap_or_default();
+        update(&mut metrics);
+        tracing::debug!(?metrics, "recording the metrics");
+        metrics.save()
+    });
+
+    if let Err(error) = result {
+        tracing::warn!(?error, "cannot record the metrics");
+    }
+}
+
+/// Returns the path of the metrics file.
+fn metrics_file() -> Result<PathBuf, GitZDirError> {
+    Ok(commit_cache::gitz_dir()?.join(METRICS_FILE_NAME))
+}
+
+/// Returns whether a boolean value is true, like Git does.
+fn is_true(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "true" | "yes" | "on" | "1"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    #[test]
+    fn averages_the_wizard_duration() {
+        let metrics = Metrics {
+            wizard_commits: 4,
+            wizard_seconds: 130,
+            lint_runs: 0,
+        };
+
+        assert_eq!(
+            metrics.average_wizard_duration(),
+            Some(Duration::from_secs(32))
+        );
+    }
+
+    #[test]
+    fn has_no_average_without_commits() {
+        assert_eq!(Metrics::default().average_wizard_duration(), None);
+    }
+
+    #[test]
+    fn reads_booleans_like_git() {
+        assert!(is_true("true\n"));
+        assert!(is_true("Yes"));
+        assert!(is_true("1"));
+        assert!(!is_true("false"));
+        assert!(!is_true(""));
+    }
+}
@@ -0,0 +1,135 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The minimum version of git-z required by the configuration.
+//!
+//! It is checked whenever a configuration is loaded, so that all the commands
+//! refuse to run with a git-z older than the one the team has agreed on.
+//!
+//! Versions are compared on their numeric components, missing ones counting as
+//! zero. The pre-release part of the running version is ignored, so that a
+//! development build satisfies the version it leads to.
+
+use std::cmp::Ordering;
+
+use thiserror::Error;
+
+use crate::config::Config;
+
+/// The version of the running binary.
+pub const CURRENT: &str = env!("CARGO_PKG_VERSION");
+
+/// Errors that can occur when checking the minimum version.
+#[derive(Debug, Error)]
+pub enum MinVersionError {
+    /// The minimum version is not a valid version.
+    #[error("`{0}` is not a valid version for `meta.min_version`")]
+    Invalid(String),
+    /// The running binary is older than the minimum version.
+    #[error(
+        "This configuration requires git-z {required} or later, but this is \
+        git-z {CURRENT}"
+    )]
+    TooOld {
+        /// The minimum version.
+        required: String,
+    },
+}
+
+/// Returns the minimum version required by the configuration, if any.
+pub fn required(config: &Config) -> Option<&str> {
+    config.meta.as_ref()?.min_version.as_deref()
+}
+
+/// Checks the running binary satisfies a minimum version.
+pub fn check(required: &str) -> Result<(), MinVersionError> {
+    check_version(required, CURRENT)
+}
+
+/// Checks a version is not older than the required one.
+fn check_version(required: &str, version: &str) -> Result<(), MinVersionError> {
+    let required_parts = parse(required)
+        .ok_or_else(|| MinVersionError::Invalid(required.to_owned()))?;
+    let parts = parse(version)
+        .ok_or_else(|| MinVersionError::Invalid(version.to_owned()))?;
+
+    if compare(&parts, &required_parts) == Ordering::Less {
+        Err(MinVersionError::TooOld {
+            required: required.to_owned(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Parses the numeric components of a version, ignoring its pre-release.
+fn parse(version: &str) -> Option<Vec<u64>> {
+    let version = version.split_once('-').map_or(version, |(core, _)| core);
+
+    version
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<_>>>()
+        .filter(|parts| (1..=3).contains(&parts.len()))
+}
+
+/// Compares two versions, missing components counting as zero.
+fn compare(left: &[u64], right: &[u64]) -> Ordering {
+    let length = left.len().max(right.len());
+    let component =
+        |parts: &[u64], index| parts.get(index).copied().unwrap_or(0);
+
+    (0..length)
+        .map(|index| component(left, index).cmp(&component(right, index)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    #[test]
+    fn accepts_newer_or_equal_versions() {
+        assert!(check_version("0.3", "0.3.0").is_ok());
+        assert!(check_version("0.2.1", "0.2.2").is_ok());
+        assert!(check_version("0.3", "1.0.0").is_ok());
+        assert!(check_version("0.3", "0.3.0-dev").is_ok());
+    }
+
+    #[test]
+    fn refuses_older_versions() {
+        assert!(matches!(
+            check_version("0.3", "0.2.2"),
+            Err(MinVersionError::TooOld { .. })
+        ));
+        assert!(matches!(
+            check_version("0.2.3", "0.2.2"),
+            Err(MinVersionError::TooOld { .. })
+        ));
+    }
+
+    #[test]
+    fn refuses_invalid_versions() {
+        for version in ["", "latest", "0.3.x", "1.2.3.4"] {
+            assert!(matches!(
+                check_version(version, "0.2.2"),
+                Err(MinVersionError::Invalid(_))
+            ));
+        }
+    }
+}
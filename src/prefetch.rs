@@ -0,0 +1,92 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Fetching of data in the background.
+
+use std::{
+    panic,
+    thread::{self, JoinHandle},
+};
+
+/// A value fetched in the background, to be waited for when it is needed.
+#[derive(Debug)]
+pub struct Prefetch<T> {
+    /// The state of the fetch.
+    state: State<T>,
+}
+
+/// The state of a fetch.
+#[derive(Debug)]
+enum State<T> {
+    /// The value is already available.
+    Ready(T),
+    /// The value is being fetched in a background thread.
+    Pending(JoinHandle<T>),
+}
+
+impl<T: Send + 'static> Prefetch<T> {
+    /// Starts fetching a value in a background thread.
+    pub fn spawn(fetch: impl FnOnce() -> T + Send + 'static) -> Self {
+        // NOTE: Entering the current span in the background thread keeps the
+        // traces of the fetch attached to the operation that has started it.
+        let span = tracing::Span::current();
+        let handle = thread::spawn(move || span.in_scope(fetch));
+
+        Self {
+            state: State::Pending(handle),
+        }
+    }
+
+    /// Wraps a value that is already available.
+    pub const fn ready(value: T) -> Self {
+        Self {
+            state: State::Ready(value),
+        }
+    }
+
+    /// Waits for the value to be fetched, then returns it.
+    pub fn wait(self) -> T {
+        match self.state {
+            State::Ready(value) => value,
+            State::Pending(handle) => handle
+                .join()
+                .unwrap_or_else(|panic| panic::resume_unwind(panic)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    #[test]
+    fn returns_a_ready_value() {
+        assert_eq!(Prefetch::ready(42).wait(), 42);
+    }
+
+    #[test]
+    fn returns_the_value_fetched_in_the_background() {
+        let prefetch = Prefetch::spawn(|| String::from("fetched"));
+        assert_eq!(prefetch.wait(), "fetched");
+    }
+
+    #[test]
+    #[should_panic(expected = "fetch failed")]
+    fn propagates_panics_from_the_background_thread() {
+        Prefetch::<()>::spawn(|| panic!("fetch failed")).wait();
+    }
+}
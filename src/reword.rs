@@ -0,0 +1,533 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Helpers to reword an existing commit message with the wizard.
+
+use std::collections::HashSet;
+
+use itertools::Itertools as _;
+
+use crate::{
+    commit_cache::WizardAnswers,
+    config::Config,
+    conventional_commit::{footer_token, ConventionalCommit},
+    lint,
+    sections::Sections,
+    subject_template,
+};
+
+/// Guesses the answers to the wizard from an old commit message.
+///
+/// The ticket is taken from the trailers first, so that a ticket only mentioned
+/// in the body is not mistaken for the one the commit references. A subject
+/// line decorated with `templates.subject` is read as the conventional one.
+pub fn prefilled_answers(config: &Config, message: &str) -> WizardAnswers {
+    let message = subject_template::undecorate(config, message);
+    let message = message.as_ref();
+
+    let ticket = config.ticket.as_ref().and_then(|ticket| {
+        lint::find_ticket(&ticket.prefixes, &Sections::split(message).trailers)
+            .or_else(|| lint::find_ticket(&ticket.prefixes, message))
+    });
+
+    match ConventionalCommit::parse(message) {
+        Ok(commit) => WizardAnswers {
+            breaking_change: commit
+                .footers
+                .into_iter()
+                .find(|footer| footer.token.starts_with("BREAKING"))
+                .map(|footer| footer.value),
+            r#type: Some(commit.r#type),
+            scope: commit.scope,
+            description: Some(commit.description),
+            ticket,
+        },
+        Err(_) => WizardAnswers {
+            description: message
+                .lines()
+                .next()
+                .map(|subject| lowercase_first(subject.trim())),
+            ticket,
+            ..WizardAnswers::default()
+        },
+    }
+}
+
+/// Builds the final message from the rendered template and the old message.
+///
+/// The subject and trailers come from the rendered template, while the body of
+/// the old message is kept in between. The old trailers are kept after the new
+/// ones, but for the ones replaced by the rendered trailers and the breaking
+/// change, which has been asked by the wizard.
+///
+/// Like in Git, trailer tokens are compared case-insensitively. An old trailer
+/// referencing only tickets already referenced by the rendered trailers is
+/// replaced as well, for instance `Refs: #12` by `Fixes: #12`.
+pub fn merge_messages(
+    config: &Config,
+    rendered: &str,
+    old_message: &str,
+) -> String {
+    let rendered = Sections::split(rendered);
+    let old = Sections::split(old_message);
+
+    let replaced = ReplacedTrailers {
+        tokens: rendered
+            .trailers
+            .lines()
+            .filter_map(footer_token)
+            .map(str::to_lowercase)
+            .collect(),
+        tickets: config.ticket.as_ref().map_or_else(Vec::new, |ticket| {
+            lint::find_tickets(&ticket.prefixes, &rendered.trailers)
+        }),
+        prefixes: config
+            .ticket
+            .as_ref()
+            .map_or(&[], |ticket| ticket.prefixes.as_slice()),
+    };
+
+    let trailers = [
+        rendered.trailers.as_str(),
+        &kept_trailers(&old.trailers, &replaced),
+    ]
+    .into_iter()
+    .filter(|trailers| !trailers.is_empty())
+    .join("\n");
+    let trailers = unique_trailers(&trailers);
+
+    let message = [
+        rendered.subject.as_str(),
+        old.body.as_str(),
+        rendered.body.as_str(),
+        &trailers,
+    ]
+    .into_iter()
+    .map(str::trim)
+    .filter(|paragraph| !paragraph.is_empty())
+    .join("\n\n");
+
+    format!("{message}\n")
+}
+
+/// Removes the duplicate trailers from a message.
+///
+/// Hooks adding trailers like `Signed-off-by` run again on each attempt, so a
+/// message reused after a failed hook can contain the same trailer several
+/// times. Only the first occurrence of each trailer is kept. A message without
+/// duplicates is returned untouched.
+pub fn dedup_trailers(message: &str) -> String {
+    let sections = Sections::split(message);
+    let trailers = unique_trailers(&sections.trailers);
+
+    if trailers == sections.trailers {
+        return message.to_owned();
+    }
+
+    tracing::debug!("removing duplicate trailers");
+
+    let message = [sections.subject, sections.body, trailers]
+        .iter()
+        .map(|paragraph| paragraph.trim())
+        .filter(|paragraph| !paragraph.is_empty())
+        .join("\n\n");
+
+    format!("{message}\n")
+}
+
+/// Removes the duplicates from trailers, keeping their first occurrence.
+///
+/// Like in Git, trailer tokens are compared case-insensitively, and the
+/// continuation lines of a trailer are removed along with it.
+fn unique_trailers(trailers: &str) -> String {
+    let mut seen = HashSet::new();
+    let mut keep = true;
+
+    trailers
+        .lines()
+        .filter(|line| {
+            if let Some(token) = footer_token(line) {
+                let value = line.get(token.len()..).unwrap_or_default();
+                let value = value.trim_start_matches(':').trim();
+                keep = seen.insert((token.to_lowercase(), value.to_owned()));
+            }
+
+            keep
+        })
+        .join("\n")
+}
+
+/// The old trailers replaced by the rendered ones.
+struct ReplacedTrailers<'a> {
+    /// The rendered tokens, in lowercase.
+    tokens: Vec<String>,
+    /// The tickets referenced by the rendered trailers.
+    tickets: Vec<String>,
+    /// The valid ticket prefixes.
+    prefixes: &'a [String],
+}
+
+impl ReplacedTrailers<'_> {
+    /// Returns whether an old trailer is replaced.
+    fn replace(&self, token: &str, trailer: &str) -> bool {
+        if token.starts_with("BREAKING")
+            || self.tokens.contains(&token.to_lowercase())
+        {
+            return true;
+        }
+
+        let tickets = lint::find_tickets(self.prefixes, trailer);
+        !tickets.is_empty()
+            && tickets.iter().all(|ticket| self.tickets.contains(ticket))
+    }
+}
+
+/// Returns the old trailers not replaced by the new ones.
+fn kept_trailers(trailers: &str, replaced: &ReplacedTrailers<'_>) -> String {
+    let mut keep = false;
+
+    trailers
+        .lines()
+        .filter(|line| {
+            if let Some(token) = footer_token(line) {
+                let value = line.get(token.len()..).unwrap_or_default();
+                keep = !replaced.replace(token, value);
+            }
+
+            keep && !line.is_empty()
+        })
+        .join("\n")
+}
+
+/// Removes the commented lines from a message.
+fn strip_comments(message: &str) -> String {
+    message
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .join("\n")
+}
+
+/// Removes the commented lines from a message and cleans up the blank lines.
+///
+/// This is what Git does when the message is edited, so that the message can
+/// be used as is when it is not.
+pub fn clean_up(message: &str) -> String {
+    strip_comments(message)
+        .lines()
+        .map(str::trim_end)
+        .coalesce(|previous, current| {
+            if previous.is_empty() && current.is_empty() {
+                Ok(previous)
+            } else {
+                Err((previous, current))
+            }
+        })
+        .join("\n")
+        .trim()
+        .to_owned()
+}
+
+/// Puts the first character of a string in lowercase.
+fn lowercase_first(string: &str) -> String {
+    let mut chars = string.chars();
+    chars
+        .next()
+        .map(|first| first.to_lowercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use indoc::indoc;
+
+    use super::*;
+    use crate::config::{Ticket, TicketPlacement};
+
+    fn config_with_tickets() -> Config {
+        Config {
+            ticket: Some(Ticket {
+                required: false,
+                prefixes: vec![String::from("#")],
+                branch_patterns: None,
+                title_command: None,
+                status_command: None,
+                closed_states: None,
+                placement: TicketPlacement::default(),
+                trailer: None,
+            }),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn prefills_the_answers_from_a_conventional_commit() {
+        let answers = prefilled_answers(
+            &Config::default(),
+            indoc! {"
+                feat(api)!: add a new endpoint
+
+                BREAKING CHANGE: The old endpoint has been removed.
+            "},
+        );
+
+        assert_eq!(answers.r#type.as_deref(), Some("feat"));
+        assert_eq!(answers.scope.as_deref(), Some("api"));
+        assert_eq!(answers.description.as_deref(), Some("add a new endpoint"));
+        assert_eq!(
+            answers.breaking_change.as_deref(),
+            Some("The old endpoint has been removed.")
+        );
+    }
+
+    #[test]
+    fn prefills_the_ticket_from_the_trailers_first() {
+        let answers = prefilled_answers(
+            &config_with_tickets(),
+            indoc! {"
+                fix: patch a bug
+
+                This is a follow-up to #12.
+
+                Refs: #42
+            "},
+        );
+
+        assert_eq!(answers.ticket.as_deref(), Some("#42"));
+    }
+
+    #[test]
+    fn prefills_the_answers_from_a_decorated_subject() {
+        let mut config = config_with_tickets();
+        config.templates.subject = Some(String::from(
+            "{% if ticket %}[{{ ticket }}] {% endif %}{{ type }}: {{ description }}",
+        ));
+
+        let answers = prefilled_answers(
+            &config,
+            indoc! {"
+                [#42] fix: patch a bug
+
+                Refs: #42
+            "},
+        );
+
+        assert_eq!(answers.r#type.as_deref(), Some("fix"));
+        assert_eq!(answers.description.as_deref(), Some("patch a bug"));
+        assert_eq!(answers.ticket.as_deref(), Some("#42"));
+    }
+
+    #[test]
+    fn prefills_the_description_from_another_message() {
+        let answers = prefilled_answers(&Config::default(), "Update stuff\n");
+
+        assert_eq!(answers.r#type, None);
+        assert_eq!(answers.description.as_deref(), Some("update stuff"));
+    }
+
+    #[test]
+    fn cleans_up_a_rendered_message() {
+        let rendered = indoc! {"
+            feat: add a feature
+
+            # Feel free to enter a longer description here.
+
+            Refs: #42
+
+
+        "};
+
+        assert_eq!(clean_up(rendered), "feat: add a feature\n\nRefs: #42");
+    }
+
+    #[test]
+    fn keeps_the_body_of_the_old_message() {
+        let rendered = indoc! {"
+            feat: add a feature
+
+            # Feel free to enter a longer description here.
+
+            Refs: #42
+        "};
+        let old_message = indoc! {"
+            Add a feature
+
+            This is the body.
+        "};
+
+        assert_eq!(
+            merge_messages(&Config::default(), rendered, old_message),
+            indoc! {"
+                feat: add a feature
+
+                This is the body.
+
+                Refs: #42
+            "}
+        );
+    }
+
+    #[test]
+    fn replaces_the_old_trailers_rendered_by_the_template() {
+        let rendered = indoc! {"
+            feat: add a feature
+
+            Refs: #42
+        "};
+        let old_message = indoc! {"
+            feat: add a feature
+
+            This is the body.
+
+            Refs: #41
+            BREAKING CHANGE: Everything changes.
+            Signed-off-by: Someone <someone@example.com>
+        "};
+
+        assert_eq!(
+            merge_messages(&Config::default(), rendered, old_message),
+            indoc! {"
+                feat: add a feature
+
+                This is the body.
+
+                Refs: #42
+                Signed-off-by: Someone <someone@example.com>
+            "}
+        );
+    }
+
+    #[test]
+    fn replaces_the_old_trailers_case_insensitively() {
+        let rendered = indoc! {"
+            feat: add a feature
+
+            Refs: #42
+        "};
+        let old_message = indoc! {"
+            feat: add a feature
+
+            refs: #41
+            Co-authored-by: Someone <someone@example.com>
+        "};
+
+        assert_eq!(
+            merge_messages(&Config::default(), rendered, old_message),
+            indoc! {"
+                feat: add a feature
+
+                Refs: #42
+                Co-authored-by: Someone <someone@example.com>
+            "}
+        );
+    }
+
+    #[test]
+    fn replaces_the_old_trailers_referencing_the_same_tickets() {
+        let rendered = indoc! {"
+            fix: patch a bug
+
+            Fixes: #42
+        "};
+        let old_message = indoc! {"
+            fix: patch a bug
+
+            Refs: #42
+            See-also: #42, #43
+        "};
+
+        assert_eq!(
+            merge_messages(&config_with_tickets(), rendered, old_message),
+            indoc! {"
+                fix: patch a bug
+
+                Fixes: #42
+                See-also: #42, #43
+            "}
+        );
+    }
+
+    #[test]
+    fn removes_the_duplicate_trailers() {
+        let message = indoc! {"
+            feat: add a feature
+
+            This is the body.
+            Signed-off-by: Someone <someone@example.com>
+
+            Refs: #42
+            Signed-off-by: Someone <someone@example.com>
+            Co-authored-by: Other <other@example.com>
+            refs: #42
+            Signed-off-by: Someone <someone@example.com>
+            co-authored-by: Other <other@example.com>
+        "};
+
+        assert_eq!(
+            dedup_trailers(message),
+            indoc! {"
+                feat: add a feature
+
+                This is the body.
+                Signed-off-by: Someone <someone@example.com>
+
+                Refs: #42
+                Signed-off-by: Someone <someone@example.com>
+                Co-authored-by: Other <other@example.com>
+            "}
+        );
+    }
+
+    #[test]
+    fn keeps_a_message_without_duplicate_trailers_untouched() {
+        let message = indoc! {"
+            feat: add a feature
+
+
+            Refs: #42
+            Signed-off-by: Someone <someone@example.com>
+            Signed-off-by: Other <other@example.com>
+        "};
+
+        assert_eq!(dedup_trailers(message), message);
+    }
+
+    #[test]
+    fn removes_the_duplicate_trailers_of_the_old_message() {
+        let rendered = indoc! {"
+            feat: add a feature
+
+            Refs: #42
+        "};
+        let old_message = indoc! {"
+            feat: add a feature
+
+            Refs: #42
+            Signed-off-by: Someone <someone@example.com>
+            Signed-off-by: Someone <someone@example.com>
+        "};
+
+        assert_eq!(
+            merge_messages(&Config::default(), rendered, old_message),
+            indoc! {"
+                feat: add a feature
+
+                Refs: #42
+                Signed-off-by: Someone <someone@example.com>
+            "}
+        );
+    }
+}
@@ -0,0 +1,154 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Frequency of the scopes in the recent history.
+//!
+//! Counting the scopes requires to parse the last commits, so the result is
+//! cached in the git-z directory until `HEAD` moves.
+
+use std::fs;
+
+use eyre::Result;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commit_cache,
+    config::Config,
+    git::{self, GitError},
+    helpers::write_atomically,
+    history,
+    tracing::LogResult as _,
+};
+
+/// The number of commits to look at.
+const RECENT_COMMITS: usize = 500;
+
+/// The name of the scope frequency cache file.
+const SCOPE_FREQUENCY_FILE_NAME: &str = "scope-frequency.toml";
+
+/// The frequency of the scopes, as of a given `HEAD`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ScopeFrequency {
+    /// The commit from which the scopes have been counted.
+    head: String,
+    /// The number of recent commits for each scope.
+    counts: IndexMap<String, usize>,
+}
+
+/// Orders the scopes, the most frequent in the recent history first.
+///
+/// Scopes with the same frequency keep their order. If the frequency cannot be
+/// computed, the scopes are returned as is.
+#[tracing::instrument(level = "trace", skip(config))]
+pub fn order_by_frequency(config: &Config, scopes: &[String]) -> Vec<String> {
+    match scope_counts(config) {
+        Ok(counts) => sort_by_count(scopes, &counts),
+        Err(error) => {
+            tracing::warn!(?error, "cannot get the frequency of the scopes");
+            scopes.to_vec()
+        }
+    }
+}
+
+/// Sorts the scopes by decreasing count.
+fn sort_by_count(
+    scopes: &[String],
+    counts: &IndexMap<String, usize>,
+) -> Vec<String> {
+    let mut scopes = scopes.to_vec();
+    scopes.sort_by_key(|scope| {
+        std::cmp::Reverse(counts.get(scope).copied().unwrap_or_default())
+    });
+    scopes
+}
+
+/// Returns the count of each scope in the recent history.
+///
+/// The counts are read from the cache when `HEAD` has not moved.
+fn scope_counts(config: &Config) -> Result<IndexMap<String, usize>> {
+    let head = head()?;
+    let cache_file = commit_cache::gitz_dir()
+        .log_err()?
+        .join(SCOPE_FREQUENCY_FILE_NAME);
+
+    if let Some(frequency) = fs::read_to_string(&cache_file)
+        .ok()
+        .and_then(|cache| toml::from_str::<ScopeFrequency>(&cache).ok())
+        .filter(|frequency| !head.is_empty() && frequency.head == head)
+    {
+        tracing::debug!(?frequency, "using the cached scope frequency");
+        return Ok(frequency.counts);
+    }
+
+    let frequency = ScopeFrequency {
+        counts: history::count(
+            history::conventional_commits(config, Some(RECENT_COMMITS))?
+                .into_iter()
+                .filter_map(|commit| commit.scope),
+        ),
+        head,
+    };
+
+    tracing::debug!(?frequency, "computed the scope frequency");
+
+    // NOTE: Without `HEAD`, there is nothing to invalidate the cache with.
+    if !frequency.head.is_empty() {
+        let cache = toml::to_string(&frequency).log_err()?;
+        if let Some(gitz_dir) = cache_file.parent() {
+            fs::create_dir_all(gitz_dir).log_err()?;
+        }
+        write_atomically(&cache_file, &cache).log_err()?;
+    }
+
+    Ok(frequency.counts)
+}
+
+/// Returns the ID of the `HEAD` commit, or an empty string if there is none.
+#[tracing::instrument(level = "trace")]
+fn head() -> Result<String> {
+    match git::run_trimmed(&["rev-parse", "--verify", "--quiet", "HEAD"]) {
+        Ok(head) => Ok(head),
+        // NOTE: Git fails when there is no commit yet.
+        Err(GitError::Failed(_)) => Ok(String::new()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use indexmap::indexmap;
+
+    use super::*;
+
+    fn strings(strings: &[&str]) -> Vec<String> {
+        strings.iter().copied().map(String::from).collect()
+    }
+
+    #[test]
+    fn sorts_scopes_by_decreasing_count() {
+        let counts = indexmap! {
+            String::from("config") => 1,
+            String::from("cli") => 3,
+        };
+
+        assert_eq!(
+            sort_by_count(&strings(&["api", "cli", "config", "docs"]), &counts),
+            ["cli", "config", "api", "docs"]
+        );
+    }
+}
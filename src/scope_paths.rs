@@ -0,0 +1,120 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Mapping of paths to scopes.
+
+use indexmap::IndexMap;
+use regex::Regex;
+
+/// Groups paths by the first scope matching them.
+///
+/// The groups are in the order of the scopes in the mapping, followed by the
+/// paths matching no scope under the `None` key.
+pub fn group_by_scope(
+    scope_paths: &IndexMap<String, String>,
+    paths: &[String],
+) -> Result<IndexMap<Option<String>, Vec<String>>, regex::Error> {
+    let regexes = scope_paths
+        .iter()
+        .map(|(scope, regex)| Ok((scope, Regex::new(regex)?)))
+        .collect::<Result<Vec<_>, regex::Error>>()?;
+
+    let mut groups: IndexMap<Option<String>, Vec<String>> = regexes
+        .iter()
+        .map(|(scope, _)| (Some((*scope).clone()), vec![]))
+        .collect();
+    groups.insert(None, vec![]);
+
+    for path in paths {
+        let scope = regexes
+            .iter()
+            .find(|(_, regex)| regex.is_match(path))
+            .map(|(scope, _)| (*scope).clone());
+
+        groups.entry(scope).or_default().push(path.clone());
+    }
+
+    groups.retain(|_, paths| !paths.is_empty());
+
+    tracing::debug!(?groups, "grouped the paths by scope");
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    fn scope_paths() -> IndexMap<String, String> {
+        IndexMap::from([
+            (String::from("api"), String::from("^crates/api/")),
+            (String::from("cli"), String::from("^crates/cli/")),
+        ])
+    }
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().copied().map(String::from).collect()
+    }
+
+    #[test]
+    fn groups_paths_by_scope_in_config_order() {
+        let groups = group_by_scope(
+            &scope_paths(),
+            &paths(&["crates/cli/main.rs", "crates/api/lib.rs"]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            groups.into_iter().collect::<Vec<_>>(),
+            vec![
+                (Some(String::from("api")), paths(&["crates/api/lib.rs"])),
+                (Some(String::from("cli")), paths(&["crates/cli/main.rs"])),
+            ]
+        );
+    }
+
+    #[test]
+    fn puts_unmapped_paths_last() {
+        let groups = group_by_scope(
+            &scope_paths(),
+            &paths(&["README.md", "crates/api/lib.rs"]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            groups.keys().cloned().collect::<Vec<_>>(),
+            vec![Some(String::from("api")), None]
+        );
+        assert_eq!(groups[&None], paths(&["README.md"]));
+    }
+
+    #[test]
+    fn uses_the_first_matching_scope() {
+        let scope_paths = IndexMap::from([
+            (String::from("api"), String::from("^crates/api/")),
+            (String::from("crates"), String::from("^crates/")),
+        ]);
+
+        let groups =
+            group_by_scope(&scope_paths, &paths(&["crates/api/lib.rs"]))
+                .unwrap();
+
+        assert_eq!(
+            groups.keys().cloned().collect::<Vec<_>>(),
+            vec![Some(String::from("api"))]
+        );
+    }
+}
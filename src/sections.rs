@@ -0,0 +1,333 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The sections of a commit message: subject, body and trailers.
+//!
+//! When a message is opened in the editor, commented markers separate its
+//! sections. They are stripped by Git like any other comment, but when the
+//! message is read back, they split it without guessing. Messages without
+//! markers are split with heuristics instead.
+
+use itertools::Itertools as _;
+
+use crate::conventional_commit::footer_token;
+
+/// The marker starting the body.
+pub const BODY_MARKER: &str = "# --- body below ---";
+
+/// The marker starting the trailers.
+pub const TRAILERS_MARKER: &str = "# --- trailers below ---";
+
+/// The sections of a commit message, without comments.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Sections {
+    /// The subject line.
+    pub subject: String,
+    /// The body, between the subject and the trailers.
+    pub body: String,
+    /// The trailers, at the end of the message.
+    pub trailers: String,
+}
+
+/// A section being read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    /// The subject line.
+    Subject,
+    /// The body.
+    Body,
+    /// The trailers.
+    Trailers,
+}
+
+impl Sections {
+    /// Splits a message into its sections.
+    ///
+    /// The markers are used when present. Otherwise, the subject is the first
+    /// line and the trailers are the last paragraphs starting with a footer.
+    pub fn split(message: &str) -> Self {
+        if has_markers(message) {
+            Self::split_on_markers(message)
+        } else {
+            Self::guess(message)
+        }
+    }
+
+    /// Splits a message on its markers.
+    fn split_on_markers(message: &str) -> Self {
+        let mut section = Section::Subject;
+        let mut lines = (vec![], vec![], vec![]);
+
+        for line in message.lines() {
+            match line.trim_end() {
+                BODY_MARKER => section = Section::Body,
+                TRAILERS_MARKER => section = Section::Trailers,
+                line if line.starts_with('#') => (),
+                line => match section {
+                    Section::Subject => lines.0.push(line),
+                    Section::Body => lines.1.push(line),
+                    Section::Trailers => lines.2.push(line),
+                },
+            }
+        }
+
+        Self {
+            subject: join(&lines.0),
+            body: join(&lines.1),
+            trailers: join(&lines.2),
+        }
+    }
+
+    /// Guesses the sections of a message without markers.
+    fn guess(message: &str) -> Self {
+        let message = message
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(str::trim_end)
+            .join("\n");
+
+        let message = message.trim_start_matches('\n');
+        let (subject, rest) = message.split_once('\n').unwrap_or((message, ""));
+
+        let paragraphs: Vec<&str> = rest
+            .split("\n\n")
+            .map(|paragraph| paragraph.trim_matches('\n'))
+            .filter(|paragraph| !paragraph.is_empty())
+            .collect();
+
+        let body_length = paragraphs
+            .iter()
+            .rposition(|paragraph| !starts_with_footer(paragraph))
+            .map_or(0, |index| index.saturating_add(1));
+        let (body, trailers) = paragraphs.split_at(body_length);
+
+        Self {
+            subject: subject.trim().to_owned(),
+            body: body.join("\n\n"),
+            trailers: trailers.join("\n\n"),
+        }
+    }
+}
+
+/// Adds the section markers to a message to open in the editor.
+///
+/// The body marker follows the subject, and the trailers marker precedes the
+/// trailers, or ends the message if there are none. A message already
+/// containing markers, like one placed by the template, is left untouched.
+pub fn scaffold(message: &str) -> String {
+    if has_markers(message) {
+        return message.to_owned();
+    }
+
+    let trailer_lines = Sections::guess(message)
+        .trailers
+        .lines()
+        .filter(|line| !line.is_empty())
+        .count();
+
+    let lines: Vec<&str> = message.lines().collect();
+    let content: Vec<usize> = lines
+        .iter()
+        .positions(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .collect();
+
+    let subject = content.first().copied();
+    let trailers = (trailer_lines > 0)
+        .then(|| content.len().checked_sub(trailer_lines))
+        .flatten()
+        .and_then(|index| content.get(index).copied());
+
+    let mut scaffold = vec![];
+    let mut skip_blank_line = false;
+
+    for (index, line) in lines.into_iter().enumerate() {
+        if skip_blank_line && line.trim().is_empty() {
+            skip_blank_line = false;
+            continue;
+        }
+
+        skip_blank_line = false;
+
+        if Some(index) == trailers {
+            // NOTE: Leave room to write the body.
+            if scaffold.last() == Some(&BODY_MARKER) {
+                scaffold.push("");
+            }
+
+            scaffold.push(TRAILERS_MARKER);
+        }
+
+        scaffold.push(line);
+
+        if Some(index) == subject {
+            scaffold.extend(["", BODY_MARKER]);
+            skip_blank_line = true;
+        }
+    }
+
+    if trailers.is_none() {
+        while scaffold.last().is_some_and(|line| line.trim().is_empty()) {
+            scaffold.pop();
+        }
+
+        scaffold.extend(["", TRAILERS_MARKER]);
+    }
+
+    format!("{}\n", scaffold.join("\n"))
+}
+
+/// Returns whether a message contains the body marker.
+fn has_markers(message: &str) -> bool {
+    message.lines().any(|line| line.trim_end() == BODY_MARKER)
+}
+
+/// Returns whether a paragraph starts with a footer.
+fn starts_with_footer(paragraph: &str) -> bool {
+    paragraph
+        .lines()
+        .next()
+        .is_some_and(|line| footer_token(line).is_some())
+}
+
+/// Joins the lines of a section, without the surrounding blank lines.
+fn join(lines: &[&str]) -> String {
+    lines.join("\n").trim_matches('\n').to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn adds_the_markers_around_the_body() {
+        let message = indoc! {"
+            feat: add a feature
+
+            # Feel free to enter a longer description here.
+
+            Refs: #42
+
+            BREAKING CHANGE: Everything changes.
+        "};
+
+        assert_eq!(
+            scaffold(message),
+            indoc! {"
+                feat: add a feature
+
+                # --- body below ---
+                # Feel free to enter a longer description here.
+
+                # --- trailers below ---
+                Refs: #42
+
+                BREAKING CHANGE: Everything changes.
+            "}
+        );
+    }
+
+    #[test]
+    fn ends_with_the_trailers_marker_without_trailers() {
+        assert_eq!(
+            scaffold("feat: add a feature\n\n"),
+            indoc! {"
+                feat: add a feature
+
+                # --- body below ---
+
+                # --- trailers below ---
+            "}
+        );
+    }
+
+    #[test]
+    fn keeps_the_markers_placed_by_the_template() {
+        let message = indoc! {"
+            feat: add a feature
+            # --- body below ---
+        "};
+
+        assert_eq!(scaffold(message), message);
+    }
+
+    #[test]
+    fn splits_an_edited_message_on_its_markers() {
+        let message = indoc! {"
+            feat: add a feature
+
+            # --- body below ---
+            Refs: this looks like a trailer, but is in the body.
+
+            # --- trailers below ---
+            Refs: #42
+        "};
+
+        assert_eq!(
+            Sections::split(message),
+            Sections {
+                subject: String::from("feat: add a feature"),
+                body: String::from(
+                    "Refs: this looks like a trailer, but is in the body."
+                ),
+                trailers: String::from("Refs: #42"),
+            }
+        );
+    }
+
+    #[test]
+    fn guesses_the_sections_without_markers() {
+        let message = indoc! {"
+            feat: add a feature
+
+            # A comment.
+            This is the body.
+
+            Refs: #42
+
+            BREAKING CHANGE: Everything changes.
+        "};
+
+        assert_eq!(
+            Sections::split(message),
+            Sections {
+                subject: String::from("feat: add a feature"),
+                body: String::from("This is the body."),
+                trailers: String::from(
+                    "Refs: #42\n\nBREAKING CHANGE: Everything changes."
+                ),
+            }
+        );
+    }
+
+    #[test]
+    fn splits_the_scaffold_of_a_message_back() {
+        let message = indoc! {"
+            feat: add a feature
+
+            This is the body.
+
+            Refs: #42
+        "};
+
+        assert_eq!(
+            Sections::split(&scaffold(message)),
+            Sections::split(message)
+        );
+    }
+}
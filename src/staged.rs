@@ -0,0 +1,363 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Information about the staged changes.
+
+use thiserror::Error;
+
+use crate::git::{self, GitError};
+
+/// The staged changes.
+#[derive(Debug, Default)]
+pub struct StagedChanges {
+    /// The staged files.
+    pub files: Vec<StagedFile>,
+}
+
+/// A staged file.
+#[derive(Debug)]
+pub struct StagedFile {
+    /// The path of the file, relative to the root of the repository.
+    pub path: String,
+    /// The number of added lines.
+    pub added: usize,
+    /// The number of deleted lines.
+    pub deleted: usize,
+}
+
+/// The patch of the staged changes.
+#[derive(Debug, Default)]
+pub struct StagedPatch {
+    /// The patch of each staged file.
+    pub files: Vec<FilePatch>,
+}
+
+/// The patch of a staged file.
+#[derive(Debug, Default)]
+pub struct FilePatch {
+    /// The path of the file, relative to the root of the repository.
+    pub path: String,
+    /// The added lines, without the leading `+`.
+    pub added_lines: Vec<String>,
+    /// The removed lines, without the leading `-`.
+    pub removed_lines: Vec<String>,
+}
+
+/// Errors that can occur when reading the staged changes.
+#[derive(Debug, Error)]
+pub enum StagedError {
+    /// An error has occurred while running Git.
+    #[error("Failed to read the staged changes")]
+    Git(#[from] GitError),
+}
+
+impl StagedChanges {
+    /// Reads the staged changes from Git.
+    #[tracing::instrument(name = "load_staged_changes", level = "trace")]
+    pub fn load() -> Result<Self, StagedError> {
+        let numstat =
+            git::run(&["diff", "--cached", "--numstat", "-z", "--no-renames"])?;
+
+        let files = numstat
+            .split('\0')
+            .filter_map(|record| {
+                let mut fields = record.splitn(3, '\t');
+                let added = fields.next()?;
+                let deleted = fields.next()?;
+                let path = fields.next()?;
+
+                // NOTE: Binary files are reported with `-` instead of numbers.
+                Some(StagedFile {
+                    path: path.to_owned(),
+                    added: added.parse().unwrap_or_default(),
+                    deleted: deleted.parse().unwrap_or_default(),
+                })
+            })
+            .collect();
+
+        let staged_changes = Self { files };
+        tracing::debug!(?staged_changes);
+        Ok(staged_changes)
+    }
+
+    /// Returns the total number of changed lines.
+    pub fn changed_lines(&self) -> usize {
+        self.files
+            .iter()
+            .map(|file| file.added + file.deleted)
+            .sum()
+    }
+}
+
+impl StagedPatch {
+    /// Reads the patch of the staged changes from Git.
+    #[tracing::instrument(name = "load_staged_patch", level = "trace")]
+    pub fn load() -> Result<Self, StagedError> {
+        let diff = git::run(&[
+            "diff",
+            "--cached",
+            "--no-color",
+            "--no-ext-diff",
+            "--no-renames",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+            "--unified=0",
+        ])?;
+
+        let staged_patch = Self::parse(&diff);
+        tracing::trace!(?staged_patch);
+        Ok(staged_patch)
+    }
+
+    /// Parses the output of `git diff`.
+    fn parse(diff: &str) -> Self {
+        let mut files: Vec<FilePatch> = vec![];
+        let mut in_hunks = false;
+
+        for line in diff.lines() {
+            if line.starts_with("diff --git ") {
+                files.push(FilePatch::default());
+                in_hunks = false;
+                continue;
+            }
+
+            let Some(file) = files.last_mut() else {
+                continue;
+            };
+
+            if in_hunks {
+                match line.split_at_checked(1) {
+                    Some(("+", added)) => {
+                        file.added_lines.push(added.to_owned());
+                    }
+                    Some(("-", removed)) => {
+                        file.removed_lines.push(removed.to_owned());
+                    }
+                    _ => (),
+                }
+            } else if line.starts_with("@@") {
+                in_hunks = true;
+            } else if let Some(path) = line
+                .strip_prefix("+++ ")
+                .or_else(|| line.strip_prefix("--- "))
+                .and_then(header_path)
+            {
+                // NOTE: The other side is `/dev/null` for added or deleted
+                // files, so let’s take the path from any side.
+                file.path = path;
+            } else {
+                // The other lines of the header are not relevant.
+            }
+        }
+
+        Self { files }
+    }
+}
+
+/// Returns the path from a `---` or `+++` header of a patch.
+///
+/// Git quotes the paths with special characters, escaping them like in C.
+/// Returns `None` for `/dev/null`.
+fn header_path(path: &str) -> Option<String> {
+    let path = match path
+        .strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+    {
+        Some(quoted) => unquote(quoted),
+        None => path.to_owned(),
+    };
+
+    path.strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .map(ToOwned::to_owned)
+}
+
+/// Unescapes a path quoted by Git.
+fn unquote(quoted: &str) -> String {
+    let mut bytes = vec![];
+    let mut buffer = [0; 4];
+    let mut chars = quoted.chars();
+
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            bytes.extend_from_slice(char.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+
+        match chars.next() {
+            Some('a') => bytes.push(0x07),
+            Some('b') => bytes.push(0x08),
+            Some('f') => bytes.push(0x0c),
+            Some('n') => bytes.push(b'\n'),
+            Some('r') => bytes.push(b'\r'),
+            Some('t') => bytes.push(b'\t'),
+            Some('v') => bytes.push(0x0b),
+            Some(digit @ '0'..='7') => {
+                let octal = [Some(digit), chars.next(), chars.next()]
+                    .into_iter()
+                    .flatten()
+                    .collect::<String>();
+                bytes.extend(u8::from_str_radix(&octal, 8).ok());
+            }
+            Some(char) => {
+                bytes.extend_from_slice(
+                    char.encode_utf8(&mut buffer).as_bytes(),
+                );
+            }
+            None => (),
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Writes the index to a tree object and returns its ID.
+pub fn write_index_tree() -> Result<String, StagedError> {
+    Ok(git::run_trimmed(&["write-tree"])?)
+}
+
+/// Replaces the index with the content of a tree object.
+pub fn read_index_tree(tree: &str) -> Result<(), StagedError> {
+    git::run(&["read-tree", tree])?;
+    Ok(())
+}
+
+/// Unstages all the staged changes, keeping the worktree untouched.
+///
+/// Before the first commit, there is no `HEAD` to reset the index to, so the
+/// index is emptied instead.
+pub fn unstage_all() -> Result<(), StagedError> {
+    if has_head()? {
+        git::run(&["reset", "--quiet"])?;
+    } else {
+        git::run(&["read-tree", "--empty"])?;
+    }
+
+    Ok(())
+}
+
+/// Returns whether `HEAD` points to a commit.
+fn has_head() -> Result<bool, StagedError> {
+    Ok(git::succeeds(&[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        "HEAD",
+    ])?)
+}
+
+/// Returns the diff of the staged changes, as printed by `git diff --cached`.
+#[tracing::instrument(level = "trace")]
+pub fn diff() -> Result<String, StagedError> {
+    Ok(git::run(&[
+        "diff",
+        "--cached",
+        "--no-color",
+        "--no-ext-diff",
+    ])?)
+}
+
+/// Stages the given paths as they are in a tree object.
+pub fn stage_from_tree(
+    tree: &str,
+    paths: &[String],
+) -> Result<(), StagedError> {
+    let source = format!("--source={tree}");
+    let pathspecs = paths
+        .iter()
+        .map(|path| format!(":(literal){path}"))
+        .collect::<Vec<_>>();
+
+    let mut args = vec!["restore", "--staged", &source, "--"];
+    args.extend(pathspecs.iter().map(String::as_str));
+
+    git::run(&args)?;
+    Ok(())
+}
+
+/// Lets the user select the changes to stage with `git add --patch`.
+pub fn stage_interactively() -> Result<(), StagedError> {
+    Ok(git::run_attached(&["add", "--patch"])?)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn parses_a_patch() {
+        let diff = indoc! {"
+            diff --git a/src/lib.rs b/src/lib.rs
+            index 1234567..89abcde 100644
+            --- a/src/lib.rs
+            +++ b/src/lib.rs
+            @@ -1 +1 @@
+            -pub fn old() {}
+            +pub fn new() {}
+            @@ -10 +10 @@
+            --- SQL comment
+            diff --git a/new.txt b/new.txt
+            new file mode 100644
+            index 0000000..1234567
+            --- /dev/null
+            +++ b/new.txt
+            @@ -0,0 +1 @@
+            +-- not a header
+            diff --git a/old.txt b/old.txt
+            deleted file mode 100644
+            index 1234567..0000000
+            --- a/old.txt
+            +++ /dev/null
+            @@ -1 +0,0 @@
+            -content
+        "};
+
+        let patch = StagedPatch::parse(diff);
+
+        assert_eq!(patch.files.len(), 3);
+        assert_eq!(patch.files[0].path, "src/lib.rs");
+        assert_eq!(
+            patch.files[0].removed_lines,
+            vec!["pub fn old() {}", "-- SQL comment"]
+        );
+        assert_eq!(patch.files[0].added_lines, vec!["pub fn new() {}"]);
+        assert_eq!(patch.files[1].path, "new.txt");
+        assert_eq!(patch.files[1].added_lines, vec!["-- not a header"]);
+        assert_eq!(patch.files[2].path, "old.txt");
+        assert_eq!(patch.files[2].removed_lines, vec!["content"]);
+    }
+
+    #[test]
+    fn parses_the_quoted_paths() {
+        let diff = indoc! {r#"
+            diff --git "a/caf\303\251 \"menu\".txt" "b/caf\303\251 \"menu\".txt"
+            index 1234567..89abcde 100644
+            --- "a/caf\303\251 \"menu\".txt"
+            +++ "b/caf\303\251 \"menu\".txt"
+            @@ -1 +1 @@
+            -old
+            +new
+        "#};
+
+        let patch = StagedPatch::parse(diff);
+
+        assert_eq!(patch.files.len(), 1);
+        assert_eq!(patch.files[0].path, "café \"menu\".txt");
+    }
+}
@@ -0,0 +1,347 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Subject lines decorated with `templates.subject`.
+//!
+//! A decorated subject like `[#12] feat: add a flag` is not a conventional
+//! commit header. To check or reword such a message, the conventional header is
+//! recovered with patterns derived from the subject template: it is rendered
+//! with placeholders instead of the answers, once for each combination of the
+//! optional answers, and the placeholders are then turned into regex groups.
+
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    collections::HashMap,
+    sync::{LazyLock, Mutex, PoisonError},
+};
+
+use indexmap::IndexMap;
+use regex::{Captures, Regex};
+use serde::Serialize;
+use tera::{Context, Tera};
+
+use crate::{
+    config::{Config, Ticket},
+    conventional_commit::{ConventionalCommit, ParseError},
+    issue_title::{self, IssueTitle},
+};
+
+/// The name of the subject template.
+pub const TEMPLATE_NAME: &str = "templates.subject";
+
+/// The patterns derived from each subject template.
+///
+/// Many messages can be checked in a row, so each template is compiled once.
+static PATTERNS: LazyLock<Mutex<HashMap<String, Vec<SubjectPattern>>>> =
+    LazyLock::new(Mutex::default);
+
+/// The variables of the subject template, with their placeholder and the regex
+/// matching their value.
+const VARIABLES: &[(&str, char, &str)] = &[
+    ("type", 'T', r"(?P<type>[^\s()!:]+)"),
+    ("scope", 'S', r"(?P<scope>[^()]+)"),
+    ("description", 'D', r"(?P<description>.+?)"),
+    ("breaking_change", 'B', r".+?"),
+    ("ticket", 'K', r".+?"),
+    ("generated_by", 'G', r".*?"),
+];
+
+/// The optional answers, which can be absent from the context.
+const OPTIONAL_VARIABLES: &[&str] = &["scope", "breaking_change", "ticket"];
+
+/// A pattern matching the subject lines rendered by the subject template.
+#[derive(Debug)]
+struct SubjectPattern {
+    /// The regex matching a decorated subject line.
+    regex: Regex,
+    /// Whether the subject is rendered for a breaking change.
+    is_breaking: bool,
+}
+
+/// The part of the configuration exposed to the subject template.
+#[derive(Debug, Serialize)]
+struct TemplateConfig<'a> {
+    /// The ticket reference configuration.
+    ticket: Option<&'a Ticket>,
+}
+
+/// Recovers the conventional subject line of a message decorated with
+/// `templates.subject`, if set.
+///
+/// A message that does not match the subject template is returned unchanged.
+pub fn undecorate<'a>(config: &Config, message: &'a str) -> Cow<'a, str> {
+    let Some(template) = &config.templates.subject else {
+        return Cow::Borrowed(message);
+    };
+
+    let Some(subject) = message
+        .lines()
+        .find(|line| !line.trim().is_empty() && !line.starts_with('#'))
+    else {
+        return Cow::Borrowed(message);
+    };
+
+    let mut patterns = PATTERNS.lock().unwrap_or_else(PoisonError::into_inner);
+    let patterns = patterns
+        .entry(template.clone())
+        .or_insert_with(|| derive_patterns(config, template));
+
+    patterns
+        .iter()
+        .find_map(|pattern| {
+            let captures = pattern.regex.captures(subject.trim())?;
+            Some(conventional_subject(&captures, pattern.is_breaking))
+        })
+        .map_or(Cow::Borrowed(message), |subject| {
+            Cow::Owned(replace_subject(message, &subject))
+        })
+}
+
+/// Parses a message as a conventional commit, recovering its conventional
+/// subject line first if it is decorated with `templates.subject`.
+pub fn parse(
+    config: &Config,
+    message: &str,
+) -> Result<ConventionalCommit, ParseError> {
+    ConventionalCommit::parse(&undecorate(config, message))
+}
+
+/// Replaces the first line of a message which is neither blank nor a comment.
+pub fn replace_subject(message: &str, subject: &str) -> String {
+    let mut replaced = false;
+
+    let mut decorated = message
+        .lines()
+        .map(|line| {
+            if replaced || line.trim().is_empty() || line.starts_with('#') {
+                line
+            } else {
+                replaced = true;
+                subject
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if message.ends_with('\n') {
+        decorated.push('\n');
+    }
+
+    decorated
+}
+
+/// Derives the patterns matching the subject lines rendered by a template.
+///
+/// The patterns with the most optional answers come first, as they are the
+/// most specific. Combinations rendering the same pattern are only kept once,
+/// with the fewest answers, so that an answer not rendered by the template is
+/// not assumed.
+fn derive_patterns(config: &Config, template: &str) -> Vec<SubjectPattern> {
+    let mut tera = Tera::default();
+    tera.register_function(issue_title::FUNCTION_NAME, IssueTitle::new(None));
+
+    if let Err(error) = tera.add_raw_template(TEMPLATE_NAME, template) {
+        tracing::warn!(?error, "invalid subject template");
+        return vec![];
+    }
+
+    let mut combinations = (0..1_u8 << OPTIONAL_VARIABLES.len())
+        .map(|bits| {
+            OPTIONAL_VARIABLES
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| bits & (1 << index) != 0)
+                .map(|(_, name)| *name)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    combinations.sort_by_key(Vec::len);
+
+    let mut patterns: Vec<(usize, String, bool)> = vec![];
+
+    for present in combinations {
+        let Some(pattern) = render_pattern(&tera, config, &present) else {
+            continue;
+        };
+
+        if patterns.iter().all(|(_, known, _)| *known != pattern) {
+            let is_breaking = present.contains(&"breaking_change");
+            patterns.push((present.len(), pattern, is_breaking));
+        }
+    }
+
+    patterns.sort_by_key(|(answers, _, _)| Reverse(*answers));
+
+    patterns
+        .into_iter()
+        .filter_map(|(_, pattern, is_breaking)| {
+            Some(SubjectPattern {
+                regex: Regex::new(&pattern).ok()?,
+                is_breaking,
+            })
+        })
+        .collect()
+}
+
+/// Renders the subject template with placeholders, then turns it into a regex.
+///
+/// Only the given optional answers are present in the context. Templates that
+/// do not render the type and description cannot be matched.
+fn render_pattern(
+    tera: &Tera,
+    config: &Config,
+    present: &[&str],
+) -> Option<String> {
+    let mut context = Context::new();
+    context.insert(
+        "config",
+        &TemplateConfig {
+            ticket: config.ticket.as_ref(),
+        },
+    );
+    context.insert(
+        "vars",
+        &config.template_vars.as_ref().unwrap_or(&IndexMap::new()),
+    );
+
+    // NOTE: Like in the context of the commit template, absent answers are
+    // null instead of undefined.
+    for (name, placeholder, _) in VARIABLES {
+        if !OPTIONAL_VARIABLES.contains(name) || present.contains(name) {
+            context.insert(*name, &placeholder_for(*placeholder));
+        } else {
+            context.insert(*name, &None::<String>);
+        }
+    }
+
+    let rendered = tera.render(TEMPLATE_NAME, &context).ok()?;
+    let mut pattern = regex::escape(rendered.trim());
+
+    for (name, placeholder, regex) in VARIABLES {
+        let placeholder = placeholder_for(*placeholder);
+        let mut occurrences = pattern.match_indices(&placeholder).count();
+
+        if occurrences == 0 && matches!(*name, "type" | "description") {
+            return None;
+        }
+
+        // NOTE: A group can be named only once, so repeated values are matched
+        // anonymously.
+        while occurrences > 1 {
+            pattern = pattern.replacen(&placeholder, ".+?", 1);
+            occurrences -= 1;
+        }
+
+        pattern = pattern.replacen(&placeholder, regex, 1);
+    }
+
+    Some(format!("^{pattern}$"))
+}
+
+/// Returns the placeholder of a variable.
+///
+/// It is made of characters from the private use area of Unicode, so that it
+/// cannot appear in the template itself.
+fn placeholder_for(placeholder: char) -> String {
+    format!("\u{e000}{placeholder}\u{e001}")
+}
+
+/// Builds a conventional subject line from the matched values.
+fn conventional_subject(captures: &Captures<'_>, is_breaking: bool) -> String {
+    let r#type = &captures["type"];
+    let scope = captures
+        .name("scope")
+        .map(|scope| format!("({})", scope.as_str()))
+        .unwrap_or_default();
+    let breaking = if is_breaking { "!" } else { "" };
+    let description = &captures["description"];
+
+    format!("{type}{scope}{breaking}: {description}")
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+    use crate::config::Templates;
+
+    fn config(subject: &str) -> Config {
+        Config {
+            templates: Templates {
+                subject: Some(String::from(subject)),
+                ..Config::default().templates
+            },
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn replaces_the_first_line_of_the_message() {
+        assert_eq!(
+            replace_subject(
+                "\nfix: patch a bug\n\nRefs: #12\n",
+                "[#12] fix: patch a bug"
+            ),
+            "\n[#12] fix: patch a bug\n\nRefs: #12\n"
+        );
+    }
+
+    #[test]
+    fn recovers_the_conventional_subject() {
+        let config = config(
+            "{% if ticket %}[{{ ticket }}] {% endif %}{{ type }}\
+                {% if scope %}({{ scope }}){% endif %}\
+                {% if breaking_change %}!{% endif %}: {{ description }}",
+        );
+
+        assert_eq!(
+            undecorate(&config, "[#12] feat: add a flag\n\nRefs: #12\n"),
+            "feat: add a flag\n\nRefs: #12\n"
+        );
+        assert_eq!(
+            undecorate(&config, "[GH-3] fix(cli)!: patch a bug\n"),
+            "fix(cli)!: patch a bug\n"
+        );
+        assert_eq!(
+            undecorate(&config, "feat(api): add a route\n"),
+            "feat(api): add a route\n"
+        );
+    }
+
+    #[test]
+    fn recovers_the_conventional_subject_with_a_suffix() {
+        let config = config("{{ type }}: {{ description }} ({{ ticket }})");
+
+        assert_eq!(
+            undecorate(&config, "feat: add a flag (#12)"),
+            "feat: add a flag"
+        );
+    }
+
+    #[test]
+    fn keeps_messages_not_matching_the_template() {
+        let config = config("[{{ ticket }}] {{ type }}: {{ description }}");
+
+        assert_eq!(undecorate(&config, "Add a flag\n"), "Add a flag\n");
+    }
+
+    #[test]
+    fn keeps_messages_without_subject_template() {
+        let message = "[#12] feat: add a flag\n";
+        assert_eq!(undecorate(&Config::default(), message), message);
+    }
+}
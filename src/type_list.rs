@@ -0,0 +1,187 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Arrangement of the commit types in the wizard.
+
+use indexmap::IndexMap;
+
+use crate::config::{Type, TypeList, TypeOrder};
+
+/// The name of the group of the types not listed in any group.
+const OTHER_GROUP: &str = "Other";
+
+/// The commit types, arranged for display.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ArrangedTypes {
+    /// The types and their description, in display order.
+    pub types: IndexMap<String, String>,
+    /// The group of each type, if groups are configured.
+    pub groups: IndexMap<String, String>,
+}
+
+/// Arranges the types in groups and orders them.
+///
+/// Groups are in the order of the configuration, followed by the types listed
+/// in no group. `recent` is the list of the types used in the most recent
+/// commits, the most recent first: it is only used with [`TypeOrder::Recent`].
+pub fn arrange(
+    types: &IndexMap<String, Type>,
+    type_list: Option<&TypeList>,
+    recent: &[String],
+) -> ArrangedTypes {
+    let order = type_list
+        .map(|type_list| type_list.order)
+        .unwrap_or_default();
+    let configured_groups =
+        type_list.and_then(|type_list| type_list.groups.as_ref());
+
+    let mut groups: IndexMap<Option<&str>, Vec<&str>> = configured_groups
+        .into_iter()
+        .flat_map(IndexMap::keys)
+        .map(|group| (Some(group.as_str()), vec![]))
+        .collect();
+
+    for name in types.keys() {
+        let group = configured_groups.map(|configured_groups| {
+            configured_groups
+                .iter()
+                .find(|(_, members)| members.contains(name))
+                .map_or(OTHER_GROUP, |(group, _)| group.as_str())
+        });
+
+        groups.entry(group).or_default().push(name.as_str());
+    }
+
+    groups.retain(|_, names| !names.is_empty());
+
+    let mut arranged = ArrangedTypes::default();
+
+    for (group, mut names) in groups {
+        match order {
+            TypeOrder::Config => (),
+            TypeOrder::Alphabetical => names.sort_unstable(),
+            TypeOrder::Recent => names.sort_by_key(|name| {
+                recent
+                    .iter()
+                    .position(|recent| recent == name)
+                    .unwrap_or(usize::MAX)
+            }),
+        }
+
+        for name in names {
+            if let Some(r#type) = types.get(name) {
+                arranged
+                    .types
+                    .insert(name.to_owned(), r#type.description().to_owned());
+            }
+
+            if let Some(group) = group {
+                arranged.groups.insert(name.to_owned(), group.to_owned());
+            }
+        }
+    }
+
+    arranged
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use indexmap::indexmap;
+
+    use super::*;
+
+    fn types() -> IndexMap<String, Type> {
+        ["fix", "wip", "feat", "chore"]
+            .into_iter()
+            .map(|name| {
+                (
+                    String::from(name),
+                    Type::Simple(format!("{name} description")),
+                )
+            })
+            .collect()
+    }
+
+    fn names(arranged: &ArrangedTypes) -> Vec<&str> {
+        arranged.types.keys().map(String::as_str).collect()
+    }
+
+    fn strings(strings: &[&str]) -> Vec<String> {
+        strings.iter().copied().map(String::from).collect()
+    }
+
+    #[test]
+    fn keeps_the_config_order_by_default() {
+        let arranged = arrange(&types(), None, &[]);
+
+        assert_eq!(names(&arranged), ["fix", "wip", "feat", "chore"]);
+        assert!(arranged.groups.is_empty());
+        assert_eq!(arranged.types["feat"], "feat description");
+    }
+
+    #[test]
+    fn orders_alphabetically() {
+        let type_list = TypeList {
+            order: TypeOrder::Alphabetical,
+            groups: None,
+            command: None,
+        };
+
+        let arranged = arrange(&types(), Some(&type_list), &[]);
+
+        assert_eq!(names(&arranged), ["chore", "feat", "fix", "wip"]);
+    }
+
+    #[test]
+    fn orders_by_recent_use() {
+        let type_list = TypeList {
+            order: TypeOrder::Recent,
+            groups: None,
+            command: None,
+        };
+
+        let arranged =
+            arrange(&types(), Some(&type_list), &strings(&["feat", "chore"]));
+
+        assert_eq!(names(&arranged), ["feat", "chore", "fix", "wip"]);
+    }
+
+    #[test]
+    fn groups_types_in_config_order_with_others_last() {
+        let type_list = TypeList {
+            order: TypeOrder::Alphabetical,
+            groups: Some(indexmap! {
+                String::from("Common") => strings(&["fix", "feat"]),
+                String::from("Temporary") => strings(&["wip"]),
+            }),
+            command: None,
+        };
+
+        let arranged = arrange(&types(), Some(&type_list), &[]);
+
+        assert_eq!(names(&arranged), ["feat", "fix", "wip", "chore"]);
+        assert_eq!(
+            arranged.groups,
+            indexmap! {
+                String::from("feat") => String::from("Common"),
+                String::from("fix") => String::from("Common"),
+                String::from("wip") => String::from("Temporary"),
+                String::from("chore") => String::from(OTHER_GROUP),
+            }
+        );
+    }
+}
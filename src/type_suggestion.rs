@@ -0,0 +1,246 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Heuristics to suggest a type from the staged changes.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::staged::{FilePatch, StagedPatch};
+
+/// Directories containing only tests.
+const TEST_DIRS: [&str; 5] = ["test", "tests", "__tests__", "spec", "specs"];
+
+/// Suffixes of the stem of test files.
+const TEST_SUFFIXES: [&str; 4] = ["_test", "_spec", ".test", ".spec"];
+
+/// Directories containing only documentation.
+const DOC_DIRS: [&str; 3] = ["doc", "docs", "documentation"];
+
+/// Extensions of documentation files.
+const DOC_EXTENSIONS: [&str; 4] = ["md", "markdown", "rst", "adoc"];
+
+/// Lockfiles, which only contain dependencies.
+const LOCKFILES: [&str; 9] = [
+    "Cargo.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "poetry.lock",
+    "uv.lock",
+    "Gemfile.lock",
+    "go.sum",
+    "flake.lock",
+];
+
+/// Manifests declaring dependencies among other things.
+const MANIFESTS: [&str; 5] = [
+    "Cargo.toml",
+    "package.json",
+    "pyproject.toml",
+    "Gemfile",
+    "go.mod",
+];
+
+/// The regex matching a line declaring a dependency version.
+#[expect(clippy::unwrap_used, reason = "This regex is known to be valid.")]
+static VERSION_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\d+\.\d+").unwrap());
+
+/// The regex matching a line declaring the version of the package itself, or
+/// of its toolchain, which are not dependencies.
+#[expect(clippy::unwrap_used, reason = "This regex is known to be valid.")]
+static PACKAGE_VERSION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*"?(version|rust-version|requires-python)"?\s*[=:]"#)
+        .unwrap()
+});
+
+/// A suggested type.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The suggested type.
+    pub r#type: &'static str,
+    /// Why the type is suggested.
+    pub reason: &'static str,
+}
+
+/// Suggests a type from the staged changes, if they all fall in one category.
+pub fn suggest(patch: &StagedPatch) -> Option<Suggestion> {
+    let all = |predicate: fn(&FilePatch) -> bool| {
+        !patch.files.is_empty() && patch.files.iter().all(predicate)
+    };
+
+    let suggestion = if all(is_test) {
+        Some(Suggestion {
+            r#type: "test",
+            reason: "only tests are staged",
+        })
+    } else if all(is_doc) {
+        Some(Suggestion {
+            r#type: "docs",
+            reason: "only documentation is staged",
+        })
+    } else if all(is_dependency_update) {
+        Some(Suggestion {
+            r#type: "deps",
+            reason: "only dependencies are updated",
+        })
+    } else {
+        None
+    };
+
+    tracing::debug!(?suggestion, "suggested a type from the staged changes");
+    suggestion
+}
+
+/// Returns whether the file is a test.
+fn is_test(file: &FilePatch) -> bool {
+    let (dirs, name) = split_path(&file.path);
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+
+    dirs.iter().any(|dir| TEST_DIRS.contains(dir))
+        || stem.starts_with("test_")
+        || TEST_SUFFIXES.iter().any(|suffix| stem.ends_with(suffix))
+}
+
+/// Returns whether the file is documentation.
+fn is_doc(file: &FilePatch) -> bool {
+    let (dirs, name) = split_path(&file.path);
+    let extension = name.rsplit_once('.').map(|(_, extension)| extension);
+
+    dirs.iter().any(|dir| DOC_DIRS.contains(dir))
+        || extension.is_some_and(|extension| {
+            DOC_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+        })
+}
+
+/// Returns whether the changes to the file only update dependencies.
+///
+/// This is the case for lockfiles, and for manifests where all the changed
+/// lines declare the version of a dependency. A bump of the version of the
+/// package itself is a release, not a dependency update.
+fn is_dependency_update(file: &FilePatch) -> bool {
+    let (_, name) = split_path(&file.path);
+
+    LOCKFILES.contains(&name)
+        || MANIFESTS.contains(&name)
+            && file
+                .added_lines
+                .iter()
+                .chain(&file.removed_lines)
+                .filter(|line| !line.trim().is_empty())
+                .all(|line| {
+                    VERSION_REGEX.is_match(line)
+                        && !PACKAGE_VERSION_REGEX.is_match(line)
+                })
+}
+
+/// Splits a path into its directories and its file name.
+fn split_path(path: &str) -> (Vec<&str>, &str) {
+    let mut components: Vec<&str> = path.split('/').collect();
+    let name = components.pop().unwrap_or_default();
+    (components, name)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    fn file(path: &str, added: &[&str], removed: &[&str]) -> FilePatch {
+        FilePatch {
+            path: String::from(path),
+            added_lines: added.iter().map(|line| String::from(*line)).collect(),
+            removed_lines: removed
+                .iter()
+                .map(|line| String::from(*line))
+                .collect(),
+        }
+    }
+
+    fn suggested_type(files: Vec<FilePatch>) -> Option<&'static str> {
+        suggest(&StagedPatch { files }).map(|suggestion| suggestion.r#type)
+    }
+
+    #[test]
+    fn suggests_test_for_test_files_only() {
+        let files = vec![
+            file("tests/commit.rs", &[], &[]),
+            file("src/app.test.ts", &[], &[]),
+            file("pkg/parser_test.go", &[], &[]),
+        ];
+
+        assert_eq!(suggested_type(files), Some("test"));
+    }
+
+    #[test]
+    fn suggests_docs_for_documentation_only() {
+        let files = vec![
+            file("README.md", &[], &[]),
+            file("docs/guide/usage.html", &[], &[]),
+        ];
+
+        assert_eq!(suggested_type(files), Some("docs"));
+    }
+
+    #[test]
+    fn suggests_deps_for_lockfiles_and_versions_in_manifests() {
+        let files = vec![
+            file("Cargo.lock", &["version = \"1.2.4\""], &[]),
+            file("Cargo.toml", &["serde = \"1.0.200\""], &["serde = \"1.0\""]),
+        ];
+
+        assert_eq!(suggested_type(files), Some("deps"));
+    }
+
+    #[test]
+    fn does_not_suggest_deps_for_other_changes_in_manifests() {
+        let files = vec![
+            file("Cargo.lock", &[], &[]),
+            file("Cargo.toml", &["[features]"], &[]),
+        ];
+
+        assert_eq!(suggested_type(files), None);
+    }
+
+    #[test]
+    fn does_not_suggest_deps_for_a_version_bump_of_the_package() {
+        let files = vec![
+            file(
+                "Cargo.toml",
+                &["version = \"0.3.0\""],
+                &["version = \"0.2.0\""],
+            ),
+            file("package.json", &["  \"version\": \"1.1.0\","], &[]),
+        ];
+
+        assert_eq!(suggested_type(files), None);
+    }
+
+    #[test]
+    fn does_not_suggest_anything_for_mixed_changes() {
+        let files =
+            vec![file("src/lib.rs", &[], &[]), file("tests/lib.rs", &[], &[])];
+
+        assert_eq!(suggested_type(files), None);
+    }
+
+    #[test]
+    fn does_not_suggest_anything_without_staged_changes() {
+        assert_eq!(suggested_type(vec![]), None);
+    }
+}
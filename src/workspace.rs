@@ -0,0 +1,541 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Packages of a Cargo, npm or pnpm workspace.
+//!
+//! The workspace manifest is read from the root of the repository, then the
+//! member patterns are expanded to find the manifest of each package. Only `*`,
+//! `?` and `[...]` in a path component, as well as `**` for any number of
+//! directories, are supported in the patterns.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use itertools::Itertools as _;
+use thiserror::Error;
+
+use crate::{
+    config::{repo_root, ScopeSource},
+    list_command::Entry,
+    tracing::LogResult as _,
+};
+
+/// The name of the manifest of Cargo packages.
+const CARGO_MANIFEST: &str = "Cargo.toml";
+/// The name of the manifest of npm packages.
+const NPM_MANIFEST: &str = "package.json";
+/// The name of the manifest of pnpm workspaces.
+const PNPM_WORKSPACE: &str = "pnpm-workspace.yaml";
+
+/// Directories never searched for packages when expanding `**`.
+const SKIPPED_DIRS: [&str; 2] = ["node_modules", "target"];
+
+/// Errors that can occur when reading a workspace.
+#[derive(Debug, Error)]
+pub enum WorkspaceError {
+    /// A manifest cannot be read.
+    #[error("Failed to read {path}")]
+    CannotRead {
+        /// The path of the manifest.
+        path: PathBuf,
+        /// The error reported by the OS.
+        #[source]
+        source: io::Error,
+    },
+    /// A manifest is invalid.
+    #[error("{path} is invalid")]
+    InvalidManifest {
+        /// The path of the manifest.
+        path: PathBuf,
+        /// Why the manifest is invalid.
+        reason: String,
+    },
+    /// A member pattern is invalid.
+    #[error("The workspace member pattern `{pattern}` is invalid")]
+    InvalidPattern {
+        /// The pattern.
+        pattern: String,
+        /// The error reported by the glob parser.
+        #[source]
+        source: globset::Error,
+    },
+}
+
+/// The member patterns of a workspace.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Patterns {
+    /// The patterns matching the member directories.
+    include: Vec<String>,
+    /// The patterns matching directories to exclude.
+    exclude: Vec<String>,
+}
+
+/// Returns the names of the packages in the workspace.
+///
+/// The packages are in the order of the member patterns, sorted by path for
+/// each pattern.
+#[tracing::instrument(level = "trace")]
+pub fn members(source: ScopeSource) -> Result<Vec<Entry>, WorkspaceError> {
+    // NOTE: There is no root in bare repositories, where the manifests are then
+    // read from the current directory.
+    let root = repo_root().unwrap_or_default();
+
+    let (patterns, root_package, manifest_name) = match source {
+        ScopeSource::CargoWorkspace => {
+            let path = root.join(CARGO_MANIFEST);
+            let (patterns, root_package) =
+                cargo_patterns(&path, &read(&path)?)?;
+            (patterns, root_package, CARGO_MANIFEST)
+        }
+        ScopeSource::NpmWorkspace => {
+            let path = root.join(NPM_MANIFEST);
+            (npm_patterns(&path, &read(&path)?)?, None, NPM_MANIFEST)
+        }
+        ScopeSource::Pnpm => {
+            let path = root.join(PNPM_WORKSPACE);
+            (pnpm_patterns(&read(&path)?), None, NPM_MANIFEST)
+        }
+    };
+
+    tracing::debug!(?patterns, ?root_package);
+
+    let excluded = glob_set(&patterns.exclude)?;
+    let mut names: Vec<String> = root_package.into_iter().collect();
+
+    for pattern in &patterns.include {
+        for dir in expand(&root, pattern)? {
+            if excluded.is_match(&dir) {
+                continue;
+            }
+
+            let path = root.join(&dir).join(manifest_name);
+
+            if !path.is_file() {
+                tracing::debug!(?path, "no manifest in the member directory");
+                continue;
+            }
+
+            let manifest = read(&path)?;
+            let name = match source {
+                ScopeSource::CargoWorkspace => cargo_name(&path, &manifest)?,
+                ScopeSource::NpmWorkspace | ScopeSource::Pnpm => {
+                    npm_name(&path, &manifest)?
+                }
+            };
+
+            names.push(name);
+        }
+    }
+
+    let entries = names
+        .into_iter()
+        .unique()
+        .map(|name| Entry {
+            name,
+            description: None,
+        })
+        .collect();
+
+    tracing::debug!(?entries);
+    Ok(entries)
+}
+
+/// Reads a manifest.
+fn read(path: &Path) -> Result<String, WorkspaceError> {
+    fs::read_to_string(path)
+        .map_err(|source| WorkspaceError::CannotRead {
+            path: path.to_owned(),
+            source,
+        })
+        .log_err()
+}
+
+/// Returns an error for an invalid manifest.
+fn invalid(path: &Path, reason: &str) -> WorkspaceError {
+    WorkspaceError::InvalidManifest {
+        path: path.to_owned(),
+        reason: reason.to_owned(),
+    }
+}
+
+/// Parses the member patterns of a Cargo workspace, and the name of the root
+/// package if any.
+fn cargo_patterns(
+    path: &Path,
+    manifest: &str,
+) -> Result<(Patterns, Option<String>), WorkspaceError> {
+    let manifest: toml::Table = toml::from_str(manifest)
+        .map_err(|error| invalid(path, error.message()))
+        .log_err()?;
+
+    let workspace = manifest
+        .get("workspace")
+        .and_then(toml::Value::as_table)
+        .ok_or_else(|| invalid(path, "There is no `[workspace]` table"))
+        .log_err()?;
+
+    let strings = |key: &str| {
+        workspace.get(key).and_then(toml::Value::as_array).map_or(
+            vec![],
+            |values| {
+                values
+                    .iter()
+                    .filter_map(toml::Value::as_str)
+                    .map(ToOwned::to_owned)
+                    .collect()
+            },
+        )
+    };
+
+    let root_package = manifest
+        .get("package")
+        .and_then(|package| package.get("name"))
+        .and_then(toml::Value::as_str)
+        .map(ToOwned::to_owned);
+
+    let patterns = Patterns {
+        include: strings("members"),
+        exclude: strings("exclude"),
+    };
+
+    Ok((patterns, root_package))
+}
+
+/// Parses the member patterns of an npm workspace.
+///
+/// The `workspaces` key can be a list of patterns or, like with Yarn, a table
+/// with a `packages` list.
+fn npm_patterns(
+    path: &Path,
+    manifest: &str,
+) -> Result<Patterns, WorkspaceError> {
+    let manifest: serde_json::Value = serde_json::from_str(manifest)
+        .map_err(|error| invalid(path, &error.to_string()))
+        .log_err()?;
+
+    let workspaces = manifest.get("workspaces").map(|workspaces| {
+        workspaces.get("packages").unwrap_or(workspaces).as_array()
+    });
+
+    let Some(Some(workspaces)) = workspaces else {
+        return Err(invalid(path, "There is no `workspaces` list")).log_err();
+    };
+
+    Ok(split_negated(
+        workspaces.iter().filter_map(serde_json::Value::as_str),
+    ))
+}
+
+/// Parses the member patterns of a pnpm workspace.
+///
+/// Only the `packages` list is read, so that there is no need for a full YAML
+/// parser.
+fn pnpm_patterns(manifest: &str) -> Patterns {
+    let mut patterns = vec![];
+    let mut in_packages = false;
+
+    for line in manifest.lines() {
+        let line = line.split(" #").next().unwrap_or_default().trim_end();
+        let trimmed = line.trim_start();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if let Some(item) = trimmed.strip_prefix('-') {
+            if in_packages {
+                patterns.push(unquote(item));
+            }
+        } else if line.starts_with(char::is_whitespace) {
+            // NOTE: This is a nested key, which is not part of a list.
+            continue;
+        } else {
+            let value = trimmed.strip_prefix("packages:");
+            in_packages = value.is_some_and(str::is_empty);
+
+            // NOTE: Handle the flow style, like `packages: ["a", "b"]`.
+            if let Some(list) = value
+                .map(str::trim)
+                .and_then(|value| value.strip_prefix('['))
+                .and_then(|value| value.strip_suffix(']'))
+            {
+                patterns.extend(list.split(',').map(unquote));
+            }
+        }
+    }
+
+    split_negated(patterns)
+}
+
+/// Removes the quotes around a YAML string.
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+        })
+        .unwrap_or(value)
+        .to_owned()
+}
+
+/// Splits the patterns into included and excluded ones, the latter starting
+/// with `!`.
+fn split_negated<S: AsRef<str>>(
+    patterns: impl IntoIterator<Item = S>,
+) -> Patterns {
+    let mut split = Patterns::default();
+
+    for pattern in patterns {
+        let pattern = pattern.as_ref();
+
+        if pattern.is_empty() {
+            continue;
+        }
+
+        match pattern.strip_prefix('!') {
+            Some(excluded) => split.exclude.push(excluded.to_owned()),
+            None => split.include.push(pattern.to_owned()),
+        }
+    }
+
+    split
+}
+
+/// Reads the name of a Cargo package.
+fn cargo_name(path: &Path, manifest: &str) -> Result<String, WorkspaceError> {
+    let manifest: toml::Table = toml::from_str(manifest)
+        .map_err(|error| invalid(path, error.message()))
+        .log_err()?;
+
+    manifest
+        .get("package")
+        .and_then(|package| package.get("name"))
+        .and_then(toml::Value::as_str)
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| invalid(path, "There is no `package.name`"))
+        .log_err()
+}
+
+/// Reads the name of an npm package.
+fn npm_name(path: &Path, manifest: &str) -> Result<String, WorkspaceError> {
+    let manifest: serde_json::Value = serde_json::from_str(manifest)
+        .map_err(|error| invalid(path, &error.to_string()))
+        .log_err()?;
+
+    manifest
+        .get("name")
+        .and_then(serde_json::Value::as_str)
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| invalid(path, "There is no `name`"))
+        .log_err()
+}
+
+/// Builds a set matching the excluded directories.
+fn glob_set(patterns: &[String]) -> Result<GlobSet, WorkspaceError> {
+    let mut builder = GlobSetBuilder::new();
+
+    for pattern in patterns {
+        builder.add(glob(normalise(pattern))?);
+    }
+
+    builder
+        .build()
+        .map_err(|source| WorkspaceError::InvalidPattern {
+            pattern: patterns.join(", "),
+            source,
+        })
+        .log_err()
+}
+
+/// Parses a glob pattern.
+fn glob(pattern: &str) -> Result<Glob, WorkspaceError> {
+    Glob::new(pattern)
+        .map_err(|source| WorkspaceError::InvalidPattern {
+            pattern: pattern.to_owned(),
+            source,
+        })
+        .log_err()
+}
+
+/// Removes the leading `./` and trailing `/` from a pattern.
+fn normalise(pattern: &str) -> &str {
+    pattern.trim_start_matches("./").trim_end_matches('/')
+}
+
+/// Expands a pattern into the matching directories, relative to the root.
+///
+/// The directories are joined with `/`, whatever the platform.
+pub fn expand(
+    root: &Path,
+    pattern: &str,
+) -> Result<Vec<String>, WorkspaceError> {
+    let mut dirs = vec![String::new()];
+
+    for component in normalise(pattern)
+        .split('/')
+        .filter(|component| !component.is_empty() && *component != ".")
+    {
+        dirs = if component == "**" {
+            dirs.iter().flat_map(|dir| descendants(root, dir)).collect()
+        } else if component.contains(['*', '?', '[']) {
+            let matcher = glob(component)?.compile_matcher();
+            dirs.iter()
+                .flat_map(|dir| {
+                    subdirs(root, dir)
+                        .into_iter()
+                        .filter(|(_, name)| matcher.is_match(name))
+                        .map(|(subdir, _)| subdir)
+                })
+                .collect()
+        } else {
+            dirs.iter()
+                .map(|dir| join(dir, component))
+                .filter(|dir| root.join(dir).is_dir())
+                .collect()
+        };
+    }
+
+    Ok(dirs)
+}
+
+/// Returns a directory and all its descendants, except the skipped ones.
+fn descendants(root: &Path, dir: &str) -> Vec<String> {
+    let mut dirs = vec![dir.to_owned()];
+
+    for (subdir, name) in subdirs(root, dir) {
+        if !SKIPPED_DIRS.contains(&name.as_str()) {
+            dirs.extend(descendants(root, &subdir));
+        }
+    }
+
+    dirs
+}
+
+/// Returns the non-hidden subdirectories of a directory, sorted by name.
+///
+/// Each subdirectory is returned with its name.
+fn subdirs(root: &Path, dir: &str) -> Vec<(String, String)> {
+    let Ok(entries) = fs::read_dir(root.join(dir)) else {
+        return vec![];
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| !name.starts_with('.'))
+        .sorted()
+        .map(|name| (join(dir, &name), name))
+        .collect()
+}
+
+/// Joins a path component to a relative directory.
+fn join(dir: &str, component: &str) -> String {
+    if dir.is_empty() {
+        component.to_owned()
+    } else {
+        format!("{dir}/{component}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::pedantic, clippy::restriction)]
+
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| String::from(*value)).collect()
+    }
+
+    #[test]
+    fn reads_the_cargo_members_and_root_package() {
+        let manifest = r#"
+            [package]
+            name = "app"
+
+            [workspace]
+            members = ["crates/*"]
+            exclude = ["crates/legacy"]
+        "#;
+
+        let (patterns, root_package) =
+            cargo_patterns(Path::new(CARGO_MANIFEST), manifest).unwrap();
+
+        assert_eq!(patterns.include, strings(&["crates/*"]));
+        assert_eq!(patterns.exclude, strings(&["crates/legacy"]));
+        assert_eq!(root_package.as_deref(), Some("app"));
+    }
+
+    #[test]
+    fn refuses_a_cargo_manifest_without_workspace() {
+        let manifest = "[package]\nname = \"app\"\n";
+
+        assert!(matches!(
+            cargo_patterns(Path::new(CARGO_MANIFEST), manifest),
+            Err(WorkspaceError::InvalidManifest { .. })
+        ));
+    }
+
+    #[test]
+    fn reads_the_npm_workspaces_as_a_list_or_a_table() {
+        let list = r#"{ "workspaces": ["packages/*", "!packages/old"] }"#;
+        let table = r#"{ "workspaces": { "packages": ["packages/*"] } }"#;
+
+        let patterns = npm_patterns(Path::new(NPM_MANIFEST), list).unwrap();
+        assert_eq!(patterns.include, strings(&["packages/*"]));
+        assert_eq!(patterns.exclude, strings(&["packages/old"]));
+
+        let patterns = npm_patterns(Path::new(NPM_MANIFEST), table).unwrap();
+        assert_eq!(patterns.include, strings(&["packages/*"]));
+    }
+
+    #[test]
+    fn reads_the_pnpm_packages() {
+        let manifest = "\
+packages:
+  # The apps
+  - 'apps/*'
+  - \"packages/**\" # All the packages
+  - '!**/test/**'
+catalog:
+  - not-a-package
+";
+
+        let patterns = pnpm_patterns(manifest);
+
+        assert_eq!(patterns.include, strings(&["apps/*", "packages/**"]));
+        assert_eq!(patterns.exclude, strings(&["**/test/**"]));
+    }
+
+    #[test]
+    fn reads_the_pnpm_packages_in_flow_style() {
+        let patterns = pnpm_patterns("packages: ['apps/*', \"lib\"]\n");
+        assert_eq!(patterns.include, strings(&["apps/*", "lib"]));
+    }
+
+    #[test]
+    fn joins_the_components_with_slashes() {
+        assert_eq!(join("", "crates"), "crates");
+        assert_eq!(join("crates", "cli"), "crates/cli");
+    }
+}
@@ -0,0 +1,79 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Repository fixture shared by the CLI tests running a real Git.
+
+// NOTE: Each test crate only uses some of the helpers.
+#![allow(dead_code)]
+
+use std::{path::Path, process::Command};
+
+use assert_cmd::cargo::cargo_bin;
+use assert_fs::{prelude::*, TempDir};
+use eyre::{bail, Result};
+
+/// Creates a temporary Git repository.
+pub fn setup_temp_dir() -> Result<TempDir> {
+    let temp_dir = TempDir::new()?;
+    init_repo(&temp_dir)?;
+    Ok(temp_dir)
+}
+
+/// Initialises a Git repository on `main`, with an identity and no signing.
+pub fn init_repo(dir: &Path) -> Result<()> {
+    git(dir, &["init", "--initial-branch=main"])?;
+    git(dir, &["config", "user.name", "git-z"])?;
+    git(dir, &["config", "user.email", "git-z@test"])?;
+    git(dir, &["config", "commit.gpgsign", "false"])?;
+    git(dir, &["config", "tag.gpgsign", "false"])?;
+    Ok(())
+}
+
+/// Runs Git in a directory and returns its standard output.
+pub fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git").current_dir(dir).args(args).output()?;
+
+    if !output.status.success() {
+        bail!("Failed to run git {}", args.join(" "));
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Makes an empty commit.
+pub fn commit(dir: &Path, message: &str) -> Result<()> {
+    git(dir, &["commit", "--allow-empty", "-m", message])?;
+    Ok(())
+}
+
+/// Returns the number of commits in the history of `HEAD`.
+pub fn commit_count(dir: &Path) -> Result<usize> {
+    Ok(git(dir, &["rev-list", "--count", "HEAD"])?.trim().parse()?)
+}
+
+/// Installs a configuration from `tests/res/config` as the `git-z.toml`.
+pub fn install_config(temp_dir: &TempDir, name: &str) -> Result<()> {
+    temp_dir
+        .child("git-z.toml")
+        .write_file(Path::new(&format!("tests/res/config/{name}")))?;
+    Ok(())
+}
+
+/// Returns a command running git-z in a directory, without colours.
+pub fn gitz(dir: &Path) -> Command {
+    let mut cmd = Command::new(cargo_bin("git-z"));
+    cmd.current_dir(dir).env("NO_COLOR", "true");
+    cmd
+}
@@ -0,0 +1,193 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `git z config`.
+
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+mod common;
+
+use std::{fs, process::Command};
+
+use assert_cmd::assert::OutputAssertExt as _;
+use assert_fs::{prelude::*, TempDir};
+use eyre::Result;
+use predicates::prelude::*;
+
+use common::{commit, gitz, install_config, setup_temp_dir};
+
+////////////////////////////////////////////////////////////////////////////////
+//                                  Helpers                                   //
+////////////////////////////////////////////////////////////////////////////////
+
+fn gitz_config_validate(temp_dir: &TempDir) -> Command {
+    let mut cmd = gitz(temp_dir);
+    cmd.args(["config", "validate"]);
+    cmd
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn accepts_scopes_matching_the_history() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_scopes-list.toml")?;
+    commit(&temp_dir, "feat(scope1): add a feature")?;
+    commit(&temp_dir, "fix(scope2): patch a bug")?;
+
+    gitz_config_validate(&temp_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("The configuration is valid."))
+        .stdout(predicate::str::contains(
+            "The scopes match the ones used in 2 commit(s).",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn reports_unused_and_unconfigured_scopes() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_scopes-list.toml")?;
+    commit(&temp_dir, "feat(scope1): add a feature")?;
+    commit(&temp_dir, "feat(api): add a route")?;
+    commit(&temp_dir, "fix(api): patch a route")?;
+    commit(&temp_dir, "refactor(api): clean the routes")?;
+    commit(&temp_dir, "fix(cli): patch a flag")?;
+
+    gitz_config_validate(&temp_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("  - scope2"))
+        .stdout(predicate::str::contains("  - api (3 commit(s))"))
+        .stdout(predicate::str::contains("cli").not());
+
+    Ok(())
+}
+
+#[test]
+fn counts_the_scopes_of_decorated_subjects() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_scopes-list-decorated-subject.toml")?;
+    commit(&temp_dir, "[#12] feat(scope1): add a feature")?;
+    commit(&temp_dir, "[#13] feat(api): add a thing")?;
+
+    gitz_config_validate(&temp_dir).assert().success().stdout(
+        predicate::str::contains(
+            "The scopes match the ones used in 2 commit(s).",
+        ),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn reports_less_used_scopes_with_min_uses() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_scopes-list.toml")?;
+    commit(&temp_dir, "fix(cli): patch a flag")?;
+
+    gitz_config_validate(&temp_dir)
+        .args(["--min-uses", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("  - cli (1 commit(s))"));
+
+    Ok(())
+}
+
+#[test]
+fn skips_the_statistics_when_any_scope_is_accepted() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_minimal.toml")?;
+    commit(&temp_dir, "feat(api): add a route")?;
+
+    gitz_config_validate(&temp_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("The configuration is valid."))
+        .stdout(predicate::str::contains("scopes").not());
+
+    Ok(())
+}
+
+#[test]
+fn rejects_an_invalid_config() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "invalid_value.toml")?;
+    commit(&temp_dir, "feat: add a feature")?;
+
+    gitz_config_validate(&temp_dir)
+        .assert()
+        .code(78)
+        .stdout(predicate::str::contains("The configuration is valid.").not());
+
+    Ok(())
+}
+
+#[test]
+fn rejects_an_invalid_template() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_template-invalid.toml")?;
+
+    gitz_config_validate(&temp_dir)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("The configuration is valid.").not());
+
+    Ok(())
+}
+
+#[test]
+fn rejects_an_invalid_regex() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_ticket-branch-patterns.toml")?;
+    let config = fs::read_to_string(temp_dir.child("git-z.toml"))?
+        .replace(r"(?<ticket>\d+)", r"(?<ticket>\d+");
+    temp_dir.child("git-z.toml").write_str(&config)?;
+
+    gitz_config_validate(&temp_dir)
+        .assert()
+        .code(78)
+        .stdout(predicate::str::contains("The configuration is valid.").not())
+        .stderr(predicate::str::contains(
+            "invalid regex at `ticket.branch_patterns[0]`",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn counts_the_scopes_printed_by_the_scopes_command() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_scopes-command.toml")?;
+    temp_dir.child("scopes.txt").write_str("api\n")?;
+    commit(&temp_dir, "feat(scope1): add a feature")?;
+    commit(&temp_dir, "feat(api): add a route")?;
+    commit(&temp_dir, "fix(api): patch a route")?;
+    commit(&temp_dir, "refactor(api): clean the routes")?;
+
+    gitz_config_validate(&temp_dir).assert().success().stdout(
+        predicate::str::contains(
+            "The scopes match the ones used in 4 commit(s).",
+        ),
+    );
+
+    Ok(())
+}
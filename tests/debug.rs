@@ -0,0 +1,360 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `git z debug`.
+
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+mod common;
+
+use std::process::Command;
+
+use assert_cmd::{assert::OutputAssertExt as _, cargo::cargo_bin};
+use assert_fs::{prelude::*, TempDir};
+use eyre::{bail, Result};
+use predicates::prelude::*;
+use rexpect::session::spawn_command;
+
+use common::{gitz, setup_temp_dir};
+
+const TIMEOUT: Option<u64> = Some(1_000);
+
+////////////////////////////////////////////////////////////////////////////////
+//                                  Helpers                                   //
+////////////////////////////////////////////////////////////////////////////////
+
+fn gitz_dir_prefix(home: &TempDir, temp_dir: &TempDir) -> String {
+    let name = temp_dir.path().file_name().unwrap().to_string_lossy();
+    format!("git-z dir: {}-", home.child(name.as_ref()).path().display())
+}
+
+fn shown_gitz_dir(temp_dir: &TempDir, home: &TempDir) -> Result<String> {
+    let output = gitz_debug(temp_dir)
+        .env("GIT_Z_HOME", home.path())
+        .arg("show-paths")
+        .output()?;
+
+    String::from_utf8(output.stdout)?
+        .lines()
+        .find(|line| line.starts_with("git-z dir: "))
+        .map(String::from)
+        .ok_or_else(|| eyre::eyre!("No git-z dir in the output"))
+}
+
+fn gitz_debug(temp_dir: &TempDir) -> Command {
+    let mut cmd = gitz(temp_dir);
+    cmd.arg("debug");
+    cmd
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn is_hidden_from_the_help() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    Command::new(cargo_bin("git-z"))
+        .current_dir(&temp_dir)
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("debug").not());
+
+    Ok(())
+}
+
+#[test]
+fn shows_the_paths() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    let root = temp_dir.path().canonicalize()?;
+
+    gitz_debug(&temp_dir)
+        .arg("show-paths")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "git dir: {}",
+            root.join(".git").display()
+        )))
+        .stdout(predicate::str::contains("git-z dir: .git/git-z"))
+        .stdout(predicate::str::contains(format!(
+            "config file: {}",
+            root.join("git-z.toml").display()
+        )))
+        .stdout(predicate::str::contains(
+            "commit cache: .git/git-z/commit-cache.toml",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn uses_git_z_home_for_the_state() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    let home = TempDir::new()?;
+
+    gitz_debug(&temp_dir)
+        .env("GIT_Z_HOME", home.path())
+        .arg("show-paths")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(gitz_dir_prefix(&home, &temp_dir)))
+        .stdout(predicate::str::is_match(format!(
+            "commit cache: {}/[^/]+/commit-cache.toml",
+            regex::escape(&home.path().display().to_string())
+        ))?);
+
+    Ok(())
+}
+
+#[test]
+fn separates_the_repositories_sharing_git_z_home() -> Result<()> {
+    let first = setup_temp_dir()?;
+    let second = setup_temp_dir()?;
+    let home = TempDir::new()?;
+
+    assert_ne!(
+        shown_gitz_dir(&first, &home)?,
+        shown_gitz_dir(&second, &home)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn resolves_a_relative_git_z_home_from_the_git_dir() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    let root = temp_dir.path().canonicalize()?;
+
+    gitz_debug(&temp_dir)
+        .env("GIT_Z_HOME", "state")
+        .arg("show-paths")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "git-z dir: {}",
+            root.join(".git/state").display()
+        )));
+
+    Ok(())
+}
+
+#[test]
+fn uses_the_git_z_home_git_config_for_the_state() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    let home = TempDir::new()?;
+
+    let status = Command::new("git")
+        .current_dir(&temp_dir)
+        .args(["config", "git-z.home"])
+        .arg(home.path())
+        .output()?
+        .status;
+
+    if !status.success() {
+        bail!("Failed to run git config");
+    }
+
+    gitz_debug(&temp_dir)
+        .arg("show-paths")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(gitz_dir_prefix(&home, &temp_dir)));
+
+    Ok(())
+}
+
+#[test]
+fn resolves_the_paths_from_the_directory_passed_with_c() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    let outside = TempDir::new()?;
+    let root = temp_dir.path().canonicalize()?;
+
+    Command::new(cargo_bin("git-z"))
+        .current_dir(&outside)
+        .env("NO_COLOR", "true")
+        .arg("-C")
+        .arg(temp_dir.path())
+        .args(["debug", "show-paths"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "git dir: {}",
+            root.join(".git").display()
+        )))
+        .stdout(predicate::str::contains(format!(
+            "config file: {}",
+            root.join("git-z.toml").display()
+        )));
+
+    Ok(())
+}
+
+#[test]
+fn resolves_successive_directories_passed_with_c() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    let root = temp_dir.path().canonicalize()?;
+    temp_dir.child("sub").create_dir_all()?;
+
+    Command::new(cargo_bin("git-z"))
+        .current_dir(temp_dir.path().parent().unwrap())
+        .env("NO_COLOR", "true")
+        .arg("-C")
+        .arg(temp_dir.path().file_name().unwrap())
+        .args(["-C", "sub", "debug", "show-paths"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "git dir: {}",
+            root.join(".git").display()
+        )));
+
+    Ok(())
+}
+
+#[test]
+fn fails_if_the_directory_passed_with_c_does_not_exist() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    Command::new(cargo_bin("git-z"))
+        .current_dir(&temp_dir)
+        .env("NO_COLOR", "true")
+        .args(["-C", "missing", "debug", "show-paths"])
+        .assert()
+        .code(64)
+        .stderr(predicate::str::contains("cannot change to missing."));
+
+    Ok(())
+}
+
+#[test]
+fn renders_the_template_from_the_answers() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    gitz_debug(&temp_dir)
+        .args(["render-template", "--type", "fix", "--scope", "api"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("fix(api): dummy commit\n"));
+
+    Ok(())
+}
+
+#[test]
+fn parses_a_message_from_the_standard_input() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    let mut cmd = gitz_debug(&temp_dir);
+    cmd.arg("parse-message");
+
+    assert_cmd::Command::from_std(cmd)
+        .write_stdin("feat(api)!: add an endpoint\n\nRefs: #42\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#"type: "feat","#))
+        .stdout(predicate::str::contains("breaking: true,"))
+        .stdout(predicate::str::contains(r#"token: "Refs","#));
+
+    Ok(())
+}
+
+#[test]
+fn parses_a_message_from_a_file() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    temp_dir.child("message").write_str("fix: solve a bug\n")?;
+
+    gitz_debug(&temp_dir)
+        .args(["parse-message", "message"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#"description: "solve a bug","#));
+
+    Ok(())
+}
+
+#[test]
+fn prints_an_error_for_an_invalid_message() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    let mut cmd = gitz_debug(&temp_dir);
+    cmd.arg("parse-message");
+
+    assert_cmd::Command::from_std(cmd)
+        .write_stdin("not a conventional commit\n")
+        .assert()
+        .code(65)
+        .stderr(predicate::str::contains("Error: invalid commit message."));
+
+    Ok(())
+}
+
+#[test]
+fn runs_the_wizard_without_committing_nor_caching() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    let mut cmd = gitz_debug(&temp_dir);
+    cmd.arg("fake-commit");
+
+    let mut process = spawn_command(cmd, TIMEOUT)?;
+
+    process.exp_string("Commit type")?;
+    process.send_line("")?;
+    process.exp_string("Scope")?;
+    process.send_line("")?;
+    process.exp_string("Short description")?;
+    process.send_line("add a test")?;
+    process.exp_string("BREAKING CHANGE")?;
+    process.send_line("")?;
+    process.exp_string(": add a test")?;
+    process.exp_eof()?;
+
+    temp_dir
+        .child(".git")
+        .child("git-z")
+        .child("commit-cache.toml")
+        .assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn keeps_the_last_commit_message_when_faking_a_commit() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    let commit_editmsg = temp_dir.child(".git").child("COMMIT_EDITMSG");
+    commit_editmsg.write_str("feat: add an aborted feature\n")?;
+
+    let mut cmd = gitz_debug(&temp_dir);
+    cmd.args(["fake-commit", "--plain"]);
+
+    let mut process = spawn_command(cmd, TIMEOUT)?;
+
+    process.exp_string("Commit type")?;
+    process.send_line("")?;
+    process.exp_string("Scope")?;
+    process.send_line("")?;
+    process.exp_string("Short description")?;
+    process.send_line("add a test")?;
+    process.exp_string("BREAKING CHANGE")?;
+    process.send_line("")?;
+    process.exp_string(": add a test")?;
+    process.exp_eof()?;
+
+    commit_editmsg.assert("feat: add an aborted feature\n");
+
+    Ok(())
+}
@@ -0,0 +1,140 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `git z export`.
+
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+mod common;
+
+use std::process::Command;
+
+use assert_cmd::assert::OutputAssertExt as _;
+use assert_fs::TempDir;
+use eyre::Result;
+use predicates::prelude::*;
+
+use common::{git, gitz, install_config, setup_temp_dir};
+
+////////////////////////////////////////////////////////////////////////////////
+//                                  Helpers                                   //
+////////////////////////////////////////////////////////////////////////////////
+
+/// Makes an empty commit, with a fixed author date.
+fn commit(temp_dir: &TempDir, message: &str) -> Result<()> {
+    git(
+        temp_dir,
+        &[
+            "commit",
+            "--allow-empty",
+            "--date=2024-05-01T12:00:00+02:00",
+            "-m",
+            message,
+        ],
+    )?;
+    Ok(())
+}
+
+fn gitz_export(temp_dir: &TempDir) -> Command {
+    let mut cmd = gitz(temp_dir);
+    cmd.arg("export");
+    cmd
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn exports_the_conventional_commits_as_json() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_ticket-optional.toml")?;
+    commit(&temp_dir, "feat(cli)!: add a flag\n\nRefs: #12, GH-3")?;
+    commit(&temp_dir, "Update stuff")?;
+
+    gitz_export(&temp_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""type": "feat""#))
+        .stdout(predicate::str::contains(r#""scope": "cli""#))
+        .stdout(predicate::str::contains(r#""description": "add a flag""#))
+        .stdout(predicate::str::contains(r#""breaking": true"#))
+        .stdout(predicate::str::contains(r#""author": "git-z""#))
+        .stdout(predicate::str::contains(r#""author_email": "git-z@test""#))
+        .stdout(predicate::str::contains(
+            r#""date": "2024-05-01T12:00:00+02:00""#,
+        ))
+        .stdout(predicate::str::is_match(
+            r##""tickets": \[\s*"\#12",\s*"GH-3"\s*\]"##,
+        )?)
+        .stdout(predicate::str::contains("Update stuff").not());
+
+    Ok(())
+}
+
+#[test]
+fn exports_the_conventional_commits_as_csv() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_ticket-optional.toml")?;
+    commit(&temp_dir, "fix: patch a bug, for real\n\nRefs: #12")?;
+
+    gitz_export(&temp_dir)
+        .args(["--format", "csv"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "hash,type,scope,description,tickets,breaking,author,author_email,date\n",
+        ))
+        .stdout(predicate::str::contains(
+            r##",fix,,"patch a bug, for real",#12,false,git-z,git-z@test,2024-05-01T12:00:00+02:00"##,
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn exports_an_explicit_range() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_ticket-optional.toml")?;
+    commit(&temp_dir, "feat: add a first feature")?;
+    commit(&temp_dir, "fix: patch a bug")?;
+
+    gitz_export(&temp_dir)
+        .args(["--format", "csv", "HEAD~1..HEAD"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("patch a bug"))
+        .stdout(predicate::str::contains("add a first feature").not());
+
+    Ok(())
+}
+
+#[test]
+fn exports_the_commits_with_a_decorated_subject() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_scopes-list-decorated-subject.toml")?;
+    commit(&temp_dir, "[#13] feat(api): add a thing")?;
+
+    gitz_export(&temp_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""type": "feat""#))
+        .stdout(predicate::str::contains(r#""scope": "api""#))
+        .stdout(predicate::str::contains(r#""description": "add a thing""#))
+        .stdout(predicate::str::contains(r##""#13""##));
+
+    Ok(())
+}
@@ -0,0 +1,219 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `git z hook`.
+
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+mod common;
+
+use std::{
+    fs, os::unix::fs::PermissionsExt as _, path::Path, process::Command,
+};
+
+use assert_cmd::{assert::OutputAssertExt as _, cargo::cargo_bin};
+use assert_fs::TempDir;
+use eyre::Result;
+use predicates::prelude::*;
+
+use common::{commit, git, gitz, setup_temp_dir};
+
+const NULL: &str = "0000000000000000000000000000000000000000";
+
+////////////////////////////////////////////////////////////////////////////////
+//                                  Helpers                                   //
+////////////////////////////////////////////////////////////////////////////////
+
+/// Returns the ID of the `HEAD` commit.
+fn head(dir: &Path) -> Result<String> {
+    Ok(git(dir, &["rev-parse", "HEAD"])?.trim().to_owned())
+}
+
+/// Sets up a repository with a first commit on `main`.
+fn setup_repo() -> Result<TempDir> {
+    let temp_dir = setup_temp_dir()?;
+    commit(temp_dir.path(), "chore: initial commit")?;
+    Ok(temp_dir)
+}
+
+/// Creates commits on a branch which is then deleted, so that they are
+/// reachable from no ref, as in a `pre-receive` hook.
+fn unreferenced_commits(dir: &Path, messages: &[&str]) -> Result<String> {
+    git(dir, &["checkout", "-q", "-b", "pushed"])?;
+
+    for message in messages {
+        commit(dir, message)?;
+    }
+
+    let tip = head(dir)?;
+    git(dir, &["checkout", "-q", "main"])?;
+    git(dir, &["branch", "-q", "-D", "pushed"])?;
+
+    Ok(tip)
+}
+
+/// Runs `git z hook pre-receive` with the given ref updates on stdin.
+fn gitz_pre_receive(dir: &Path, updates: &str) -> assert_cmd::Command {
+    let mut cmd = assert_cmd::Command::from_std(gitz(dir));
+    cmd.args(["hook", "pre-receive"]).write_stdin(updates);
+    cmd
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn accepts_valid_commits() -> Result<()> {
+    let temp_dir = setup_repo()?;
+    let tip = unreferenced_commits(
+        temp_dir.path(),
+        &["feat: add a flag", "fix: fix it"],
+    )?;
+
+    gitz_pre_receive(
+        temp_dir.path(),
+        &format!("{NULL} {tip} refs/heads/main\n"),
+    )
+    .assert()
+    .success();
+
+    Ok(())
+}
+
+#[test]
+fn rejects_invalid_commits_with_per_commit_errors() -> Result<()> {
+    let temp_dir = setup_repo()?;
+    let tip = unreferenced_commits(
+        temp_dir.path(),
+        &["feat: add a flag", "Add something", "feature: do things"],
+    )?;
+
+    gitz_pre_receive(
+        temp_dir.path(),
+        &format!("{NULL} {tip} refs/heads/main\n"),
+    )
+    .assert()
+    .code(2)
+    .stdout(predicate::str::contains("refs/heads/main:"))
+    .stdout(predicate::str::contains("Add something"))
+    .stdout(predicate::str::contains("feature: do things"))
+    .stdout(predicate::str::contains("feat: add a flag").not())
+    .stderr(predicate::str::contains(
+        "2 pushed commit(s) do not follow the convention",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn only_checks_the_new_commits() -> Result<()> {
+    let temp_dir = setup_repo()?;
+    commit(temp_dir.path(), "Legacy commit")?;
+    let tip = unreferenced_commits(temp_dir.path(), &["feat: add a flag"])?;
+
+    gitz_pre_receive(
+        temp_dir.path(),
+        &format!("{NULL} {tip} refs/heads/main\n"),
+    )
+    .assert()
+    .success();
+
+    Ok(())
+}
+
+#[test]
+fn ignores_deleted_refs() -> Result<()> {
+    let temp_dir = setup_repo()?;
+    let head = head(temp_dir.path())?;
+
+    gitz_pre_receive(
+        temp_dir.path(),
+        &format!("{head} {NULL} refs/heads/old\n"),
+    )
+    .assert()
+    .success();
+
+    Ok(())
+}
+
+#[test]
+fn uses_the_server_side_config() -> Result<()> {
+    let temp_dir = setup_repo()?;
+    let tip =
+        unreferenced_commits(temp_dir.path(), &["feat(api): add a flag"])?;
+    let config = std::env::current_dir()?
+        .join("tests/res/config/latest_scopes-list.toml");
+
+    gitz_pre_receive(
+        temp_dir.path(),
+        &format!("{NULL} {tip} refs/heads/main\n"),
+    )
+    .arg("--config")
+    .arg(config)
+    .assert()
+    .code(1)
+    .stdout(predicate::str::contains("feat(api): add a flag"));
+
+    Ok(())
+}
+
+#[test]
+fn rejects_malformed_ref_updates() -> Result<()> {
+    let temp_dir = setup_repo()?;
+
+    gitz_pre_receive(temp_dir.path(), "garbage\n")
+        .assert()
+        .code(64)
+        .stderr(predicate::str::contains("invalid ref update: `garbage`"));
+
+    Ok(())
+}
+
+#[test]
+fn rejects_a_push_to_a_bare_repository() -> Result<()> {
+    let server = TempDir::new()?;
+    git(server.path(), &["init", "--bare", "--initial-branch=main"])?;
+
+    let hook = server.path().join("hooks").join("pre-receive");
+    fs::write(
+        &hook,
+        format!(
+            "#!/bin/sh\nexec {} hook pre-receive\n",
+            cargo_bin("git-z").display()
+        ),
+    )?;
+    fs::set_permissions(&hook, fs::Permissions::from_mode(0o755))?;
+
+    let client = setup_repo()?;
+    let server_path = server.path().to_str().unwrap();
+    git(client.path(), &["remote", "add", "origin", server_path])?;
+    git(client.path(), &["push", "-q", "origin", "main"])?;
+
+    commit(client.path(), "Not conventional")?;
+
+    Command::new("git")
+        .current_dir(client.path())
+        .env("NO_COLOR", "true")
+        .args(["push", "origin", "main"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("remote: refs/heads/main:"))
+        .stderr(predicate::str::contains("Not conventional"))
+        .stderr(predicate::str::contains("pre-receive hook declined"));
+
+    Ok(())
+}
@@ -0,0 +1,310 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `git z init`.
+
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+use std::{
+    env,
+    ffi::OsString,
+    os::unix::fs::MetadataExt as _,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use assert_cmd::{assert::OutputAssertExt as _, cargo::cargo_bin};
+use assert_fs::{prelude::*, TempDir};
+use eyre::Result;
+use predicates::prelude::*;
+
+////////////////////////////////////////////////////////////////////////////////
+//                                  Helpers                                   //
+////////////////////////////////////////////////////////////////////////////////
+
+fn setup_temp_dir() -> Result<TempDir> {
+    let temp_dir = TempDir::new()?;
+    temp_dir.child(".git").create_dir_all()?;
+    Ok(temp_dir)
+}
+
+fn fake_git_path() -> Result<OsString> {
+    let fake_git = cargo_bin("git");
+    eyre::ensure!(
+        fake_git.exists(),
+        "The fake Git is not built, please run `cargo build -p fake-git`"
+    );
+
+    let fake_bin = fake_git.parent().map(Path::to_path_buf);
+    let path = env::var_os("PATH").unwrap_or_default();
+    Ok(env::join_paths(
+        fake_bin.into_iter().chain(env::split_paths(&path)),
+    )?)
+}
+
+fn gitz_init(temp_dir: &TempDir) -> Result<Command> {
+    let mut cmd = Command::new(cargo_bin("git-z"));
+    cmd.current_dir(temp_dir)
+        .env("NO_COLOR", "true")
+        .env("PATH", fake_git_path()?)
+        .stdin(Stdio::null())
+        .arg("init");
+    Ok(cmd)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn writes_the_config_from_the_flags_without_asking() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    gitz_init(&temp_dir)?
+        .args(["--scopes", "none", "--ticket", "required"])
+        .assert()
+        .success();
+
+    temp_dir
+        .child("git-z.toml")
+        .assert(predicate::str::contains("\n# [scopes]\n"))
+        .assert(predicate::str::contains("\nrequired = true\n"));
+
+    Ok(())
+}
+
+#[test]
+fn answers_the_other_questions_with_the_preset() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    gitz_init(&temp_dir)?
+        .args(["--preset", "strict", "--ticket", "optional"])
+        .assert()
+        .success();
+
+    temp_dir
+        .child("git-z.toml")
+        .assert(predicate::str::contains("\naccept = \"list\"\n"))
+        .assert(predicate::str::contains("\nrequired = false\n"));
+
+    Ok(())
+}
+
+#[test]
+fn reads_the_types_from_a_file() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    temp_dir
+        .child("types.txt")
+        .write_str("feat\nhotfix\tpatch a bug in production\n")?;
+
+    gitz_init(&temp_dir)?
+        .args(["--types-file", "types.txt"])
+        .assert()
+        .success();
+
+    temp_dir
+        .child("git-z.toml")
+        .assert(predicate::str::contains(
+            "\n[types]\n\
+        feat = \"add a new feature in the code (including tests for the \
+        feature)\"\n\
+        hotfix = \"patch a bug in production\"\n\n",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn fails_if_the_types_file_is_empty() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    temp_dir.child("types.txt").touch()?;
+
+    gitz_init(&temp_dir)?
+        .args(["--types-file", "types.txt"])
+        .assert()
+        .code(65)
+        .stderr(predicate::str::contains("there is no type in types.txt."));
+
+    temp_dir
+        .child("git-z.toml")
+        .assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn fails_if_a_type_is_listed_twice() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    temp_dir
+        .child("types.txt")
+        .write_str("feat\nfix\nfeat\tadd a feature\n")?;
+
+    gitz_init(&temp_dir)?
+        .args(["--types-file", "types.txt"])
+        .assert()
+        .code(65)
+        .stderr(predicate::str::contains(
+            "the type feat is listed twice in types.txt.",
+        ));
+
+    temp_dir
+        .child("git-z.toml")
+        .assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn refuses_the_flags_with_default() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    gitz_init(&temp_dir)?
+        .args(["--default", "--scopes", "any"])
+        .assert()
+        .code(2);
+
+    Ok(())
+}
+
+#[test]
+fn installs_the_hooks() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    gitz_init(&temp_dir)?
+        .args(["--default", "--install-hooks"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "The `commit-msg` hook has been installed.",
+        ));
+
+    temp_dir
+        .child(".git/hooks/commit-msg")
+        .assert(predicate::str::contains("git z lint --message-file"));
+    temp_dir
+        .child(".git/hooks/prepare-commit-msg")
+        .assert(predicate::path::is_file());
+
+    let mode = temp_dir.child(".git/hooks/commit-msg").metadata()?.mode();
+    assert_eq!(mode & 0o111, 0o111);
+
+    Ok(())
+}
+
+#[test]
+fn does_not_overwrite_an_existing_hook() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    temp_dir
+        .child(".git/hooks/commit-msg")
+        .write_str("#!/bin/sh\nexit 0\n")?;
+
+    gitz_init(&temp_dir)?
+        .args(["--default", "--install-hooks"])
+        .assert()
+        .success();
+
+    temp_dir
+        .child(".git/hooks/commit-msg")
+        .assert("#!/bin/sh\nexit 0\n");
+
+    Ok(())
+}
+
+#[test]
+fn writes_the_ci_job() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    gitz_init(&temp_dir)?
+        .args(["--default", "--ci", "github"])
+        .assert()
+        .success();
+
+    temp_dir
+        .child(".github/workflows/git-z.yml")
+        .assert(predicate::str::contains("git z lint"));
+
+    Ok(())
+}
+
+#[test]
+fn copies_the_config_of_another_repository() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    let other_repo = setup_temp_dir()?;
+    let config = include_str!("res/config/latest_scopes-list.toml");
+    other_repo.child("git-z.toml").write_str(config)?;
+
+    gitz_init(&temp_dir)?
+        .arg("--sync-from")
+        .arg(other_repo.path())
+        .assert()
+        .success();
+
+    temp_dir.child("git-z.toml").assert(config);
+
+    Ok(())
+}
+
+#[test]
+fn fails_if_the_other_repository_has_no_config() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    let other_repo = setup_temp_dir()?;
+
+    gitz_init(&temp_dir)?
+        .arg("--sync-from")
+        .arg(other_repo.path())
+        .assert()
+        .code(64)
+        .stderr(predicate::str::contains("there is no git-z.toml in"));
+
+    temp_dir
+        .child("git-z.toml")
+        .assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn does_not_copy_an_invalid_config() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    let other_repo = setup_temp_dir()?;
+    other_repo
+        .child("git-z.toml")
+        .write_str("version = \"0.3-dev.0\"\n[types\n")?;
+
+    gitz_init(&temp_dir)?
+        .arg("--sync-from")
+        .arg(other_repo.path())
+        .assert()
+        .code(78);
+
+    temp_dir
+        .child("git-z.toml")
+        .assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn refuses_the_flags_with_sync_from() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    gitz_init(&temp_dir)?
+        .args(["--sync-from", "../other", "--preset", "strict"])
+        .assert()
+        .code(2);
+
+    Ok(())
+}
@@ -0,0 +1,740 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `git z lint`.
+
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+mod common;
+
+use std::{fs, process::Command};
+
+use assert_cmd::assert::OutputAssertExt as _;
+use assert_fs::{prelude::*, TempDir};
+use eyre::Result;
+use indoc::indoc;
+use predicates::prelude::*;
+
+use common::{commit, git, gitz, init_repo, install_config, setup_temp_dir};
+
+////////////////////////////////////////////////////////////////////////////////
+//                                  Helpers                                   //
+////////////////////////////////////////////////////////////////////////////////
+
+fn setup_repos(temp_dir: &TempDir, names: &[&str]) -> Result<()> {
+    for name in names {
+        let repo = temp_dir.child(name);
+        repo.create_dir_all()?;
+        init_repo(&repo)?;
+    }
+
+    Ok(())
+}
+
+fn install_baseline(temp_dir: &TempDir, baseline: &str) -> Result<()> {
+    temp_dir.child("git-z.toml").write_str(&format!(
+        indoc! {r#"
+            version = "0.3-dev.0"
+
+            [types]
+            feat = "add a new feature"
+            fix = "patch a bug"
+
+            [templates]
+            commit = "{{{{ type }}}}: {{{{ description }}}}"
+
+            [lint]
+            baseline = "{}"
+        "#},
+        baseline
+    ))?;
+    Ok(())
+}
+
+fn gitz_lint(temp_dir: &TempDir) -> Command {
+    let mut cmd = gitz(temp_dir);
+    cmd.arg("lint");
+    cmd
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn accepts_a_valid_history() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "feat: add a first feature")?;
+    commit(&temp_dir, "fix(cli): patch a bug in the cli")?;
+
+    gitz_lint(&temp_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "2 commits checked, all following the convention.",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn reports_invalid_commits() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "feat: add a first feature")?;
+    commit(&temp_dir, "Update stuff")?;
+    commit(&temp_dir, "feature: Add a second feature")?;
+
+    gitz_lint(&temp_dir)
+        .assert()
+        .failure()
+        .code(2)
+        .stdout(predicate::str::contains("Update stuff"))
+        .stdout(predicate::str::contains(
+            "The first line does not follow the `type(scope)!: description` format.",
+        ))
+        .stdout(predicate::str::contains(
+            "The type `feature` is not a valid commit type.",
+        ))
+        .stdout(predicate::str::contains(
+            "The description must start in lowercase.",
+        ))
+        .stderr(predicate::str::contains(
+            "2 commits do not follow the convention",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn checks_an_explicit_range() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "Update stuff")?;
+    commit(&temp_dir, "feat: add a first feature")?;
+
+    gitz_lint(&temp_dir)
+        .arg("HEAD~1..HEAD")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "1 commit checked, all following the convention.",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn checks_commits_since_the_latest_tag() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "Update stuff")?;
+    git(&temp_dir, &["tag", "v0.1.0"])?;
+    commit(&temp_dir, "feat: add a first feature")?;
+
+    gitz_lint(&temp_dir)
+        .arg("--since-tag")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "1 commit checked, all following the convention.",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn fails_with_since_tag_when_there_is_no_tag() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "feat: add a first feature")?;
+
+    gitz_lint(&temp_dir)
+        .arg("--since-tag")
+        .assert()
+        .failure()
+        .code(exitcode::USAGE)
+        .stderr(predicate::str::contains(
+            "there is no tag reachable from HEAD",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn checks_commits_not_merged_in_a_base_branch() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "Update stuff")?;
+    git(&temp_dir, &["switch", "-c", "feature"])?;
+    commit(&temp_dir, "feat: add a first feature")?;
+
+    gitz_lint(&temp_dir)
+        .args(["--base", "main"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "1 commit checked, all following the convention.",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn excludes_merge_commits_with_no_merges() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "feat: add a first feature")?;
+    git(&temp_dir, &["switch", "-c", "feature"])?;
+    commit(&temp_dir, "feat: add a second feature")?;
+    git(&temp_dir, &["switch", "main"])?;
+    git(
+        &temp_dir,
+        &["merge", "--no-ff", "-m", "Merge feature", "feature"],
+    )?;
+
+    gitz_lint(&temp_dir).assert().failure();
+
+    gitz_lint(&temp_dir)
+        .arg("--no-merges")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "2 commits checked, all following the convention.",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn skips_merge_commits_with_a_generated_message() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "feat: add a first feature")?;
+    git(&temp_dir, &["switch", "-c", "feature"])?;
+    commit(&temp_dir, "feat: add a second feature")?;
+    git(&temp_dir, &["switch", "main"])?;
+    git(&temp_dir, &["merge", "--no-ff", "--no-edit", "feature"])?;
+
+    gitz_lint(&temp_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "2 commits checked, all following the convention.",
+        ));
+
+    gitz_lint(&temp_dir)
+        .arg("--check-merge-messages")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Merge branch 'feature'"));
+
+    Ok(())
+}
+
+#[test]
+fn validates_squash_merge_suffixes() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(
+        &temp_dir,
+        "feat: add a feature coming from a long pull request (#12)",
+    )?;
+
+    gitz_lint(&temp_dir).assert().failure();
+    gitz_lint(&temp_dir)
+        .arg("--squash-merges")
+        .assert()
+        .success();
+
+    commit(&temp_dir, "fix: patch a bug from a pull request (#xyz)")?;
+
+    gitz_lint(&temp_dir)
+        .arg("--squash-merges")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "The pull request number must be appended as ` (#123)`.",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn ignores_commits_older_than_the_configured_baseline() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "Update stuff")?;
+    commit(&temp_dir, "feat: adopt the convention")?;
+    git(&temp_dir, &["tag", "adoption"])?;
+    commit(&temp_dir, "fix: patch a bug after adoption")?;
+    install_baseline(&temp_dir, "adoption")?;
+
+    gitz_lint(&temp_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "2 commits checked, all following the convention.",
+        ));
+
+    gitz_lint(&temp_dir)
+        .arg("--no-baseline")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Update stuff"));
+
+    Ok(())
+}
+
+#[test]
+fn ignores_commits_older_than_the_baseline_option() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "Update stuff")?;
+    commit(&temp_dir, "feat: adopt the convention")?;
+    commit(&temp_dir, "fix: patch a bug after adoption")?;
+
+    gitz_lint(&temp_dir)
+        .args(["--baseline", "HEAD~1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "2 commits checked, all following the convention.",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn refuses_a_range_with_since_tag() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    gitz_lint(&temp_dir)
+        .args(["HEAD", "--since-tag"])
+        .assert()
+        .failure()
+        .code(2);
+
+    Ok(())
+}
+
+#[test]
+fn works_in_a_bare_repository() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "feat: add a first feature")?;
+    commit(&temp_dir, "Update stuff")?;
+
+    let bare_dir = TempDir::new()?;
+    git(
+        &temp_dir,
+        &["clone", "--bare", ".", &bare_dir.to_string_lossy()],
+    )?;
+
+    gitz_lint(&bare_dir)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Update stuff"));
+
+    Ok(())
+}
+
+#[test]
+fn reads_the_configuration_from_head_in_a_bare_repository() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "Update stuff")?;
+    commit(&temp_dir, "feat: add a first feature")?;
+    install_baseline(&temp_dir, "HEAD")?;
+    git(&temp_dir, &["add", "git-z.toml"])?;
+    commit(&temp_dir, "feat: add the git-z configuration")?;
+
+    let bare_dir = TempDir::new()?;
+    git(
+        &temp_dir,
+        &["clone", "--bare", ".", &bare_dir.to_string_lossy()],
+    )?;
+
+    gitz_lint(&bare_dir).assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn accepts_a_valid_message_file() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    temp_dir.child("message").write_str(indoc! {"
+        feat: add a feature
+
+        # Please enter the commit message for your changes.
+        # ------------------------ >8 ------------------------
+        diff --git a/a b/a
+    "})?;
+
+    gitz_lint(&temp_dir)
+        .args(["--message-file", "message"])
+        .assert()
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn reports_an_invalid_message_file() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    temp_dir.child("message").write_str("Update stuff\n")?;
+
+    gitz_lint(&temp_dir)
+        .args(["--message-file", "message"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "the commit message does not follow the convention.",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn accepts_a_message_file_with_a_decorated_subject() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_template-subject.toml")?;
+    temp_dir.child("message").write_str(indoc! {"
+        [#12] type: add a flag
+
+        Refs: #12
+    "})?;
+
+    gitz_lint(&temp_dir)
+        .args(["--message-file", "message"])
+        .assert()
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn refuses_a_range_with_a_message_file() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    gitz_lint(&temp_dir)
+        .args(["HEAD", "--message-file", "message"])
+        .assert()
+        .failure()
+        .code(2);
+
+    Ok(())
+}
+
+#[test]
+fn lints_the_repositories_listed_in_a_file() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    setup_repos(&temp_dir, &["api", "web"])?;
+    commit(&temp_dir.child("api"), "feat: add a first feature")?;
+    commit(&temp_dir.child("web"), "Update stuff")?;
+    temp_dir
+        .child("repos.txt")
+        .write_str("# The services\napi\n\nweb\n")?;
+
+    gitz_lint(&temp_dir)
+        .args(["--repos", "repos.txt"])
+        .assert()
+        .failure()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "api: 1 commit checked, all following the convention.",
+        ))
+        .stdout(predicate::str::contains(
+            "web: 1 of 1 commit does not follow the convention.",
+        ))
+        .stderr(predicate::str::contains(
+            "1 commit does not follow the convention",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn lints_the_repositories_matching_a_glob_pattern() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    setup_repos(&temp_dir, &["services/api", "services/web"])?;
+    commit(&temp_dir.child("services/api"), "feat: add a first feature")?;
+    commit(&temp_dir.child("services/web"), "fix: patch a bug")?;
+
+    gitz_lint(&temp_dir)
+        .args(["--repos", "services/*"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "services/api: 1 commit checked, all following the convention.",
+        ))
+        .stdout(predicate::str::contains(
+            "services/web: 1 commit checked, all following the convention.",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn lints_the_repositories_with_a_common_config() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    setup_repos(&temp_dir, &["api", "web"])?;
+    commit(&temp_dir.child("api"), "Update stuff")?;
+    commit(&temp_dir.child("web"), "feat: add a first feature")?;
+    commit(&temp_dir.child("web"), "fix: patch a bug")?;
+    install_baseline(&temp_dir, "HEAD")?;
+
+    gitz_lint(&temp_dir)
+        .args(["--repos", "*", "--common-config", "git-z.toml"])
+        .assert()
+        .failure()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "web: 1 commit checked, all following the convention.",
+        ))
+        .stdout(predicate::str::contains(
+            "api: 1 of 1 commit does not follow the convention.",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn reports_the_repositories_which_cannot_be_linted() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    setup_repos(&temp_dir, &["api"])?;
+    temp_dir.child("docs").create_dir_all()?;
+    commit(&temp_dir.child("api"), "feat: add a first feature")?;
+
+    gitz_lint(&temp_dir)
+        .args(["--repos", "*"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "api: 1 commit checked, all following the convention.",
+        ))
+        .stdout(predicate::str::contains("docs: failed to lint"))
+        .stderr(predicate::str::contains("1 repository could not be linted"));
+
+    Ok(())
+}
+
+#[test]
+fn reads_the_lists_of_each_repository() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    setup_repos(&temp_dir, &["api", "web"])?;
+
+    for (repo, scope) in [("api", "server"), ("web", "client")] {
+        let repo = temp_dir.child(repo);
+        repo.child("git-z.toml").write_str(indoc! {r#"
+            version = "0.3-dev.0"
+
+            [types]
+            feat = "add a new feature"
+
+            [scopes]
+            accept = "list"
+            list = []
+            command = "cat scopes.txt"
+
+            [templates]
+            commit = "{{ type }}: {{ description }}"
+        "#})?;
+        repo.child("scopes.txt").write_str(&format!("{scope}\n"))?;
+        commit(&repo, &format!("feat({scope}): add a first feature"))?;
+    }
+
+    gitz_lint(&temp_dir)
+        .args(["--repos", "*"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "api: 1 commit checked, all following the convention.",
+        ))
+        .stdout(predicate::str::contains(
+            "web: 1 commit checked, all following the convention.",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn fails_when_no_repository_matches() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+
+    gitz_lint(&temp_dir)
+        .args(["--repos", "services/*"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "no repository found in `services/*`",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn reports_invalid_commits_in_order_with_several_jobs() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "Update stuff")?;
+    commit(&temp_dir, "feat: add a first feature")?;
+    commit(&temp_dir, "Fix stuff")?;
+
+    gitz_lint(&temp_dir)
+        .args(["--jobs", "2"])
+        .assert()
+        .failure()
+        .code(2)
+        .stdout(predicate::str::is_match("(?s)Fix stuff.*Update stuff")?)
+        .stderr(predicate::str::contains(
+            "2 commits do not follow the convention",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn reuses_the_cached_verdicts() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "feat: add a first feature")?;
+
+    gitz_lint(&temp_dir).assert().success();
+
+    let cache = temp_dir.child(".git/git-z/lint-cache");
+    let tampered = fs::read_to_string(&cache)?.replace("[]", r#"["Cached"]"#);
+    fs::write(&cache, tampered)?;
+
+    gitz_lint(&temp_dir)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("- Cached."));
+
+    gitz_lint(&temp_dir).arg("--no-cache").assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn checks_all_the_commits_again_when_the_config_changes() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "feat: add a first feature")?;
+
+    gitz_lint(&temp_dir).assert().success();
+
+    let cache = temp_dir.child(".git/git-z/lint-cache");
+    let tampered = fs::read_to_string(&cache)?.replace("[]", r#"["Cached"]"#);
+    fs::write(&cache, tampered)?;
+
+    install_baseline(&temp_dir, "HEAD")?;
+
+    gitz_lint(&temp_dir).assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn runs_the_scopes_command_only_for_unknown_scopes() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    temp_dir.child("git-z.toml").write_str(indoc! {r#"
+        version = "0.3-dev.0"
+
+        [types]
+        feat = "add a new feature"
+
+        [scopes]
+        accept = "list"
+        list = ["cli"]
+        command = "touch ran.txt && cat scopes.txt"
+
+        [templates]
+        commit = "{{ type }}: {{ description }}"
+    "#})?;
+    temp_dir.child("scopes.txt").write_str("core\n")?;
+    commit(&temp_dir, "feat(cli): add a first feature")?;
+
+    gitz_lint(&temp_dir).assert().success();
+    temp_dir.child("ran.txt").assert(predicate::path::missing());
+
+    commit(&temp_dir, "feat(api): add a second feature")?;
+
+    gitz_lint(&temp_dir)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "The scope `api` is not part of the list of valid scopes.",
+        ));
+    temp_dir.child("ran.txt").assert(predicate::path::exists());
+
+    Ok(())
+}
+
+#[test]
+fn does_not_cache_the_verdicts_depending_on_the_scopes_command() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    temp_dir.child("git-z.toml").write_str(indoc! {r#"
+        version = "0.3-dev.0"
+
+        [types]
+        feat = "add a new feature"
+
+        [scopes]
+        accept = "list"
+        list = ["cli"]
+        command = "cat scopes.txt"
+
+        [templates]
+        commit = "{{ type }}: {{ description }}"
+    "#})?;
+    temp_dir.child("scopes.txt").write_str("core\n")?;
+    commit(&temp_dir, "feat(newmod): add a module")?;
+
+    gitz_lint(&temp_dir)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "The scope `newmod` is not part of the list of valid scopes.",
+        ));
+
+    temp_dir.child("scopes.txt").write_str("core\nnewmod\n")?;
+
+    gitz_lint(&temp_dir).assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn runs_the_types_command_only_for_unknown_types() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    temp_dir.child("git-z.toml").write_str(indoc! {r#"
+        version = "0.3-dev.0"
+
+        [types]
+        feat = "add a new feature"
+
+        [type_list]
+        command = "touch ran.txt && cat types.txt"
+
+        [templates]
+        commit = "{{ type }}: {{ description }}"
+    "#})?;
+    temp_dir
+        .child("types.txt")
+        .write_str("fix\tpatch a bug\n")?;
+    commit(&temp_dir, "feat: add a first feature")?;
+
+    gitz_lint(&temp_dir).assert().success();
+    temp_dir.child("ran.txt").assert(predicate::path::missing());
+
+    commit(&temp_dir, "chore: update a dependency")?;
+
+    gitz_lint(&temp_dir)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "The type `chore` is not a valid commit type.",
+        ));
+    temp_dir.child("ran.txt").assert(predicate::path::exists());
+
+    Ok(())
+}
@@ -0,0 +1,135 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `git z migrate-history`.
+
+// NOTE: rexpect is only compatible with Unix-like systems, so let’s just not
+// compile the CLI tests on Windows.
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+mod common;
+
+use std::process::Command;
+
+use assert_cmd::assert::OutputAssertExt as _;
+use assert_fs::{prelude::*, TempDir};
+use eyre::Result;
+use predicates::prelude::*;
+use rexpect::session::spawn_command;
+
+use common::{commit, git, gitz, setup_temp_dir};
+
+const TIMEOUT: Option<u64> = Some(5_000);
+
+////////////////////////////////////////////////////////////////////////////////
+//                                  Helpers                                   //
+////////////////////////////////////////////////////////////////////////////////
+
+fn gitz_migrate_history(temp_dir: &TempDir) -> Command {
+    let mut cmd = gitz(temp_dir);
+    cmd.arg("migrate-history");
+    cmd
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn does_nothing_when_the_history_follows_the_convention() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "feat: add a first feature")?;
+
+    gitz_migrate_history(&temp_dir).assert().success().stdout(
+        predicate::str::contains(
+            "All the commits already follow the convention.",
+        ),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn rewrites_the_commits_not_following_the_convention() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "Update the stuff\n\nWith a body.")?;
+    commit(&temp_dir, "feat: add a first feature")?;
+
+    let mut process = spawn_command(gitz_migrate_history(&temp_dir), TIMEOUT)?;
+
+    process.exp_string("REWRITES THE HISTORY")?;
+    process.exp_string("Do you want to rewrite the history?")?;
+    process.send_line("y")?;
+    process.exp_string("Update the stuff")?;
+    process.exp_string("Commit type")?;
+    process.send_line("")?;
+    process.exp_string("Scope")?;
+    process.send_line("")?;
+    process.exp_string("Short description")?;
+    process.send_line("")?;
+    process.exp_string("BREAKING CHANGE")?;
+    process.send_line("")?;
+    process.exp_string("The history has been rewritten.")?;
+    process.exp_eof()?;
+
+    assert_eq!(
+        git(&temp_dir, &["log", "--format=%B", "-1", "HEAD~1"])?,
+        "feat: update the stuff\n\nWith a body.\n\n"
+    );
+    assert_eq!(
+        git(&temp_dir, &["log", "--format=%s", "-1", "HEAD"])?,
+        "feat: add a first feature\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn leaves_the_history_untouched_when_not_confirmed() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "Update the stuff")?;
+    let head = git(&temp_dir, &["rev-parse", "HEAD"])?;
+
+    let mut process = spawn_command(gitz_migrate_history(&temp_dir), TIMEOUT)?;
+
+    process.exp_string("Do you want to rewrite the history?")?;
+    process.send_line("n")?;
+    process.exp_string("The history has been left untouched.")?;
+    process.exp_eof()?;
+
+    assert_eq!(git(&temp_dir, &["rev-parse", "HEAD"])?, head);
+
+    Ok(())
+}
+
+#[test]
+fn refuses_to_run_with_uncommitted_changes() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    temp_dir.child("file").touch()?;
+    git(&temp_dir, &["add", "file"])?;
+    commit(&temp_dir, "Update the stuff")?;
+    temp_dir.child("file").write_str("changes")?;
+
+    gitz_migrate_history(&temp_dir)
+        .assert()
+        .failure()
+        .code(exitcode::TEMPFAIL)
+        .stderr(predicate::str::contains(
+            "the worktree contains uncommitted changes",
+        ));
+
+    Ok(())
+}
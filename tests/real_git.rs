@@ -0,0 +1,488 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `git z commit` against real Git repositories.
+//!
+//! They catch divergences between the fake Git used by the other tests and the
+//! real one. They are slower, so they only run with the `real-git-tests`
+//! feature.
+
+#![cfg(all(feature = "real-git-tests", not(target_os = "windows")))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+mod common;
+
+use std::{
+    fs::{self, Permissions},
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    process::Command,
+};
+
+use assert_cmd::assert::OutputAssertExt as _;
+use assert_fs::{prelude::*, TempDir};
+use eyre::Result;
+use indoc::{formatdoc, indoc};
+use predicates::prelude::*;
+use rexpect::session::{spawn_command, PtySession};
+
+use common::{commit_count, git, gitz, init_repo, install_config};
+
+const TIMEOUT: Option<u64> = Some(5_000);
+
+////////////////////////////////////////////////////////////////////////////////
+//                                  Helpers                                   //
+////////////////////////////////////////////////////////////////////////////////
+
+fn setup_temp_dir() -> Result<TempDir> {
+    let temp_dir = common::setup_temp_dir()?;
+    set_editor(&temp_dir)?;
+    Ok(temp_dir)
+}
+
+fn git_init(path: &Path) -> Result<()> {
+    init_repo(path)?;
+    set_editor(path)
+}
+
+fn set_editor(path: &Path) -> Result<()> {
+    // NOTE: `true` leaves the message as is, as if the user had just saved it.
+    git(path, &["config", "core.editor", "true"])?;
+    Ok(())
+}
+
+fn install_hook(path: &Path, name: &str, content: &str) -> Result<()> {
+    let hooks_dir = path.join(".git").join("hooks");
+    fs::create_dir_all(&hooks_dir)?;
+
+    let hook = hooks_dir.join(name);
+    fs::write(&hook, content)?;
+    fs::set_permissions(hook, Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+fn install_failing_hook(path: &Path, name: &str) -> Result<()> {
+    install_hook(
+        path,
+        name,
+        &formatdoc! {r##"
+            #!/bin/sh
+            echo "{name} has failed"
+            exit 1
+        "##},
+    )
+}
+
+fn new_tracked_file(path: &Path, file_name: &str) -> Result<()> {
+    fs::write(path.join(file_name), file_name)?;
+    git(path, &["add", file_name])?;
+    Ok(())
+}
+
+fn head_message(path: &Path) -> Result<String> {
+    git(path, &["log", "-1", "--format=%B", "HEAD"])
+}
+
+fn stashed_patch_count(path: &Path) -> Result<usize> {
+    let mut count = 0;
+
+    for entry in fs::read_dir(path.join(".git/git-z"))? {
+        if entry?.path().extension().is_some_and(|ext| ext == "patch") {
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+fn gitz_commit(path: &Path) -> Command {
+    let mut cmd = gitz(path);
+    cmd.arg("commit");
+    cmd
+}
+
+fn gitz_commit_non_interactive(
+    path: &Path,
+    r#type: &str,
+    description: &str,
+) -> Command {
+    let mut cmd = gitz_commit(path);
+    cmd.args([
+        "--non-interactive",
+        "--type",
+        r#type,
+        "--description",
+        description,
+    ]);
+    cmd
+}
+
+fn fill_wizard(process: &mut PtySession, description: &str) -> Result<()> {
+    process.exp_string("Commit type")?;
+    process.send_line("")?;
+    process.exp_string("Scope")?;
+    process.send_line("")?;
+    process.exp_string("Short description")?;
+    process.send_line(description)?;
+    process.exp_string("BREAKING CHANGE")?;
+    process.send_line("")?;
+    Ok(())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn commits_with_the_rendered_message() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    new_tracked_file(&temp_dir, "a")?;
+
+    gitz_commit_non_interactive(&temp_dir, "feat", "add a feature")
+        .assert()
+        .success();
+
+    assert_eq!(head_message(&temp_dir)?, "feat: add a feature\n\n");
+
+    Ok(())
+}
+
+#[test]
+fn passes_the_message_to_the_commit_msg_hook() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_hook(
+        &temp_dir,
+        "commit-msg",
+        indoc! {r##"
+            #!/bin/sh
+            cp "$1" hook-message
+        "##},
+    )?;
+    new_tracked_file(&temp_dir, "a")?;
+
+    gitz_commit_non_interactive(&temp_dir, "feat", "add a feature")
+        .assert()
+        .success();
+
+    temp_dir
+        .child("hook-message")
+        .assert(predicate::str::starts_with("feat: add a feature\n"));
+
+    Ok(())
+}
+
+#[test]
+fn does_not_commit_if_the_pre_commit_hook_fails() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_failing_hook(&temp_dir, "pre-commit")?;
+    new_tracked_file(&temp_dir, "a")?;
+
+    gitz_commit_non_interactive(&temp_dir, "feat", "add a feature")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("pre-commit has failed"));
+
+    assert!(git(&temp_dir, &["rev-parse", "--verify", "HEAD"]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn reuses_the_commit_editmsg_after_a_commit_msg_hook_failure() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_failing_hook(&temp_dir, "commit-msg")?;
+    new_tracked_file(&temp_dir, "a")?;
+
+    let mut process = spawn_command(gitz_commit(&temp_dir), TIMEOUT)?;
+    fill_wizard(&mut process, "add a feature")?;
+    process.exp_string("commit-msg has failed")?;
+    process.exp_eof()?;
+
+    temp_dir
+        .child(".git")
+        .child("COMMIT_EDITMSG")
+        .assert(predicate::str::starts_with("feat: add a feature\n"));
+
+    fs::remove_file(temp_dir.child(".git").child("hooks").child("commit-msg"))?;
+
+    let mut process = spawn_command(gitz_commit(&temp_dir), TIMEOUT)?;
+    process.exp_string(
+        "A previous run has been aborted. Do you want to reuse your commit \
+            message?",
+    )?;
+    process.send_line("y")?;
+    process.exp_eof()?;
+
+    assert_eq!(head_message(&temp_dir)?, "feat: add a feature\n\n");
+
+    Ok(())
+}
+
+#[test]
+fn amends_the_last_commit() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    new_tracked_file(&temp_dir, "a")?;
+    git(
+        &temp_dir,
+        &["commit", "-m", "feat: add a feature\n\nThe body."],
+    )?;
+
+    gitz_commit_non_interactive(&temp_dir, "fix", "fix a bug")
+        .arg("--amend")
+        .assert()
+        .success();
+
+    assert_eq!(commit_count(&temp_dir)?, 1);
+    assert_eq!(head_message(&temp_dir)?, "fix: fix a bug\n\nThe body.\n\n");
+
+    Ok(())
+}
+
+#[test]
+fn loads_the_config_from_a_subdirectory() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_types-custom.toml")?;
+    temp_dir.child("subdir").create_dir_all()?;
+    new_tracked_file(&temp_dir, "subdir/a")?;
+
+    gitz_commit_non_interactive(
+        &temp_dir.child("subdir"),
+        "second_type",
+        "use a custom type",
+    )
+    .assert()
+    .success();
+
+    assert_eq!(
+        head_message(&temp_dir)?,
+        "second_type: use a custom type\n\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn commits_in_a_linked_worktree() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let main = temp_dir.child("main");
+    let worktree = temp_dir.child("worktree");
+
+    main.create_dir_all()?;
+    git_init(&main)?;
+    new_tracked_file(&main, "a")?;
+    git(&main, &["commit", "-m", "feat: add a feature"])?;
+    git(&main, &["worktree", "add", "-b", "other", "../worktree"])?;
+    new_tracked_file(&worktree, "b")?;
+
+    gitz_commit_non_interactive(&worktree, "fix", "fix a bug")
+        .assert()
+        .success();
+
+    assert_eq!(head_message(&worktree)?, "fix: fix a bug\n\n");
+    assert_eq!(head_message(&main)?, "feat: add a feature\n\n");
+
+    Ok(())
+}
+
+#[test]
+fn keeps_the_commit_cache_in_the_git_dir_of_the_worktree() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let main = temp_dir.child("main");
+    let worktree = temp_dir.child("worktree");
+
+    main.create_dir_all()?;
+    git_init(&main)?;
+    new_tracked_file(&main, "a")?;
+    git(&main, &["commit", "-m", "feat: add a feature"])?;
+    git(&main, &["worktree", "add", "-b", "other", "../worktree"])?;
+    install_failing_hook(&main, "pre-commit")?;
+    new_tracked_file(&worktree, "b")?;
+
+    let mut process = spawn_command(gitz_commit(&worktree), TIMEOUT)?;
+    fill_wizard(&mut process, "fix a bug")?;
+    process.exp_string("pre-commit has failed")?;
+    process.exp_eof()?;
+
+    main.child(".git")
+        .child("worktrees")
+        .child("worktree")
+        .child("git-z")
+        .child("commit-cache.toml")
+        .assert(predicate::str::contains(r#"description = "fix a bug""#));
+    main.child(".git")
+        .child("git-z")
+        .child("commit-cache.toml")
+        .assert(predicate::path::missing());
+
+    Ok(())
+}
+
+#[test]
+fn runs_the_other_hooks_once_git_z_has_run_the_pre_commit_hook() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_hooks-run-pre-commit.toml")?;
+    install_hook(
+        &temp_dir,
+        "pre-commit",
+        indoc! {r##"
+            #!/bin/sh
+            echo "run" >> pre-commit-runs
+        "##},
+    )?;
+    install_hook(
+        &temp_dir,
+        "commit-msg",
+        indoc! {r##"
+            #!/bin/sh
+            cp "$1" hook-message
+        "##},
+    )?;
+    new_tracked_file(&temp_dir, "a")?;
+
+    gitz_commit_non_interactive(&temp_dir, "feat", "add a feature")
+        .assert()
+        .success();
+
+    temp_dir.child("pre-commit-runs").assert("run\n");
+    temp_dir
+        .child("hook-message")
+        .assert(predicate::str::starts_with("feat: add a feature\n"));
+
+    Ok(())
+}
+
+#[test]
+fn runs_the_pre_commit_hook_on_the_index_with_stash_unstaged() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_hooks-run-pre-commit.toml")?;
+    install_hook(
+        &temp_dir,
+        "pre-commit",
+        indoc! {r##"
+            #!/bin/sh
+            cp a hook-saw
+        "##},
+    )?;
+    new_tracked_file(&temp_dir, "a")?;
+    git(&temp_dir, &["commit", "-m", "feat: add a"])?;
+    fs::write(temp_dir.join("a"), "staged")?;
+    git(&temp_dir, &["add", "a"])?;
+    fs::write(temp_dir.join("a"), "unstaged")?;
+
+    gitz_commit_non_interactive(&temp_dir, "fix", "fix a bug")
+        .arg("--stash-unstaged")
+        .assert()
+        .success();
+
+    temp_dir.child("hook-saw").assert("staged");
+    temp_dir.child("a").assert("unstaged");
+    assert_eq!(git(&temp_dir, &["show", "HEAD:a"])?, "staged");
+
+    Ok(())
+}
+
+#[test]
+fn runs_the_pre_commit_hook_on_the_index_with_only_stash_unstaged() -> Result<()>
+{
+    let temp_dir = setup_temp_dir()?;
+    install_hook(
+        &temp_dir,
+        "pre-commit",
+        indoc! {r##"
+            #!/bin/sh
+            cp a hook-saw
+        "##},
+    )?;
+    new_tracked_file(&temp_dir, "a")?;
+    git(&temp_dir, &["commit", "-m", "feat: add a"])?;
+    fs::write(temp_dir.join("a"), "staged")?;
+    git(&temp_dir, &["add", "a"])?;
+    fs::write(temp_dir.join("a"), "unstaged")?;
+
+    gitz_commit_non_interactive(&temp_dir, "fix", "fix a bug")
+        .arg("--stash-unstaged")
+        .assert()
+        .success();
+
+    temp_dir.child("hook-saw").assert("staged");
+    temp_dir.child("a").assert("unstaged");
+
+    Ok(())
+}
+
+#[test]
+fn keeps_the_changes_of_the_hook_and_the_patch_on_conflict() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_hooks-run-pre-commit.toml")?;
+    install_hook(
+        &temp_dir,
+        "pre-commit",
+        indoc! {r##"
+            #!/bin/sh
+            echo "fixed" > a
+        "##},
+    )?;
+    new_tracked_file(&temp_dir, "a")?;
+    git(&temp_dir, &["commit", "-m", "feat: add a"])?;
+    fs::write(temp_dir.join("a"), "staged")?;
+    git(&temp_dir, &["add", "a"])?;
+    fs::write(temp_dir.join("a"), "unstaged")?;
+
+    gitz_commit_non_interactive(&temp_dir, "fix", "fix a bug")
+        .arg("--stash-unstaged")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the unstaged changes conflict with the changes made by the \
+                pre-commit hook.",
+        ));
+
+    temp_dir.child("a").assert("fixed\n");
+    assert_eq!(commit_count(&temp_dir)?, 1);
+    assert_eq!(stashed_patch_count(&temp_dir)?, 1);
+
+    Ok(())
+}
+
+#[test]
+fn refuses_to_stash_over_a_leftover_patch() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_hooks-run-pre-commit.toml")?;
+    install_hook(&temp_dir, "pre-commit", "#!/bin/sh\n")?;
+    new_tracked_file(&temp_dir, "a")?;
+    git(&temp_dir, &["commit", "-m", "feat: add a"])?;
+    fs::write(temp_dir.join("a"), "staged")?;
+    git(&temp_dir, &["add", "a"])?;
+    fs::write(temp_dir.join("a"), "unstaged")?;
+    temp_dir
+        .child(".git/git-z/unstaged-1-1.patch")
+        .write_str("leftover")?;
+
+    gitz_commit_non_interactive(&temp_dir, "fix", "fix a bug")
+        .arg("--stash-unstaged")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "unstaged changes from a previous run have not been restored.",
+        ));
+
+    temp_dir.child("a").assert("unstaged");
+    temp_dir
+        .child(".git/git-z/unstaged-1-1.patch")
+        .assert("leftover");
+    assert_eq!(commit_count(&temp_dir)?, 1);
+
+    Ok(())
+}
@@ -0,0 +1,275 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `git z serve`.
+
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+mod common;
+
+use std::process::Command;
+
+use assert_fs::{prelude::*, TempDir};
+use eyre::{bail, Result};
+use indoc::indoc;
+use serde_json::{json, Value};
+
+use common::{git, gitz};
+
+////////////////////////////////////////////////////////////////////////////////
+//                                  Helpers                                   //
+////////////////////////////////////////////////////////////////////////////////
+
+fn setup_temp_dir() -> Result<TempDir> {
+    let temp_dir = common::setup_temp_dir()?;
+
+    temp_dir.child("git-z.toml").write_str(indoc! {r#"
+        version = "0.3-dev.0"
+
+        [types]
+        feat = "add a new feature"
+        fix = "patch a bug"
+
+        [scopes]
+        accept = "list"
+        list = ["cli", "config"]
+
+        [templates]
+        commit = "{{ type }}{% if scope %}({{ scope }}){% endif %}: {{ description }}"
+    "#})?;
+
+    Ok(temp_dir)
+}
+
+/// Sends the requests to `git z serve` and returns the responses.
+fn serve(temp_dir: &TempDir, requests: &[Value]) -> Result<Vec<Value>> {
+    let stdin = requests
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let output = assert_cmd::Command::from_std(gitz_serve(temp_dir))
+        .write_stdin(stdin)
+        .output()?;
+
+    if !output.status.success() {
+        bail!("git z serve has failed");
+    }
+
+    String::from_utf8(output.stdout)?
+        .lines()
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+fn request(id: u64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+}
+
+fn gitz_serve(temp_dir: &TempDir) -> Command {
+    let mut cmd = gitz(temp_dir);
+    cmd.arg("serve");
+    cmd
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn lists_the_types() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    let responses = serve(&temp_dir, &[request(1, "list_types", json!({}))])?;
+
+    assert_eq!(
+        responses,
+        [json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": [
+                { "name": "feat", "description": "add a new feature" },
+                { "name": "fix", "description": "patch a bug" },
+            ],
+        })]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn lists_the_scopes() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    let responses = serve(&temp_dir, &[request(1, "list_scopes", json!({}))])?;
+
+    assert_eq!(
+        responses[0]["result"],
+        json!({ "accept": "list", "list": ["cli", "config"], "required": false })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn validates_descriptions() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    let responses = serve(
+        &temp_dir,
+        &[
+            request(
+                1,
+                "validate_description",
+                json!({ "description": "add a feature" }),
+            ),
+            request(2, "validate_description", json!({ "description": "Add" })),
+        ],
+    )?;
+
+    assert_eq!(responses[0]["result"], json!({ "valid": true }));
+    assert_eq!(
+        responses[1]["result"],
+        json!({
+            "valid": false,
+            "reason": "The description must be longer than 5 characters",
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn renders_the_message() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    let responses = serve(
+        &temp_dir,
+        &[request(
+            1,
+            "render_message",
+            json!({ "type": "feat", "scope": "cli", "description": "add serve" }),
+        )],
+    )?;
+
+    assert_eq!(
+        responses[0]["result"],
+        json!({ "message": "feat(cli): add serve" })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn reports_invalid_answers() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    let responses = serve(
+        &temp_dir,
+        &[request(
+            1,
+            "render_message",
+            json!({ "type": "feature", "description": "add serve" }),
+        )],
+    )?;
+
+    assert_eq!(responses[0]["error"]["code"], json!(-32_000));
+    assert_eq!(
+        responses[0]["error"]["data"]["violations"],
+        json!(["The type `feature` is not a valid commit type"])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn commits_with_the_rendered_message() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    temp_dir.child("file").touch()?;
+    git(&temp_dir, &["add", "file"])?;
+
+    let responses = serve(
+        &temp_dir,
+        &[request(
+            1,
+            "commit",
+            json!({ "type": "fix", "description": "patch the server" }),
+        )],
+    )?;
+
+    assert_eq!(
+        responses[0]["result"]["message"],
+        json!("fix: patch the server")
+    );
+    assert_eq!(
+        git(&temp_dir, &["log", "-1", "--format=%B"])?,
+        "fix: patch the server\n\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn refuses_interactive_git_commit_arguments() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    temp_dir.child("file").touch()?;
+    git(&temp_dir, &["add", "file"])?;
+
+    let responses = serve(
+        &temp_dir,
+        &[request(
+            1,
+            "commit",
+            json!({
+                "type": "fix",
+                "description": "patch the server",
+                "extra_args": ["--edit"],
+            }),
+        )],
+    )?;
+
+    assert_eq!(responses[0]["error"]["code"], json!(-32_602));
+    assert!(git(&temp_dir, &["rev-parse", "--verify", "HEAD"]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn reports_unknown_methods() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    let responses = serve(&temp_dir, &[request(1, "unknown", json!({}))])?;
+
+    assert_eq!(responses[0]["error"]["code"], json!(-32_601));
+
+    Ok(())
+}
+
+#[test]
+fn reports_parse_errors() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    let output = assert_cmd::Command::from_std(gitz_serve(&temp_dir))
+        .write_stdin("{not json\n")
+        .output()?;
+    let response: Value = serde_json::from_slice(&output.stdout)?;
+
+    assert_eq!(response["id"], Value::Null);
+    assert_eq!(response["error"]["code"], json!(-32_700));
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `git z stats`.
+
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+mod common;
+
+use std::process::Command;
+
+use assert_cmd::assert::OutputAssertExt as _;
+use assert_fs::TempDir;
+use eyre::Result;
+use predicates::prelude::*;
+
+use common::{commit, gitz, setup_temp_dir};
+
+////////////////////////////////////////////////////////////////////////////////
+//                                  Helpers                                   //
+////////////////////////////////////////////////////////////////////////////////
+
+fn gitz_with_metrics(temp_dir: &TempDir, metrics: &str) -> Command {
+    let mut cmd = gitz(temp_dir);
+    cmd.env("GIT_Z_METRICS", metrics);
+    cmd
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn shows_that_nothing_has_been_recorded_yet() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "feat: add a route")?;
+
+    gitz_with_metrics(&temp_dir, "true")
+        .args(["stats", "--self"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No usage has been recorded yet."));
+
+    Ok(())
+}
+
+#[test]
+fn counts_the_lint_runs() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "feat: add a route")?;
+
+    for _ in 0..2 {
+        gitz_with_metrics(&temp_dir, "true")
+            .arg("lint")
+            .assert()
+            .success();
+    }
+
+    gitz_with_metrics(&temp_dir, "true")
+        .args(["stats", "--self"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Commits with the wizard:  0\n"))
+        .stdout(predicate::str::contains("Average wizard duration:  -\n"))
+        .stdout(predicate::str::contains("Runs of git z lint:       2\n"));
+
+    Ok(())
+}
+
+#[test]
+fn records_nothing_unless_enabled() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "feat: add a route")?;
+
+    gitz_with_metrics(&temp_dir, "false")
+        .arg("lint")
+        .assert()
+        .success();
+
+    gitz_with_metrics(&temp_dir, "false")
+        .args(["stats", "--self"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No usage has been recorded yet."))
+        .stderr(predicate::str::contains(
+            "git config --global git-z.metrics",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn requires_the_self_flag() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit(&temp_dir, "feat: add a route")?;
+
+    gitz_with_metrics(&temp_dir, "true")
+        .arg("stats")
+        .assert()
+        .code(2);
+
+    Ok(())
+}
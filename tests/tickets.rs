@@ -0,0 +1,184 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `git z tickets`.
+
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+mod common;
+
+use std::process::Command;
+
+use assert_cmd::assert::OutputAssertExt as _;
+use assert_fs::TempDir;
+use eyre::Result;
+use predicates::prelude::*;
+
+use common::{commit, git, gitz, install_config, setup_temp_dir};
+
+////////////////////////////////////////////////////////////////////////////////
+//                                  Helpers                                   //
+////////////////////////////////////////////////////////////////////////////////
+
+fn gitz_tickets(temp_dir: &TempDir) -> Command {
+    let mut cmd = gitz(temp_dir);
+    cmd.arg("tickets");
+    cmd
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn lists_the_tickets_with_their_commits() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_ticket-status-command.toml")?;
+    commit(&temp_dir, "feat: add a route\n\nRefs: #1")?;
+    commit(&temp_dir, "fix: patch a flag\n\nRefs: GH-2, #1")?;
+    commit(&temp_dir, "chore: bump the version")?;
+
+    gitz_tickets(&temp_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(
+            r"(?m)^#1\n  \w{10} fix: patch a flag\n  \w{10} feat: add a route\n",
+        )?)
+        .stdout(predicate::str::is_match(
+            r"(?m)^GH-2\n  \w{10} fix: patch a flag\n",
+        )?)
+        .stdout(predicate::str::contains("bump").not());
+
+    Ok(())
+}
+
+#[test]
+fn lists_the_tickets_in_a_range() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_ticket-status-command.toml")?;
+    commit(&temp_dir, "feat: add a route\n\nRefs: #1")?;
+    git(&temp_dir, &["tag", "v1.0.0"])?;
+    commit(&temp_dir, "fix: patch a flag\n\nRefs: GH-2")?;
+
+    gitz_tickets(&temp_dir)
+        .arg("v1.0.0..HEAD")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("GH-2"))
+        .stdout(predicate::str::contains("#1").not());
+
+    Ok(())
+}
+
+#[test]
+fn reports_when_no_ticket_is_referenced() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_ticket-status-command.toml")?;
+    commit(&temp_dir, "chore: bump the version")?;
+
+    gitz_tickets(&temp_dir).assert().success().stdout(
+        predicate::str::contains("No ticket referenced in 1 commit(s)."),
+    );
+
+    Ok(())
+}
+
+#[test]
+fn fails_when_some_tickets_are_still_open() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_ticket-status-command.toml")?;
+    commit(&temp_dir, "feat: add a route\n\nRefs: #1")?;
+    commit(&temp_dir, "fix: patch a flag\n\nRefs: GH-2")?;
+
+    gitz_tickets(&temp_dir)
+        .arg("--check-status")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("#1 (open)"))
+        .stdout(predicate::str::contains("GH-2 (closed)"))
+        .stderr(predicate::str::contains(
+            "1 referenced ticket(s) are still open",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn succeeds_when_all_tickets_are_closed() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_ticket-status-command.toml")?;
+    commit(&temp_dir, "fix: patch a flag\n\nRefs: GH-2")?;
+
+    gitz_tickets(&temp_dir)
+        .arg("--check-status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("GH-2 (closed)"));
+
+    Ok(())
+}
+
+#[test]
+fn fails_when_the_state_cannot_be_fetched() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_ticket-status-command.toml")?;
+    commit(&temp_dir, "fix: patch a flag\n\nRefs: #3")?;
+
+    gitz_tickets(&temp_dir)
+        .arg("--check-status")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("#3 (unknown)"))
+        .stderr(predicate::str::contains(
+            "The state of #3 cannot be fetched",
+        ))
+        .stderr(predicate::str::contains(
+            "the state of 1 referenced ticket(s) is unknown",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn requires_a_status_command_to_check_the_status() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_ticket-required.toml")?;
+    commit(&temp_dir, "fix: patch a flag\n\nRefs: #1")?;
+
+    gitz_tickets(&temp_dir)
+        .arg("--check-status")
+        .assert()
+        .code(78)
+        .stderr(predicate::str::contains(
+            "no `ticket.status_command` is configured",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn requires_a_ticket_config() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "latest_minimal.toml")?;
+    commit(&temp_dir, "fix: patch a flag")?;
+
+    gitz_tickets(&temp_dir)
+        .assert()
+        .code(78)
+        .stderr(predicate::str::contains("no ticket is configured"));
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `--trace-out`.
+
+#![cfg(all(not(target_os = "windows"), feature = "chrome-trace"))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+mod common;
+
+use std::fs;
+
+use assert_cmd::assert::OutputAssertExt as _;
+use assert_fs::prelude::*;
+use eyre::Result;
+use predicates::prelude::*;
+use serde_json::Value;
+
+use common::{gitz, setup_temp_dir};
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn exports_the_spans_in_the_chrome_format() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    gitz(&temp_dir)
+        .args(["--trace-out", "trace.json", "debug", "show-paths"])
+        .assert()
+        .success()
+        .stderr(predicate::str::is_empty());
+
+    let trace: Value = serde_json::from_str(&fs::read_to_string(
+        temp_dir.child("trace.json"),
+    )?)?;
+    let events = trace.as_array().expect("the trace is not an array");
+
+    assert!(events
+        .iter()
+        .any(|event| event["name"] == "debug" && event["ph"] == "B"));
+    assert!(events
+        .iter()
+        .any(|event| event["name"] == "debug" && event["ph"] == "E"));
+
+    Ok(())
+}
+
+#[test]
+fn completes_the_trace_when_the_command_fails() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    gitz(&temp_dir)
+        .args(["--trace-out", "trace.json", "debug", "parse-message"])
+        .arg("missing-file")
+        .assert()
+        .code(74);
+
+    let trace: Value = serde_json::from_str(&fs::read_to_string(
+        temp_dir.child("trace.json"),
+    )?)?;
+
+    assert!(trace.is_array());
+
+    Ok(())
+}
@@ -0,0 +1,200 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `git z undo`.
+
+// NOTE: rexpect is only compatible with Unix-like systems, so let’s just not
+// compile the CLI tests on Windows.
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+mod common;
+
+use std::process::Command;
+
+use assert_cmd::assert::OutputAssertExt as _;
+use assert_fs::{prelude::*, TempDir};
+use eyre::Result;
+use indoc::indoc;
+use predicates::prelude::*;
+use rexpect::session::spawn_command;
+
+use common::{commit_count, git, gitz, setup_temp_dir};
+
+const TIMEOUT: Option<u64> = Some(5_000);
+
+////////////////////////////////////////////////////////////////////////////////
+//                                  Helpers                                   //
+////////////////////////////////////////////////////////////////////////////////
+
+fn commit_file(temp_dir: &TempDir, file: &str, message: &str) -> Result<()> {
+    temp_dir.child(file).write_str(file)?;
+    git(temp_dir, &["add", file])?;
+    git(temp_dir, &["commit", "-m", message])?;
+    Ok(())
+}
+
+fn gitz_undo(temp_dir: &TempDir) -> Command {
+    let mut cmd = gitz(temp_dir);
+    cmd.arg("undo");
+    cmd
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn undoes_the_last_commit_and_keeps_its_changes_staged() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit_file(&temp_dir, "a", "feat: add a")?;
+    commit_file(&temp_dir, "b", "feat(api)!: add b\n\nBREAKING CHANGE: b.")?;
+
+    gitz_undo(&temp_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "`feat(api)!: add b` has been undone, its changes are staged \
+                again.",
+        ));
+
+    assert_eq!(commit_count(&temp_dir)?, 1);
+    assert_eq!(git(&temp_dir, &["diff", "--cached", "--name-only"])?, "b\n");
+
+    Ok(())
+}
+
+#[test]
+fn restores_the_answers_in_the_commit_cache() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit_file(&temp_dir, "a", "feat: add a")?;
+    commit_file(&temp_dir, "b", "feat(api)!: add b\n\nBREAKING CHANGE: b.")?;
+
+    gitz_undo(&temp_dir).assert().success();
+
+    temp_dir
+        .child(".git")
+        .child("git-z")
+        .child("commit-cache.toml")
+        .assert(predicate::str::contains(indoc! {r#"
+            [wizard_answers]
+            type = "feat"
+            scope = "api"
+            description = "add b"
+            breaking_change = "b."
+        "#}));
+
+    Ok(())
+}
+
+#[test]
+fn keeps_the_undone_message_in_the_commit_cache() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit_file(&temp_dir, "a", "feat: add a")?;
+    commit_file(
+        &temp_dir,
+        "b",
+        "feat: add b\n\nSome details.\n\nCo-authored-by: Someone <someone@example.com>",
+    )?;
+
+    gitz_undo(&temp_dir)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "The body and trailers of the undone\ncommit are kept.",
+        ));
+
+    temp_dir
+        .child(".git")
+        .child("git-z")
+        .child("commit-cache.toml")
+        .assert(predicate::str::contains(indoc! {r#"
+            wizard_state = "ongoing"
+            old_message = """
+            feat: add b
+
+            Some details.
+
+            Co-authored-by: Someone <someone@example.com>"""
+        "#}));
+
+    Ok(())
+}
+
+#[test]
+fn refuses_to_undo_the_root_commit() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit_file(&temp_dir, "a", "feat: add a")?;
+
+    gitz_undo(&temp_dir).assert().failure().code(64).stderr(
+        predicate::str::contains("Error: the last commit is the root commit."),
+    );
+
+    assert_eq!(commit_count(&temp_dir)?, 1);
+
+    Ok(())
+}
+
+#[test]
+fn refuses_to_undo_a_commit_during_another_one() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit_file(&temp_dir, "a", "feat: add a")?;
+    commit_file(&temp_dir, "b", "feat: add b")?;
+
+    temp_dir
+        .child(".git")
+        .child("git-z")
+        .child("commit-cache.toml")
+        .write_str(indoc! {r#"
+            version = "0.2"
+            wizard_state = "ongoing"
+
+            [wizard_answers]
+            type = "fix"
+        "#})?;
+
+    gitz_undo(&temp_dir)
+        .assert()
+        .failure()
+        .code(75)
+        .stderr(predicate::str::contains("Error: a commit is in progress."));
+
+    assert_eq!(commit_count(&temp_dir)?, 2);
+
+    Ok(())
+}
+
+#[test]
+fn asks_before_undoing_a_pushed_commit() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    commit_file(&temp_dir, "a", "feat: add a")?;
+    commit_file(&temp_dir, "b", "feat: add b")?;
+    git(
+        &temp_dir,
+        &["update-ref", "refs/remotes/origin/main", "HEAD"],
+    )?;
+
+    let mut process = spawn_command(gitz_undo(&temp_dir), TIMEOUT)?;
+
+    process.exp_string("The last commit has already been pushed.")?;
+    process.exp_string("Do you want to undo it anyway?")?;
+    process.send_line("n")?;
+    process.exp_string("The last commit has been left untouched.")?;
+    process.exp_eof()?;
+
+    assert_eq!(commit_count(&temp_dir)?, 2);
+
+    Ok(())
+}
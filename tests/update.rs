@@ -0,0 +1,252 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `git z update`.
+
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use assert_cmd::{assert::OutputAssertExt as _, cargo::cargo_bin};
+use assert_fs::{prelude::*, TempDir};
+use eyre::Result;
+use predicates::prelude::*;
+
+////////////////////////////////////////////////////////////////////////////////
+//                                  Helpers                                   //
+////////////////////////////////////////////////////////////////////////////////
+
+fn setup_temp_dir() -> Result<TempDir> {
+    let temp_dir = TempDir::new()?;
+    temp_dir.child(".git").create_dir_all()?;
+    Ok(temp_dir)
+}
+
+fn config_path(name: &str) -> Result<PathBuf> {
+    Ok(std::env::current_dir()?
+        .join("tests")
+        .join("res")
+        .join("config")
+        .join(name))
+}
+
+fn install_config(temp_dir: &TempDir, name: &str) -> Result<()> {
+    temp_dir
+        .child("git-z.toml")
+        .write_file(&config_path(name)?)?;
+    Ok(())
+}
+
+fn install_backup(
+    temp_dir: &TempDir,
+    timestamp: u64,
+    name: &str,
+) -> Result<()> {
+    temp_dir
+        .child(".git")
+        .child("git-z")
+        .child("backups")
+        .child(format!("git-z.toml.{timestamp}"))
+        .write_file(&config_path(name)?)?;
+    Ok(())
+}
+
+fn backups(temp_dir: &TempDir) -> Result<Vec<PathBuf>> {
+    let backups_dir = temp_dir.child(".git").child("git-z").child("backups");
+
+    if !backups_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    Ok(fs::read_dir(backups_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?)
+}
+
+fn fake_git_path() -> Result<OsString> {
+    let fake_git = cargo_bin("git");
+    eyre::ensure!(
+        fake_git.exists(),
+        "The fake Git is not built, please run `cargo build -p fake-git`"
+    );
+
+    let fake_bin = fake_git.parent().map(Path::to_path_buf);
+    let path = env::var_os("PATH").unwrap_or_default();
+    Ok(env::join_paths(
+        fake_bin.into_iter().chain(env::split_paths(&path)),
+    )?)
+}
+
+fn gitz_update(temp_dir: &TempDir) -> Result<Command> {
+    let mut cmd = Command::new(cargo_bin("git-z"));
+    cmd.current_dir(temp_dir)
+        .env("NO_COLOR", "true")
+        .env("PATH", fake_git_path()?)
+        .arg("update");
+    Ok(cmd)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn backs_up_the_config_before_updating() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "v0_2_standard.toml")?;
+
+    gitz_update(&temp_dir)?.assert().success();
+
+    let backups = backups(&temp_dir)?;
+    assert_eq!(backups.len(), 1);
+    assert!(backups[0]
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("git-z.toml.")));
+    assert_eq!(
+        fs::read_to_string(&backups[0])?,
+        fs::read_to_string(config_path("v0_2_standard.toml")?)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn does_not_back_up_an_up_to_date_config() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "v0_3_standard.toml")?;
+
+    gitz_update(&temp_dir)?
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already up to date"));
+
+    assert!(backups(&temp_dir)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn restores_the_config_from_before_the_update() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "v0_2_standard.toml")?;
+
+    gitz_update(&temp_dir)?.assert().success();
+    gitz_update(&temp_dir)?
+        .arg("--rollback")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "The configuration has been restored from its backup.",
+        ));
+
+    temp_dir
+        .child("git-z.toml")
+        .assert(predicate::path::eq_file(config_path("v0_2_standard.toml")?));
+    assert!(backups(&temp_dir)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn restores_the_latest_backup_first() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "v0_3_standard.toml")?;
+    install_backup(&temp_dir, 200, "v0_2_standard.toml")?;
+    install_backup(&temp_dir, 1000, "v0_1_standard.toml")?;
+
+    gitz_update(&temp_dir)?.arg("--rollback").assert().success();
+    temp_dir
+        .child("git-z.toml")
+        .assert(predicate::path::eq_file(config_path("v0_1_standard.toml")?));
+
+    gitz_update(&temp_dir)?.arg("--rollback").assert().success();
+    temp_dir
+        .child("git-z.toml")
+        .assert(predicate::path::eq_file(config_path("v0_2_standard.toml")?));
+
+    Ok(())
+}
+
+#[test]
+fn keeps_a_backup_of_each_update_in_a_row() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "v0_2_scopes-any.toml")?;
+    gitz_update(&temp_dir)?.assert().success();
+    install_config(&temp_dir, "v0_2_standard.toml")?;
+    gitz_update(&temp_dir)?.assert().success();
+
+    assert_eq!(backups(&temp_dir)?.len(), 2);
+
+    gitz_update(&temp_dir)?.arg("--rollback").assert().success();
+    temp_dir
+        .child("git-z.toml")
+        .assert(predicate::path::eq_file(config_path("v0_2_standard.toml")?));
+
+    gitz_update(&temp_dir)?.arg("--rollback").assert().success();
+    temp_dir
+        .child("git-z.toml")
+        .assert(predicate::path::eq_file(config_path(
+            "v0_2_scopes-any.toml",
+        )?));
+
+    Ok(())
+}
+
+#[test]
+fn prints_an_error_if_the_backups_cannot_be_listed() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "v0_3_standard.toml")?;
+    temp_dir
+        .child(".git")
+        .child("git-z")
+        .child("backups")
+        .write_str("not a directory")?;
+
+    gitz_update(&temp_dir)?
+        .arg("--rollback")
+        .assert()
+        .code(74)
+        .stderr(predicate::str::contains("failed to list the backups."));
+
+    Ok(())
+}
+
+#[test]
+fn prints_an_error_if_there_is_no_backup() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_config(&temp_dir, "v0_3_standard.toml")?;
+
+    gitz_update(&temp_dir)?
+        .arg("--rollback")
+        .assert()
+        .code(64)
+        .stderr(predicate::str::contains(
+            "no backup of git-z.toml to restore.",
+        ));
+
+    temp_dir
+        .child("git-z.toml")
+        .assert(predicate::path::eq_file(config_path("v0_3_standard.toml")?));
+
+    Ok(())
+}
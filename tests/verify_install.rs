@@ -0,0 +1,170 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `git z verify-install`.
+
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+mod common;
+
+use std::{env, fs, os::unix::fs::PermissionsExt as _, process::Command};
+
+use assert_cmd::{assert::OutputAssertExt as _, cargo::cargo_bin};
+use assert_fs::{prelude::*, TempDir};
+use eyre::Result;
+use indoc::indoc;
+use predicates::prelude::*;
+
+use common::gitz;
+
+////////////////////////////////////////////////////////////////////////////////
+//                                  Helpers                                   //
+////////////////////////////////////////////////////////////////////////////////
+
+fn setup_temp_dir() -> Result<TempDir> {
+    let temp_dir = common::setup_temp_dir()?;
+
+    temp_dir.child("git-z.toml").write_str(indoc! {r#"
+        version = "0.3-dev.0"
+
+        [types]
+        feat = "add a new feature"
+
+        [templates]
+        commit = "{{ type }}: {{ description }}"
+    "#})?;
+
+    Ok(temp_dir)
+}
+
+fn install_hook(temp_dir: &TempDir, name: &str, content: &str) -> Result<()> {
+    let hook = temp_dir.child(".git").child("hooks").child(name);
+    hook.write_str(content)?;
+    fs::set_permissions(hook.path(), fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+/// Returns the `PATH`, with or without the directory containing git-z.
+fn path(with_gitz: bool) -> Result<String> {
+    let gitz = cargo_bin("git-z");
+    let gitz_dir = gitz.parent().expect("git-z has no parent directory");
+    let path = env::var_os("PATH").unwrap_or_default();
+
+    let dirs = env::split_paths(&path).filter(|dir| dir != gitz_dir);
+    let dirs = if with_gitz {
+        std::iter::once(gitz_dir.to_owned())
+            .chain(dirs)
+            .collect::<Vec<_>>()
+    } else {
+        dirs.collect()
+    };
+
+    Ok(env::join_paths(dirs)?.into_string().unwrap())
+}
+
+fn gitz_verify_install(temp_dir: &TempDir, with_gitz: bool) -> Result<Command> {
+    let mut cmd = gitz(temp_dir);
+    cmd.env("PATH", path(with_gitz)?).args([
+        "verify-install",
+        "--format",
+        "json",
+    ]);
+    Ok(cmd)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn succeeds_when_git_z_is_properly_installed() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_hook(&temp_dir, "commit-msg", "#!/bin/sh\nexec git z lint\n")?;
+
+    gitz_verify_install(&temp_dir, true)?
+        .args(["--hook", "commit-msg"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""ok":true"#))
+        .stdout(predicate::str::contains(
+            r#"{"name":"hook.prepare-commit-msg","status":"skipped","detail":"not installed"}"#,
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn fails_when_git_z_is_not_in_the_path() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    gitz_verify_install(&temp_dir, false)?
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(r#""ok":false"#))
+        .stdout(predicate::str::contains(
+            r#"{"name":"path","status":"failed""#,
+        ))
+        .stderr(predicate::str::contains(
+            "1 check(s) of the installation have failed",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn fails_when_a_required_hook_is_not_installed() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    install_hook(&temp_dir, "commit-msg", "#!/bin/sh\nexec true\n")?;
+
+    gitz_verify_install(&temp_dir, true)?
+        .args(["--hook", "commit-msg", "--hook", "prepare-commit-msg"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            r#"{"name":"hook.commit-msg","status":"failed","detail":"installed, but does not call git-z"}"#,
+        ))
+        .stdout(predicate::str::contains(
+            r#"{"name":"hook.prepare-commit-msg","status":"failed","detail":"not installed"}"#,
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn fails_when_git_z_is_older_than_the_min_version() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    temp_dir.child("git-z.toml").write_str(indoc! {r#"
+        version = "0.3-dev.0"
+
+        [types]
+        feat = "add a new feature"
+
+        [templates]
+        commit = "{{ type }}: {{ description }}"
+
+        [meta]
+        min_version = "999.0"
+    "#})?;
+
+    gitz_verify_install(&temp_dir, true)?
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "This configuration requires git-z 999.0 or later",
+        ));
+
+    Ok(())
+}
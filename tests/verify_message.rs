@@ -0,0 +1,189 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `git z verify-message`.
+
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use assert_fs::{prelude::*, TempDir};
+use eyre::Result;
+use indoc::indoc;
+use predicates::prelude::*;
+
+////////////////////////////////////////////////////////////////////////////////
+//                                  Helpers                                   //
+////////////////////////////////////////////////////////////////////////////////
+
+fn config(name: &str) -> PathBuf {
+    PathBuf::from("tests/res/config").join(name)
+}
+
+/// Runs `git z verify-message` outside of any repository.
+fn gitz_verify_message(temp_dir: &TempDir, message: &str) -> Result<Command> {
+    let mut cmd = Command::cargo_bin("git-z")?;
+    cmd.current_dir(temp_dir)
+        .env("NO_COLOR", "true")
+        .env("GIT_CEILING_DIRECTORIES", temp_dir.path())
+        .arg("verify-message")
+        .write_stdin(message);
+    Ok(cmd)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn accepts_a_valid_message_outside_of_a_repository() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+
+    gitz_verify_message(&temp_dir, "feat: add a flag\n")?
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""verdict":"valid""#))
+        .stdout(predicate::str::contains(r#""violations":[]"#));
+
+    Ok(())
+}
+
+#[test]
+fn reports_the_violations_as_json() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+
+    gitz_verify_message(&temp_dir, "feat(api): Add a flag\n")?
+        .arg("--config")
+        .arg(std::env::current_dir()?.join(config("latest_scopes-list.toml")))
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(r#""verdict":"invalid""#))
+        .stdout(predicate::str::contains(r#""code":"unknown_type""#))
+        .stdout(predicate::str::contains(r#""code":"unknown_scope""#))
+        .stdout(predicate::str::contains(
+            r#""code":"description_not_lowercase""#,
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn reports_the_violations_as_text() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+
+    gitz_verify_message(&temp_dir, "not conventional\n")?
+        .args(["--format", "text"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::starts_with("not_conventional: "));
+
+    Ok(())
+}
+
+#[test]
+fn skips_merge_messages_unless_requested() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    let message = "Merge branch 'feature'\n";
+
+    gitz_verify_message(&temp_dir, message)?
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""verdict":"skipped""#));
+
+    gitz_verify_message(&temp_dir, message)?
+        .arg("--check-merge-messages")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(r#""verdict":"invalid""#));
+
+    Ok(())
+}
+
+#[test]
+fn accepts_the_suffix_of_squash_merges_if_requested() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+
+    gitz_verify_message(&temp_dir, "feat: add a flag (#12)\n")?
+        .arg("--squash-merges")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""verdict":"valid""#));
+
+    Ok(())
+}
+
+#[test]
+fn reports_names_from_external_lists_as_unverifiable() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+    temp_dir.child("git-z.toml").write_str(indoc! {r#"
+        version = "0.3-dev.0"
+
+        [types]
+        feat = "add a new feature"
+
+        [scopes]
+        accept = "list"
+        list = ["cli"]
+        command = "touch ran.txt; echo core"
+
+        [templates]
+        commit = "{{ type }}: {{ description }}"
+    "#})?;
+
+    gitz_verify_message(&temp_dir, "feat(core): add a flag\n")?
+        .args(["--config", "git-z.toml"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(r#""verdict":"unverifiable""#))
+        .stdout(predicate::str::contains(r#""code":"unverifiable_scope""#));
+
+    temp_dir.child("ran.txt").assert(predicate::path::missing());
+
+    gitz_verify_message(&temp_dir, "feat(cli): add a flag\n")?
+        .args(["--config", "git-z.toml"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""verdict":"valid""#));
+
+    Ok(())
+}
+
+#[test]
+fn fails_if_the_config_cannot_be_read() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+
+    gitz_verify_message(&temp_dir, "feat: add a flag\n")?
+        .args(["--config", "missing.toml"])
+        .assert()
+        .code(74)
+        .stderr(predicate::str::contains("failed to read missing.toml"));
+
+    Ok(())
+}
+
+#[test]
+fn fails_if_the_config_is_invalid() -> Result<()> {
+    let temp_dir = TempDir::new()?;
+
+    gitz_verify_message(&temp_dir, "feat: add a flag\n")?
+        .arg("--config")
+        .arg(std::env::current_dir()?.join(config("invalid_value.toml")))
+        .assert()
+        .code(78);
+
+    Ok(())
+}
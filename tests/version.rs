@@ -0,0 +1,108 @@
+// git-z - A Git extension to go beyond.
+// Copyright (C) 2024 Jean-Philippe Cugnet <jean-philippe@cugnet.eu>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! CLI tests for `git z --version`.
+
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::pedantic, clippy::restriction)]
+
+mod common;
+
+use assert_cmd::assert::OutputAssertExt as _;
+use assert_fs::prelude::*;
+use eyre::Result;
+use indoc::indoc;
+use predicates::prelude::*;
+
+use common::{gitz, setup_temp_dir};
+
+////////////////////////////////////////////////////////////////////////////////
+//                                   Tests                                    //
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn prints_the_long_version() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    gitz(&temp_dir)
+        .arg("--version")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("revision: "))
+        .stdout(predicate::str::contains("[diagnostics]").not());
+
+    Ok(())
+}
+
+#[test]
+fn prints_diagnostics_with_verbose() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+    temp_dir.child("git-z.toml").write_str(indoc! {r##"
+        version = "0.3-dev.0"
+
+        [types]
+        feat = "add a new feature"
+
+        [templates]
+        commit = "{{ type }}: {{ description }}"
+
+        [ticket]
+        required = false
+        prefixes = ["#"]
+    "##})?;
+    temp_dir
+        .child(".git/hooks/commit-msg")
+        .write_str("#!/bin/sh\n")?;
+
+    gitz(&temp_dir)
+        .args(["--version", "--verbose"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("revision: "))
+        .stdout(predicate::str::contains(
+            "\n\n[diagnostics]\nconfig_path = ",
+        ))
+        .stdout(predicate::str::contains("config_found = true\n"))
+        .stdout(predicate::str::contains("config_version = \"0.3-dev.0\"\n"))
+        .stdout(predicate::str::contains("backend = \"git\"\n"))
+        .stdout(predicate::str::contains("integrations = [\"ticket\"]\n"))
+        .stdout(predicate::str::contains("hooks = [\"commit-msg\"]\n"));
+
+    Ok(())
+}
+
+#[test]
+fn prints_diagnostics_without_configuration() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    gitz(&temp_dir)
+        .args(["--verbose", "--version"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("config_found = false\n"))
+        .stdout(predicate::str::contains("config_version").not())
+        .stdout(predicate::str::contains("integrations = []\n"));
+
+    Ok(())
+}
+
+#[test]
+fn rejects_verbose_without_version() -> Result<()> {
+    let temp_dir = setup_temp_dir()?;
+
+    gitz(&temp_dir).arg("--verbose").assert().failure();
+
+    Ok(())
+}